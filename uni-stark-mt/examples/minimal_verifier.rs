@@ -0,0 +1,77 @@
+//! Verifier-only binary, the reference for the size budget of the `minimal` feature.
+//!
+//! Reads a postcard-encoded Fibonacci proof from the file given as first argument and
+//! verifies it under the BabyBear + Poseidon2 preset, the one preset-dependent part of the
+//! build:
+//!
+//! ```text
+//! cargo build --release --example minimal_verifier --no-default-features \
+//!     --features minimal,presets
+//! ```
+
+use std::process::ExitCode;
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field};
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Config};
+use p3_uni_stark_mt::{verify, AirLayout, AuxTraceBuilder, Proof, PuncturedAir};
+
+/// Fibonacci AIR: the canonical two-column AIR
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for FibonacciAir {}
+
+impl<F> PuncturedAir<F> for FibonacciAir {}
+
+impl<F> AirLayout<F> for FibonacciAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for FibonacciAir {}
+
+impl<AB: AirBuilder> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+
+        builder.when_first_row().assert_zero(local[0].clone());
+        builder.when_first_row().assert_one(local[1].clone());
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1].clone(), next[0].clone());
+        when_transition.assert_eq(local[0].clone() + local[1].clone(), next[1].clone());
+    }
+}
+
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: minimal_verifier <proof file>");
+        return ExitCode::FAILURE;
+    };
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!("can't read {path}: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Ok(proof) = postcard::from_bytes::<Proof<Config>>(&bytes) else {
+        eprintln!("malformed proof");
+        return ExitCode::FAILURE;
+    };
+    match verify(&default_config(), &FibonacciAir, &proof, &[]) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("verification failed: {err:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,8 +1,17 @@
 //! AIR trait extensions for multi-trace proving
 
-use p3_air::BaseAir;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
 use p3_field::{ExtensionField, Field};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{
+    BoundaryConstraint, ChallengeSampling, ChallengeSet, OpeningSchedule, StateDigests,
+    ZeroDenominator, ZeroDenominatorPolicy,
+};
 
 /// Trait for AIRs that can build auxiliary trace columns.
 ///
@@ -20,66 +29,482 @@ use p3_matrix::dense::RowMajorMatrix;
 ///         1 // One running sum column
 ///     }
 ///
-///     fn num_challenges(&self) -> usize {
-///         2 // α and β for LogUp
+///     fn challenge_names(&self) -> Vec<&'static str> {
+///         vec!["logup_alpha", "logup_beta"]
 ///     }
 ///
 ///     fn build_aux_trace(
 ///         &self,
 ///         main_trace: &RowMajorMatrix<F>,
-///         challenges: &[EF],
+///         challenges: &ChallengeSet<'_, EF>,
 ///     ) -> RowMajorMatrix<EF> {
-///         let (alpha, beta) = (challenges[0], challenges[1]);
+///         let (alpha, beta) = (challenges["logup_alpha"], challenges["logup_beta"]);
 ///         // Build LogUp running sum...
 ///     }
 /// }
 /// ```
+///
+/// AIRs combining several independent arguments can instead list them as
+/// [`AuxSegment`]s in [`aux_segments`](Self::aux_segments); the width, challenges and
+/// builder then default to the segments laid out side by side.
 pub trait AuxTraceBuilder<F: Field, EF: ExtensionField<F>>: BaseAir<F> + Sync {
-    /// Number of auxiliary trace columns.
+    /// Independent parts of the extension-field auxiliary trace, in column order.
+    ///
+    /// Each segment is built from its own challenges and columns, and all of them are
+    /// committed jointly. Returns no segments by default.
+    fn aux_segments(&self) -> Vec<&dyn AuxSegment<F, EF>> {
+        Vec::new()
+    }
+
+    /// Number of auxiliary trace columns over the extension field.
     ///
-    /// Returns 0 for AIRs without auxiliary traces (single-phase proving).
+    /// Returns 0 for AIRs without extension-field auxiliary columns; with no base-field
+    /// ones either (see [`aux_base_width()`](Self::aux_base_width)) proving is
+    /// single-phase. Defaults to the total width of the [`aux_segments`](Self::aux_segments).
     fn aux_width(&self) -> usize {
-        0
+        self.aux_segments()
+            .iter()
+            .map(|segment| segment.width())
+            .sum()
     }
 
-    /// Number of random challenge elements needed to build the auxiliary trace.
+    /// Names of the random challenges needed to build the auxiliary trace, in sampling
+    /// order.
     ///
-    /// Returns 0 for AIRs without auxiliary traces.
-    fn num_challenges(&self) -> usize {
-        0
+    /// One challenge is sampled per name and handed to the builders as a
+    /// [`ChallengeSet`]. Names must be unique. Returns no names for AIRs without auxiliary
+    /// traces (over either field). Defaults to the challenges of the
+    /// [`aux_segments`](Self::aux_segments), in segment order.
+    fn challenge_names(&self) -> Vec<&'static str> {
+        self.aux_segments()
+            .iter()
+            .flat_map(|segment| segment.challenge_names())
+            .collect()
+    }
+
+    /// How the values of the [`challenge_names`](Self::challenge_names) are sampled.
+    ///
+    /// AIRs with many challenges, e.g. one per column of a wide lookup, can derive them
+    /// all as powers of one sampled element with [`ChallengeSampling::Powers`], so
+    /// verifiers draw and bind a single element.
+    ///
+    /// Returns [`ChallengeSampling::Independent`] by default.
+    fn challenge_sampling(&self) -> ChallengeSampling {
+        ChallengeSampling::Independent
+    }
+
+    /// Columns of segment `index` of [`aux_segments`](Self::aux_segments) within the
+    /// auxiliary trace.
+    ///
+    /// # Panics
+    /// - If there is no such segment
+    fn aux_segment_columns(&self, index: usize) -> Range<usize> {
+        let segments = self.aux_segments();
+        let start = segments[..index]
+            .iter()
+            .map(|segment| segment.width())
+            .sum();
+        start..start + segments[index].width()
     }
 
     /// Build the auxiliary trace from the main trace and challenges.
     ///
     /// # Arguments
-    /// - `main_trace`: The main execution trace (already committed)
-    /// - `challenges`: Random challenges sampled after main trace commitment
+    /// - `main_trace`: The main execution trace (already committed), as given to the
+    ///   prover: one row per trace row in natural order, never its low-degree extension
+    /// - `challenges`: Random challenges sampled after main trace commitment, named as in
+    ///   [`challenge_names()`](Self::challenge_names)
     ///
     /// # Returns
     /// A matrix of auxiliary trace columns, with:
     /// - Width: [`aux_width()`](Self::aux_width)
     /// - Height: Same as `main_trace.height()`
     ///
+    /// The default builds each of the [`aux_segments`](Self::aux_segments) in turn,
+    /// handing it only its own [`challenge_names()`](AuxSegment::challenge_names), and
+    /// places their columns side by side.
+    ///
     /// # Panics
     /// - If called when `aux_width() == 0`
-    /// - If `challenges.len() != num_challenges()`
+    /// - If `challenges` are not those of `challenge_names()`
     fn build_aux_trace(
         &self,
         main_trace: &RowMajorMatrix<F>,
-        challenges: &[EF],
+        challenges: &ChallengeSet<'_, EF>,
     ) -> RowMajorMatrix<EF> {
+        let segments = self.aux_segments();
+        assert!(
+            !segments.is_empty(),
+            "build_aux_trace called but aux_width() is 0"
+        );
+        build_aux_segments(&segments, main_trace, challenges)
+    }
+
+    /// Number of auxiliary trace columns over the base field.
+    ///
+    /// Auxiliary columns that only ever hold base-field values (multiplicities,
+    /// counters, ...) belong here rather than in [`aux_width()`](Self::aux_width): each is
+    /// committed as one column over `F` instead of `EF::DIMENSION` flattened ones.
+    /// Constraints read them through [`AuxBuilder::aux_base`](crate::AuxBuilder::aux_base).
+    ///
+    /// Returns 0 for AIRs without base-field auxiliary columns.
+    fn aux_base_width(&self) -> usize {
+        0
+    }
+
+    /// Build the base-field auxiliary columns from the main trace and challenges.
+    ///
+    /// Called in the same phase as [`build_aux_trace`](Self::build_aux_trace), with the
+    /// same challenges.
+    ///
+    /// # Returns
+    /// A matrix of base-field auxiliary columns, with:
+    /// - Width: [`aux_base_width()`](Self::aux_base_width)
+    /// - Height: Same as `main_trace.height()`
+    ///
+    /// # Panics
+    /// - If called when `aux_base_width() == 0`
+    fn build_aux_base_trace(
+        &self,
+        main_trace: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<F> {
         let _ = (main_trace, challenges);
-        panic!("build_aux_trace called but aux_width() is 0")
+        panic!("build_aux_base_trace called but aux_base_width() is 0")
+    }
+
+    /// Number of leading base-field auxiliary columns that don't depend on the challenges.
+    ///
+    /// Data such as sorted copies of the main trace or lookup multiplicities is fixed by
+    /// the main trace alone. The prover commits these columns right after the main trace,
+    /// before any challenge is sampled, and AIRs whose auxiliary columns are all
+    /// challenge-free have no auxiliary phase at all. Constraints still read them as the
+    /// first columns of [`AuxBuilder::aux_base`](crate::AuxBuilder::aux_base).
+    ///
+    /// At most [`aux_base_width()`](Self::aux_base_width). Returns 0 by default.
+    fn challenge_free_aux_width(&self) -> usize {
+        0
+    }
+
+    /// Build the challenge-free auxiliary columns from the main trace.
+    ///
+    /// # Returns
+    /// A matrix of base-field columns, with:
+    /// - Width: [`challenge_free_aux_width()`](Self::challenge_free_aux_width)
+    /// - Height: Same as `main_trace.height()`
+    ///
+    /// Its columns must be the leading columns of the base-field trace
+    /// [`build_aux_traces`](Self::build_aux_traces) returns; the prover checks they are.
+    ///
+    /// # Panics
+    /// - If called when `challenge_free_aux_width() == 0`
+    fn build_challenge_free_aux_trace(&self, main_trace: &RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        let _ = main_trace;
+        panic!("build_challenge_free_aux_trace called but challenge_free_aux_width() is 0")
+    }
+
+    /// Number of extension-field values the auxiliary phase exposes in the proof.
+    ///
+    /// Exposed values, e.g. the final sum of a LogUp accumulator, are returned by
+    /// [`build_aux_traces`](Self::build_aux_traces), observed by the transcript after the
+    /// auxiliary commitment and read by constraints through
+    /// [`AuxBuilder::aux_exposed_values`](crate::AuxBuilder::aux_exposed_values).
+    ///
+    /// Returns 0 for AIRs exposing no values.
+    fn num_aux_exposed_values(&self) -> usize {
+        0
+    }
+
+    /// Index of the exposed value holding this AIR's share of a bus shared with other
+    /// proofs, see [`check_bus_balance`](crate::check_bus_balance).
+    ///
+    /// Below [`num_aux_exposed_values()`](Self::num_aux_exposed_values). Returns `None`
+    /// by default.
+    fn bus_contribution_index(&self) -> Option<usize> {
+        None
+    }
+
+    /// Build both auxiliary segments of the phase from the main trace and challenges.
+    ///
+    /// The default calls [`build_aux_trace`](Self::build_aux_trace) and
+    /// [`build_aux_base_trace`](Self::build_aux_base_trace) for the segments with a
+    /// nonzero width, leaving the others empty, and exposes no values. Override it instead
+    /// of those two when the segments share work, e.g. multiplicity columns feeding LogUp
+    /// accumulators, or to expose values.
+    fn build_aux_traces(
+        &self,
+        main_trace: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        AuxTraces {
+            ext: if self.aux_width() > 0 {
+                self.build_aux_trace(main_trace, challenges)
+            } else {
+                RowMajorMatrix::new(Vec::new(), 0)
+            },
+            base: if self.aux_base_width() > 0 {
+                self.build_aux_base_trace(main_trace, challenges)
+            } else {
+                RowMajorMatrix::new(Vec::new(), 0)
+            },
+            exposed: Vec::new(),
+        }
+    }
+
+    /// Build the auxiliary traces as [`build_aux_traces`](Self::build_aux_traces) does,
+    /// reporting a vanishing denominator rather than panicking on it.
+    ///
+    /// The prover and the [`MockProver`](crate::MockProver) build through this. The
+    /// default calls `build_aux_traces`; AIRs dividing by challenge-dependent terms
+    /// override it, inverting with [`checked_batch_inverse`](crate::checked_batch_inverse).
+    ///
+    /// # Errors
+    /// The first denominator that vanished.
+    fn try_build_aux_traces(
+        &self,
+        main_trace: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> Result<AuxTraces<F, EF>, ZeroDenominator> {
+        Ok(self.build_aux_traces(main_trace, challenges))
+    }
+
+    /// What to do when [`try_build_aux_traces`](Self::try_build_aux_traces) reports a
+    /// zero denominator, see [`ZeroDenominatorPolicy`].
+    ///
+    /// Returns [`ZeroDenominatorPolicy::Fail`] by default.
+    fn zero_denominator_policy(&self) -> ZeroDenominatorPolicy {
+        ZeroDenominatorPolicy::Fail
+    }
+}
+
+/// An independent part of an auxiliary trace, e.g. one lookup or permutation argument.
+///
+/// See [`AuxTraceBuilder::aux_segments`].
+pub trait AuxSegment<F: Field, EF: ExtensionField<F>>: Sync {
+    /// Number of columns of the segment.
+    fn width(&self) -> usize;
+
+    /// Names of the random challenges needed to build the segment.
+    ///
+    /// Must not clash with the names of the other segments of the AIR.
+    fn challenge_names(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
+    /// Build the segment's columns from the main trace and its own challenges.
+    ///
+    /// Must return [`width()`](Self::width) columns and as many rows as `main_trace`.
+    fn build(
+        &self,
+        main_trace: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF>;
+}
+
+/// Build `segments` and lay their columns out side by side.
+fn build_aux_segments<F, EF>(
+    segments: &[&dyn AuxSegment<F, EF>],
+    main_trace: &RowMajorMatrix<F>,
+    challenges: &ChallengeSet<'_, EF>,
+) -> RowMajorMatrix<EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    let height = main_trace.height();
+    let mut next_challenge = 0;
+    let built: Vec<RowMajorMatrix<EF>> = segments
+        .iter()
+        .map(|segment| {
+            let own = next_challenge..next_challenge + segment.challenge_names().len();
+            next_challenge = own.end;
+            let columns = segment.build(main_trace, &challenges.slice(own));
+            assert_eq!(columns.width, segment.width(), "Aux segment width mismatch");
+            assert_eq!(columns.height(), height, "Aux segment height mismatch");
+            columns
+        })
+        .collect();
+    assert_eq!(
+        next_challenge,
+        challenges.len(),
+        "More challenges than the aux segments use"
+    );
+
+    let width = built.iter().map(|columns| columns.width).sum();
+    let mut values = Vec::with_capacity(width * height);
+    for row in 0..height {
+        for columns in &built {
+            values
+                .extend_from_slice(&columns.values[row * columns.width..(row + 1) * columns.width]);
+        }
+    }
+    RowMajorMatrix::new(values, width)
+}
+
+/// The auxiliary columns of one phase, see [`AuxTraceBuilder::build_aux_traces`].
+#[derive(Clone, Debug)]
+pub struct AuxTraces<F, EF> {
+    /// Extension-field columns, [`aux_width()`](AuxTraceBuilder::aux_width) wide
+    pub ext: RowMajorMatrix<EF>,
+
+    /// Base-field columns, [`aux_base_width()`](AuxTraceBuilder::aux_base_width) wide
+    pub base: RowMajorMatrix<F>,
+
+    /// Values exposed in the proof,
+    /// [`num_aux_exposed_values()`](AuxTraceBuilder::num_aux_exposed_values) of them
+    pub exposed: Vec<EF>,
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraces<F, EF> {
+    /// Height of the columns, `main_height` if there are none.
+    ///
+    /// Both segments must be equally tall, but may be shorter than the main trace if their
+    /// height divides its `main_height`: constraints then read row `i` of the auxiliary
+    /// trace on every row `i mod height` of the main trace, and the prover commits it on
+    /// a domain of its own, see [`Proof::log_aux_degree`](crate::Proof::log_aux_degree).
+    ///
+    /// # Errors
+    /// If the segments' heights differ, or don't divide `main_height`.
+    pub fn height(&self, main_height: usize) -> Result<usize, &'static str> {
+        let mut heights = [
+            (self.ext.width(), self.ext.height()),
+            (self.base.width(), self.base.height()),
+        ]
+        .into_iter()
+        .filter(|&(width, _)| width > 0)
+        .map(|(_, height)| height);
+        let Some(height) = heights.next() else {
+            return Ok(main_height);
+        };
+        if heights.any(|other| other != height) {
+            return Err("the auxiliary segments aren't equally tall");
+        }
+        if height == 0 || main_height % height != 0 {
+            return Err("the auxiliary trace's height doesn't divide the main trace's");
+        }
+        Ok(height)
+    }
+}
+
+/// Whether `air` has auxiliary columns, over the extension or the base field.
+pub(crate) fn has_aux_trace<F, EF, A>(air: &A) -> bool
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: AuxTraceBuilder<F, EF> + ?Sized,
+{
+    air.aux_width() > 0 || air.aux_base_width() > 0
+}
+
+/// Whether `air` has auxiliary columns depending on challenges, built and committed in an
+/// auxiliary phase.
+pub(crate) fn has_aux_phase<F, EF, A>(air: &A) -> bool
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: AuxTraceBuilder<F, EF> + ?Sized,
+{
+    air.aux_width() > 0 || air.aux_base_width() > air.challenge_free_aux_width()
+}
+
+/// Trait for AIRs whose constraints only hold on part of the trace.
+///
+/// Constraints are normally enforced on every row, i.e. divided by the vanishing
+/// polynomial `Z_H` of the whole trace domain. Arguments that leave the last `k` rows
+/// unconstrained (e.g. padding) can puncture them instead: the quotient then divides by
+/// `Z_H` with those `k` roots removed, and the verifier evaluates the adjusted `Z_H` at ζ.
+///
+/// Each punctured row raises the degree of the quotient numerator by one, so keep `k`
+/// small relative to the trace height. Punctures require the PCS to support them, see
+/// [`TraceDomain`](crate::TraceDomain).
+pub trait PuncturedAir<F>: BaseAir<F> {
+    /// Number of trailing rows on which constraints are not enforced.
+    ///
+    /// Returns 0 for AIRs constrained on every row.
+    fn num_punctured_rows(&self) -> usize {
+        0
+    }
+}
+
+/// Trait for the layout of an AIR's trace: the statements it encodes, the points its
+/// traces are opened at, and the rows and public values it pins.
+///
+/// Every method has a default for a trace encoding one statement, opened at the current
+/// and next rows, with nothing pinned, so most AIRs implement it empty.
+pub trait AirLayout<F>: BaseAir<F> {
+    /// Number of independent statements the trace encodes.
+    ///
+    /// The rows are split into this many segments of equal height, one per statement,
+    /// and the public values into as many equal groups, statement `j` owning group `j`.
+    /// Constraints see each segment's boundaries and public values through
+    /// [`StatementBuilder`](crate::StatementBuilder). The count must divide the trace
+    /// height, leaving at least two rows per statement, and the number of public values.
+    ///
+    /// Returns 1 for AIRs proving a single statement.
+    fn num_statements(&self) -> usize {
+        1
+    }
+
+    /// Points the main trace is opened at.
+    ///
+    /// AIRs whose constraints never read the main trace's next row can skip its opening
+    /// at ζ·g with [`OpeningSchedule::LOCAL`]; the constraints then see a one-row
+    /// window. Opening the last row exposes it to every row through
+    /// [`AuxBuilder::main_last_row`](crate::AuxBuilder::main_last_row).
+    ///
+    /// Returns [`OpeningSchedule::TRANSITION`] by default.
+    fn main_openings(&self) -> OpeningSchedule {
+        OpeningSchedule::TRANSITION
+    }
+
+    /// Points the auxiliary trace, over both fields, is opened at.
+    ///
+    /// Opening the last row exposes it to every row through
+    /// [`AuxBuilder::aux_last_row`](crate::AuxBuilder::aux_last_row) and
+    /// [`aux_base_last_row`](crate::AuxBuilder::aux_base_last_row): a boundary check on
+    /// an accumulator, e.g. that a running sum ends at an exposed value, becomes a plain
+    /// equality rather than a constraint filtered by `is_last_row`. Ignored for AIRs
+    /// without an auxiliary trace.
+    ///
+    /// Returns [`OpeningSchedule::TRANSITION`] by default.
+    fn aux_openings(&self) -> OpeningSchedule {
+        OpeningSchedule::TRANSITION
+    }
+
+    /// Main trace cells pinned to public values at fixed rows.
+    ///
+    /// Unlike constraints filtered by a selector, these can name any row, e.g. inputs
+    /// and outputs placed mid-trace: the main trace is opened at each pinned row, and the
+    /// verifier checks the opened cells. Every row must lie in the trace, and every
+    /// column and public value index in range.
+    ///
+    /// Returns no constraints by default.
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint> {
+        Vec::new()
+    }
+
+    /// Public values holding digests of the state the trace starts from and ends in, for
+    /// proving an execution in shards, see [`verify_chain`](crate::verify_chain).
+    ///
+    /// Both digests must lie within the public values, and the constraints must tie them
+    /// to the state the first and last rows hold. Returns `None` by default.
+    fn state_digests(&self) -> Option<StateDigests> {
+        None
     }
 }
 
 /// Marker trait for AIRs that can be proven with this crate.
 ///
-/// This is automatically implemented for any type that implements both:
-/// - [`BaseAir<F>`]
+/// This is automatically implemented for any type that implements all of:
+/// - [`BaseAirWithPublicValues<F>`]
 /// - [`AuxTraceBuilder<F, EF>`]
+/// - [`PuncturedAir<F>`]
+/// - [`AirLayout<F>`]
+///
+/// AIRs without public values, punctures or batched statements only need empty
+/// `BaseAirWithPublicValues`, `PuncturedAir` and `AirLayout` impls.
 pub trait MultiTraceAir<F: Field, EF: ExtensionField<F>>:
-    BaseAir<F> + AuxTraceBuilder<F, EF>
+    BaseAirWithPublicValues<F> + AuxTraceBuilder<F, EF> + PuncturedAir<F> + AirLayout<F>
 {
 }
 
@@ -88,6 +513,191 @@ impl<F, EF, T> MultiTraceAir<F, EF> for T
 where
     F: Field,
     EF: ExtensionField<F>,
-    T: BaseAir<F> + AuxTraceBuilder<F, EF>,
+    T: BaseAirWithPublicValues<F> + AuxTraceBuilder<F, EF> + PuncturedAir<F> + AirLayout<F>,
 {
 }
+
+/// A plain single-trace AIR, made provable with this crate.
+///
+/// Wrapping an existing `Air + BaseAirWithPublicValues` gives it the [`MultiTraceAir`]
+/// defaults: no auxiliary columns or challenges, no punctured rows, a single statement
+/// and the default opening schedules. Constraints and public values are the wrapped
+/// AIR's.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoAux<A>(pub A);
+
+impl<A> NoAux<A> {
+    /// The wrapped AIR.
+    pub const fn inner(&self) -> &A {
+        &self.0
+    }
+}
+
+impl<F, A: BaseAir<F>> BaseAir<F> for NoAux<A> {
+    fn width(&self) -> usize {
+        self.0.width()
+    }
+}
+
+impl<F, A: BaseAirWithPublicValues<F>> BaseAirWithPublicValues<F> for NoAux<A> {
+    fn num_public_values(&self) -> usize {
+        self.0.num_public_values()
+    }
+}
+
+impl<F, A: BaseAir<F>> PuncturedAir<F> for NoAux<A> {}
+
+impl<F, A: BaseAir<F>> AirLayout<F> for NoAux<A> {}
+
+impl<F: Field, EF: ExtensionField<F>, A: BaseAir<F> + Sync> AuxTraceBuilder<F, EF> for NoAux<A> {}
+
+impl<AB: AirBuilder, A: Air<AB>> Air<AB> for NoAux<A> {
+    fn eval(&self, builder: &mut AB) {
+        self.0.eval(builder);
+    }
+}
+
+/// Implement [`BaseAirWithPublicValues`], [`PuncturedAir`], [`AirLayout`] and
+/// [`AuxTraceBuilder`] for an AIR wrapper `$wrapper<'a, F, A>` by delegating to the AIR in
+/// its `air` field.
+///
+/// The wrapper implements `BaseAir` and `Air` itself.
+#[cfg(not(feature = "minimal"))]
+macro_rules! delegate_air_traits {
+    ($wrapper:ident) => {
+        impl<'a, F, A> p3_air::BaseAirWithPublicValues<F> for $wrapper<'a, F, A>
+        where
+            F: p3_field::Field,
+            A: p3_air::BaseAirWithPublicValues<F>,
+        {
+            fn num_public_values(&self) -> usize {
+                self.air.num_public_values()
+            }
+        }
+
+        impl<'a, F, A> $crate::PuncturedAir<F> for $wrapper<'a, F, A>
+        where
+            F: p3_field::Field,
+            A: $crate::PuncturedAir<F>,
+        {
+            fn num_punctured_rows(&self) -> usize {
+                self.air.num_punctured_rows()
+            }
+        }
+
+        impl<'a, F, A> $crate::AirLayout<F> for $wrapper<'a, F, A>
+        where
+            F: p3_field::Field,
+            A: $crate::AirLayout<F>,
+        {
+            fn num_statements(&self) -> usize {
+                self.air.num_statements()
+            }
+
+            fn main_openings(&self) -> $crate::OpeningSchedule {
+                self.air.main_openings()
+            }
+
+            fn aux_openings(&self) -> $crate::OpeningSchedule {
+                self.air.aux_openings()
+            }
+
+            fn boundary_constraints(&self) -> alloc::vec::Vec<$crate::BoundaryConstraint> {
+                self.air.boundary_constraints()
+            }
+
+            fn state_digests(&self) -> Option<$crate::StateDigests> {
+                self.air.state_digests()
+            }
+        }
+
+        impl<'a, F, EF, A> $crate::AuxTraceBuilder<F, EF> for $wrapper<'a, F, A>
+        where
+            F: p3_field::Field,
+            EF: p3_field::ExtensionField<F>,
+            A: $crate::AuxTraceBuilder<F, EF>,
+        {
+            fn aux_segments(&self) -> alloc::vec::Vec<&dyn $crate::AuxSegment<F, EF>> {
+                self.air.aux_segments()
+            }
+
+            fn aux_width(&self) -> usize {
+                self.air.aux_width()
+            }
+
+            fn challenge_names(&self) -> alloc::vec::Vec<&'static str> {
+                self.air.challenge_names()
+            }
+
+            fn challenge_sampling(&self) -> $crate::ChallengeSampling {
+                self.air.challenge_sampling()
+            }
+
+            fn aux_segment_columns(&self, index: usize) -> core::ops::Range<usize> {
+                self.air.aux_segment_columns(index)
+            }
+
+            fn build_aux_trace(
+                &self,
+                main_trace: &p3_matrix::dense::RowMajorMatrix<F>,
+                challenges: &$crate::ChallengeSet<'_, EF>,
+            ) -> p3_matrix::dense::RowMajorMatrix<EF> {
+                self.air.build_aux_trace(main_trace, challenges)
+            }
+
+            fn aux_base_width(&self) -> usize {
+                self.air.aux_base_width()
+            }
+
+            fn build_aux_base_trace(
+                &self,
+                main_trace: &p3_matrix::dense::RowMajorMatrix<F>,
+                challenges: &$crate::ChallengeSet<'_, EF>,
+            ) -> p3_matrix::dense::RowMajorMatrix<F> {
+                self.air.build_aux_base_trace(main_trace, challenges)
+            }
+
+            fn challenge_free_aux_width(&self) -> usize {
+                self.air.challenge_free_aux_width()
+            }
+
+            fn build_challenge_free_aux_trace(
+                &self,
+                main_trace: &p3_matrix::dense::RowMajorMatrix<F>,
+            ) -> p3_matrix::dense::RowMajorMatrix<F> {
+                self.air.build_challenge_free_aux_trace(main_trace)
+            }
+
+            fn num_aux_exposed_values(&self) -> usize {
+                self.air.num_aux_exposed_values()
+            }
+
+            fn bus_contribution_index(&self) -> Option<usize> {
+                self.air.bus_contribution_index()
+            }
+
+            fn build_aux_traces(
+                &self,
+                main_trace: &p3_matrix::dense::RowMajorMatrix<F>,
+                challenges: &$crate::ChallengeSet<'_, EF>,
+            ) -> $crate::AuxTraces<F, EF> {
+                self.air.build_aux_traces(main_trace, challenges)
+            }
+
+            fn try_build_aux_traces(
+                &self,
+                main_trace: &p3_matrix::dense::RowMajorMatrix<F>,
+                challenges: &$crate::ChallengeSet<'_, EF>,
+            ) -> Result<$crate::AuxTraces<F, EF>, $crate::ZeroDenominator> {
+                self.air.try_build_aux_traces(main_trace, challenges)
+            }
+
+            fn zero_denominator_policy(&self) -> $crate::ZeroDenominatorPolicy {
+                self.air.zero_denominator_policy()
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "minimal"))]
+pub(crate) use delegate_air_traits;
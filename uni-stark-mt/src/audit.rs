@@ -0,0 +1,35 @@
+//! Trace digests for storage auditing
+//!
+//! A [`TraceDigest`] lets an operator who keeps the witness store check, after the fact,
+//! which main trace a stored proof was produced from, without re-running the prover. The
+//! digest travels in the proof envelope only: it is never observed by the challenger, so
+//! attaching, changing or stripping it doesn't affect verification.
+//!
+//! The digest is keyed. Traces often have little entropy, so an unkeyed hash could be
+//! brute-forced to recover the witness from a published proof; with a secret key, the
+//! digest is meaningless to anyone but the key holder.
+
+use p3_field::Field;
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_symmetric::CryptographicHasher;
+
+/// Keyed Keccak-256 digest of a main trace.
+pub type TraceDigest = [u8; 32];
+
+/// Compute the keyed digest of `trace`.
+///
+/// Hashes the key length, the key, the trace dimensions and the `postcard` encoding of
+/// every value, so traces with the same values in a different shape get different digests.
+pub fn trace_digest<F: Field>(trace: &RowMajorMatrix<F>, key: &[u8]) -> TraceDigest {
+    let mut bytes = alloc::vec::Vec::new();
+    bytes.extend((key.len() as u64).to_le_bytes());
+    bytes.extend(key);
+    bytes.extend((trace.width() as u64).to_le_bytes());
+    bytes.extend((trace.height() as u64).to_le_bytes());
+    for value in &trace.values {
+        bytes = postcard::to_extend(value, bytes).expect("field elements are serializable");
+    }
+    Keccak256Hash {}.hash_iter(bytes)
+}
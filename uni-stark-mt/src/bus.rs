@@ -0,0 +1,109 @@
+//! Buses balanced across independently produced proofs
+//!
+//! In distributed proving, each table of one execution is proven on its own, possibly on
+//! another machine, and the tables talk over a shared bus (e.g. a LogUp argument). Each
+//! proof exposes its table's share of the bus sum as an auxiliary exposed value, marked by
+//! [`AuxTraceBuilder::bus_contribution_index`](crate::AuxTraceBuilder::bus_contribution_index),
+//! and the shares must add up to zero.
+//!
+//! The shares are only comparable if every table computed its share under the same bus
+//! challenges. Challenges sampled from a proof's own transcript differ from proof to
+//! proof, so AIRs taking part in a bus read its challenges from public values instead,
+//! fixed by the application once every table's main trace is committed, and the
+//! application checks all proofs were verified against the same ones.
+//!
+//! The chips of a machine, see [`prove_machine`](crate::prove_machine), draw their bus
+//! challenges from the machine's shared transcript instead, and
+//! [`verify_machine`](crate::verify_machine) checks their shares balance itself.
+
+use alloc::vec::Vec;
+
+use p3_field::Field;
+use serde::{Deserialize, Serialize};
+
+use crate::{Challenge, ChipProof, Proof, StarkGenericConfig, VerifyingKey};
+
+/// One proof's share of a bus sum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusContribution<EF> {
+    /// The table's share of the sum
+    pub sum: EF,
+}
+
+/// Error decoding a [`BusContribution`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusDecodeError {
+    /// The bytes don't encode a contribution
+    Malformed,
+    /// The bytes decode, but aren't the canonical encoding of what they decode to
+    NonCanonical,
+}
+
+/// The bus contributions don't add up to zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BusImbalance<EF> {
+    /// Sum of all contributions
+    pub sum: EF,
+}
+
+impl<EF: Field> BusContribution<EF> {
+    /// The contribution a verified `proof` exposes, if its AIR takes part in a bus.
+    ///
+    /// Only meaningful once `proof` has been verified against `vk`: the exposed value is
+    /// bound by the proof's transcript and checked by the AIR's constraints.
+    pub fn from_proof<SC>(vk: &VerifyingKey, proof: &Proof<SC>) -> Option<Self>
+    where
+        SC: StarkGenericConfig<Challenge = EF>,
+    {
+        let index = vk.bus_contribution_index?;
+        let sum: Challenge<SC> = *proof.aux_exposed_values.get(index)?;
+        Some(Self { sum })
+    }
+
+    /// The contribution a machine's chip exposes, if it takes part in a bus.
+    ///
+    /// As for [`from_proof`](Self::from_proof), only meaningful once the machine's proof
+    /// has been verified.
+    pub fn from_chip_proof(vk: &VerifyingKey, chip: &ChipProof<EF>) -> Option<Self> {
+        let index = vk.bus_contribution_index?;
+        let sum = *chip.aux_exposed_values.get(index)?;
+        Some(Self { sum })
+    }
+
+    /// Canonical `postcard` encoding, for sending contributions between machines.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        postcard::to_allocvec(self).expect("field elements are serializable")
+    }
+
+    /// Decode a contribution, accepting only its canonical encoding.
+    ///
+    /// # Errors
+    /// See [`BusDecodeError`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BusDecodeError> {
+        let contribution: Self =
+            postcard::from_bytes(bytes).map_err(|_| BusDecodeError::Malformed)?;
+        // A share encoded in two ways could be counted twice by byte-level deduplication
+        if contribution.to_bytes() != bytes {
+            return Err(BusDecodeError::NonCanonical);
+        }
+        Ok(contribution)
+    }
+}
+
+/// Check the `contributions` of all tables on a bus add up to zero.
+///
+/// # Errors
+/// [`BusImbalance`] with the total if it isn't zero.
+pub fn check_bus_balance<EF: Field>(
+    contributions: &[BusContribution<EF>],
+) -> Result<(), BusImbalance<EF>> {
+    let sum: EF = contributions
+        .iter()
+        .map(|contribution| contribution.sum)
+        .sum();
+    if sum == EF::ZERO {
+        Ok(())
+    } else {
+        Err(BusImbalance { sum })
+    }
+}
@@ -0,0 +1,186 @@
+//! Capability discovery
+//!
+//! Which gadgets and integrations are available depends on the cargo features the crate
+//! was built with, and a service linking it can't see those. [`capabilities`] describes
+//! the linked build, so tooling can check at startup that it supports what a workflow
+//! needs rather than failing halfway through:
+//!
+//! ```ignore
+//! if let Err(missing) = capabilities().check(&[Capability::Symbolic, Capability::Parallel]) {
+//!     panic!("this build lacks {missing:?}");
+//! }
+//! ```
+
+#[cfg(feature = "presets")]
+use p3_commit::Pcs;
+#[cfg(feature = "presets")]
+use p3_field::BasedVectorSpace;
+
+#[cfg(feature = "presets")]
+use crate::config::{baby_bear_poseidon2 as bb, goldilocks_keccak as gk};
+use crate::PROTOCOL_VERSION;
+
+/// An optional part of the crate, compiled in or out by cargo features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Multi-threaded proving and trace generation (`parallel`)
+    Parallel,
+
+    /// Tracing spans and events (`tracing`, without `minimal`)
+    Tracing,
+
+    /// Prover and verifier metrics reported through the `metrics` facade (`metrics`)
+    Metrics,
+
+    /// Timed prover reports and the counting allocator (`std`)
+    Std,
+
+    /// Helpers for tests of AIRs and the proof system (`test-utils`)
+    TestUtils,
+
+    /// Proving with the circle PCS (`circle`)
+    Circle,
+
+    /// In-circuit verifier folding (not with `minimal`)
+    Recursion,
+
+    /// Symbolic constraint extraction (not with `minimal`)
+    Symbolic,
+
+    /// Compilation of constraints to shared-subexpression programs (not with `minimal`)
+    ConstraintCompilation,
+
+    /// Degree lowering through helper columns (not with `minimal`)
+    DegreeLowering,
+
+    /// Structural proof diffing (not with `minimal`)
+    ProofDiff,
+}
+
+/// A ready-made configuration, see the [`config`](crate::config) presets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConfigPreset {
+    /// Name of the preset's module
+    pub name: &'static str,
+
+    /// Degree of the challenge field over the base field
+    pub extension_degree: usize,
+
+    /// Whether its PCS is zero-knowledge
+    pub zk: bool,
+}
+
+/// An encoding proofs can be written in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProofEncoding {
+    /// Any `serde` format: proofs, claims and verifying keys implement `Serialize` and
+    /// `Deserialize`
+    Serde,
+
+    /// The compact `postcard` encoding, which proof sizes are measured in
+    Postcard,
+
+    /// The versioned binary encoding of [`Proof::to_bytes`](crate::Proof::to_bytes), which
+    /// names its format, fields and PCS in a header
+    Versioned,
+}
+
+/// Description of the linked build of this crate, see [`capabilities`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Version of the crate
+    pub version: &'static str,
+
+    /// Version of the proving protocol, see [`PROTOCOL_VERSION`]
+    pub protocol_version: u32,
+
+    /// Optional parts compiled in
+    pub compiled: &'static [Capability],
+
+    /// Ready-made configurations, none without the `presets` feature
+    pub presets: &'static [ConfigPreset],
+
+    /// Encodings proofs can be written in
+    pub proof_encodings: &'static [ProofEncoding],
+}
+
+impl Capabilities {
+    /// Whether `capability` is compiled in.
+    pub fn has(&self, capability: Capability) -> bool {
+        self.compiled.contains(&capability)
+    }
+
+    /// Check that every one of `required` is compiled in.
+    ///
+    /// # Errors
+    /// The first of `required` that isn't.
+    pub fn check(&self, required: &[Capability]) -> Result<(), Capability> {
+        match required.iter().find(|&&capability| !self.has(capability)) {
+            Some(&missing) => Err(missing),
+            None => Ok(()),
+        }
+    }
+
+    /// The preset named `name`, if this build has it.
+    pub fn preset(&self, name: &str) -> Option<&ConfigPreset> {
+        self.presets.iter().find(|preset| preset.name == name)
+    }
+}
+
+/// Optional parts compiled into this build.
+const COMPILED: &[Capability] = &[
+    #[cfg(feature = "parallel")]
+    Capability::Parallel,
+    #[cfg(all(feature = "tracing", not(feature = "minimal")))]
+    Capability::Tracing,
+    #[cfg(feature = "metrics")]
+    Capability::Metrics,
+    #[cfg(feature = "std")]
+    Capability::Std,
+    #[cfg(feature = "test-utils")]
+    Capability::TestUtils,
+    #[cfg(feature = "circle")]
+    Capability::Circle,
+    #[cfg(not(feature = "minimal"))]
+    Capability::Recursion,
+    #[cfg(not(feature = "minimal"))]
+    Capability::Symbolic,
+    #[cfg(not(feature = "minimal"))]
+    Capability::ConstraintCompilation,
+    #[cfg(not(feature = "minimal"))]
+    Capability::DegreeLowering,
+    #[cfg(not(feature = "minimal"))]
+    Capability::ProofDiff,
+];
+
+/// The ready-made configurations, with the `presets` feature.
+const PRESETS: &[ConfigPreset] = &[
+    #[cfg(feature = "presets")]
+    ConfigPreset {
+        name: "baby_bear_poseidon2",
+        extension_degree: <bb::Challenge as BasedVectorSpace<bb::Val>>::DIMENSION,
+        zk: <bb::Pcs as Pcs<bb::Challenge, bb::Challenger>>::ZK,
+    },
+    #[cfg(feature = "presets")]
+    ConfigPreset {
+        name: "goldilocks_keccak",
+        extension_degree: <gk::Challenge as BasedVectorSpace<gk::Val>>::DIMENSION,
+        zk: <gk::Pcs as Pcs<gk::Challenge, gk::Challenger>>::ZK,
+    },
+];
+
+/// Describe the linked build of this crate: its version and the features it was
+/// compiled with.
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        protocol_version: PROTOCOL_VERSION,
+        compiled: COMPILED,
+        presets: PRESETS,
+        proof_encodings: &[
+            ProofEncoding::Serde,
+            ProofEncoding::Postcard,
+            ProofEncoding::Versioned,
+        ],
+    }
+}
@@ -0,0 +1,200 @@
+//! Named challenges for the auxiliary phase
+
+use alloc::vec::Vec;
+use core::ops::{Index, Range};
+
+use p3_challenger::{CanSample, FieldChallenger};
+use p3_field::{BasedVectorSpace, ExtensionField, Field};
+use serde::{Deserialize, Serialize};
+
+/// Challenges sampled for the auxiliary phase, addressed by name.
+///
+/// An AIR declares its challenges with
+/// [`AuxTraceBuilder::challenge_names`](crate::AuxTraceBuilder::challenge_names), e.g.
+/// `["logup_alpha", "perm_beta"]`. The prover and verifier sample one challenge per name,
+/// in declaration order, and the trace builders look them up by name rather than by
+/// position, so reordering or adding challenges cannot silently swap them.
+///
+/// # Example
+///
+/// ```ignore
+/// let alpha = challenges["logup_alpha"];
+/// let beta = challenges.get("perm_beta").expect("perm_beta was declared");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ChallengeSet<'a, EF> {
+    names: &'a [&'a str],
+    values: &'a [EF],
+}
+
+impl<'a, EF> ChallengeSet<'a, EF> {
+    /// Pair sampled `values` with the declared `names`, in order.
+    ///
+    /// # Panics
+    /// - If `names` and `values` have different lengths
+    /// - If a name is declared twice
+    pub fn new(names: &'a [&'a str], values: &'a [EF]) -> Self {
+        assert_eq!(
+            names.len(),
+            values.len(),
+            "Expected one challenge per declared name"
+        );
+        for (i, name) in names.iter().enumerate() {
+            assert!(
+                !names[..i].contains(name),
+                "Challenge `{name}` declared twice"
+            );
+        }
+        Self { names, values }
+    }
+
+    /// A set without challenges.
+    pub const fn empty() -> Self {
+        Self {
+            names: &[],
+            values: &[],
+        }
+    }
+
+    /// The challenge declared as `name`, if any.
+    pub fn get(&self, name: &str) -> Option<&'a EF> {
+        let values = self.values;
+        self.names
+            .iter()
+            .position(|declared| *declared == name)
+            .map(|i| &values[i])
+    }
+
+    /// Declared names, in sampling order.
+    pub const fn names(&self) -> &'a [&'a str] {
+        self.names
+    }
+
+    /// Sampled values, in the order of [`names()`](Self::names).
+    pub const fn values(&self) -> &'a [EF] {
+        self.values
+    }
+
+    /// Number of challenges.
+    pub const fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the set has no challenges.
+    pub const fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The challenges at positions `range`, as a set of their own.
+    pub(crate) fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            names: &self.names[range.clone()],
+            values: &self.values[range],
+        }
+    }
+}
+
+/// How the values of an AIR's challenges are drawn from the transcript.
+///
+/// See [`AuxTraceBuilder::challenge_sampling`](crate::AuxTraceBuilder::challenge_sampling).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeSampling {
+    /// One sampled element per challenge
+    #[default]
+    Independent,
+
+    /// A single sampled element `β`, the challenges being `β, β², β³, ...` in declaration
+    /// order
+    ///
+    /// A verifier, and any circuit verifying it, then draws one element and derives the
+    /// rest with multiplications. Each challenge is still uniformly distributed, but
+    /// they are no longer independent: arguments combining them must tolerate a soundness
+    /// loss growing with the degree of the powers, as for a random linear combination.
+    Powers,
+}
+
+impl ChallengeSampling {
+    /// Derive `count` challenges from elements drawn with `draw`.
+    pub(crate) fn sample<EF: Field>(self, count: usize, mut draw: impl FnMut() -> EF) -> Vec<EF> {
+        match self {
+            Self::Independent => (0..count).map(|_| draw()).collect(),
+            Self::Powers if count == 0 => Vec::new(),
+            Self::Powers => draw().powers().skip(1).take(count).collect(),
+        }
+    }
+}
+
+/// How each extension-field challenge is drawn from the challenger.
+///
+/// See [`StarkGenericConfig::extension_sampling`](crate::StarkGenericConfig::extension_sampling).
+/// PCS-internal challenges, e.g. FRI folding challenges, are drawn by the PCS itself
+/// and not affected.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtensionSampling {
+    /// One extension element, as the challenger samples it
+    #[default]
+    Direct,
+
+    /// One base-field element per extension coefficient
+    ///
+    /// For challengers whose extension sampling is biased or not specified in terms of
+    /// base-field samples, e.g. when a circuit re-derives the challenges.
+    BaseSamples,
+
+    /// As [`BaseSamples`](Self::BaseSamples), drawing again while the result lies in the
+    /// base field
+    ///
+    /// Such challenges can collide with base-field trace values, e.g. make a LogUp
+    /// denominator `α - x` vanish. Requires a proper extension field.
+    BaseSamplesRejecting,
+}
+
+impl ExtensionSampling {
+    /// Draw one extension-field challenge from `challenger`.
+    ///
+    /// # Panics
+    /// For [`BaseSamplesRejecting`](Self::BaseSamplesRejecting) if `EF` is the base field.
+    pub(crate) fn draw<F, EF, C>(self, challenger: &mut C) -> EF
+    where
+        F: Field,
+        EF: ExtensionField<F>,
+        C: FieldChallenger<F> + CanSample<EF>,
+    {
+        match self {
+            Self::Direct => challenger.sample(),
+            Self::BaseSamples => challenger.sample_algebra_element(),
+            Self::BaseSamplesRejecting => {
+                assert!(
+                    <EF as BasedVectorSpace<F>>::DIMENSION > 1,
+                    "rejecting base-field challenges needs a proper extension field"
+                );
+                loop {
+                    let challenge: EF = challenger.sample_algebra_element();
+                    if !challenge.is_in_basefield() {
+                        return challenge;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Encoding in the protocol identifier.
+    pub(crate) const fn id(self) -> u32 {
+        match self {
+            Self::Direct => 0,
+            Self::BaseSamples => 1,
+            Self::BaseSamplesRejecting => 2,
+        }
+    }
+}
+
+impl<EF> Index<&str> for ChallengeSet<'_, EF> {
+    type Output = EF;
+
+    /// # Panics
+    /// - If no challenge was declared as `name`
+    fn index(&self, name: &str) -> &EF {
+        self.get(name)
+            .unwrap_or_else(|| panic!("Unknown challenge `{name}`"))
+    }
+}
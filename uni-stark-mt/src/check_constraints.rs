@@ -0,0 +1,836 @@
+//! Constraint checking on concrete trace values
+//!
+//! The prover folds constraints over low-degree extensions, so a bad trace only shows up
+//! as a failed verification at the very end. [`DebugConstraintBuilder`] instead evaluates
+//! the AIR row by row on the actual trace values and records every constraint that does
+//! not vanish.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Write};
+
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PermutationAirBuilder,
+};
+use p3_field::{BasedVectorSpace, ExtensionField, Field};
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::stack::VerticalPair;
+use p3_matrix::Matrix;
+
+use crate::mock_prover::splitmix64;
+use crate::openings::check_boundary_constraints;
+use crate::rows::trace_row;
+use crate::statements::check_statement_layout;
+use crate::{
+    AuxBuilder, ChallengeSet, MultiTraceAir, OpeningSchedule, RotatedRows, StatementBuilder,
+    StatementSelectors, TraceProvenance, TraceRow,
+};
+
+/// A constraint that evaluated to a nonzero value on a concrete trace row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintViolation<EF> {
+    /// Row at which the constraint was evaluated
+    pub row: usize,
+
+    /// Index of the constraint, in the order `Air::eval` asserts them
+    pub constraint_index: usize,
+
+    /// Value of the constraint on this row
+    pub value: EF,
+}
+
+/// All constraint violations found by [`check_constraints`], in row order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintViolations<EF>(pub Vec<ConstraintViolation<EF>>);
+
+impl<EF: Debug> ConstraintViolations<EF> {
+    /// Describe every violation, one per line, naming the origin of its row when
+    /// `provenance` labels it.
+    pub fn report(&self, provenance: &TraceProvenance) -> String {
+        let mut report = String::new();
+        for violation in &self.0 {
+            write!(
+                report,
+                "row {}, constraint {}: {:?}",
+                violation.row, violation.constraint_index, violation.value
+            )
+            .expect("writing to a String can't fail");
+            if let Some(label) = provenance.label(violation.row) {
+                write!(report, " ({label})").expect("writing to a String can't fail");
+            }
+            report.push('\n');
+        }
+        report
+    }
+}
+
+/// Two consecutive rows of a trace, borrowed without copying.
+type RowPair<'a, T> = VerticalPair<RowMajorMatrixView<'a, T>, RowMajorMatrixView<'a, T>>;
+
+/// Builder for evaluating constraints on a single row of concrete trace values.
+///
+/// Unlike [`ProverFolder`](crate::ProverFolder), constraints are not combined with
+/// random challenges: each one is checked individually and any nonzero value is
+/// recorded as a [`ConstraintViolation`].
+pub struct DebugConstraintBuilder<'a, F: Field, EF: ExtensionField<F>> {
+    /// Index of the row being checked
+    row: usize,
+
+    /// Main trace values (local and next rows)
+    main: RowPair<'a, F>,
+
+    /// Auxiliary trace values (local and next rows)
+    /// Empty if no auxiliary trace
+    aux: RowPair<'a, EF>,
+
+    /// Base-field auxiliary trace values (local and next rows)
+    /// Empty if no base-field auxiliary columns
+    aux_base: RowPair<'a, F>,
+
+    /// Challenges the auxiliary trace was built with
+    challenges: ChallengeSet<'a, EF>,
+
+    /// Values exposed by the auxiliary phase
+    aux_exposed_values: &'a [EF],
+
+    /// Auxiliary trace values on the previous row, if the AIR opens them
+    aux_prev_row: &'a [EF],
+
+    /// Base-field auxiliary trace values on the previous row, if the AIR opens them
+    aux_base_prev_row: &'a [F],
+
+    /// Main trace values on the previous row, if the AIR opens them
+    main_prev_row: &'a [F],
+
+    /// Auxiliary trace values on the rows of the AIR's further rotations
+    aux_rotated: RotatedRows<'a, EF>,
+
+    /// Base-field auxiliary trace values on the rows of the AIR's further rotations
+    aux_base_rotated: RotatedRows<'a, F>,
+
+    /// Main trace values on the rows of the AIR's further rotations
+    main_rotated: RotatedRows<'a, F>,
+
+    /// Auxiliary trace values on the last row, if the AIR opens them
+    aux_last_row: &'a [EF],
+
+    /// Base-field auxiliary trace values on the last row, if the AIR opens them
+    aux_base_last_row: &'a [F],
+
+    /// Main trace values on the last row, if the AIR opens them
+    main_last_row: &'a [F],
+
+    /// Public input/output values
+    public_values: &'a [F],
+
+    /// Selector: 1 on first row, 0 elsewhere
+    is_first_row: F,
+
+    /// Selector: 1 on last row, 0 elsewhere
+    is_last_row: F,
+
+    /// Selector: 1 on all rows except last, 0 on last
+    is_transition: F,
+
+    /// Statement boundary selectors and public values
+    statements: StatementSelectors<'a, F>,
+
+    /// Current constraint index
+    constraint_index: usize,
+
+    /// Violations found so far
+    violations: &'a mut Vec<ConstraintViolation<EF>>,
+}
+
+impl<'a, F, EF> DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    fn record(&mut self, value: EF) {
+        if value != EF::ZERO {
+            self.violations.push(ConstraintViolation {
+                row: self.row,
+                constraint_index: self.constraint_index,
+                value,
+            });
+        }
+        self.constraint_index += 1;
+    }
+}
+
+impl<'a, F, EF> AirBuilder for DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    type F = F;
+    type Expr = F;
+    type Var = F;
+    type M = RowPair<'a, F>;
+
+    fn main(&self) -> Self::M {
+        self.main
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.is_first_row
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.is_last_row
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        assert_eq!(size, 2, "Only window size 2 is supported");
+        self.is_transition
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.record(EF::from(x.into()));
+    }
+}
+
+impl<'a, F, EF> AirBuilderWithPublicValues for DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    type PublicVar = F;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+impl<'a, F, EF> StatementBuilder for DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    fn is_first_statement_row(&self) -> Self::Expr {
+        self.statements.is_first_row
+    }
+
+    fn is_last_statement_row(&self) -> Self::Expr {
+        self.statements.is_last_row
+    }
+
+    fn is_statement_transition(&self) -> Self::Expr {
+        self.statements.is_transition
+    }
+
+    fn statement_public_values(&self) -> &[Self::Expr] {
+        self.statements.public_values
+    }
+}
+
+impl<'a, F, EF> ExtensionBuilder for DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    type EF = EF;
+    type ExprEF = EF;
+    type VarEF = EF;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.record(x.into());
+    }
+}
+
+impl<'a, F, EF> PermutationAirBuilder for DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    type MP = RowPair<'a, EF>;
+    type RandomVar = Self::VarEF;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.challenges()
+    }
+}
+
+impl<'a, F, EF> AuxBuilder for DebugConstraintBuilder<'a, F, EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    type MAux = RowPair<'a, EF>;
+
+    fn aux(&self) -> Self::MAux {
+        self.aux
+    }
+
+    fn aux_base(&self) -> Self::M {
+        self.aux_base
+    }
+
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF> {
+        self.challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
+
+    fn aux_prev_row(&self) -> &[Self::VarEF] {
+        self.aux_prev_row
+    }
+
+    fn aux_base_prev_row(&self) -> &[Self::Var] {
+        self.aux_base_prev_row
+    }
+
+    fn main_prev_row(&self) -> &[Self::Var] {
+        self.main_prev_row
+    }
+
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF] {
+        self.aux_rotated.row(offset)
+    }
+
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.aux_base_rotated.row(offset)
+    }
+
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.main_rotated.row(offset)
+    }
+
+    fn aux_last_row(&self) -> &[Self::VarEF] {
+        self.aux_last_row
+    }
+
+    fn aux_base_last_row(&self) -> &[Self::Var] {
+        self.aux_base_last_row
+    }
+
+    fn main_last_row(&self) -> &[Self::Var] {
+        self.main_last_row
+    }
+}
+
+/// Borrow rows `row` and `row + 1` (wrapping at `height`) of `trace` as a two-row window,
+/// or row `row` alone as a one-row window if `openings` skips the next row.
+///
+/// `height` is passed explicitly so that zero-width traces still yield (empty) rows.
+fn row_pair<T: Clone + Send + Sync>(
+    trace: &RowMajorMatrix<T>,
+    row: usize,
+    height: usize,
+    openings: OpeningSchedule,
+) -> RowPair<'_, T> {
+    let row = TraceRow(row);
+    let next = if openings.next_row {
+        trace_row(trace, row.next(height))
+    } else {
+        &[]
+    };
+    VerticalPair::new(
+        RowMajorMatrixView::new_row(trace_row(trace, row)),
+        RowMajorMatrixView::new(next, trace.width),
+    )
+}
+
+/// The row before `row` (wrapping at `height`) of `trace` if `openings` opens it, else
+/// nothing.
+fn prev_row<T>(
+    trace: &RowMajorMatrix<T>,
+    row: usize,
+    height: usize,
+    openings: OpeningSchedule,
+) -> &[T] {
+    if openings.prev_row {
+        trace_row(trace, TraceRow(row).prev(height))
+    } else {
+        &[]
+    }
+}
+
+/// The rows of `trace` at each of the further rotations `openings` opens, from `row`
+/// (wrapping at `height`).
+fn rotated_rows<T: Clone>(
+    trace: &RowMajorMatrix<T>,
+    row: usize,
+    height: usize,
+    openings: OpeningSchedule,
+) -> Vec<Vec<T>> {
+    openings
+        .rotations
+        .iter()
+        .map(|offset| trace_row(trace, TraceRow(row).rotate(offset, height)).to_vec())
+        .collect()
+}
+
+/// The last of the `height` rows of `trace` if `openings` opens it, else nothing.
+fn last_row<T>(trace: &RowMajorMatrix<T>, height: usize, openings: OpeningSchedule) -> &[T] {
+    if openings.last_row {
+        trace_row(trace, TraceRow::last(height))
+    } else {
+        &[]
+    }
+}
+
+/// Check that concrete traces satisfy every constraint of `air`.
+///
+/// No PCS or challenger is involved, which makes this suitable for unit-testing AIRs.
+/// If `aux_trace` is `None` and the AIR has auxiliary columns, they are built from
+/// `challenges` with [`build_aux_traces`](crate::AuxTraceBuilder::build_aux_traces).
+/// Base-field auxiliary columns are always built that way. `challenges` are given in the
+/// order of [`challenge_names`](crate::AuxTraceBuilder::challenge_names), and are also
+/// what the constraints see through [`AuxBuilder::challenge`]. Values the AIR exposes are
+/// always taken from `build_aux_traces`.
+///
+/// Rows punctured by [`PuncturedAir`](crate::PuncturedAir) are not checked.
+///
+/// # Errors
+/// Every constraint that doesn't vanish, on every row.
+///
+/// # Panics
+/// - If the number of `challenges` doesn't match `challenge_names`
+/// - If the AIR's statements don't split the trace and public values evenly, see
+///   [`num_statements`](crate::AirLayout::num_statements)
+/// - If a boundary constraint names a row, column or public value out of range, see
+///   [`boundary_constraints`](crate::AirLayout::boundary_constraints)
+/// - If building the auxiliary trace hits a zero denominator, see
+///   [`try_build_aux_traces`](crate::AuxTraceBuilder::try_build_aux_traces)
+pub fn check_constraints<F, EF, A>(
+    air: &A,
+    main_trace: &RowMajorMatrix<F>,
+    aux_trace: Option<&RowMajorMatrix<EF>>,
+    challenges: &[EF],
+    public_values: &[F],
+) -> Result<(), ConstraintViolations<EF>>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    let names = air.challenge_names();
+    let challenges = ChallengeSet::new(&names, challenges);
+    let needs_build = (aux_trace.is_none() && air.aux_width() > 0)
+        || air.aux_base_width() > 0
+        || air.num_aux_exposed_values() > 0;
+    let built = needs_build.then(|| {
+        air.try_build_aux_traces(main_trace, &challenges)
+            .unwrap_or_else(|zero| panic!("Building the auxiliary trace failed: {zero:?}"))
+    });
+    let aux_trace = aux_trace.or(built.as_ref().map(|built| &built.ext));
+    let aux_base_trace = built.as_ref().map(|built| &built.base);
+    let aux_exposed_values = built.as_ref().map_or(&[][..], |built| &built.exposed);
+
+    let violations = collect_constraint_violations(
+        air,
+        main_trace,
+        aux_trace,
+        aux_base_trace,
+        challenges,
+        aux_exposed_values,
+        public_values,
+    );
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ConstraintViolations(violations))
+    }
+}
+
+/// Evaluate every constraint of `air` on every row of the given traces, except the
+/// punctured ones, then its boundary constraints, numbered after the others.
+///
+/// Returns all violations, in row order.
+pub(crate) fn collect_constraint_violations<F, EF, A>(
+    air: &A,
+    main: &RowMajorMatrix<F>,
+    aux: Option<&RowMajorMatrix<EF>>,
+    aux_base: Option<&RowMajorMatrix<F>>,
+    challenges: ChallengeSet<'_, EF>,
+    aux_exposed_values: &[EF],
+    public_values: &[F],
+) -> Vec<ConstraintViolation<EF>>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    let height = main.height();
+    let constrained_rows = height.saturating_sub(air.num_punctured_rows());
+    let empty_aux = RowMajorMatrix::new(Vec::new(), 0);
+    let aux = aux.unwrap_or(&empty_aux);
+    let empty_aux_base = RowMajorMatrix::new(Vec::new(), 0);
+    let aux_base = aux_base.unwrap_or(&empty_aux_base);
+    // A shorter auxiliary trace repeats down the main trace
+    let period = |width: usize, trace_height: usize| if width > 0 { trace_height } else { height };
+    let aux_height = period(aux.width(), aux.height());
+    let aux_base_height = period(aux_base.width(), aux_base.height());
+
+    let num_statements = air.num_statements();
+    if let Err(message) = check_statement_layout(num_statements, public_values.len(), height) {
+        panic!("{message}");
+    }
+    let boundary_constraints = air.boundary_constraints();
+    if let Err(message) = check_boundary_constraints(
+        &boundary_constraints,
+        height,
+        main.width(),
+        public_values.len(),
+    ) {
+        panic!("{message}");
+    }
+    let (main_openings, aux_openings) = (air.main_openings(), air.aux_openings());
+    let main_last_row = last_row(main, height, main_openings);
+    let aux_last_row = last_row(aux, aux_height, aux_openings);
+    let aux_base_last_row = last_row(aux_base, aux_base_height, aux_openings);
+
+    let statement_height = height / num_statements;
+    let per_statement = public_values.len() / num_statements;
+    // Statement public values are only defined on boundary rows, as in the quotient
+    let off_boundary = F::zero_vec(per_statement);
+
+    let mut violations = Vec::new();
+    let mut num_constraints = 0;
+    for row in 0..constrained_rows {
+        let is_first_statement_row = row % statement_height == 0;
+        let is_last_statement_row = row % statement_height == statement_height - 1;
+        let statement = row / statement_height;
+        let statement_public_values = if is_first_statement_row || is_last_statement_row {
+            &public_values[statement * per_statement..(statement + 1) * per_statement]
+        } else {
+            &off_boundary
+        };
+        let main_rotated = rotated_rows(main, row, height, main_openings);
+        let (aux_row, aux_base_row) = (row % aux_height, row % aux_base_height);
+        let aux_rotated = rotated_rows(aux, aux_row, aux_height, aux_openings);
+        let aux_base_rotated = rotated_rows(aux_base, aux_base_row, aux_base_height, aux_openings);
+        let mut builder = DebugConstraintBuilder {
+            row,
+            main: row_pair(main, row, height, main_openings),
+            aux: row_pair(aux, aux_row, aux_height, aux_openings),
+            aux_base: row_pair(aux_base, aux_base_row, aux_base_height, aux_openings),
+            challenges,
+            aux_exposed_values,
+            aux_prev_row: prev_row(aux, aux_row, aux_height, aux_openings),
+            aux_base_prev_row: prev_row(aux_base, aux_base_row, aux_base_height, aux_openings),
+            main_prev_row: prev_row(main, row, height, main_openings),
+            aux_rotated: RotatedRows {
+                offsets: aux_openings.rotations,
+                rows: &aux_rotated,
+            },
+            aux_base_rotated: RotatedRows {
+                offsets: aux_openings.rotations,
+                rows: &aux_base_rotated,
+            },
+            main_rotated: RotatedRows {
+                offsets: main_openings.rotations,
+                rows: &main_rotated,
+            },
+            aux_last_row,
+            aux_base_last_row,
+            main_last_row,
+            public_values,
+            is_first_row: F::from_bool(row == 0),
+            is_last_row: F::from_bool(row == height - 1),
+            is_transition: F::from_bool(row != height - 1),
+            statements: StatementSelectors {
+                is_first_row: F::from_bool(is_first_statement_row),
+                is_last_row: F::from_bool(is_last_statement_row),
+                is_transition: F::from_bool(!is_last_statement_row),
+                public_values: statement_public_values,
+            },
+            constraint_index: 0,
+            violations: &mut violations,
+        };
+        air.eval(&mut builder);
+        num_constraints = builder.constraint_index;
+    }
+
+    // Pinned cells hold their public values, whether or not their row is punctured
+    for (index, constraint) in boundary_constraints.iter().enumerate() {
+        let cell = trace_row(main, TraceRow(constraint.row))[constraint.column];
+        let value = cell - public_values[constraint.public_value];
+        if value != F::ZERO {
+            violations.push(ConstraintViolation {
+                row: constraint.row,
+                constraint_index: num_constraints + index,
+                value: value.into(),
+            });
+        }
+    }
+    violations.sort_by_key(|violation| violation.row);
+    violations
+}
+
+/// Number of constraints asserted by `air`.
+///
+/// Evaluates the AIR once on an all-zero window with all-zero challenges; the values are
+/// irrelevant, only the number of assertions is counted.
+pub(crate) fn count_constraints<F, EF, A>(
+    air: &A,
+    main_width: usize,
+    aux_width: usize,
+    aux_base_width: usize,
+    num_public_values: usize,
+) -> usize
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    evaluate_on_window(
+        air,
+        main_width,
+        aux_width,
+        aux_base_width,
+        num_public_values,
+        |_| F::ZERO,
+    )
+    .0
+}
+
+/// Largest degree [`constraint_degree`] measures.
+const MAX_PROBED_DEGREE: usize = 16;
+
+/// Largest degree of the constraints of `air`, counting trace values and row selectors
+/// as degree 1, and public values, challenges and exposed values as constants.
+///
+/// Evaluates the AIR along a pseudo-random line through its window, at
+/// `MAX_PROBED_DEGREE + 1` points. On it, each constraint is a univariate polynomial of
+/// the constraint's degree, except with negligible probability, and that degree is the
+/// order of its last nonvanishing finite difference.
+pub(crate) fn constraint_degree<F, EF, A>(
+    air: &A,
+    main_width: usize,
+    aux_width: usize,
+    aux_base_width: usize,
+    num_public_values: usize,
+) -> usize
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    let evaluations: Vec<Vec<EF>> = (0..=MAX_PROBED_DEGREE)
+        .map(|t| {
+            let (t, mut state) = (F::from_usize(t), u64::from_le_bytes(*b"AIRdegre"));
+            let (num_constraints, violations) = evaluate_on_window::<F, EF, A>(
+                air,
+                main_width,
+                aux_width,
+                aux_base_width,
+                num_public_values,
+                |kind| {
+                    let base = F::from_u64(splitmix64(&mut state));
+                    let direction = F::from_u64(splitmix64(&mut state));
+                    match kind {
+                        WindowValue::Variable => base + t * direction,
+                        WindowValue::Constant => base,
+                    }
+                },
+            );
+            let mut values = EF::zero_vec(num_constraints);
+            for violation in violations {
+                values[violation.constraint_index] = violation.value;
+            }
+            values
+        })
+        .collect();
+    (0..evaluations[0].len())
+        .map(|index| {
+            let mut differences: Vec<EF> = evaluations.iter().map(|values| values[index]).collect();
+            let mut degree = 0;
+            for order in 0..=MAX_PROBED_DEGREE {
+                if differences[0] != EF::ZERO {
+                    degree = order;
+                }
+                differences = differences
+                    .windows(2)
+                    .map(|pair| pair[1] - pair[0])
+                    .collect();
+            }
+            degree
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// What a value drawn by [`evaluate_on_window`] stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum WindowValue {
+    /// A trace value or a row selector, which varies over the trace domain
+    Variable,
+    /// A public value, challenge or exposed value, constant over the trace domain
+    Constant,
+}
+
+/// Evaluate `air` once on a window of two rows, with every trace value, selector, public
+/// value, challenge and exposed value drawn from `value` in a fixed order, each told
+/// whether it is a [`WindowValue::Variable`] or a [`WindowValue::Constant`].
+///
+/// Returns the number of constraints asserted, and each one that doesn't vanish with its
+/// value. On pseudo-random values, these identify the constraint polynomials themselves:
+/// two AIRs asserting different constraints disagree on them except with negligible
+/// probability.
+pub(crate) fn evaluate_on_window<F, EF, A>(
+    air: &A,
+    main_width: usize,
+    aux_width: usize,
+    aux_base_width: usize,
+    num_public_values: usize,
+    mut value: impl FnMut(WindowValue) -> F,
+) -> (usize, Vec<ConstraintViolation<EF>>)
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    const HEIGHT: usize = 2;
+    let main = RowMajorMatrix::new(
+        (0..HEIGHT * main_width)
+            .map(|_| value(WindowValue::Variable))
+            .collect(),
+        main_width,
+    );
+    let aux = RowMajorMatrix::new(
+        (0..HEIGHT * aux_width)
+            .map(|_| {
+                <EF as BasedVectorSpace<F>>::from_basis_coefficients_fn(|_| {
+                    value(WindowValue::Variable)
+                })
+            })
+            .collect(),
+        aux_width,
+    );
+    let aux_base = RowMajorMatrix::new(
+        (0..HEIGHT * aux_base_width)
+            .map(|_| value(WindowValue::Variable))
+            .collect(),
+        aux_base_width,
+    );
+    let public_values: Vec<F> = (0..num_public_values)
+        .map(|_| value(WindowValue::Constant))
+        .collect();
+    let challenge_names = air.challenge_names();
+    let challenge_values: Vec<EF> = (0..challenge_names.len())
+        .map(|_| {
+            <EF as BasedVectorSpace<F>>::from_basis_coefficients_fn(|_| {
+                value(WindowValue::Constant)
+            })
+        })
+        .collect();
+    let aux_exposed_values: Vec<EF> = (0..air.num_aux_exposed_values())
+        .map(|_| {
+            <EF as BasedVectorSpace<F>>::from_basis_coefficients_fn(|_| {
+                value(WindowValue::Constant)
+            })
+        })
+        .collect();
+    let (main_openings, aux_openings) = (air.main_openings(), air.aux_openings());
+    let statement_public_values: Vec<F> = (0..num_public_values / air.num_statements().max(1))
+        .map(|_| value(WindowValue::Constant))
+        .collect();
+
+    // The last row is opened once, as constants
+    let main_last: Vec<F> = (0..main_width * usize::from(main_openings.last_row))
+        .map(|_| value(WindowValue::Constant))
+        .collect();
+    let aux_last: Vec<EF> = (0..aux_width * usize::from(aux_openings.last_row))
+        .map(|_| {
+            <EF as BasedVectorSpace<F>>::from_basis_coefficients_fn(|_| {
+                value(WindowValue::Constant)
+            })
+        })
+        .collect();
+    let aux_base_last: Vec<F> = (0..aux_base_width * usize::from(aux_openings.last_row))
+        .map(|_| value(WindowValue::Constant))
+        .collect();
+
+    let main_rotated = rotated_rows(&main, 0, HEIGHT, main_openings);
+    let aux_rotated = rotated_rows(&aux, 0, HEIGHT, aux_openings);
+    let aux_base_rotated = rotated_rows(&aux_base, 0, HEIGHT, aux_openings);
+
+    let mut violations = Vec::new();
+    let mut builder = DebugConstraintBuilder {
+        row: 0,
+        main: row_pair(&main, 0, HEIGHT, main_openings),
+        aux: row_pair(&aux, 0, HEIGHT, aux_openings),
+        aux_base: row_pair(&aux_base, 0, HEIGHT, aux_openings),
+        challenges: ChallengeSet::new(&challenge_names, &challenge_values),
+        aux_exposed_values: &aux_exposed_values,
+        aux_prev_row: prev_row(&aux, 0, HEIGHT, aux_openings),
+        aux_base_prev_row: prev_row(&aux_base, 0, HEIGHT, aux_openings),
+        main_prev_row: prev_row(&main, 0, HEIGHT, main_openings),
+        aux_rotated: RotatedRows {
+            offsets: aux_openings.rotations,
+            rows: &aux_rotated,
+        },
+        aux_base_rotated: RotatedRows {
+            offsets: aux_openings.rotations,
+            rows: &aux_base_rotated,
+        },
+        main_rotated: RotatedRows {
+            offsets: main_openings.rotations,
+            rows: &main_rotated,
+        },
+        aux_last_row: &aux_last,
+        aux_base_last_row: &aux_base_last,
+        main_last_row: &main_last,
+        public_values: &public_values,
+        is_first_row: value(WindowValue::Variable),
+        is_last_row: value(WindowValue::Variable),
+        is_transition: value(WindowValue::Variable),
+        statements: StatementSelectors {
+            is_first_row: value(WindowValue::Variable),
+            is_last_row: value(WindowValue::Variable),
+            is_transition: value(WindowValue::Variable),
+            public_values: &statement_public_values,
+        },
+        constraint_index: 0,
+        violations: &mut violations,
+    };
+    air.eval(&mut builder);
+    (builder.constraint_index, violations)
+}
+
+/// Panic with a report of every violated constraint if the traces don't satisfy `air`.
+#[cfg(all(debug_assertions, not(feature = "minimal")))]
+pub(crate) fn debug_assert_constraints<F, EF, A>(
+    air: &A,
+    main: &RowMajorMatrix<F>,
+    aux: Option<&RowMajorMatrix<EF>>,
+    aux_base: Option<&RowMajorMatrix<F>>,
+    challenges: ChallengeSet<'_, EF>,
+    aux_exposed_values: &[EF],
+    public_values: &[F],
+) where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    let violations = collect_constraint_violations(
+        air,
+        main,
+        aux,
+        aux_base,
+        challenges,
+        aux_exposed_values,
+        public_values,
+    );
+    if let Some(first) = violations.first() {
+        panic!(
+            "{} constraint violation(s); first at row {}, constraint {}: {:?}",
+            violations.len(),
+            first.row,
+            first.constraint_index,
+            first.value
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Constraints compiled to a flat program
+//!
+//! Evaluating an AIR through `Air::eval` rebuilds every constraint expression on every
+//! row, recomputing the subexpressions it writes out more than once. A
+//! [`ConstraintProgram`] is compiled once from the AIR's [`SymbolicConstraints`]: a
+//! straight-line list of [`Instruction`]s writing one register each, with structurally
+//! equal subexpressions shared, run row by row by a small interpreter.
+//!
+//! [`compile_air`] wraps an AIR into a [`CompiledAir`] that evaluates its program instead
+//! of `Air::eval`, under the prover, the verifier and the debug builders alike:
+//!
+//! ```ignore
+//! let compiled = compile_air::<Val, Challenge, _>(&air);
+//! let proof = prove(&config, &compiled, main_trace, &public_values)?;
+//! verify(&config, &compiled, &proof, &public_values)?;
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use p3_air::{Air, BaseAir};
+use p3_field::{ExtensionField, Field};
+use p3_matrix::Matrix;
+
+use crate::air::delegate_air_traits;
+use crate::program::eval_program;
+use crate::{
+    symbolic_constraints, AuxBuilder, CompiledConstraint, ConstraintProgram, Entry, Instruction,
+    Leaf, MultiTraceAir, StatementBuilder, SymbolicAirBuilder, SymbolicConstraints,
+    SymbolicExpression,
+};
+
+impl<F: Field> ConstraintProgram<F> {
+    /// Compile `symbolic`, sharing structurally equal subexpressions.
+    pub fn compile(symbolic: &SymbolicConstraints<F>) -> Self {
+        let mut compiler = Compiler {
+            program: Self {
+                instructions: Vec::new(),
+                extension: Vec::new(),
+                constraints: Vec::with_capacity(symbolic.constraints.len()),
+            },
+            visited: BTreeMap::new(),
+            challenges: symbolic
+                .variables
+                .iter()
+                .filter_map(|entry| match entry {
+                    Entry::Challenge(name) => Some(*name),
+                    _ => None,
+                })
+                .collect(),
+        };
+        for constraint in &symbolic.constraints {
+            let register = compiler.compile(&constraint.expr);
+            compiler.program.constraints.push(CompiledConstraint {
+                register,
+                extension: constraint.extension,
+            });
+        }
+        compiler.program
+    }
+}
+
+/// State of [`ConstraintProgram::compile`].
+struct Compiler<F> {
+    /// The program compiled so far
+    program: ConstraintProgram<F>,
+
+    /// Register of each expression node already compiled, by address, so subtrees an
+    /// AIR shares through cloned expressions are only walked once
+    visited: BTreeMap<*const SymbolicExpression<F>, usize>,
+
+    /// Names of the challenges, in declaration order
+    challenges: Vec<&'static str>,
+}
+
+impl<F: Field> Compiler<F> {
+    /// Compile `expr`, returning the register holding it.
+    fn compile(&mut self, expr: &SymbolicExpression<F>) -> usize {
+        let address = expr as *const SymbolicExpression<F>;
+        if let Some(&register) = self.visited.get(&address) {
+            return register;
+        }
+        let instruction = match expr {
+            SymbolicExpression::Add(a, b) => {
+                let (a, b) = (self.compile(a), self.compile(b));
+                Instruction::Add(a.min(b), a.max(b))
+            }
+            SymbolicExpression::Sub(a, b) => Instruction::Sub(self.compile(a), self.compile(b)),
+            SymbolicExpression::Neg(a) => Instruction::Neg(self.compile(a)),
+            SymbolicExpression::Mul(a, b) => {
+                let (a, b) = (self.compile(a), self.compile(b));
+                Instruction::Mul(a.min(b), a.max(b))
+            }
+            leaf => Instruction::Leaf(self.leaf(leaf)),
+        };
+        let register = self.push(instruction);
+        self.visited.insert(address, register);
+        register
+    }
+
+    /// The program leaf standing for the expression leaf `leaf`.
+    fn leaf(&self, leaf: &SymbolicExpression<F>) -> Leaf<F> {
+        match leaf {
+            SymbolicExpression::Variable(v) => match v.entry {
+                Entry::Main { row, column } => Leaf::Main { row, column },
+                Entry::Aux { row, column } => Leaf::Aux { row, column },
+                Entry::AuxBase { row, column } => Leaf::AuxBase { row, column },
+                Entry::Public(index) => Leaf::Public(index),
+                Entry::Challenge(name) => Leaf::Challenge(
+                    self.challenges
+                        .iter()
+                        .position(|&declared| declared == name)
+                        .expect("constraints only read declared challenges"),
+                ),
+                Entry::AuxExposed(index) => Leaf::AuxExposed(index),
+                Entry::StatementPublic(index) => Leaf::StatementPublic(index),
+            },
+            SymbolicExpression::IsFirstRow => Leaf::IsFirstRow,
+            SymbolicExpression::IsLastRow => Leaf::IsLastRow,
+            SymbolicExpression::IsTransition => Leaf::IsTransition,
+            SymbolicExpression::IsFirstStatementRow => Leaf::IsFirstStatementRow,
+            SymbolicExpression::IsLastStatementRow => Leaf::IsLastStatementRow,
+            SymbolicExpression::IsStatementTransition => Leaf::IsStatementTransition,
+            SymbolicExpression::Constant(c) => Leaf::Constant(*c),
+            _ => unreachable!("only leaves are compiled to leaves"),
+        }
+    }
+
+    /// The register computing `instruction`, appending it unless one already does.
+    fn push(&mut self, instruction: Instruction<F>) -> usize {
+        let program = &mut self.program;
+        if let Some(register) = program.instructions.iter().position(|i| *i == instruction) {
+            return register;
+        }
+        let is_extension = match &instruction {
+            Instruction::Leaf(leaf) => leaf.is_extension(),
+            Instruction::Add(a, b) | Instruction::Sub(a, b) | Instruction::Mul(a, b) => {
+                program.extension[*a] || program.extension[*b]
+            }
+            Instruction::Neg(a) => program.extension[*a],
+        };
+        program.instructions.push(instruction);
+        program.extension.push(is_extension);
+        program.instructions.len() - 1
+    }
+}
+
+/// An AIR evaluated through its [`ConstraintProgram`], see [`compile_air`].
+///
+/// Everything but the constraint evaluation is the wrapped AIR's.
+pub struct CompiledAir<'a, F, A> {
+    /// The wrapped AIR
+    air: &'a A,
+
+    /// Its constraints, compiled
+    program: ConstraintProgram<F>,
+}
+
+/// Compile the constraints of `air` into a [`ConstraintProgram`].
+///
+/// `EF` is only used to query the AIR's layout, see [`symbolic_constraints`].
+pub fn compile_air<F, EF, A>(air: &A) -> CompiledAir<'_, F, A>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<SymbolicAirBuilder<'a, F>>,
+{
+    let program = ConstraintProgram::compile(&symbolic_constraints::<F, EF, A>(air));
+    CompiledAir { air, program }
+}
+
+impl<'a, F: Field, A> CompiledAir<'a, F, A> {
+    /// The compiled constraints.
+    pub const fn program(&self) -> &ConstraintProgram<F> {
+        &self.program
+    }
+
+    /// The wrapped AIR.
+    pub const fn inner(&self) -> &'a A {
+        self.air
+    }
+}
+
+impl<'a, F: Field, A: BaseAir<F>> BaseAir<F> for CompiledAir<'a, F, A> {
+    fn width(&self) -> usize {
+        self.air.width()
+    }
+}
+
+delegate_air_traits!(CompiledAir);
+
+impl<'a, AB, A> Air<AB> for CompiledAir<'a, AB::F, A>
+where
+    AB: AuxBuilder + StatementBuilder,
+    AB::F: Field,
+    AB::MAux: Matrix<AB::VarEF>,
+    A: BaseAir<AB::F>,
+{
+    fn eval(&self, builder: &mut AB) {
+        eval_program(&self.program, builder);
+    }
+}
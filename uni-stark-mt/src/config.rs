@@ -1,8 +1,35 @@
 //! Configuration types for multi-trace STARK
+//!
+//! Ready-made configurations live in the preset submodules, e.g.
+//! [`baby_bear_poseidon2`] and [`goldilocks_keccak`], behind the `presets` feature.
 
-use p3_challenger::{CanObserve, CanSample, FieldChallenger};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use p3_challenger::{CanObserve, CanSample, FieldChallenger, GrindingChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::ExtensionField;
+use p3_field::{BasedVectorSpace, ExtensionField, PrimeCharacteristicRing};
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_symmetric::CryptographicHasher;
+
+#[cfg(feature = "presets")]
+pub mod baby_bear_poseidon2;
+#[cfg(feature = "presets")]
+mod builder;
+#[cfg(feature = "presets")]
+pub mod goldilocks_keccak;
+
+#[cfg(feature = "presets")]
+pub use builder::*;
+
+use crate::pcs::max_log_trace_size;
+use crate::prover::LOG_QUOTIENT_DEGREE;
+use crate::{
+    ExtensionSampling, SecurityError, SecurityLevel, SecurityParameters, SecurityTarget,
+    TraceDomain,
+};
 
 /// Domain type from the PCS
 pub type Domain<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
@@ -27,6 +54,24 @@ pub type PackedChallenge<SC> =
 /// Challenger type
 pub type Challenger<SC> = <SC as StarkGenericConfig>::Challenger;
 
+/// Commitment type from the PCS
+pub type Commitment<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Commitment;
+
+/// Prover data kept by the PCS for each commitment
+pub type ProverData<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::ProverData;
+
+/// Opening proof type from the PCS
+pub type PcsProof<SC> = <<SC as StarkGenericConfig>::Pcs as Pcs<
+    <SC as StarkGenericConfig>::Challenge,
+    <SC as StarkGenericConfig>::Challenger,
+>>::Proof;
+
 /// Generic STARK configuration trait matching upstream p3-uni-stark pattern
 pub trait StarkGenericConfig {
     /// Polynomial commitment scheme
@@ -37,6 +82,7 @@ pub trait StarkGenericConfig {
 
     /// Fiat-Shamir challenger
     type Challenger: FieldChallenger<Val<Self>>
+        + GrindingChallenger<Witness = Val<Self>>
         + CanObserve<<Self::Pcs as Pcs<Self::Challenge, Self::Challenger>>::Commitment>
         + CanSample<Self::Challenge>;
 
@@ -50,6 +96,151 @@ pub trait StarkGenericConfig {
     fn is_zk(&self) -> usize {
         Self::Pcs::ZK as usize
     }
+
+    /// Most main trace columns committed under one commitment, or `None` for no limit.
+    ///
+    /// The PCS builds one Merkle tree per commitment over the LDE of all its columns.
+    /// Very wide traces are split into batches of at most this many columns, each
+    /// committed and opened separately, which bounds the peak memory of tree building.
+    /// The prover and verifier must agree on it.
+    fn main_commit_batch_width(&self) -> Option<usize> {
+        None
+    }
+
+    /// Application domain separator, absorbed first by every transcript.
+    ///
+    /// Binds proofs to the application (and e.g. its version or deployment) that made
+    /// them, so a proof for one application can't be replayed to another using the same
+    /// AIR. The prover and verifier must agree on it. Empty by default.
+    fn domain_separator(&self) -> &[u8] {
+        &[]
+    }
+
+    /// How the prover and verifier draw their extension-field challenges, bound into the
+    /// [`ProtocolId`](crate::ProtocolId). [`ExtensionSampling::Direct`] by default.
+    fn extension_sampling(&self) -> ExtensionSampling {
+        ExtensionSampling::Direct
+    }
+
+    /// Whether proofs of AIRs without an auxiliary phase still go through one, with no
+    /// challenges and a [placeholder commitment](placeholder_aux_commitment).
+    ///
+    /// Every proof then has the same transcript shape, whether or not its AIR has
+    /// auxiliary columns, which keeps e.g. recursive verifiers of heterogeneous tables
+    /// simple. Bound into the [`ProtocolId`](crate::ProtocolId). Off by default.
+    fn uniform_aux_phase(&self) -> bool {
+        false
+    }
+
+    /// Number of out-of-domain points the constraints are checked at.
+    ///
+    /// Each point is sampled after the quotient commitment; every trace is opened at all
+    /// of them and the verifier checks the quotient identity at each. One point gives the
+    /// usual DEEP soundness error, a degree bound over the size of the extension field;
+    /// further points multiply it away for deployments that want a margin under conjectured
+    /// soundness, at the cost of more opened values. Bound into the
+    /// [`ProtocolId`](crate::ProtocolId). 1 by default.
+    fn num_ood_points(&self) -> usize {
+        1
+    }
+
+    /// Proof-of-work bits the prover grinds before drawing the out-of-domain points.
+    ///
+    /// Every attempt at a quotient commitment that makes the out-of-domain check pass
+    /// costs `2^pow_bits` hashes, so deployments can buy back security bits with prover
+    /// time instead of larger proofs. The witness travels in the proof; 0 skips grinding
+    /// and leaves the transcript as without it. Bound into the
+    /// [`ProtocolId`](crate::ProtocolId). 0 by default.
+    fn pow_bits(&self) -> usize {
+        0
+    }
+
+    /// The parameters the soundness of proofs under this configuration depends on, if
+    /// known.
+    ///
+    /// The PCS doesn't expose its FRI parameters, so configurations record them on the
+    /// side; [`StarkConfigBuilder`] does. `None` by default.
+    fn security_parameters(&self) -> Option<SecurityParameters> {
+        None
+    }
+
+    /// log2 of the tallest trace proofs under this configuration may have, if lower than
+    /// the PCS's largest domain allows.
+    ///
+    /// The prover refuses taller traces, and the verifier rejects a proof claiming one
+    /// before building any domain for it. Deployments that never prove tall traces cap
+    /// the work a hostile proof can ask of their verifiers. `None` by default.
+    fn max_log_degree(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Column ranges of the main trace commitments for a trace of `width` columns, see
+/// [`StarkGenericConfig::main_commit_batch_width`].
+///
+/// Always at least one range, so a trace without columns still has a commitment.
+pub(crate) fn main_commit_batches<SC: StarkGenericConfig>(
+    config: &SC,
+    width: usize,
+) -> Vec<Range<usize>> {
+    match config.main_commit_batch_width() {
+        Some(batch_width) if width > batch_width => (0..width)
+            .step_by(batch_width)
+            .map(|start| start..width.min(start + batch_width))
+            .collect(),
+        _ => vec![0..width],
+    }
+}
+
+/// The auxiliary commitment of proofs without an auxiliary phase under
+/// [`StarkGenericConfig::uniform_aux_phase`]: a single zero on a one-row domain.
+///
+/// Depends on nothing but the PCS, so the verifier recomputes it, and it is never opened.
+pub(crate) fn placeholder_aux_commitment<SC: StarkGenericConfig>(config: &SC) -> Commitment<SC> {
+    let pcs = config.pcs();
+    let domain = pcs.natural_domain_for_degree(1);
+    let (commit, _) = pcs.commit([(domain, RowMajorMatrix::new_col(vec![Val::<SC>::ZERO]))]);
+    commit
+}
+
+/// Short digest of the parameters proofs under a configuration are made with, see
+/// [`config_digest`].
+pub type ConfigDigest = [u8; 8];
+
+/// Digest of the parameters proofs under `config` are made with, carried in
+/// [`Proof::config_digest`](crate::Proof::config_digest).
+///
+/// Covers the base field, the extension degree, the out-of-domain points and grinding,
+/// the recorded [`security_parameters`](StarkGenericConfig::security_parameters) (FRI
+/// blowup, number of queries and query grinding) and the commitment scheme with its hash
+/// function, through its commitment to a fixed matrix. A proof checked under other
+/// parameters fails verification anyway; comparing digests first says why.
+///
+/// The PCS doesn't expose its FRI parameters, so a configuration that doesn't record them
+/// has no digest: one without them would let configurations differing only in their
+/// blowup or number of queries pass for each other.
+pub fn config_digest<SC: StarkGenericConfig>(config: &SC) -> Option<ConfigDigest> {
+    let parameters = config.security_parameters()?;
+    let mut bytes =
+        postcard::to_allocvec(&Val::<SC>::NEG_ONE).expect("field elements are serializable");
+    let counts = [
+        <SC::Challenge as BasedVectorSpace<Val<SC>>>::DIMENSION,
+        config.num_ood_points(),
+        config.pow_bits(),
+        parameters.log_blowup,
+        parameters.num_queries,
+        parameters.query_pow_bits,
+        parameters.log_challenge_field_size,
+    ];
+    for count in counts {
+        bytes.extend((count as u64).to_le_bytes());
+    }
+    bytes.extend(
+        postcard::to_allocvec(&placeholder_aux_commitment(config))
+            .expect("commitments are serializable"),
+    );
+    let hash = Keccak256Hash {}.hash_iter(bytes);
+    Some(core::array::from_fn(|i| hash[i]))
 }
 
 /// Concrete STARK configuration
@@ -59,6 +250,22 @@ pub struct StarkConfig<Pcs, Challenge, Challenger> {
     pub pcs: Pcs,
     /// Initial challenger state
     pub challenger: Challenger,
+    /// See [`StarkGenericConfig::main_commit_batch_width`]
+    pub main_commit_batch_width: Option<usize>,
+    /// See [`StarkGenericConfig::domain_separator`]
+    pub domain_separator: &'static [u8],
+    /// See [`StarkGenericConfig::extension_sampling`]
+    pub extension_sampling: ExtensionSampling,
+    /// See [`StarkGenericConfig::uniform_aux_phase`]
+    pub uniform_aux_phase: bool,
+    /// See [`StarkGenericConfig::num_ood_points`]
+    pub num_ood_points: usize,
+    /// See [`StarkGenericConfig::pow_bits`]
+    pub pow_bits: usize,
+    /// See [`StarkGenericConfig::security_parameters`]
+    pub security_parameters: Option<SecurityParameters>,
+    /// See [`StarkGenericConfig::max_log_degree`]
+    pub max_log_degree: Option<usize>,
     _phantom: core::marker::PhantomData<Challenge>,
 }
 
@@ -67,9 +274,137 @@ impl<Pcs, Challenge, Challenger> StarkConfig<Pcs, Challenge, Challenger> {
         Self {
             pcs,
             challenger,
+            main_commit_batch_width: None,
+            domain_separator: &[],
+            extension_sampling: ExtensionSampling::Direct,
+            uniform_aux_phase: false,
+            num_ood_points: 1,
+            pow_bits: 0,
+            security_parameters: None,
+            max_log_degree: None,
             _phantom: core::marker::PhantomData,
         }
     }
+
+    /// Commit the main trace in batches of at most `width` columns.
+    ///
+    /// # Panics
+    /// If `width` is 0.
+    #[must_use]
+    pub const fn with_main_commit_batch_width(mut self, width: usize) -> Self {
+        assert!(width > 0, "commit batches need at least one column");
+        self.main_commit_batch_width = Some(width);
+        self
+    }
+
+    /// Absorb `separator` at the start of every transcript, see
+    /// [`StarkGenericConfig::domain_separator`].
+    #[must_use]
+    pub const fn with_domain_separator(mut self, separator: &'static [u8]) -> Self {
+        self.domain_separator = separator;
+        self
+    }
+
+    /// Draw extension-field challenges as `sampling` says, see
+    /// [`StarkGenericConfig::extension_sampling`].
+    #[must_use]
+    pub const fn with_extension_sampling(mut self, sampling: ExtensionSampling) -> Self {
+        self.extension_sampling = sampling;
+        self
+    }
+
+    /// Give proofs of AIRs without an auxiliary phase a placeholder one if `uniform`,
+    /// see [`StarkGenericConfig::uniform_aux_phase`].
+    #[must_use]
+    pub const fn with_uniform_aux_phase(mut self, uniform: bool) -> Self {
+        self.uniform_aux_phase = uniform;
+        self
+    }
+
+    /// Check the constraints at `count` out-of-domain points, see
+    /// [`StarkGenericConfig::num_ood_points`].
+    ///
+    /// # Panics
+    /// If `count` is 0.
+    #[must_use]
+    pub const fn with_num_ood_points(mut self, count: usize) -> Self {
+        assert!(count > 0, "constraints are checked at least at one point");
+        self.num_ood_points = count;
+        self
+    }
+
+    /// Grind `bits` of proof-of-work before the out-of-domain points, see
+    /// [`StarkGenericConfig::pow_bits`].
+    #[must_use]
+    pub const fn with_pow_bits(mut self, bits: usize) -> Self {
+        self.pow_bits = bits;
+        self
+    }
+
+    /// Record the parameters the PCS was built with, see
+    /// [`StarkGenericConfig::security_parameters`].
+    #[must_use]
+    pub const fn with_security_parameters(mut self, parameters: SecurityParameters) -> Self {
+        self.security_parameters = Some(parameters);
+        self
+    }
+
+    /// Accept no trace taller than `2^log_degree` rows, see
+    /// [`StarkGenericConfig::max_log_degree`].
+    #[must_use]
+    pub const fn with_max_log_degree(mut self, log_degree: usize) -> Self {
+        self.max_log_degree = Some(log_degree);
+        self
+    }
+}
+
+impl<P, Challenge, Challenger> StarkConfig<P, Challenge, Challenger>
+where
+    P: Pcs<Challenge, Challenger>,
+    P::Domain: TraceDomain,
+{
+    /// Bits of security of proofs under this configuration, for the tallest trace it
+    /// accepts, or `None` if its parameters weren't recorded.
+    ///
+    /// The out-of-domain grinding bits are this configuration's, see
+    /// [`with_pow_bits`](Self::with_pow_bits). See [`SecurityParameters::security_level`].
+    pub fn security_bits(&self) -> Option<SecurityLevel> {
+        self.security_parameters_with_pow()
+            .map(|parameters| parameters.security_level(self.log_max_height(), self.num_ood_points))
+    }
+
+    /// Check proofs under this configuration reach `target`, for the tallest trace it
+    /// accepts, and return their [`security_bits`](Self::security_bits).
+    ///
+    /// ```ignore
+    /// let config = StarkConfigBuilder::baby_bear().queries(100).build()?;
+    /// config.validate(SecurityTarget::Bits(100))?;
+    /// ```
+    ///
+    /// # Errors
+    /// [`SecurityError::UnknownParameters`] if the configuration doesn't record its
+    /// parameters, [`SecurityError::Insufficient`] with the term at fault if it falls short.
+    pub fn validate(&self, target: SecurityTarget) -> Result<SecurityLevel, SecurityError> {
+        self.security_parameters_with_pow()
+            .ok_or(SecurityError::UnknownParameters)?
+            .validate(self.log_max_height(), self.num_ood_points, target)
+    }
+
+    /// The recorded parameters, with this configuration's out-of-domain grinding.
+    fn security_parameters_with_pow(&self) -> Option<SecurityParameters> {
+        self.security_parameters
+            .map(|parameters| SecurityParameters {
+                ood_pow_bits: self.pow_bits,
+                ..parameters
+            })
+    }
+
+    /// log2 of the tallest trace the PCS supports, or the configuration accepts if lower.
+    fn log_max_height(&self) -> usize {
+        let supported = max_log_trace_size::<P::Domain>(LOG_QUOTIENT_DEGREE);
+        self.max_log_degree
+            .map_or(supported, |max_log_degree| max_log_degree.min(supported))
+    }
 }
 
 impl<P, Challenge, C> StarkGenericConfig for StarkConfig<P, Challenge, C>
@@ -77,6 +412,7 @@ where
     Challenge: ExtensionField<<P::Domain as PolynomialSpace>::Val>,
     P: Pcs<Challenge, C>,
     C: FieldChallenger<<P::Domain as PolynomialSpace>::Val>
+        + GrindingChallenger<Witness = <P::Domain as PolynomialSpace>::Val>
         + CanObserve<P::Commitment>
         + CanSample<Challenge>
         + Clone,
@@ -92,4 +428,36 @@ where
     fn initialise_challenger(&self) -> Self::Challenger {
         self.challenger.clone()
     }
+
+    fn main_commit_batch_width(&self) -> Option<usize> {
+        self.main_commit_batch_width
+    }
+
+    fn domain_separator(&self) -> &[u8] {
+        self.domain_separator
+    }
+
+    fn extension_sampling(&self) -> ExtensionSampling {
+        self.extension_sampling
+    }
+
+    fn uniform_aux_phase(&self) -> bool {
+        self.uniform_aux_phase
+    }
+
+    fn num_ood_points(&self) -> usize {
+        self.num_ood_points
+    }
+
+    fn pow_bits(&self) -> usize {
+        self.pow_bits
+    }
+
+    fn security_parameters(&self) -> Option<SecurityParameters> {
+        self.security_parameters
+    }
+
+    fn max_log_degree(&self) -> Option<usize> {
+        self.max_log_degree
+    }
 }
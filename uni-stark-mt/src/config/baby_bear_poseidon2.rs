@@ -0,0 +1,58 @@
+//! BabyBear + Poseidon2 + FRI preset
+//!
+//! Quartic extension of BabyBear, Poseidon2 (width 16) for Merkle hashing and the
+//! duplex challenger, and [`TwoAdicFriPcs`]. Use [`default_config`] for the default
+//! parameters or [`StarkConfigBuilder::baby_bear`] to tune them.
+
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_fri::TwoAdicFriPcs;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+
+use crate::{StarkConfig, StarkConfigBuilder};
+
+/// Base field
+pub type Val = BabyBear;
+
+/// Poseidon2 permutation shared by the hasher, compressor and challenger
+pub type Perm = Poseidon2BabyBear<16>;
+
+/// Leaf hasher
+pub type Hash = PaddingFreeSponge<Perm, 16, 8, 8>;
+
+/// Two-to-one compression for Merkle nodes
+pub type Compress = TruncatedPermutation<Perm, 2, 8, 16>;
+
+/// Merkle tree commitments to base-field matrices
+pub type ValMmcs =
+    MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, Hash, Compress, 8>;
+
+/// Challenge field
+pub type Challenge = BinomialExtensionField<Val, 4>;
+
+/// Merkle tree commitments to extension-field matrices
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+/// Fiat-Shamir challenger
+pub type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+
+/// DFT used for low-degree extensions
+pub type Dft = Radix2DitParallel<Val>;
+
+/// Polynomial commitment scheme
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+/// The complete configuration
+pub type Config = StarkConfig<Pcs, Challenge, Challenger>;
+
+/// The preset with [`StarkConfigBuilder`]'s default parameters.
+pub fn default_config() -> Config {
+    StarkConfigBuilder::baby_bear()
+        .build()
+        .expect("default parameters are valid")
+}
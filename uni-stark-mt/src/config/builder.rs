@@ -0,0 +1,187 @@
+//! Builder for ready-made STARK configurations
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use p3_baby_bear::default_babybear_poseidon2_16;
+use p3_field::{BasedVectorSpace, PrimeField64};
+use p3_fri::{create_test_fri_params, FriParameters};
+
+use super::baby_bear_poseidon2 as bb;
+use super::goldilocks_keccak as gk;
+use crate::prover::LOG_QUOTIENT_DEGREE;
+use crate::{SecurityParameters, StarkConfig};
+
+/// BabyBear with a quartic extension, Poseidon2 Merkle hashing and challenger, and FRI.
+///
+/// See [`baby_bear_poseidon2`](super::baby_bear_poseidon2) for its components.
+pub type BabyBearPoseidon2Config = bb::Config;
+
+/// Goldilocks with a quadratic extension, Keccak-256 Merkle hashing and challenger, and FRI.
+///
+/// See [`goldilocks_keccak`](super::goldilocks_keccak) for its components.
+pub type GoldilocksKeccakConfig = gk::Config;
+
+/// Marker for the BabyBear + Poseidon2 preset of [`StarkConfigBuilder`].
+#[derive(Clone, Copy, Debug)]
+pub struct BabyBearPoseidon2;
+
+/// Marker for the Goldilocks + Keccak preset of [`StarkConfigBuilder`].
+#[derive(Clone, Copy, Debug)]
+pub struct GoldilocksKeccak;
+
+/// Error building a configuration from a [`StarkConfigBuilder`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The trace LDE is too small to evaluate the quotient on
+    BlowupTooSmall {
+        /// Requested log2 of the FRI blowup
+        log_blowup: usize,
+        /// Smallest supported log2 blowup
+        min_log_blowup: usize,
+    },
+    /// FRI needs at least one query
+    NoQueries,
+}
+
+/// Builder for a concrete [`StarkConfig`] with sane defaults.
+///
+/// ```ignore
+/// let config = StarkConfigBuilder::baby_bear().log_blowup(2).queries(100).build()?;
+/// let evm_config = StarkConfigBuilder::goldilocks().queries(80).build()?;
+/// ```
+///
+/// Defaults: blowup 4 (`log_blowup = 2`, the smallest the quotient allows), 100 FRI
+/// queries, a constant final polynomial and no grinding before the out-of-domain points.
+/// FRI's own proof-of-work bits are left as [`create_test_fri_params`] sets them.
+#[derive(Clone, Copy, Debug)]
+pub struct StarkConfigBuilder<P> {
+    log_blowup: usize,
+    num_queries: usize,
+    log_final_poly_len: usize,
+    pow_bits: usize,
+    _preset: PhantomData<P>,
+}
+
+impl StarkConfigBuilder<BabyBearPoseidon2> {
+    /// Start from the BabyBear + Poseidon2 preset.
+    pub const fn baby_bear() -> Self {
+        Self::new()
+    }
+
+    /// Assemble the configuration.
+    ///
+    /// # Errors
+    /// See [`ConfigError`].
+    pub fn build(self) -> Result<BabyBearPoseidon2Config, ConfigError> {
+        let perm = default_babybear_poseidon2_16();
+        let hash = bb::Hash::new(perm.clone());
+        let compress = bb::Compress::new(perm.clone());
+        let val_mmcs = bb::ValMmcs::new(hash, compress);
+        let challenge_mmcs = bb::ChallengeMmcs::new(val_mmcs.clone());
+        let fri_params = self.fri_params(challenge_mmcs)?;
+        let pcs = bb::Pcs::new(bb::Dft::default(), val_mmcs, fri_params);
+        Ok(StarkConfig::new(pcs, bb::Challenger::new(perm))
+            .with_pow_bits(self.pow_bits)
+            .with_security_parameters(self.security_parameters::<bb::Val, bb::Challenge>()))
+    }
+}
+
+impl StarkConfigBuilder<GoldilocksKeccak> {
+    /// Start from the Goldilocks + Keccak preset.
+    pub const fn goldilocks() -> Self {
+        Self::new()
+    }
+
+    /// Assemble the configuration.
+    ///
+    /// # Errors
+    /// See [`ConfigError`].
+    pub fn build(self) -> Result<GoldilocksKeccakConfig, ConfigError> {
+        let byte_hash = gk::ByteHash {};
+        let field_hash = gk::FieldHash::new(byte_hash);
+        let compress = gk::Compress::new(byte_hash);
+        let val_mmcs = gk::ValMmcs::new(field_hash, compress);
+        let challenge_mmcs = gk::ChallengeMmcs::new(val_mmcs.clone());
+        let fri_params = self.fri_params(challenge_mmcs)?;
+        let pcs = gk::Pcs::new(gk::Dft::default(), val_mmcs, fri_params);
+        Ok(
+            StarkConfig::new(pcs, gk::Challenger::from_hasher(Vec::new(), byte_hash))
+                .with_pow_bits(self.pow_bits)
+                .with_security_parameters(self.security_parameters::<gk::Val, gk::Challenge>()),
+        )
+    }
+}
+
+impl<P> StarkConfigBuilder<P> {
+    const fn new() -> Self {
+        Self {
+            log_blowup: LOG_QUOTIENT_DEGREE,
+            num_queries: 100,
+            log_final_poly_len: 0,
+            pow_bits: 0,
+            _preset: PhantomData,
+        }
+    }
+
+    /// Set log2 of the FRI blowup factor.
+    #[must_use]
+    pub const fn log_blowup(mut self, log_blowup: usize) -> Self {
+        self.log_blowup = log_blowup;
+        self
+    }
+
+    /// Set the number of FRI queries.
+    #[must_use]
+    pub const fn queries(mut self, num_queries: usize) -> Self {
+        self.num_queries = num_queries;
+        self
+    }
+
+    /// Set log2 of the length of the final FRI polynomial.
+    #[must_use]
+    pub const fn log_final_poly_len(mut self, log_final_poly_len: usize) -> Self {
+        self.log_final_poly_len = log_final_poly_len;
+        self
+    }
+
+    /// Set the proof-of-work bits ground before the out-of-domain points, see
+    /// [`StarkGenericConfig::pow_bits`](crate::StarkGenericConfig::pow_bits).
+    #[must_use]
+    pub const fn pow_bits(mut self, pow_bits: usize) -> Self {
+        self.pow_bits = pow_bits;
+        self
+    }
+
+    /// The parameters soundness depends on, for the challenge field `EF` over `F`.
+    ///
+    /// The field size is rounded down per base field factor, and the preset's FRI
+    /// grinding isn't counted, so estimates from them err on the low side.
+    fn security_parameters<F: PrimeField64, EF: BasedVectorSpace<F>>(&self) -> SecurityParameters {
+        SecurityParameters {
+            log_blowup: self.log_blowup,
+            num_queries: self.num_queries,
+            query_pow_bits: 0,
+            ood_pow_bits: self.pow_bits,
+            log_challenge_field_size: EF::DIMENSION * F::ORDER_U64.ilog2() as usize,
+        }
+    }
+
+    /// FRI parameters for `mmcs` with this builder's settings.
+    fn fri_params<M>(&self, mmcs: M) -> Result<FriParameters<M>, ConfigError> {
+        // The quotient is evaluated on the trace LDE, so it must be at least that large
+        if self.log_blowup < LOG_QUOTIENT_DEGREE {
+            return Err(ConfigError::BlowupTooSmall {
+                log_blowup: self.log_blowup,
+                min_log_blowup: LOG_QUOTIENT_DEGREE,
+            });
+        }
+        if self.num_queries == 0 {
+            return Err(ConfigError::NoQueries);
+        }
+        let mut params = create_test_fri_params(mmcs, self.log_final_poly_len);
+        params.log_blowup = self.log_blowup;
+        params.num_queries = self.num_queries;
+        Ok(params)
+    }
+}
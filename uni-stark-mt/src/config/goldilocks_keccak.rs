@@ -0,0 +1,58 @@
+//! Goldilocks + Keccak-256 + FRI preset
+//!
+//! Quadratic extension of Goldilocks, Keccak-256 for Merkle hashing and the
+//! byte-oriented challenger, and [`TwoAdicFriPcs`]. Keccak commitments are cheap to check
+//! on the EVM. Use [`default_config`] for the default parameters or
+//! [`StarkConfigBuilder::goldilocks`] to tune them.
+
+use p3_challenger::{HashChallenger, SerializingChallenger64};
+use p3_commit::ExtensionMmcs;
+use p3_dft::Radix2DitParallel;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::TwoAdicFriPcs;
+use p3_goldilocks::Goldilocks;
+use p3_keccak::Keccak256Hash;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher};
+
+use crate::{StarkConfig, StarkConfigBuilder};
+
+/// Base field
+pub type Val = Goldilocks;
+
+/// Keccak-256 over bytes
+pub type ByteHash = Keccak256Hash;
+
+/// Leaf hasher: serializes field elements and hashes the bytes
+pub type FieldHash = SerializingHasher<ByteHash>;
+
+/// Two-to-one compression for Merkle nodes
+pub type Compress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+
+/// Merkle tree commitments to base-field matrices
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, Compress, 32>;
+
+/// Challenge field
+pub type Challenge = BinomialExtensionField<Val, 2>;
+
+/// Merkle tree commitments to extension-field matrices
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+/// Fiat-Shamir challenger over Keccak-256
+pub type Challenger = SerializingChallenger64<Val, HashChallenger<u8, ByteHash, 32>>;
+
+/// DFT used for low-degree extensions
+pub type Dft = Radix2DitParallel<Val>;
+
+/// Polynomial commitment scheme
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+/// The complete configuration
+pub type Config = StarkConfig<Pcs, Challenge, Challenger>;
+
+/// The preset with [`StarkConfigBuilder`]'s default parameters.
+pub fn default_config() -> Config {
+    StarkConfigBuilder::goldilocks()
+        .build()
+        .expect("default parameters are valid")
+}
@@ -0,0 +1,405 @@
+//! Automatic degree lowering through helper columns
+//!
+//! The quotient domain grows with the highest constraint degree, so a single `x^7` term
+//! makes every column's low-degree extension several times larger. [`lower_degree`]
+//! rewrites [`SymbolicConstraints`] into constraints of degree at most a target, by
+//! replacing high-degree main trace subexpressions with fresh helper columns appended to
+//! the main trace, each pinned down by a constraint defining it.
+//!
+//! [`lower_air`] wraps an AIR into a [`LoweredAir`] that evaluates the lowered
+//! constraints, compiled to a [`ConstraintProgram`], instead of its own, and fills in the
+//! helper columns of its main traces with [`LoweredAir::extend_trace`]:
+//!
+//! ```ignore
+//! let lowered = lower_air::<Val, Challenge, _>(&air, 3)?;
+//! let trace = lowered.extend_trace(&main_trace, &public_values);
+//! let proof = prove(&config, &lowered, trace, &public_values)?;
+//! ```
+//!
+//! Helper columns only ever stand for expressions over the main trace's local and next
+//! rows, public values and constants, which the prover can evaluate row by row. Factors
+//! reading auxiliary columns, challenges or selectors are kept as they are, so a
+//! constraint multiplying too many of those can't be lowered.
+
+use alloc::vec::Vec;
+
+use p3_air::{Air, BaseAir};
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
+
+use crate::air::delegate_air_traits;
+use crate::program::eval_program;
+use crate::{
+    symbolic_constraints, AuxBuilder, ConstraintProgram, Entry, MultiTraceAir, RowOffset,
+    StatementBuilder, SymbolicAirBuilder, SymbolicConstraint, SymbolicConstraints,
+    SymbolicExpression, SymbolicVariable,
+};
+
+/// A constraint whose degree can't be brought down to the target, as its factors that
+/// helper columns can't stand for already exceed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnlowerableConstraint {
+    /// Index of the constraint, in the order `Air::eval` asserts them
+    pub constraint_index: usize,
+
+    /// Lowest degree the constraint can be rewritten to
+    pub lowest_degree: usize,
+}
+
+/// Constraints of bounded degree, over a main trace widened with helper columns.
+#[derive(Clone, Debug)]
+pub struct LoweredConstraints<F> {
+    /// The AIR's constraints rewritten over the helper columns, in their original order,
+    /// followed by the definition `helper - expr = 0` of each helper column
+    pub symbolic: SymbolicConstraints<F>,
+
+    /// Expression each helper column holds, helper `i` being main column
+    /// `main_width + i`
+    pub helpers: Vec<SymbolicExpression<F>>,
+
+    /// Width of the AIR's own main trace
+    pub main_width: usize,
+}
+
+impl<F: Field> LoweredConstraints<F> {
+    /// Append the helper columns to `trace`, a main trace of the original AIR.
+    ///
+    /// Helpers reading the next row wrap around on the last row, as the constraints do.
+    ///
+    /// # Panics
+    /// - If `trace` isn't [`main_width`](Self::main_width) wide
+    pub fn extend_trace(
+        &self,
+        trace: &RowMajorMatrix<F>,
+        public_values: &[F],
+    ) -> RowMajorMatrix<F> {
+        let main_width = self.main_width;
+        assert_eq!(trace.width(), main_width, "Main trace width mismatch");
+        let (width, height) = (main_width + self.helpers.len(), trace.height());
+        let mut values = F::zero_vec(width * height);
+        values
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(index, row)| {
+                let local = &trace.values[index * main_width..(index + 1) * main_width];
+                let next_index = (index + 1) % height;
+                let next = &trace.values[next_index * main_width..(next_index + 1) * main_width];
+                let (main, helpers) = row.split_at_mut(main_width);
+                main.copy_from_slice(local);
+                for (i, definition) in self.helpers.iter().enumerate() {
+                    let (filled, rest) = helpers.split_at_mut(i);
+                    rest[0] = definition.evaluate(&|leaf| match leaf {
+                        SymbolicExpression::Variable(v) => match v.entry {
+                            Entry::Main {
+                                row: RowOffset::Local,
+                                column,
+                            } if column < main_width => local[column],
+                            Entry::Main {
+                                row: RowOffset::Local,
+                                column,
+                            } => filled[column - main_width],
+                            Entry::Main {
+                                row: RowOffset::Next,
+                                column,
+                            } => next[column],
+                            Entry::Public(index) => public_values[index],
+                            entry => unreachable!("helper reads {}", entry.name()),
+                        },
+                        SymbolicExpression::Constant(c) => *c,
+                        _ => unreachable!("helpers read no selector"),
+                    });
+                }
+            });
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+/// Rewrite `symbolic` into constraints of degree at most `max_degree`, see
+/// [`SymbolicExpression::degree`].
+///
+/// Constraints already within `max_degree` are left untouched. Helper columns holding
+/// the same expression are shared between constraints.
+///
+/// # Errors
+/// The first constraint that can't be lowered to `max_degree`.
+///
+/// # Panics
+/// - If `max_degree` is below 2, the degree of a helper's definition
+pub fn lower_degree<F: Field>(
+    symbolic: &SymbolicConstraints<F>,
+    max_degree: usize,
+) -> Result<LoweredConstraints<F>, UnlowerableConstraint> {
+    assert!(
+        max_degree >= 2,
+        "Helper columns need constraints of degree 2"
+    );
+    let main_width = symbolic
+        .variables
+        .iter()
+        .filter(|entry| {
+            matches!(
+                entry,
+                Entry::Main {
+                    row: RowOffset::Local,
+                    ..
+                }
+            )
+        })
+        .count();
+
+    let mut lowering = Lowering {
+        main_width,
+        max_degree,
+        helpers: Vec::new(),
+    };
+    let mut constraints = Vec::with_capacity(symbolic.constraints.len());
+    for (constraint_index, constraint) in symbolic.constraints.iter().enumerate() {
+        let lowest_degree = lowest_degree(&constraint.expr);
+        if lowest_degree > max_degree {
+            return Err(UnlowerableConstraint {
+                constraint_index,
+                lowest_degree,
+            });
+        }
+        constraints.push(SymbolicConstraint {
+            expr: lowering.lower(&constraint.expr, max_degree),
+            extension: constraint.extension,
+        });
+    }
+    let helpers = lowering.helpers;
+    constraints.extend(
+        helpers
+            .iter()
+            .enumerate()
+            .map(|(i, definition)| SymbolicConstraint {
+                expr: helper_variable(main_width + i) - definition.clone(),
+                extension: false,
+            }),
+    );
+
+    // Helper columns are main columns: declare them after the AIR's own
+    let (main_local, others) = symbolic.variables.split_at(main_width);
+    let variables = main_local
+        .iter()
+        .copied()
+        .chain((0..helpers.len()).map(|i| Entry::Main {
+            row: RowOffset::Local,
+            column: main_width + i,
+        }))
+        .chain(others.iter().copied())
+        .collect();
+    Ok(LoweredConstraints {
+        symbolic: SymbolicConstraints {
+            constraints,
+            variables,
+        },
+        helpers,
+        main_width,
+    })
+}
+
+/// The local-row variable of main column `column`.
+fn helper_variable<F: Field>(column: usize) -> SymbolicExpression<F> {
+    SymbolicVariable::new(Entry::Main {
+        row: RowOffset::Local,
+        column,
+    })
+    .into()
+}
+
+/// Whether a helper column can stand for `expr`: it only reads the main trace's local
+/// and next rows, public values and constants.
+fn is_helper_input<F>(expr: &SymbolicExpression<F>) -> bool {
+    match expr {
+        SymbolicExpression::Variable(v) => matches!(
+            v.entry,
+            Entry::Main {
+                row: RowOffset::Local | RowOffset::Next,
+                ..
+            } | Entry::Public(_)
+        ),
+        SymbolicExpression::Constant(_) => true,
+        SymbolicExpression::Add(a, b)
+        | SymbolicExpression::Sub(a, b)
+        | SymbolicExpression::Mul(a, b) => is_helper_input(a) && is_helper_input(b),
+        SymbolicExpression::Neg(a) => is_helper_input(a),
+        _ => false,
+    }
+}
+
+/// Lowest degree `expr` can be rewritten to, each of its subexpressions a helper column
+/// can stand for counting at most one.
+fn lowest_degree<F>(expr: &SymbolicExpression<F>) -> usize {
+    if is_helper_input(expr) {
+        return expr.degree().min(1);
+    }
+    match expr {
+        SymbolicExpression::Add(a, b) | SymbolicExpression::Sub(a, b) => {
+            lowest_degree(a).max(lowest_degree(b))
+        }
+        SymbolicExpression::Neg(a) => lowest_degree(a),
+        SymbolicExpression::Mul(a, b) => lowest_degree(a) + lowest_degree(b),
+        leaf => leaf.degree(),
+    }
+}
+
+/// State of [`lower_degree`]: the helper columns introduced so far.
+struct Lowering<F> {
+    /// Width of the AIR's own main trace
+    main_width: usize,
+
+    /// Degree every constraint is lowered to
+    max_degree: usize,
+
+    /// Expression of each helper column
+    helpers: Vec<SymbolicExpression<F>>,
+}
+
+impl<F: Field> Lowering<F> {
+    /// Rewrite `expr` to degree at most `budget`, which is at least its
+    /// [`lowest_degree`].
+    ///
+    /// Subexpressions a helper column can stand for are lowered to the maximum degree
+    /// first, and only replaced by a helper if that still exceeds `budget`.
+    fn lower(&mut self, expr: &SymbolicExpression<F>, budget: usize) -> SymbolicExpression<F> {
+        if expr.degree() <= budget {
+            return expr.clone();
+        }
+        if is_helper_input(expr) {
+            let definition = self.lower_operands(expr, self.max_degree);
+            return if definition.degree() <= budget {
+                definition
+            } else {
+                self.helper(definition)
+            };
+        }
+        self.lower_operands(expr, budget)
+    }
+
+    /// Rewrite the operands of `expr` so that it has degree at most `budget`.
+    fn lower_operands(
+        &mut self,
+        expr: &SymbolicExpression<F>,
+        budget: usize,
+    ) -> SymbolicExpression<F> {
+        match expr {
+            SymbolicExpression::Add(a, b) => self.lower(a, budget) + self.lower(b, budget),
+            SymbolicExpression::Sub(a, b) => self.lower(a, budget) - self.lower(b, budget),
+            SymbolicExpression::Neg(a) => -self.lower(a, budget),
+            SymbolicExpression::Mul(a, b) => {
+                // The factor no helper can stand for gets the slack
+                let (first, second) = if is_helper_input(a) && !is_helper_input(b) {
+                    (b, a)
+                } else {
+                    (a, b)
+                };
+                let first = self.lower(first, budget - lowest_degree(second));
+                let second = self.lower(second, budget - first.degree());
+                first * second
+            }
+            leaf => leaf.clone(),
+        }
+    }
+
+    /// The helper column holding `definition`, introducing it unless one already does.
+    fn helper(&mut self, definition: SymbolicExpression<F>) -> SymbolicExpression<F> {
+        let index = self
+            .helpers
+            .iter()
+            .position(|helper| *helper == definition)
+            .unwrap_or_else(|| {
+                self.helpers.push(definition);
+                self.helpers.len() - 1
+            });
+        helper_variable(self.main_width + index)
+    }
+}
+
+/// An AIR whose constraints were lowered to a bounded degree, see [`lower_air`].
+///
+/// Its main trace is the wrapped AIR's followed by the helper columns, as
+/// [`extend_trace`](Self::extend_trace) builds it. Everything but the constraints and the
+/// width is the wrapped AIR's: auxiliary traces are built from the widened main trace,
+/// whose leading columns are the AIR's own.
+pub struct LoweredAir<'a, F, A> {
+    /// The wrapped AIR
+    air: &'a A,
+
+    /// Its constraints, lowered
+    lowered: LoweredConstraints<F>,
+
+    /// The lowered constraints, compiled
+    program: ConstraintProgram<F>,
+}
+
+/// Lower the constraints of `air` to degree at most `max_degree`, see [`lower_degree`].
+///
+/// # Errors
+/// The first constraint of `air` that can't be lowered to `max_degree`.
+///
+/// # Panics
+/// - If `max_degree` is below 2
+pub fn lower_air<F, EF, A>(
+    air: &A,
+    max_degree: usize,
+) -> Result<LoweredAir<'_, F, A>, UnlowerableConstraint>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<SymbolicAirBuilder<'a, F>>,
+{
+    let lowered = lower_degree(&symbolic_constraints::<F, EF, A>(air), max_degree)?;
+    let program = ConstraintProgram::compile(&lowered.symbolic);
+    Ok(LoweredAir {
+        air,
+        lowered,
+        program,
+    })
+}
+
+impl<'a, F: Field, A> LoweredAir<'a, F, A> {
+    /// The lowered constraints and the helper columns they read.
+    pub const fn lowered(&self) -> &LoweredConstraints<F> {
+        &self.lowered
+    }
+
+    /// The wrapped AIR.
+    pub const fn inner(&self) -> &'a A {
+        self.air
+    }
+
+    /// The main trace of this AIR: `trace`, a main trace of the wrapped AIR, followed by
+    /// the helper columns.
+    ///
+    /// # Panics
+    /// - If `trace` isn't as wide as the wrapped AIR
+    pub fn extend_trace(
+        &self,
+        trace: &RowMajorMatrix<F>,
+        public_values: &[F],
+    ) -> RowMajorMatrix<F> {
+        self.lowered.extend_trace(trace, public_values)
+    }
+}
+
+impl<'a, F: Field, A: BaseAir<F>> BaseAir<F> for LoweredAir<'a, F, A> {
+    fn width(&self) -> usize {
+        self.lowered.main_width + self.lowered.helpers.len()
+    }
+}
+
+delegate_air_traits!(LoweredAir);
+
+impl<'a, AB, A> Air<AB> for LoweredAir<'a, AB::F, A>
+where
+    AB: AuxBuilder + StatementBuilder,
+    AB::F: Field,
+    AB::MAux: Matrix<AB::VarEF>,
+    A: BaseAir<AB::F>,
+{
+    fn eval(&self, builder: &mut AB) {
+        eval_program(&self.program, builder);
+    }
+}
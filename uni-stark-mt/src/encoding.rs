@@ -0,0 +1,306 @@
+//! Versioned binary encoding of proofs
+//!
+//! [`Proof::to_bytes`] writes a [`ProofHeader`] followed by the proof's `postcard`
+//! encoding, its payload. The header names the format of the payload, the field and the
+//! PCS the proof was made over and the payload's length, so stored proofs can be
+//! checked with [`ProofHeader::read`] before their payload is decoded, and
+//! [`Proof::from_bytes`] keeps decoding every format version earlier crates wrote.
+
+use alloc::vec::Vec;
+
+use p3_field::{BasedVectorSpace, PrimeField64};
+use serde::{Deserialize, Serialize};
+
+use crate::{Challenge, IdentifiedPcs, Proof, StarkGenericConfig, Val};
+
+/// First bytes of every encoded proof
+pub const PROOF_MAGIC: [u8; 4] = *b"P3MT";
+
+/// Format version of the payloads [`Proof::to_bytes`] writes.
+///
+/// Bump this whenever the layout of [`Proof`] changes, and keep decoding the earlier
+/// versions in [`Proof::from_bytes`]:
+/// 1. the first format
+/// 2. adds [`Proof::config_digest`]
+pub const PROOF_FORMAT_VERSION: u16 = 2;
+
+/// Length of a [`ProofHeader`] in bytes
+pub const PROOF_HEADER_LEN: usize = 20;
+
+/// A polynomial commitment scheme proofs can be encoded for, see [`IdentifiedPcs`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PcsId {
+    /// FRI over multiplicative subgroups of a two-adic field
+    TwoAdicFri,
+    /// FRI over the circle group of a complex-extendable field
+    CircleFri,
+}
+
+impl PcsId {
+    /// The header byte naming this PCS.
+    pub const fn to_byte(self) -> u8 {
+        match self {
+            Self::TwoAdicFri => 0,
+            Self::CircleFri => 1,
+        }
+    }
+
+    /// The PCS a header byte names, if any.
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::TwoAdicFri),
+            1 => Some(Self::CircleFri),
+            _ => None,
+        }
+    }
+}
+
+/// The fields a proof was made over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FieldId {
+    /// Order of the base field
+    pub order: u64,
+
+    /// Degree of the challenge field over the base field
+    pub extension_degree: u8,
+}
+
+impl FieldId {
+    /// The fields of proofs under `SC`.
+    pub fn of<SC>() -> Self
+    where
+        SC: StarkGenericConfig,
+        Val<SC>: PrimeField64,
+    {
+        Self {
+            order: Val::<SC>::ORDER_U64,
+            extension_degree: <Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION as u8,
+        }
+    }
+}
+
+/// The header of an encoded proof, see [`Proof::to_bytes`].
+///
+/// Its layout never changes, whatever the format version:
+///
+/// | bytes    | content                                           |
+/// |----------|---------------------------------------------------|
+/// | `0..4`   | [`PROOF_MAGIC`]                                   |
+/// | `4..6`   | format version, little-endian                     |
+/// | `6..14`  | order of the base field, little-endian            |
+/// | `14`     | degree of the challenge field over the base field |
+/// | `15`     | PCS, see [`PcsId::to_byte`]                       |
+/// | `16..20` | length of the payload, little-endian              |
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofHeader {
+    /// Format version of the payload, see [`PROOF_FORMAT_VERSION`]
+    pub format_version: u16,
+
+    /// The fields the proof was made over
+    pub field: FieldId,
+
+    /// The PCS the proof was made with
+    pub pcs: PcsId,
+
+    /// Length of the payload in bytes
+    pub payload_len: u32,
+}
+
+/// Why bytes don't decode to a [`Proof`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofDecodeError {
+    /// The bytes don't start with [`PROOF_MAGIC`]
+    BadMagic,
+    /// The payload is in a format this version of the crate doesn't know, e.g. one
+    /// written by a later version
+    UnsupportedFormatVersion(u16),
+    /// The header names a PCS this version of the crate doesn't know
+    UnknownPcs(u8),
+    /// The proof was made over other fields than the configuration's
+    FieldMismatch { expected: FieldId, actual: FieldId },
+    /// The proof was made with another PCS than the configuration's
+    PcsMismatch { expected: PcsId, actual: PcsId },
+    /// The bytes end before the header or the payload does
+    Truncated,
+    /// Bytes follow the payload
+    TrailingBytes,
+    /// The payload doesn't encode a proof
+    Malformed,
+}
+
+impl core::fmt::Display for ProofDecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::BadMagic => f.write_str("the bytes don't start with a proof header"),
+            Self::UnsupportedFormatVersion(version) => {
+                write!(f, "unsupported proof format version {version}")
+            }
+            Self::UnknownPcs(byte) => write!(f, "unknown PCS {byte}"),
+            Self::FieldMismatch { expected, actual } => write!(
+                f,
+                "expected a proof over a degree {} extension of the field of order {}, got a \
+                 degree {} extension of the field of order {}",
+                expected.extension_degree, expected.order, actual.extension_degree, actual.order
+            ),
+            Self::PcsMismatch { expected, actual } => {
+                write!(f, "expected a proof with PCS {expected:?}, got {actual:?}")
+            }
+            Self::Truncated => f.write_str("the proof is truncated"),
+            Self::TrailingBytes => f.write_str("bytes follow the proof"),
+            Self::Malformed => f.write_str("the payload doesn't encode a proof"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofDecodeError {}
+
+impl ProofHeader {
+    /// The header of a proof under `SC` with a payload of `payload_len` bytes, in the
+    /// current format.
+    pub fn new<SC>(payload_len: u32) -> Self
+    where
+        SC: StarkGenericConfig,
+        SC::Pcs: IdentifiedPcs,
+        Val<SC>: PrimeField64,
+    {
+        Self {
+            format_version: PROOF_FORMAT_VERSION,
+            field: FieldId::of::<SC>(),
+            pcs: <SC::Pcs as IdentifiedPcs>::PCS_ID,
+            payload_len,
+        }
+    }
+
+    /// Read the header of an encoded proof, returning it and the payload.
+    ///
+    /// Only the header is decoded: the payload is checked to have the length the header
+    /// gives, not to encode a proof.
+    ///
+    /// # Errors
+    /// [`ProofDecodeError::BadMagic`], [`ProofDecodeError::UnknownPcs`],
+    /// [`ProofDecodeError::Truncated`] or [`ProofDecodeError::TrailingBytes`] if the
+    /// bytes don't have a header and a payload of the length it gives.
+    pub fn read(bytes: &[u8]) -> Result<(Self, &[u8]), ProofDecodeError> {
+        if bytes.len() < PROOF_HEADER_LEN {
+            // Bytes cut short within the magic are still the start of a proof
+            return Err(if bytes.starts_with(&PROOF_MAGIC[..bytes.len().min(4)]) {
+                ProofDecodeError::Truncated
+            } else {
+                ProofDecodeError::BadMagic
+            });
+        }
+        let (header, payload) = bytes.split_at(PROOF_HEADER_LEN);
+        if header[0..4] != PROOF_MAGIC {
+            return Err(ProofDecodeError::BadMagic);
+        }
+        let le_bytes = |range: core::ops::Range<usize>| {
+            let mut word = [0; 8];
+            word[..range.len()].copy_from_slice(&header[range]);
+            u64::from_le_bytes(word)
+        };
+        let header = Self {
+            format_version: le_bytes(4..6) as u16,
+            field: FieldId {
+                order: le_bytes(6..14),
+                extension_degree: header[14],
+            },
+            pcs: PcsId::from_byte(header[15]).ok_or(ProofDecodeError::UnknownPcs(header[15]))?,
+            payload_len: le_bytes(16..20) as u32,
+        };
+        match payload.len().cmp(&(header.payload_len as usize)) {
+            core::cmp::Ordering::Less => Err(ProofDecodeError::Truncated),
+            core::cmp::Ordering::Greater => Err(ProofDecodeError::TrailingBytes),
+            core::cmp::Ordering::Equal => Ok((header, payload)),
+        }
+    }
+
+    /// Check a proof with this header decodes under `SC`.
+    ///
+    /// # Errors
+    /// [`ProofDecodeError::UnsupportedFormatVersion`], [`ProofDecodeError::FieldMismatch`]
+    /// or [`ProofDecodeError::PcsMismatch`] if it doesn't.
+    pub fn check<SC>(&self) -> Result<(), ProofDecodeError>
+    where
+        SC: StarkGenericConfig,
+        SC::Pcs: IdentifiedPcs,
+        Val<SC>: PrimeField64,
+    {
+        if !(1..=PROOF_FORMAT_VERSION).contains(&self.format_version) {
+            return Err(ProofDecodeError::UnsupportedFormatVersion(
+                self.format_version,
+            ));
+        }
+        let expected = Self::new::<SC>(self.payload_len);
+        if self.field != expected.field {
+            return Err(ProofDecodeError::FieldMismatch {
+                expected: expected.field,
+                actual: self.field,
+            });
+        }
+        if self.pcs != expected.pcs {
+            return Err(ProofDecodeError::PcsMismatch {
+                expected: expected.pcs,
+                actual: self.pcs,
+            });
+        }
+        Ok(())
+    }
+
+    /// The header's bytes, see [`ProofHeader`] for their layout.
+    pub fn to_bytes(&self) -> [u8; PROOF_HEADER_LEN] {
+        let mut bytes = [0; PROOF_HEADER_LEN];
+        bytes[0..4].copy_from_slice(&PROOF_MAGIC);
+        bytes[4..6].copy_from_slice(&self.format_version.to_le_bytes());
+        bytes[6..14].copy_from_slice(&self.field.order.to_le_bytes());
+        bytes[14] = self.field.extension_degree;
+        bytes[15] = self.pcs.to_byte();
+        bytes[16..20].copy_from_slice(&self.payload_len.to_le_bytes());
+        bytes
+    }
+}
+
+impl<SC> Proof<SC>
+where
+    SC: StarkGenericConfig,
+    SC::Pcs: IdentifiedPcs,
+    Val<SC>: PrimeField64,
+{
+    /// Encode the proof: a [`ProofHeader`] followed by its `postcard` encoding, in format
+    /// version [`PROOF_FORMAT_VERSION`].
+    ///
+    /// # Panics
+    /// If the proof's encoding is 4 GiB or larger.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let payload = postcard::to_allocvec(self).expect("proofs are always serializable");
+        let payload_len = u32::try_from(payload.len()).expect("proofs are smaller than 4 GiB");
+        let mut bytes = Vec::with_capacity(PROOF_HEADER_LEN + payload.len());
+        bytes.extend(ProofHeader::new::<SC>(payload_len).to_bytes());
+        bytes.extend(payload);
+        bytes
+    }
+
+    /// Decode a proof [`to_bytes`](Self::to_bytes) encoded, by this or an earlier version
+    /// of the crate.
+    ///
+    /// The header is checked against `SC` before the payload is decoded. The decoded proof
+    /// still has to be verified.
+    ///
+    /// # Errors
+    /// See [`ProofDecodeError`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProofDecodeError> {
+        let (header, payload) = ProofHeader::read(bytes)?;
+        header.check::<SC>()?;
+        let decode = |payload: &[u8]| match postcard::take_from_bytes(payload) {
+            Ok((proof, [])) => Ok(proof),
+            _ => Err(ProofDecodeError::Malformed),
+        };
+        match header.format_version {
+            // Version 1 ends before the configuration digest, the last field: append its
+            // encoding as `None`
+            1 => decode(&[payload, &[0u8]].concat()),
+            2 => decode(payload),
+            version => Err(ProofDecodeError::UnsupportedFormatVersion(version)),
+        }
+    }
+}
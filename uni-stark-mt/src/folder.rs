@@ -1,15 +1,32 @@
 //! Constraint folders for prover and verifier
 
-use p3_air::{AirBuilder, ExtensionBuilder};
-use p3_field::PackedField;
+use alloc::vec::Vec;
+
+use p3_air::{AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PermutationAirBuilder};
+use p3_field::{Field, PackedField};
 use p3_matrix::dense::RowMajorMatrixView;
 
-use crate::{Challenge, Val};
+use crate::{
+    Challenge, ChallengeSet, OpeningSchedule, Rotations, StatementBuilder, StatementSelectors, Val,
+};
+
+/// Powers of `alpha` in descending order: `[α^{n-1}, ..., α, 1]`.
+///
+/// Weighting constraint `i` by `α^{n-1-i}` makes the [`ProverFolder`] sum equal to the
+/// Horner fold computed by [`VerifierFolder`], so both sides agree on the combination.
+/// The buffer is filled from the back, so no separate reversal pass is needed.
+pub(crate) fn constraint_alpha_powers<EF: Field>(alpha: EF, num_constraints: usize) -> Vec<EF> {
+    let mut powers = EF::zero_vec(num_constraints);
+    for (slot, power) in powers.iter_mut().rev().zip(alpha.powers()) {
+        *slot = power;
+    }
+    powers
+}
 
 /// Builder for evaluating constraints during proving.
 ///
 /// This folder accumulates constraints using random challenges, computing:
-/// `C_0 + α·C_1 + α²·C_2 + ...`
+/// `α^{n-1}·C_0 + α^{n-2}·C_1 + ... + C_{n-1}`
 pub struct ProverFolder<'a, SC: crate::StarkGenericConfig>
 where
     Val<SC>: PackedField,
@@ -21,6 +38,46 @@ where
     /// Empty if no auxiliary trace
     pub aux: RowMajorMatrixView<'a, Challenge<SC>>,
 
+    /// Base-field auxiliary trace values (local and next rows, packed)
+    /// Empty if no base-field auxiliary columns
+    pub aux_base: RowMajorMatrixView<'a, Val<SC>>,
+
+    /// Challenges sampled for the auxiliary phase
+    pub challenges: ChallengeSet<'a, Challenge<SC>>,
+
+    /// Values exposed by the auxiliary phase
+    pub aux_exposed_values: &'a [Challenge<SC>],
+
+    /// Auxiliary trace values on the previous row, if the AIR opens them
+    pub aux_prev_row: &'a [Challenge<SC>],
+
+    /// Base-field auxiliary trace values on the previous row, if the AIR opens them
+    pub aux_base_prev_row: &'a [Val<SC>],
+
+    /// Main trace values on the previous row, if the AIR opens them
+    pub main_prev_row: &'a [Val<SC>],
+
+    /// Auxiliary trace values on the rows of the AIR's further rotations
+    pub aux_rotated: RotatedRows<'a, Challenge<SC>>,
+
+    /// Base-field auxiliary trace values on the rows of the AIR's further rotations
+    pub aux_base_rotated: RotatedRows<'a, Val<SC>>,
+
+    /// Main trace values on the rows of the AIR's further rotations
+    pub main_rotated: RotatedRows<'a, Val<SC>>,
+
+    /// Auxiliary trace values on the last row, if the AIR opens them
+    pub aux_last_row: &'a [Challenge<SC>],
+
+    /// Base-field auxiliary trace values on the last row, if the AIR opens them
+    pub aux_base_last_row: &'a [Val<SC>],
+
+    /// Main trace values on the last row, if the AIR opens them
+    pub main_last_row: &'a [Val<SC>],
+
+    /// Public input/output values
+    pub public_values: &'a [Val<SC>],
+
     /// Selector: 1 on first row, 0 elsewhere
     pub is_first_row: Val<SC>,
 
@@ -30,6 +87,9 @@ where
     /// Selector: 1 on all rows except last, 0 on last
     pub is_transition: Val<SC>,
 
+    /// Statement boundary selectors and public values
+    pub statements: StatementSelectors<'a, Val<SC>>,
+
     /// Powers of α for constraint randomization
     pub alpha_powers: &'a [Challenge<SC>],
 
@@ -75,6 +135,40 @@ where
     }
 }
 
+impl<'a, SC> AirBuilderWithPublicValues for ProverFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+{
+    type PublicVar = Val<SC>;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+impl<'a, SC> StatementBuilder for ProverFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+{
+    fn is_first_statement_row(&self) -> Self::Expr {
+        self.statements.is_first_row
+    }
+
+    fn is_last_statement_row(&self) -> Self::Expr {
+        self.statements.is_last_row
+    }
+
+    fn is_statement_transition(&self) -> Self::Expr {
+        self.statements.is_transition
+    }
+
+    fn statement_public_values(&self) -> &[Self::Expr] {
+        self.statements.public_values
+    }
+}
+
 impl<'a, SC> ExtensionBuilder for ProverFolder<'a, SC>
 where
     SC: crate::StarkGenericConfig,
@@ -96,12 +190,321 @@ where
 }
 
 /// Extension trait for accessing auxiliary trace in constraints.
+///
+/// The crate's builders also implement p3-air's `PermutationAirBuilder`, so AIRs written
+/// against it are proven without changes: their permutation trace is the
+/// extension-field auxiliary trace, and its randomness the
+/// [`challenges`](Self::challenges), both built and sampled as declared by the AIR's
+/// [`AuxTraceBuilder`](crate::AuxTraceBuilder).
 pub trait AuxBuilder: ExtensionBuilder {
     /// Matrix type for auxiliary trace
     type MAux;
 
     /// Access the auxiliary trace columns
     fn aux(&self) -> Self::MAux;
+
+    /// Access the base-field auxiliary trace columns
+    ///
+    /// See [`AuxTraceBuilder::aux_base_width`](crate::AuxTraceBuilder::aux_base_width).
+    fn aux_base(&self) -> Self::M;
+
+    /// Access the challenges sampled for the auxiliary phase, by name
+    ///
+    /// See [`AuxTraceBuilder::challenge_names`](crate::AuxTraceBuilder::challenge_names).
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF>;
+
+    /// Challenges sampled for the auxiliary phase, in declaration order
+    fn challenges(&self) -> &[Self::VarEF] {
+        self.challenge_set().values()
+    }
+
+    /// The challenge declared as `name`.
+    ///
+    /// # Panics
+    /// - If the AIR declares no challenge `name`
+    fn challenge(&self, name: &str) -> Self::VarEF {
+        self.challenge_set()[name]
+    }
+
+    /// Values exposed by the auxiliary phase
+    ///
+    /// The same on every row. See
+    /// [`AuxTraceBuilder::num_aux_exposed_values`](crate::AuxTraceBuilder::num_aux_exposed_values).
+    fn aux_exposed_values(&self) -> &[Self::VarEF];
+
+    /// Auxiliary trace values on the previous row, one per extension-field column
+    ///
+    /// The first row's previous row is the last. Empty unless the AIR opens its auxiliary
+    /// trace there, see [`OpeningSchedule::prev_row`](crate::OpeningSchedule::prev_row).
+    fn aux_prev_row(&self) -> &[Self::VarEF];
+
+    /// Base-field auxiliary trace values on the previous row
+    ///
+    /// Empty unless the AIR opens its auxiliary trace there.
+    fn aux_base_prev_row(&self) -> &[Self::Var];
+
+    /// Main trace values on the previous row
+    ///
+    /// The first row's previous row is the last. Empty unless the AIR opens its main
+    /// trace there, see [`AirLayout::main_openings`](crate::AirLayout::main_openings).
+    fn main_prev_row(&self) -> &[Self::Var];
+
+    /// Auxiliary trace values on the row `offset` rows from the current one, one per
+    /// extension-field column
+    ///
+    /// Rows wrap around the trace. Empty unless `offset` is one of the further rotations
+    /// the AIR opens its auxiliary trace at, see
+    /// [`OpeningSchedule::rotations`](crate::OpeningSchedule::rotations); the current,
+    /// next and previous rows are read through [`Self::aux`] and [`Self::aux_prev_row`].
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF];
+
+    /// Base-field auxiliary trace values on the row `offset` rows from the current one
+    ///
+    /// Empty unless `offset` is one of the further rotations the AIR opens its auxiliary
+    /// trace at.
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var];
+
+    /// Main trace values on the row `offset` rows from the current one
+    ///
+    /// Rows wrap around the trace. Empty unless `offset` is one of the further rotations
+    /// the AIR opens its main trace at, see
+    /// [`AirLayout::main_openings`](crate::AirLayout::main_openings); the current,
+    /// next and previous rows are read through `main()` and [`Self::main_prev_row`].
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var];
+
+    /// Auxiliary trace values on the last row, one per extension-field column
+    ///
+    /// The same on every row. Empty unless the AIR opens its auxiliary trace there, see
+    /// [`AirLayout::aux_openings`](crate::AirLayout::aux_openings).
+    fn aux_last_row(&self) -> &[Self::VarEF];
+
+    /// Base-field auxiliary trace values on the last row
+    ///
+    /// The same on every row. Empty unless the AIR opens its auxiliary trace there.
+    fn aux_base_last_row(&self) -> &[Self::Var];
+
+    /// Main trace values on the last row
+    ///
+    /// The same on every row. Empty unless the AIR opens its main trace there, see
+    /// [`AirLayout::main_openings`](crate::AirLayout::main_openings).
+    fn main_last_row(&self) -> &[Self::Var];
+
+    /// Embed a base-field (main trace) expression into the extension field.
+    ///
+    /// The embedding is the identity on values, so the degree of `x` is unchanged.
+    fn embed_main<I: Into<Self::Expr>>(&self, x: I) -> Self::ExprEF {
+        x.into().into()
+    }
+
+    /// Assert that a main trace expression equals an auxiliary trace expression.
+    ///
+    /// Asserts `main - aux = 0` over the extension field.
+    fn assert_eq_main_aux<M, A>(&mut self, main: M, aux: A)
+    where
+        M: Into<Self::Expr>,
+        A: Into<Self::ExprEF>,
+    {
+        let main = self.embed_main(main);
+        self.assert_eq_ext(main, aux);
+    }
+
+    /// Assert that an auxiliary trace expression equals a main trace expression.
+    ///
+    /// Asserts `aux - main = 0` over the extension field.
+    fn assert_eq_aux_main<A, M>(&mut self, aux: A, main: M)
+    where
+        A: Into<Self::ExprEF>,
+        M: Into<Self::Expr>,
+    {
+        let main = self.embed_main(main);
+        self.assert_eq_ext(aux, main);
+    }
+
+    /// Builder whose constraints, over either field, only apply where `condition` is
+    /// nonzero.
+    ///
+    /// The extension-field counterpart of `when`: constraints passed to
+    /// [`assert_zero_ext`](ExtensionBuilder::assert_zero_ext) are multiplied by the
+    /// embedded condition too, and the auxiliary trace stays readable.
+    fn when_ext<I: Into<Self::Expr>>(&mut self, condition: I) -> FilteredAuxBuilder<'_, Self>
+    where
+        Self: Sized,
+    {
+        FilteredAuxBuilder {
+            condition: condition.into(),
+            inner: self,
+        }
+    }
+
+    /// Builder whose constraints, over either field, only apply on the first row.
+    fn when_first_row_ext(&mut self) -> FilteredAuxBuilder<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.when_ext(self.is_first_row())
+    }
+
+    /// Builder whose constraints, over either field, only apply on the last row.
+    fn when_last_row_ext(&mut self) -> FilteredAuxBuilder<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.when_ext(self.is_last_row())
+    }
+
+    /// Builder whose constraints, over either field, apply on every row but the last.
+    ///
+    /// LogUp transition constraints read
+    /// `builder.when_transition_ext().assert_eq_ext(s_next - s, ...)`.
+    fn when_transition_ext(&mut self) -> FilteredAuxBuilder<'_, Self>
+    where
+        Self: Sized,
+    {
+        self.when_ext(self.is_transition())
+    }
+}
+
+/// An [`AuxBuilder`] whose constraints are multiplied by a base-field selector, see
+/// [`AuxBuilder::when_ext`].
+///
+/// Unlike `p3_air::FilteredAirBuilder`, it filters extension-field constraints and gives
+/// access to the auxiliary trace.
+pub struct FilteredAuxBuilder<'a, AB: AuxBuilder> {
+    /// The builder the filtered constraints go to
+    pub inner: &'a mut AB,
+
+    condition: AB::Expr,
+}
+
+impl<AB: AuxBuilder> FilteredAuxBuilder<'_, AB> {
+    /// The selector constraints are multiplied by.
+    pub fn condition(&self) -> AB::Expr {
+        self.condition.clone()
+    }
+}
+
+impl<AB: AuxBuilder> AirBuilder for FilteredAuxBuilder<'_, AB> {
+    type F = AB::F;
+    type Expr = AB::Expr;
+    type Var = AB::Var;
+    type M = AB::M;
+
+    fn main(&self) -> Self::M {
+        self.inner.main()
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.inner.is_first_row()
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.inner.is_last_row()
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        self.inner.is_transition_window(size)
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.inner.assert_zero(self.condition() * x.into());
+    }
+}
+
+impl<AB: AuxBuilder + AirBuilderWithPublicValues> AirBuilderWithPublicValues
+    for FilteredAuxBuilder<'_, AB>
+{
+    type PublicVar = AB::PublicVar;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.inner.public_values()
+    }
+}
+
+impl<AB: AuxBuilder> ExtensionBuilder for FilteredAuxBuilder<'_, AB> {
+    type EF = AB::EF;
+    type ExprEF = AB::ExprEF;
+    type VarEF = AB::VarEF;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        let condition = self.inner.embed_main(self.condition());
+        self.inner.assert_zero_ext(condition * x.into());
+    }
+}
+
+impl<AB: AuxBuilder> AuxBuilder for FilteredAuxBuilder<'_, AB> {
+    type MAux = AB::MAux;
+
+    fn aux(&self) -> Self::MAux {
+        self.inner.aux()
+    }
+
+    fn aux_base(&self) -> Self::M {
+        self.inner.aux_base()
+    }
+
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF> {
+        self.inner.challenge_set()
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.inner.aux_exposed_values()
+    }
+
+    fn aux_prev_row(&self) -> &[Self::VarEF] {
+        self.inner.aux_prev_row()
+    }
+
+    fn aux_base_prev_row(&self) -> &[Self::Var] {
+        self.inner.aux_base_prev_row()
+    }
+
+    fn main_prev_row(&self) -> &[Self::Var] {
+        self.inner.main_prev_row()
+    }
+
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF] {
+        self.inner.aux_rotated_row(offset)
+    }
+
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.inner.aux_base_rotated_row(offset)
+    }
+
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.inner.main_rotated_row(offset)
+    }
+
+    fn aux_last_row(&self) -> &[Self::VarEF] {
+        self.inner.aux_last_row()
+    }
+
+    fn aux_base_last_row(&self) -> &[Self::Var] {
+        self.inner.aux_base_last_row()
+    }
+
+    fn main_last_row(&self) -> &[Self::Var] {
+        self.inner.main_last_row()
+    }
+}
+
+impl<'a, SC> PermutationAirBuilder for ProverFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+    Val<SC>: PackedField,
+{
+    type MP = RowMajorMatrixView<'a, Challenge<SC>>;
+    type RandomVar = Self::VarEF;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.challenges()
+    }
 }
 
 impl<'a, SC> AuxBuilder for ProverFolder<'a, SC>
@@ -114,6 +517,54 @@ where
     fn aux(&self) -> Self::MAux {
         self.aux
     }
+
+    fn aux_base(&self) -> Self::M {
+        self.aux_base
+    }
+
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF> {
+        self.challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
+
+    fn aux_prev_row(&self) -> &[Self::VarEF] {
+        self.aux_prev_row
+    }
+
+    fn aux_base_prev_row(&self) -> &[Self::Var] {
+        self.aux_base_prev_row
+    }
+
+    fn main_prev_row(&self) -> &[Self::Var] {
+        self.main_prev_row
+    }
+
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF] {
+        self.aux_rotated.row(offset)
+    }
+
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.aux_base_rotated.row(offset)
+    }
+
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.main_rotated.row(offset)
+    }
+
+    fn aux_last_row(&self) -> &[Self::VarEF] {
+        self.aux_last_row
+    }
+
+    fn aux_base_last_row(&self) -> &[Self::Var] {
+        self.aux_base_last_row
+    }
+
+    fn main_last_row(&self) -> &[Self::Var] {
+        self.main_last_row
+    }
 }
 
 /// Builder for verifying constraints.
@@ -127,12 +578,65 @@ pub struct VerifierFolder<'a, SC: crate::StarkGenericConfig> {
     /// Main trace values (next row)
     pub main_next: &'a [Challenge<SC>],
 
-    /// Auxiliary trace values (local row)
+    /// Auxiliary trace values (local row), one per extension-field column
+    ///
+    /// See [`recompose_aux_openings`](crate::recompose_aux_openings).
     pub aux_local: &'a [Challenge<SC>],
 
-    /// Auxiliary trace values (next row)
+    /// Auxiliary trace values (next row), one per extension-field column
     pub aux_next: &'a [Challenge<SC>],
 
+    /// Base-field auxiliary trace values (local row)
+    pub aux_base_local: &'a [Challenge<SC>],
+
+    /// Base-field auxiliary trace values (next row)
+    pub aux_base_next: &'a [Challenge<SC>],
+
+    /// Challenges sampled for the auxiliary phase
+    pub challenges: ChallengeSet<'a, Challenge<SC>>,
+
+    /// Values exposed by the auxiliary phase
+    pub aux_exposed_values: &'a [Challenge<SC>],
+
+    /// Auxiliary trace values on the previous row, one per extension-field column, if
+    /// the AIR opens them
+    pub aux_prev_row: &'a [Challenge<SC>],
+
+    /// Base-field auxiliary trace values on the previous row, if the AIR opens them
+    pub aux_base_prev_row: &'a [Challenge<SC>],
+
+    /// Main trace values on the previous row, if the AIR opens them
+    pub main_prev_row: &'a [Challenge<SC>],
+
+    /// Auxiliary trace values on the rows of the AIR's further rotations, one per
+    /// extension-field column
+    pub aux_rotated: RotatedRows<'a, Challenge<SC>>,
+
+    /// Base-field auxiliary trace values on the rows of the AIR's further rotations
+    pub aux_base_rotated: RotatedRows<'a, Challenge<SC>>,
+
+    /// Main trace values on the rows of the AIR's further rotations
+    pub main_rotated: RotatedRows<'a, Challenge<SC>>,
+
+    /// Auxiliary trace values on the last row, one per extension-field column, if the
+    /// AIR opens them
+    pub aux_last_row: &'a [Challenge<SC>],
+
+    /// Base-field auxiliary trace values on the last row, if the AIR opens them
+    pub aux_base_last_row: &'a [Challenge<SC>],
+
+    /// Main trace values on the last row, if the AIR opens them
+    pub main_last_row: &'a [Challenge<SC>],
+
+    /// Points the main trace was opened at
+    pub main_openings: OpeningSchedule,
+
+    /// Points the auxiliary trace was opened at
+    pub aux_openings: OpeningSchedule,
+
+    /// Public input/output values
+    pub public_values: &'a [Val<SC>],
+
     /// Selector: 1 on first row, 0 elsewhere
     pub is_first_row: Challenge<SC>,
 
@@ -142,6 +646,9 @@ pub struct VerifierFolder<'a, SC: crate::StarkGenericConfig> {
     /// Selector: 1 on all rows except last, 0 on last
     pub is_transition: Challenge<SC>,
 
+    /// Statement boundary selectors and public values
+    pub statements: StatementSelectors<'a, Challenge<SC>>,
+
     /// Randomness for combining constraints
     pub alpha: Challenge<SC>,
 
@@ -149,24 +656,89 @@ pub struct VerifierFolder<'a, SC: crate::StarkGenericConfig> {
     pub accumulator: Challenge<SC>,
 }
 
+/// Rows of a trace at the further [`Rotations`] it is opened at, one per offset in
+/// increasing order.
+#[derive(Debug)]
+pub struct RotatedRows<'a, T> {
+    /// Offsets of the rows
+    pub offsets: Rotations,
+
+    /// The rows, one per offset
+    pub rows: &'a [Vec<T>],
+}
+
+impl<'a, T> RotatedRows<'a, T> {
+    /// No rows, for traces opened at no further rotation.
+    pub const fn empty() -> Self {
+        Self {
+            offsets: Rotations::NONE,
+            rows: &[],
+        }
+    }
+
+    /// The row at `offset`, empty if the trace isn't opened there.
+    pub fn row(&self, offset: isize) -> &'a [T] {
+        self.offsets
+            .position(offset)
+            .and_then(|index| self.rows.get(index))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<T> Clone for RotatedRows<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RotatedRows<'_, T> {}
+
+impl<T> Default for RotatedRows<'_, T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 /// Simple view for verifier (just vectors of challenges)
+///
+/// Holds the local and next rows, or only the local row for traces not opened at ζ·g
+/// (see [`OpeningSchedule`](crate::OpeningSchedule)).
 #[derive(Copy, Clone)]
 pub struct VerifierView<'a, EF> {
     local: &'a [EF],
-    next: &'a [EF],
+    next: Option<&'a [EF]>,
 }
 
 impl<'a, EF: Copy> VerifierView<'a, EF> {
     pub fn new(local: &'a [EF], next: &'a [EF]) -> Self {
-        Self { local, next }
+        Self {
+            local,
+            next: Some(next),
+        }
+    }
+
+    /// A one-row view, for traces not opened at the next row.
+    pub fn new_local(local: &'a [EF]) -> Self {
+        Self { local, next: None }
+    }
+
+    /// A view of `next` as the next row if `next_row` is scheduled, else of `local` alone.
+    pub(crate) fn scheduled(local: &'a [EF], next: &'a [EF], next_row: bool) -> Self {
+        if next_row {
+            Self::new(local, next)
+        } else {
+            Self::new_local(local)
+        }
     }
 
     pub fn get_local(&self, col: usize) -> EF {
         self.local[col]
     }
 
+    /// # Panics
+    /// - If the view has no next row
     pub fn get_next(&self, col: usize) -> EF {
-        self.next[col]
+        self.next.expect("next row not opened")[col]
     }
 }
 
@@ -177,13 +749,13 @@ impl<'a, EF: Copy + Send + Sync> p3_matrix::Matrix<EF> for VerifierView<'a, EF>
     }
 
     fn height(&self) -> usize {
-        2 // local and next
+        1 + self.next.is_some() as usize // local and, if opened, next
     }
 
     unsafe fn get_unchecked(&self, row: usize, col: usize) -> EF {
-        match row {
-            0 => *self.local.get_unchecked(col),
-            1 => *self.next.get_unchecked(col),
+        match (row, self.next) {
+            (0, _) => *self.local.get_unchecked(col),
+            (1, Some(next)) => *next.get_unchecked(col),
             _ => core::hint::unreachable_unchecked(),
         }
     }
@@ -191,7 +763,7 @@ impl<'a, EF: Copy + Send + Sync> p3_matrix::Matrix<EF> for VerifierView<'a, EF>
     fn row_slice(&self, r: usize) -> Option<&[EF]> {
         match r {
             0 => Some(self.local),
-            1 => Some(self.next),
+            1 => self.next,
             _ => None,
         }
     }
@@ -207,7 +779,7 @@ where
     type M = VerifierView<'a, Challenge<SC>>;
 
     fn main(&self) -> Self::M {
-        VerifierView::new(self.main_local, self.main_next)
+        VerifierView::scheduled(self.main_local, self.main_next, self.main_openings.next_row)
     }
 
     fn is_first_row(&self) -> Self::Expr {
@@ -228,6 +800,38 @@ where
     }
 }
 
+impl<'a, SC> AirBuilderWithPublicValues for VerifierFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+{
+    type PublicVar = Val<SC>;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+impl<'a, SC> StatementBuilder for VerifierFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+{
+    fn is_first_statement_row(&self) -> Self::Expr {
+        self.statements.is_first_row
+    }
+
+    fn is_last_statement_row(&self) -> Self::Expr {
+        self.statements.is_last_row
+    }
+
+    fn is_statement_transition(&self) -> Self::Expr {
+        self.statements.is_transition
+    }
+
+    fn statement_public_values(&self) -> &[Self::Expr] {
+        self.statements.public_values
+    }
+}
+
 impl<'a, SC> ExtensionBuilder for VerifierFolder<'a, SC>
 where
     SC: crate::StarkGenericConfig,
@@ -244,6 +848,22 @@ where
     }
 }
 
+impl<'a, SC> PermutationAirBuilder for VerifierFolder<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+{
+    type MP = VerifierView<'a, Challenge<SC>>;
+    type RandomVar = Self::VarEF;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.challenges()
+    }
+}
+
 impl<'a, SC> AuxBuilder for VerifierFolder<'a, SC>
 where
     SC: crate::StarkGenericConfig,
@@ -251,6 +871,58 @@ where
     type MAux = VerifierView<'a, Challenge<SC>>;
 
     fn aux(&self) -> Self::MAux {
-        VerifierView::new(self.aux_local, self.aux_next)
+        VerifierView::scheduled(self.aux_local, self.aux_next, self.aux_openings.next_row)
+    }
+
+    fn aux_base(&self) -> Self::M {
+        VerifierView::scheduled(
+            self.aux_base_local,
+            self.aux_base_next,
+            self.aux_openings.next_row,
+        )
+    }
+
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF> {
+        self.challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
+
+    fn aux_prev_row(&self) -> &[Self::VarEF] {
+        self.aux_prev_row
+    }
+
+    fn aux_base_prev_row(&self) -> &[Self::Var] {
+        self.aux_base_prev_row
+    }
+
+    fn main_prev_row(&self) -> &[Self::Var] {
+        self.main_prev_row
+    }
+
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF] {
+        self.aux_rotated.row(offset)
+    }
+
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.aux_base_rotated.row(offset)
+    }
+
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.main_rotated.row(offset)
+    }
+
+    fn aux_last_row(&self) -> &[Self::VarEF] {
+        self.aux_last_row
+    }
+
+    fn aux_base_last_row(&self) -> &[Self::Var] {
+        self.aux_base_last_row
+    }
+
+    fn main_last_row(&self) -> &[Self::Var] {
+        self.main_last_row
     }
 }
@@ -0,0 +1,69 @@
+//! Checked inversion of auxiliary-trace denominators
+//!
+//! LogUp-style arguments divide by terms like `α - x` on every row. Such a denominator
+//! vanishes when a challenge collides with the trace values, or because of a bug, and
+//! inverting it then panics or yields garbage depending on the field. Builders invert
+//! their denominators with [`checked_batch_inverse`] instead, and return the
+//! [`ZeroDenominator`] it reports from
+//! [`try_build_aux_traces`](crate::AuxTraceBuilder::try_build_aux_traces).
+
+use alloc::vec::Vec;
+
+use p3_field::{batch_multiplicative_inverse, Field};
+
+/// A denominator that vanished while building an auxiliary trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZeroDenominator {
+    /// Row of the vanishing denominator
+    pub row: usize,
+    /// Name of the challenge the denominator was built from
+    pub challenge: &'static str,
+}
+
+impl core::fmt::Display for ZeroDenominator {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "zero denominator on row {} for challenge `{}`",
+            self.row, self.challenge
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ZeroDenominator {}
+
+/// What to do when building the auxiliary trace hits a [`ZeroDenominator`].
+///
+/// See [`AuxTraceBuilder::zero_denominator_policy`](crate::AuxTraceBuilder::zero_denominator_policy).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZeroDenominatorPolicy {
+    /// Report the zero denominator
+    #[default]
+    Fail,
+
+    /// Draw fresh challenges and build again, at most `max_attempts` builds in all
+    ///
+    /// Only possible where the challenges aren't bound by a transcript, as in the
+    /// [`MockProver`](crate::MockProver). The prover can't draw again without the
+    /// verifier following, so it reports the zero denominator whatever the policy.
+    Resample {
+        /// Most builds tried, the first one included
+        max_attempts: usize,
+    },
+}
+
+/// Invert `denominators`, one per row, with a single field inversion.
+///
+/// # Errors
+/// The first row whose denominator is zero, naming `challenge` as the one it was built
+/// from.
+pub fn checked_batch_inverse<F: Field>(
+    denominators: &[F],
+    challenge: &'static str,
+) -> Result<Vec<F>, ZeroDenominator> {
+    match denominators.iter().position(F::is_zero) {
+        Some(row) => Err(ZeroDenominator { row, challenge }),
+        None => Ok(batch_multiplicative_inverse(denominators)),
+    }
+}
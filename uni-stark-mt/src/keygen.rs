@@ -0,0 +1,218 @@
+//! Verifying key generation
+//!
+//! A [`VerifyingKey`] captures the structural data the verifier needs about an AIR, so
+//! verifiers can check a proof's shape without re-deriving it from the `Air` on every
+//! call.
+//!
+//! The key also carries the AIR's [`AirFingerprint`], observed at the start of every
+//! transcript: a proof for one AIR doesn't verify against another with the same shape but
+//! different constraints.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_field::{ExtensionField, Field};
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::CryptographicHasher;
+use p3_util::log2_ceil_usize;
+use serde::{Deserialize, Serialize};
+
+use crate::check_constraints::{constraint_degree, count_constraints, evaluate_on_window};
+use crate::mock_prover::splitmix64;
+use crate::prover::LOG_QUOTIENT_DEGREE;
+use crate::{
+    BoundaryConstraint, Challenge, ChallengeSampling, DebugConstraintBuilder, MultiTraceAir,
+    OpeningSchedule, ProtocolId, StarkGenericConfig, StateDigests, TranscriptPhase, Val,
+};
+
+/// Keccak-256 fingerprint of an AIR's constraint system, see [`VerifyingKey::fingerprint`].
+pub type AirFingerprint = [u8; 32];
+
+/// Structural data about an AIR needed to verify its proofs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyingKey {
+    /// Number of main trace columns
+    pub main_width: usize,
+
+    /// Number of auxiliary trace columns over the extension field
+    pub aux_width: usize,
+
+    /// Number of auxiliary trace columns over the base field
+    pub aux_base_width: usize,
+
+    /// Number of leading base-field auxiliary columns committed with the main trace
+    pub challenge_free_aux_width: usize,
+
+    /// Names of the challenges sampled before building the auxiliary trace, in sampling
+    /// order
+    pub challenge_names: Vec<String>,
+
+    /// How the challenges are sampled
+    pub challenge_sampling: ChallengeSampling,
+
+    /// Number of values the auxiliary phase exposes in the proof
+    pub num_aux_exposed_values: usize,
+
+    /// Index of the exposed value holding the AIR's share of a cross-proof bus, if any
+    pub bus_contribution_index: Option<usize>,
+
+    /// Points the main trace is opened at
+    pub main_openings: OpeningSchedule,
+
+    /// Points the auxiliary trace is opened at
+    pub aux_openings: OpeningSchedule,
+
+    /// Main trace cells pinned to public values, see
+    /// [`AirLayout::boundary_constraints`](crate::AirLayout::boundary_constraints)
+    pub boundary_constraints: Vec<BoundaryConstraint>,
+
+    /// Public values holding the state digests chaining shards, see
+    /// [`AirLayout::state_digests`](crate::AirLayout::state_digests)
+    pub state_digests: Option<StateDigests>,
+
+    /// Number of public values the AIR expects
+    pub num_public_values: usize,
+
+    /// Number of constraints asserted by the AIR
+    pub num_constraints: usize,
+
+    /// Largest degree of the AIR's constraints, counting trace values and row selectors
+    /// as degree 1
+    pub constraint_degree: usize,
+
+    /// Number of trailing rows the constraints don't apply to
+    pub num_punctured_rows: usize,
+
+    /// Number of independent statements each trace encodes
+    pub num_statements: usize,
+
+    /// log2 of the number of quotient chunks, enough for the quotient of a constraint of
+    /// [`constraint_degree`](Self::constraint_degree) and at least 2
+    ///
+    /// Configurations need a blowup of at least the number of chunks: the default one of
+    /// 4 covers constraints up to degree 5.
+    pub log_quotient_degree: usize,
+
+    /// Protocol variant proofs for this AIR are produced with
+    pub protocol_id: ProtocolId,
+
+    /// Fingerprint of the AIR, observed after the protocol identifier
+    ///
+    /// Hashes every other field of the key, then the value of each constraint on a fixed
+    /// pseudo-random window: AIRs of the same shape asserting different constraints get
+    /// different fingerprints. The window is public, so this tells honest AIRs apart
+    /// rather than resisting an AIR crafted to collide with another.
+    pub fingerprint: AirFingerprint,
+}
+
+impl VerifyingKey {
+    /// Whether the AIR has auxiliary columns, over either field.
+    pub const fn has_aux_trace(&self) -> bool {
+        self.aux_width > 0 || self.aux_base_width > 0
+    }
+
+    /// Whether proofs for this AIR carry an auxiliary commitment, built after sampling
+    /// challenges.
+    pub const fn has_aux_phase(&self) -> bool {
+        self.aux_width > 0 || self.aux_base_width > self.challenge_free_aux_width
+    }
+
+    /// Whether proofs for this AIR carry a commitment to challenge-free auxiliary
+    /// columns, made with the main trace.
+    pub const fn has_challenge_free_aux(&self) -> bool {
+        self.challenge_free_aux_width > 0
+    }
+
+    /// Number of named challenges sampled in `phase` of the transcript.
+    ///
+    /// Only the auxiliary phase samples any, all of the
+    /// [`challenge_names`](Self::challenge_names) and only if proofs have that phase: a
+    /// placeholder auxiliary commitment, or challenge-free columns, sample none. The
+    /// constraint combination challenge and the out-of-domain points aren't named
+    /// challenges.
+    pub fn num_challenges(&self, phase: TranscriptPhase) -> usize {
+        match phase {
+            TranscriptPhase::Aux if self.has_aux_phase() => self.challenge_names.len(),
+            _ => 0,
+        }
+    }
+}
+
+/// Derive the verifying key for `air` under `config`.
+pub fn keygen<SC, A>(config: &SC, air: &A) -> VerifyingKey
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>
+        + ?Sized,
+{
+    let constraint_degree = constraint_degree::<Val<SC>, Challenge<SC>, A>(
+        air,
+        air.width(),
+        air.aux_width(),
+        air.aux_base_width(),
+        air.num_public_values(),
+    );
+    let mut vk = VerifyingKey {
+        main_width: air.width(),
+        aux_width: air.aux_width(),
+        aux_base_width: air.aux_base_width(),
+        challenge_free_aux_width: air.challenge_free_aux_width(),
+        challenge_names: air
+            .challenge_names()
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        challenge_sampling: air.challenge_sampling(),
+        num_aux_exposed_values: air.num_aux_exposed_values(),
+        bus_contribution_index: air.bus_contribution_index(),
+        main_openings: air.main_openings(),
+        aux_openings: air.aux_openings(),
+        boundary_constraints: air.boundary_constraints(),
+        state_digests: air.state_digests(),
+        num_public_values: air.num_public_values(),
+        num_constraints: count_constraints::<Val<SC>, Challenge<SC>, A>(
+            air,
+            air.width(),
+            air.aux_width(),
+            air.aux_base_width(),
+            air.num_public_values(),
+        ),
+        constraint_degree,
+        num_punctured_rows: air.num_punctured_rows(),
+        num_statements: air.num_statements(),
+        // A constraint of degree d has a quotient of degree d - 1 over the trace domain
+        log_quotient_degree: LOG_QUOTIENT_DEGREE
+            .max(log2_ceil_usize(constraint_degree.saturating_sub(1))),
+        protocol_id: ProtocolId::new(config, air),
+        fingerprint: AirFingerprint::default(),
+    };
+    vk.fingerprint = air_fingerprint::<Val<SC>, Challenge<SC>, A>(air, &vk);
+    vk
+}
+
+/// Fingerprint `air`, whose key is `vk` but for its fingerprint.
+fn air_fingerprint<F, EF, A>(air: &A, vk: &VerifyingKey) -> AirFingerprint
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>> + ?Sized,
+{
+    let mut bytes = postcard::to_allocvec(vk).expect("verifying keys are serializable");
+    let mut state = u64::from_le_bytes(*b"AIRprint");
+    let (num_constraints, values) = evaluate_on_window::<F, EF, A>(
+        air,
+        vk.main_width,
+        vk.aux_width,
+        vk.aux_base_width,
+        vk.num_public_values,
+        |_| F::from_u64(splitmix64(&mut state)),
+    );
+    bytes.extend((num_constraints as u64).to_le_bytes());
+    for value in &values {
+        bytes.extend((value.constraint_index as u64).to_le_bytes());
+        bytes.extend(postcard::to_allocvec(&value.value).expect("field elements are serializable"));
+    }
+    Keccak256Hash {}.hash_iter(bytes)
+}
@@ -10,23 +10,103 @@
 //!
 //! The key abstraction is [`AuxTraceBuilder`], which allows AIRs to specify how to build
 //! auxiliary trace columns from the main trace and random challenges.
+//!
+//! The `minimal` feature trims the build for size-constrained hosts: tracing spans and
+//! events, the prover's debug constraint checks and the recursion, symbolic extraction,
+//! constraint compilation, degree lowering and proof diffing gadgets are compiled out.
+//!
+//! The `test-utils` feature adds [`test_utils`], helpers for reproducible and negative
+//! tests of AIRs and the proof system.
+//!
+//! [`capabilities()`] describes which of these the linked build was compiled with.
 
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(all(feature = "minimal", feature = "metrics"))]
+compile_error!("the `minimal` feature compiles out metrics: disable `metrics`");
 
 mod air;
-mod config;
+mod audit;
+mod bus;
+mod capabilities;
+mod challenges;
+mod check_constraints;
+#[cfg(not(feature = "minimal"))]
+mod compiled;
+pub mod config;
+#[cfg(not(feature = "minimal"))]
+mod degree_lowering;
+mod encoding;
 mod folder;
+mod inversion;
+mod keygen;
+mod machine;
+mod mock_prover;
+mod openings;
+mod pcs;
+mod program;
 mod proof;
+#[cfg(not(feature = "minimal"))]
+mod proof_diff;
+mod protocol;
+mod provenance;
 mod prover;
+#[cfg(not(feature = "minimal"))]
+mod recursion;
+mod rows;
+mod security;
+mod shard;
+mod statements;
+#[cfg(not(feature = "minimal"))]
+mod symbolic;
+mod telemetry;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+mod trace_generator;
+mod transcript;
 mod verifier;
 
 pub use air::*;
+pub use audit::*;
+pub use bus::*;
+pub use capabilities::*;
+pub use challenges::*;
+pub use check_constraints::*;
+#[cfg(not(feature = "minimal"))]
+pub use compiled::*;
 pub use config::*;
+#[cfg(not(feature = "minimal"))]
+pub use degree_lowering::*;
+pub use encoding::*;
 pub use folder::*;
+pub use inversion::*;
+pub use keygen::*;
+pub use machine::*;
+pub use mock_prover::*;
+pub use openings::*;
+pub use pcs::*;
+pub use program::*;
 pub use proof::*;
+#[cfg(not(feature = "minimal"))]
+pub use proof_diff::*;
+pub use protocol::*;
+pub use provenance::*;
 pub use prover::*;
+#[cfg(not(feature = "minimal"))]
+pub use recursion::*;
+pub use rows::*;
+pub use security::*;
+pub use shard::*;
+pub use statements::*;
+#[cfg(not(feature = "minimal"))]
+pub use symbolic::*;
+pub use telemetry::*;
+pub use trace_generator::*;
+pub use transcript::*;
 pub use verifier::*;
 
 // Re-export key Plonky3 types
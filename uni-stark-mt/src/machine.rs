@@ -0,0 +1,957 @@
+//! Proving several chips under one transcript
+//!
+//! A virtual machine splits its execution over tables of different widths and heights, a
+//! CPU table next to a memory table next to a range-check table. [`prove_machine`] proves
+//! such a set of *chips* at once: their main traces are committed together, each on its
+//! own trace domain, the auxiliary phase draws one set of challenges they all share, each
+//! chip gets its own quotient, and a single PCS opening covers every trace at one
+//! out-of-domain point. The result is one [`MachineProof`], checked by
+//! [`verify_machine`].
+//!
+//! Chips declaring a challenge of the same name see the same value, so arguments spanning
+//! several chips, e.g. a LogUp bus, are computed under the same challenges. The verifier
+//! checks the shares chips expose at their
+//! [`bus_contribution_index`](crate::AuxTraceBuilder::bus_contribution_index) add up to
+//! zero, so a machine whose chips send and receive different values on the bus is
+//! rejected.
+//!
+//! Each chip reads its own public values, and the machine may carry a global segment no
+//! chip reads, such as a program digest; the transcript binds all of them, see
+//! [`MachinePublicValues`].
+//!
+//! Machines support the chip features most tables need: main and auxiliary columns opened
+//! at the current and next rows, public values and exposed auxiliary values. Chips
+//! punctured, pinned, split into statements or opened at other rows are rejected with
+//! [`ProverError::InvalidMachine`] and [`VerificationError::InvalidMachine`], as are
+//! configurations sampling more than one out-of-domain point.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_commit::{Pcs, PolynomialSpace};
+use p3_field::{BasedVectorSpace, Field, PackedField, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+use serde::{Deserialize, Serialize};
+use tracing::info_span;
+
+use crate::air::has_aux_phase;
+use crate::prover::{check_trace_height, committed_aux_trace, compute_quotient_values};
+use crate::telemetry::record_verification;
+use crate::verifier::check_log_degree;
+use crate::{
+    check_bus_balance, constraint_alpha_powers, domain_separator_elements, keygen,
+    recompose_aux_openings, recompose_quotient_from_chunks, AuxTraces, BusContribution, Challenge,
+    ChallengeSampling, ChallengeSet, Commitment, DebugConstraintBuilder, Domain, MultiTraceAir,
+    OpeningSchedule, PcsProof, ProverError, ProverFolder, RotatedRows, StarkGenericConfig,
+    StatementSelectors, TraceDomain, TranscriptPhase, Val, VerificationError, VerifierFolder,
+    VerifyingKey,
+};
+
+/// Tag absorbed after the domain separator of a machine's transcript, so a machine's
+/// proof never replays as a single AIR's.
+const MACHINE_TAG: u32 = u32::from_be_bytes(*b"MACH");
+
+/// An AIR that can be a chip of a machine, see [`prove_machine`].
+///
+/// Implemented for every AIR the single-trace prover and verifier accept, so chips of
+/// different types can be proven together as `&dyn Chip<SC>`.
+pub trait Chip<SC: StarkGenericConfig>:
+    MultiTraceAir<Val<SC>, Challenge<SC>>
+    + for<'a> Air<ProverFolder<'a, SC>>
+    + for<'a> Air<VerifierFolder<'a, SC>>
+    + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>
+where
+    Val<SC>: PackedField,
+{
+}
+
+impl<SC, A> Chip<SC> for A
+where
+    SC: StarkGenericConfig,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+}
+
+/// Public values of a machine, see [`prove_machine`].
+#[derive(Clone, Copy, Debug)]
+pub struct MachinePublicValues<'a, F> {
+    /// Values of the whole machine no chip reads, e.g. a program digest, or the machine's
+    /// initial and final state, bound by the transcript for the application to check
+    pub global: &'a [F],
+
+    /// Each chip's public values, in the order of the chips, as many as it declares in
+    /// [`num_public_values`](p3_air::BaseAirWithPublicValues::num_public_values); empty
+    /// if no chip takes any
+    pub chips: &'a [&'a [F]],
+}
+
+impl<'a, F> MachinePublicValues<'a, F> {
+    /// Public values with a `global` segment and each chip's values.
+    pub const fn new(global: &'a [F], chips: &'a [&'a [F]]) -> Self {
+        Self { global, chips }
+    }
+
+    /// The public values of chip `index`.
+    pub fn chip(&self, index: usize) -> &'a [F] {
+        self.chips.get(index).copied().unwrap_or_default()
+    }
+}
+
+impl<F> Default for MachinePublicValues<'_, F> {
+    fn default() -> Self {
+        Self::new(&[], &[])
+    }
+}
+
+/// Proof that a machine's chips all satisfy their constraints, see [`prove_machine`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MachineProof<SC: StarkGenericConfig> {
+    /// Commitment to every chip's main trace, one matrix per chip in order
+    pub main_commit: Commitment<SC>,
+
+    /// Commitment to the auxiliary traces of the chips with an auxiliary phase, in order,
+    /// if any has one
+    pub aux_commit: Option<Commitment<SC>>,
+
+    /// Commitment to every chip's quotient chunks, chip after chip
+    pub quotient_commit: Commitment<SC>,
+
+    /// Each chip's height and opened values, in the order of the chips
+    pub chips: Vec<ChipProof<Challenge<SC>>>,
+
+    /// Proof-of-work witness ground before the out-of-domain point, see
+    /// [`StarkGenericConfig::pow_bits`]
+    pub pow_witness: Val<SC>,
+
+    /// The PCS opening proof for all commitments
+    pub opening_proof: PcsProof<SC>,
+}
+
+impl<SC: StarkGenericConfig> Clone for MachineProof<SC> {
+    fn clone(&self) -> Self {
+        Self {
+            main_commit: self.main_commit.clone(),
+            aux_commit: self.aux_commit.clone(),
+            quotient_commit: self.quotient_commit.clone(),
+            chips: self.chips.clone(),
+            pow_witness: self.pow_witness,
+            opening_proof: self.opening_proof.clone(),
+        }
+    }
+}
+
+/// A chip's part of a [`MachineProof`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChipProof<EF> {
+    /// log2 of the chip's trace height
+    pub log_degree: u8,
+
+    /// Main trace at ζ
+    pub main_local: Vec<EF>,
+
+    /// Main trace at ζ·g, if the chip reads the next row
+    pub main_next: Vec<EF>,
+
+    /// Extension-field auxiliary columns at ζ, flattened to their base-field coefficients
+    pub aux_local: Vec<EF>,
+
+    /// Extension-field auxiliary columns at ζ·g, if the chip reads the next row
+    pub aux_next: Vec<EF>,
+
+    /// Base-field auxiliary columns at ζ
+    pub aux_base_local: Vec<EF>,
+
+    /// Base-field auxiliary columns at ζ·g, if the chip reads the next row
+    pub aux_base_next: Vec<EF>,
+
+    /// Values the chip's auxiliary phase exposes
+    pub aux_exposed_values: Vec<EF>,
+
+    /// Quotient chunks at ζ, each flattened to its base-field coefficients
+    pub quotient_chunks: Vec<Vec<EF>>,
+}
+
+/// Check `chip` only uses the features machines support.
+fn check_chip<SC, A>(chip: &A) -> Result<(), &'static str>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + ?Sized,
+{
+    let opened_beyond_next = |schedule: OpeningSchedule| {
+        schedule.prev_row || schedule.last_row || !schedule.rotations.is_empty()
+    };
+    if chip.num_punctured_rows() != 0 || chip.num_statements() != 1 {
+        return Err("chips constrain every row as one statement");
+    }
+    if !chip.boundary_constraints().is_empty() {
+        return Err("chips pin no cells");
+    }
+    if chip.challenge_free_aux_width() != 0 {
+        return Err("chips have no challenge-free auxiliary columns");
+    }
+    if chip.challenge_sampling() != ChallengeSampling::Independent {
+        return Err("chips draw their challenges independently");
+    }
+    if opened_beyond_next(chip.main_openings()) || opened_beyond_next(chip.aux_openings()) {
+        return Err("chips are only opened at the current and next rows");
+    }
+    Ok(())
+}
+
+/// Check `config` and `chips` make a machine.
+fn check_machine<SC, A>(
+    config: &SC,
+    chips: &[&A],
+    public_values: &MachinePublicValues<'_, Val<SC>>,
+) -> Result<(), &'static str>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + ?Sized,
+{
+    if chips.is_empty() {
+        return Err("a machine has at least one chip");
+    }
+    if config.num_ood_points() != 1 {
+        return Err("machines are opened at a single out-of-domain point");
+    }
+    if !public_values.chips.is_empty() && public_values.chips.len() != chips.len() {
+        return Err("machines take one slice of public values per chip");
+    }
+    chips.iter().try_for_each(|chip| check_chip::<SC, A>(*chip))
+}
+
+/// Check `public_values` holds each chip's public values, or return the `(expected,
+/// actual)` number of values of the first chip it doesn't.
+fn check_public_values<SC, A>(
+    chips: &[&A],
+    public_values: &MachinePublicValues<'_, Val<SC>>,
+) -> Result<(), (usize, usize)>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + ?Sized,
+{
+    for (i, chip) in chips.iter().enumerate() {
+        let (expected, actual) = (chip.num_public_values(), public_values.chip(i).len());
+        if expected != actual {
+            return Err((expected, actual));
+        }
+    }
+    Ok(())
+}
+
+/// The challenges of all chips, each name once, in order of first declaration.
+fn machine_challenge_names<SC, A>(chips: &[&A]) -> Vec<&'static str>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + ?Sized,
+{
+    let mut names: Vec<&'static str> = Vec::new();
+    for chip in chips {
+        if has_aux_phase::<Val<SC>, Challenge<SC>, A>(chip) {
+            for name in chip.challenge_names() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    names
+}
+
+/// The values of `chip`'s challenges, picked from the machine's.
+fn chip_challenge_values<SC, A>(
+    chip: &A,
+    names: &[&'static str],
+    values: &[Challenge<SC>],
+) -> Vec<Challenge<SC>>
+where
+    SC: StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>> + ?Sized,
+{
+    chip.challenge_names()
+        .iter()
+        .map(|name| {
+            let index = names
+                .iter()
+                .position(|machine_name| machine_name == name)
+                .expect("the machine draws every chip's challenges");
+            values[index]
+        })
+        .collect()
+}
+
+/// Absorb what identifies a machine: the domain separator, the machine tag, each chip's
+/// protocol identifier, fingerprint and height, then the length of the global public
+/// values.
+fn observe_machine<SC: StarkGenericConfig>(
+    challenger: &mut SC::Challenger,
+    config: &SC,
+    vks: &[VerifyingKey],
+    log_degrees: &[u8],
+    num_global_values: usize,
+) {
+    challenger.observe_slice(&domain_separator_elements::<Val<SC>>(
+        config.domain_separator(),
+    ));
+    challenger.observe(Val::<SC>::from_u32(MACHINE_TAG));
+    challenger.observe(Val::<SC>::from_usize(vks.len()));
+    for (vk, &log_degree) in vks.iter().zip(log_degrees) {
+        challenger.observe_slice(&vk.protocol_id.to_field_elements::<Val<SC>>());
+        challenger.observe_slice(&vk.fingerprint.map(Val::<SC>::from_u8));
+        challenger.observe(Val::<SC>::from_u8(log_degree));
+    }
+    challenger.observe(Val::<SC>::from_usize(num_global_values));
+}
+
+/// Absorb a machine's public values: the global segment, then each chip's.
+fn observe_public_values<SC: StarkGenericConfig>(
+    challenger: &mut SC::Challenger,
+    num_chips: usize,
+    public_values: &MachinePublicValues<'_, Val<SC>>,
+) {
+    challenger.observe_slice(public_values.global);
+    for i in 0..num_chips {
+        challenger.observe_slice(public_values.chip(i));
+    }
+}
+
+/// Prove that every chip of a machine satisfies its constraints on its trace.
+///
+/// `traces[i]` is the main trace of `chips[i]`; traces may have different heights, each
+/// a power of two. Chips of different types are passed as `&dyn Chip<SC>`. Each chip's
+/// constraints read its slice of `public_values`.
+///
+/// # Errors
+/// - [`ProverError::InvalidMachine`] if there is no chip, a chip uses a feature machines
+///   don't support, `config` samples more than one out-of-domain point, or
+///   `public_values` doesn't hold a slice per chip
+/// - [`ProverError::PublicValuesMismatch`] if a chip's public values don't have the
+///   length it declares
+/// - [`ProverError::DegreeExceedsFieldTwoAdicity`] if a trace is too tall for the PCS
+/// - [`ProverError::ZeroDenominator`] if a denominator of an auxiliary trace vanishes
+///
+/// # Panics
+/// If `traces` doesn't hold one trace per chip, or a trace's or its auxiliary trace's
+/// dimensions don't match its chip.
+pub fn prove_machine<SC, A>(
+    config: &SC,
+    chips: &[&A],
+    traces: Vec<RowMajorMatrix<Val<SC>>>,
+    public_values: &MachinePublicValues<'_, Val<SC>>,
+) -> Result<MachineProof<SC>, ProverError>
+where
+    SC: StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: Chip<SC> + ?Sized,
+{
+    check_machine(config, chips, public_values).map_err(ProverError::InvalidMachine)?;
+    check_public_values::<SC, A>(chips, public_values)
+        .map_err(|(expected, actual)| ProverError::PublicValuesMismatch { expected, actual })?;
+    assert_eq!(chips.len(), traces.len(), "Expected one trace per chip");
+    let vks: Vec<_> = chips.iter().map(|chip| keygen(config, *chip)).collect();
+    for ((chip, vk), trace) in chips.iter().zip(&vks).zip(&traces) {
+        assert_eq!(trace.width(), chip.width(), "Main trace width mismatch");
+        check_trace_height(config, trace.height(), vk.log_quotient_degree)?;
+    }
+
+    let pcs = config.pcs();
+    let extension_sampling = config.extension_sampling();
+    let mut challenger = config.initialise_challenger();
+    let heights: Vec<usize> = traces.iter().map(|trace| trace.height()).collect();
+    let log_degrees: Vec<u8> = heights
+        .iter()
+        .map(|&height| log2_strict_usize(height) as u8)
+        .collect();
+    let trace_domains: Vec<Domain<SC>> = heights
+        .iter()
+        .map(|&height| pcs.natural_domain_for_degree(height))
+        .collect();
+    observe_machine(
+        &mut challenger,
+        config,
+        &vks,
+        &log_degrees,
+        public_values.global.len(),
+    );
+
+    // ==================== PHASE 1: Main traces ====================
+    challenger.observe(TranscriptPhase::Main.to_field_element::<Val<SC>>());
+    let (main_commit, main_data) = info_span!("pcs_commit_main").in_scope(|| {
+        pcs.commit(
+            trace_domains
+                .iter()
+                .copied()
+                .zip(traces.iter().cloned())
+                .collect::<Vec<_>>(),
+        )
+    });
+    challenger.observe(main_commit.clone());
+    observe_public_values::<SC>(&mut challenger, chips.len(), public_values);
+
+    // ==================== PHASE 2: Auxiliary traces ====================
+    // Chips with an auxiliary phase are committed together, under the shared challenges
+    let challenge_names = machine_challenge_names::<SC, A>(chips);
+    let aux_chips: Vec<bool> = chips
+        .iter()
+        .map(|chip| has_aux_phase::<Val<SC>, Challenge<SC>, A>(chip))
+        .collect();
+    let mut challenge_values: Vec<Challenge<SC>> = Vec::new();
+    let mut chip_challenges: Vec<Vec<Challenge<SC>>> = vec![Vec::new(); chips.len()];
+    let mut exposed_values: Vec<Vec<Challenge<SC>>> = vec![Vec::new(); chips.len()];
+    let mut aux = None;
+    if aux_chips.contains(&true) {
+        challenger.observe(TranscriptPhase::Aux.to_field_element::<Val<SC>>());
+        challenge_values = (0..challenge_names.len())
+            .map(|_| extension_sampling.draw::<Val<SC>, _, _>(&mut challenger))
+            .collect();
+
+        let mut committed = Vec::new();
+        for (i, chip) in chips.iter().enumerate().filter(|&(i, _)| aux_chips[i]) {
+            let (trace, height) = (&traces[i], heights[i]);
+            chip_challenges[i] =
+                chip_challenge_values::<SC, A>(*chip, &challenge_names, &challenge_values);
+            let chip_names = chip.challenge_names();
+            let challenges = ChallengeSet::new(&chip_names, &chip_challenges[i]);
+            let aux_traces = chip
+                .try_build_aux_traces(trace, &challenges)
+                .map_err(ProverError::ZeroDenominator)?;
+            let aux_height = aux_traces
+                .height(height)
+                .unwrap_or_else(|message| panic!("{message}"));
+            if aux_height != height {
+                return Err(ProverError::InvalidMachine(
+                    "chips' auxiliary traces are as tall as their main traces",
+                ));
+            }
+            let AuxTraces { ext, base, exposed } = aux_traces;
+            assert_eq!(
+                ext.width,
+                chip.aux_width(),
+                "Auxiliary trace width mismatch"
+            );
+            assert_eq!(
+                base.width,
+                chip.aux_base_width(),
+                "Base-field auxiliary trace width mismatch"
+            );
+            assert_eq!(
+                exposed.len(),
+                chip.num_aux_exposed_values(),
+                "Exposed auxiliary value count mismatch"
+            );
+
+            #[cfg(all(debug_assertions, not(feature = "minimal")))]
+            crate::check_constraints::debug_assert_constraints(
+                *chip,
+                trace,
+                Some(&ext),
+                Some(&base),
+                challenges,
+                &exposed,
+                public_values.chip(i),
+            );
+
+            committed.push((trace_domains[i], committed_aux_trace(&ext, &base, height)));
+            exposed_values[i] = exposed;
+        }
+
+        let (aux_commit, aux_data) =
+            info_span!("pcs_commit_aux").in_scope(|| pcs.commit(committed));
+        challenger.observe(aux_commit.clone());
+        for value in exposed_values.iter().flatten() {
+            challenger.observe_algebra_element(*value);
+        }
+        aux = Some((aux_commit, aux_data));
+    }
+    #[cfg(all(debug_assertions, not(feature = "minimal")))]
+    for (i, chip) in chips.iter().enumerate().filter(|&(i, _)| !aux_chips[i]) {
+        crate::check_constraints::debug_assert_constraints(
+            *chip,
+            &traces[i],
+            None,
+            None,
+            ChallengeSet::empty(),
+            &[],
+            public_values.chip(i),
+        );
+    }
+
+    // ==================== PHASE 3: Quotients ====================
+    challenger.observe(TranscriptPhase::Quotient.to_field_element::<Val<SC>>());
+    let alpha: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
+
+    let mut quotient_chunks = Vec::new();
+    // Each chip's auxiliary matrix, in the order of the auxiliary commitment
+    let aux_indices: Vec<Option<usize>> = aux_chips
+        .iter()
+        .scan(0, |next, &has_aux| {
+            Some(has_aux.then(|| {
+                *next += 1;
+                *next - 1
+            }))
+        })
+        .collect();
+    for (i, chip) in chips.iter().enumerate() {
+        let (trace_domain, height) = (trace_domains[i], heights[i]);
+        let quotient_degree = 1 << vks[i].log_quotient_degree;
+        let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
+        let main_on_quotient = pcs.get_evaluations_on_domain(&main_data, i, quotient_domain);
+        let aux_on_quotient = aux
+            .as_ref()
+            .zip(aux_indices[i])
+            .map(|((_, data), index)| pcs.get_evaluations_on_domain(data, index, quotient_domain));
+        let chip_names = chip.challenge_names();
+        let challenges = if aux_chips[i] {
+            ChallengeSet::new(&chip_names, &chip_challenges[i])
+        } else {
+            ChallengeSet::empty()
+        };
+        let quotient_values = info_span!("quotient", chip = i).in_scope(|| {
+            compute_quotient_values::<SC, A, _, _>(
+                *chip,
+                trace_domain,
+                quotient_domain,
+                &trace_domain.selectors_on_coset(quotient_domain),
+                &[main_on_quotient],
+                None,
+                aux_on_quotient.as_ref(),
+                challenges,
+                &exposed_values[i],
+                &[],
+                &[],
+                &[],
+                &constraint_alpha_powers(alpha, vks[i].num_constraints),
+                public_values.chip(i),
+                &[],
+                None,
+            )
+        });
+        let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
+        quotient_chunks.extend(
+            quotient_domain
+                .split_domains(quotient_degree)
+                .into_iter()
+                .zip(quotient_domain.split_evals(quotient_degree, quotient_flat)),
+        );
+    }
+    let (quotient_commit, quotient_data) =
+        info_span!("pcs_commit_quotient").in_scope(|| pcs.commit(quotient_chunks));
+    challenger.observe(quotient_commit.clone());
+
+    // ==================== PHASE 4: Opening ====================
+    challenger.observe(TranscriptPhase::Opening.to_field_element::<Val<SC>>());
+    let pow_witness = match config.pow_bits() {
+        0 => Val::<SC>::ZERO,
+        bits => info_span!("grind").in_scope(|| challenger.grind(bits)),
+    };
+    let zeta: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
+
+    // Each chip is opened at ζ, and at its own ζ·g if it reads the next row
+    let points = |i: usize, schedule: OpeningSchedule| {
+        let mut points = vec![zeta];
+        if schedule.next_row {
+            points.push(
+                trace_domains[i]
+                    .next_point(zeta)
+                    .expect("domain must support next_point"),
+            );
+        }
+        points
+    };
+    let mut opening_points = vec![(
+        &main_data,
+        chips
+            .iter()
+            .enumerate()
+            .map(|(i, chip)| points(i, chip.main_openings()))
+            .collect(),
+    )];
+    if let Some((_, aux_data)) = &aux {
+        opening_points.push((
+            aux_data,
+            chips
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| aux_chips[i])
+                .map(|(i, chip)| points(i, chip.aux_openings()))
+                .collect(),
+        ));
+    }
+    opening_points.push((
+        &quotient_data,
+        vec![vec![zeta]; vks.iter().map(|vk| 1 << vk.log_quotient_degree).sum()],
+    ));
+    let (opened_values, opening_proof) =
+        info_span!("pcs_open").in_scope(|| pcs.open(opening_points, &mut challenger));
+
+    // Split the opened values back by chip
+    let ext_degree = <Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let mut opened = opened_values.into_iter();
+    let main_opened = opened.next().expect("the main traces are opened");
+    let mut aux_opened = aux.is_some().then(|| {
+        opened
+            .next()
+            .expect("the auxiliary traces are opened")
+            .into_iter()
+    });
+    let mut quotient_opened = opened.next().expect("the quotients are opened").into_iter();
+    let chip_proofs = chips
+        .iter()
+        .zip(main_opened)
+        .enumerate()
+        .map(|(i, (chip, mut main))| {
+            let quotient: Vec<_> = quotient_opened
+                .by_ref()
+                .take(1 << vks[i].log_quotient_degree)
+                .collect();
+            let mut proof = ChipProof {
+                log_degree: log_degrees[i],
+                main_local: main.remove(0),
+                main_next: main.pop().unwrap_or_default(),
+                aux_local: Vec::new(),
+                aux_next: Vec::new(),
+                aux_base_local: Vec::new(),
+                aux_base_next: Vec::new(),
+                aux_exposed_values: core::mem::take(&mut exposed_values[i]),
+                quotient_chunks: quotient.iter().map(|chunk| chunk[0].clone()).collect(),
+            };
+            if aux_chips[i] {
+                let mut rows = aux_opened
+                    .as_mut()
+                    .and_then(Iterator::next)
+                    .expect("every chip with an auxiliary phase is opened");
+                let split = chip.aux_width() * ext_degree;
+                let mut local = rows.remove(0);
+                proof.aux_base_local = local.split_off(split);
+                proof.aux_local = local;
+                if let Some(mut next) = rows.pop() {
+                    proof.aux_base_next = next.split_off(split);
+                    proof.aux_next = next;
+                }
+            }
+            proof
+        })
+        .collect();
+
+    Ok(MachineProof {
+        main_commit,
+        aux_commit: aux.map(|(commit, _)| commit),
+        quotient_commit,
+        chips: chip_proofs,
+        pow_witness,
+        opening_proof,
+    })
+}
+
+/// Verify a [`MachineProof`] of `chips`, given in the prover's order.
+///
+/// As [`verify_with_vk`](crate::verify_with_vk), verification never panics on a
+/// malformed proof; `config` and `chips` are trusted.
+///
+/// # Errors
+/// - [`VerificationError::InvalidMachine`] if `config`, `chips` and `public_values` don't
+///   make a machine, see [`prove_machine`]
+/// - [`VerificationError::InvalidProof`] if the proof doesn't have the chips' shape,
+///   [`VerificationError::LogDegreeOutOfRange`] if a chip claims a taller trace than
+///   `config` accepts, and [`VerificationError::QuotientChunkCount`] if a chip's quotient
+///   doesn't have its number of chunks
+/// - [`VerificationError::InvalidPowWitness`],
+///   [`VerificationError::PcsVerificationFailed`] and
+///   [`VerificationError::ConstraintVerificationFailed`] as for a single AIR
+/// - [`VerificationError::PublicValuesMismatch`] if a chip's public values don't have the
+///   length it declares
+/// - [`VerificationError::UnbalancedBus`] if the chips' bus contributions don't add up to
+///   zero
+pub fn verify_machine<SC, A>(
+    config: &SC,
+    chips: &[&A],
+    proof: &MachineProof<SC>,
+    public_values: &MachinePublicValues<'_, Val<SC>>,
+) -> Result<(), VerificationError>
+where
+    SC: StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: Chip<SC> + ?Sized,
+{
+    let result = verify_machine_inner(config, chips, proof, public_values);
+    record_verification(&result);
+    result
+}
+
+fn verify_machine_inner<SC, A>(
+    config: &SC,
+    chips: &[&A],
+    proof: &MachineProof<SC>,
+    public_values: &MachinePublicValues<'_, Val<SC>>,
+) -> Result<(), VerificationError>
+where
+    SC: StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: Chip<SC> + ?Sized,
+{
+    check_machine(config, chips, public_values).map_err(VerificationError::InvalidMachine)?;
+    check_public_values::<SC, A>(chips, public_values).map_err(|(expected, actual)| {
+        VerificationError::PublicValuesMismatch { expected, actual }
+    })?;
+    if proof.chips.len() != chips.len() {
+        return Err(VerificationError::InvalidProof("wrong number of chips"));
+    }
+
+    // Everything after these checks may index into the opened values
+    let ext_degree = <Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let vks: Vec<_> = chips.iter().map(|chip| keygen(config, *chip)).collect();
+    for (vk, chip_proof) in vks.iter().zip(&proof.chips) {
+        check_log_degree(config, vk, chip_proof.log_degree)?;
+        let scheduled = |opened: bool, width: usize| if opened { width } else { 0 };
+        let (main, aux) = (vk.main_openings, vk.aux_openings);
+        let aux_width = vk.aux_width * ext_degree;
+        if chip_proof.main_local.len() != vk.main_width
+            || chip_proof.main_next.len() != scheduled(main.next_row, vk.main_width)
+        {
+            return Err(VerificationError::InvalidProof(
+                "main trace openings have the wrong width",
+            ));
+        }
+        if chip_proof.aux_local.len() != aux_width
+            || chip_proof.aux_next.len() != scheduled(aux.next_row, aux_width)
+            || chip_proof.aux_base_local.len() != vk.aux_base_width
+            || chip_proof.aux_base_next.len() != scheduled(aux.next_row, vk.aux_base_width)
+        {
+            return Err(VerificationError::InvalidProof(
+                "auxiliary trace openings have the wrong width",
+            ));
+        }
+        if chip_proof.aux_exposed_values.len() != vk.num_aux_exposed_values {
+            return Err(VerificationError::InvalidProof(
+                "wrong number of exposed auxiliary values",
+            ));
+        }
+        if chip_proof.quotient_chunks.len() != 1 << vk.log_quotient_degree {
+            return Err(VerificationError::QuotientChunkCount {
+                expected: 1 << vk.log_quotient_degree,
+                actual: chip_proof.quotient_chunks.len(),
+            });
+        }
+        if chip_proof
+            .quotient_chunks
+            .iter()
+            .any(|chunk| chunk.len() != ext_degree)
+        {
+            return Err(VerificationError::InvalidProof(
+                "quotient chunks have the wrong shape",
+            ));
+        }
+    }
+    let aux_chips: Vec<bool> = vks.iter().map(|vk| vk.has_aux_phase()).collect();
+    if proof.aux_commit.is_some() != aux_chips.contains(&true) {
+        return Err(VerificationError::InvalidProof(
+            "auxiliary commitment doesn't match the chips",
+        ));
+    }
+
+    let pcs = config.pcs();
+    let extension_sampling = config.extension_sampling();
+    let mut challenger = config.initialise_challenger();
+    let log_degrees: Vec<u8> = proof.chips.iter().map(|chip| chip.log_degree).collect();
+    observe_machine(
+        &mut challenger,
+        config,
+        &vks,
+        &log_degrees,
+        public_values.global.len(),
+    );
+
+    // Replay the transcript, as the prover
+    challenger.observe(TranscriptPhase::Main.to_field_element::<Val<SC>>());
+    challenger.observe(proof.main_commit.clone());
+    observe_public_values::<SC>(&mut challenger, chips.len(), public_values);
+    let challenge_names = machine_challenge_names::<SC, A>(chips);
+    let mut challenge_values: Vec<Challenge<SC>> = Vec::new();
+    if let Some(aux_commit) = &proof.aux_commit {
+        challenger.observe(TranscriptPhase::Aux.to_field_element::<Val<SC>>());
+        challenge_values = (0..challenge_names.len())
+            .map(|_| extension_sampling.draw::<Val<SC>, _, _>(&mut challenger))
+            .collect();
+        challenger.observe(aux_commit.clone());
+        for value in proof.chips.iter().flat_map(|chip| &chip.aux_exposed_values) {
+            challenger.observe_algebra_element(*value);
+        }
+    }
+    challenger.observe(TranscriptPhase::Quotient.to_field_element::<Val<SC>>());
+    let alpha: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
+    challenger.observe(proof.quotient_commit.clone());
+
+    challenger.observe(TranscriptPhase::Opening.to_field_element::<Val<SC>>());
+    match config.pow_bits() {
+        0 if proof.pow_witness != Val::<SC>::ZERO => {
+            return Err(VerificationError::InvalidProof(
+                "proof-of-work witness without grinding",
+            ));
+        }
+        0 => {}
+        bits => {
+            if !challenger.check_witness(bits, proof.pow_witness) {
+                return Err(VerificationError::InvalidPowWitness);
+            }
+        }
+    }
+    let zeta: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
+
+    // Open every chip at ζ, and at its ζ·g if it reads the next row, and check its
+    // constraints against its quotient there
+    let mut main_openings = Vec::new();
+    let mut aux_openings = Vec::new();
+    let mut quotient_openings = Vec::new();
+    let mut constraints_hold = true;
+    for (i, ((chip, vk), chip_proof)) in chips.iter().zip(&vks).zip(&proof.chips).enumerate() {
+        let height = 1usize << chip_proof.log_degree;
+        let trace_domain = pcs.natural_domain_for_degree(height);
+        let vanishing_at_zeta = trace_domain.vanishing_poly_at_point(zeta);
+        if vanishing_at_zeta.is_zero() {
+            return Err(VerificationError::InvalidProof(
+                "out-of-domain point lies in the trace domain",
+            ));
+        }
+        let next = trace_domain
+            .next_point(zeta)
+            .ok_or(VerificationError::InvalidProof(
+                "trace domain does not support next_point",
+            ))?;
+        let opened =
+            |next_row: bool, local: Vec<Challenge<SC>>, next_values: Vec<Challenge<SC>>| {
+                let mut openings = vec![(zeta, local)];
+                if next_row {
+                    openings.push((next, next_values));
+                }
+                openings
+            };
+        main_openings.push((
+            trace_domain,
+            opened(
+                vk.main_openings.next_row,
+                chip_proof.main_local.clone(),
+                chip_proof.main_next.clone(),
+            ),
+        ));
+        if aux_chips[i] {
+            // Both segments are committed as one matrix, base-field columns last
+            aux_openings.push((
+                trace_domain,
+                opened(
+                    vk.aux_openings.next_row,
+                    [
+                        chip_proof.aux_local.clone(),
+                        chip_proof.aux_base_local.clone(),
+                    ]
+                    .concat(),
+                    [
+                        chip_proof.aux_next.clone(),
+                        chip_proof.aux_base_next.clone(),
+                    ]
+                    .concat(),
+                ),
+            ));
+        }
+        let quotient_degree = 1 << vk.log_quotient_degree;
+        let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
+        let quotient_chunk_domains = quotient_domain.split_domains(quotient_degree);
+        quotient_openings.extend(
+            quotient_chunk_domains
+                .iter()
+                .zip(&chip_proof.quotient_chunks)
+                .map(|(&domain, chunk)| (domain, vec![(zeta, chunk.clone())])),
+        );
+
+        let selectors = trace_domain.selectors_at_point(zeta);
+        let chip_public_values = public_values.chip(i);
+        let public_values_ext: Vec<Challenge<SC>> = chip_public_values
+            .iter()
+            .map(|&value| value.into())
+            .collect();
+        let aux_local = recompose_aux_openings::<SC>(&chip_proof.aux_local);
+        let aux_next = recompose_aux_openings::<SC>(&chip_proof.aux_next);
+        let chip_names = chip.challenge_names();
+        let chip_values = if aux_chips[i] {
+            chip_challenge_values::<SC, A>(*chip, &challenge_names, &challenge_values)
+        } else {
+            Vec::new()
+        };
+        let challenges = if aux_chips[i] {
+            ChallengeSet::new(&chip_names, &chip_values)
+        } else {
+            ChallengeSet::empty()
+        };
+        let mut folder = VerifierFolder {
+            main_local: &chip_proof.main_local,
+            main_next: &chip_proof.main_next,
+            main_last_row: &[],
+            main_openings: vk.main_openings,
+            aux_openings: vk.aux_openings,
+            aux_local: &aux_local,
+            aux_next: &aux_next,
+            aux_base_local: &chip_proof.aux_base_local,
+            aux_base_next: &chip_proof.aux_base_next,
+            challenges,
+            aux_exposed_values: &chip_proof.aux_exposed_values,
+            aux_prev_row: &[],
+            aux_base_prev_row: &[],
+            main_prev_row: &[],
+            aux_rotated: RotatedRows::empty(),
+            aux_base_rotated: RotatedRows::empty(),
+            main_rotated: RotatedRows::empty(),
+            aux_last_row: &[],
+            aux_base_last_row: &[],
+            public_values: chip_public_values,
+            is_first_row: selectors.is_first_row,
+            is_last_row: selectors.is_last_row,
+            is_transition: selectors.is_transition,
+            statements: StatementSelectors {
+                is_first_row: selectors.is_first_row,
+                is_last_row: selectors.is_last_row,
+                is_transition: selectors.is_transition,
+                public_values: &public_values_ext,
+            },
+            alpha,
+            accumulator: Challenge::<SC>::ZERO,
+        };
+        chip.eval(&mut folder);
+        let quotient_at_zeta = recompose_quotient_from_chunks::<SC>(
+            &quotient_chunk_domains,
+            &chip_proof.quotient_chunks,
+            zeta,
+        );
+        constraints_hold &= folder.accumulator * selectors.inv_vanishing == quotient_at_zeta;
+    }
+
+    let mut coms_to_verify = vec![(proof.main_commit.clone(), main_openings)];
+    if let Some(aux_commit) = &proof.aux_commit {
+        coms_to_verify.push((aux_commit.clone(), aux_openings));
+    }
+    coms_to_verify.push((proof.quotient_commit.clone(), quotient_openings));
+    pcs.verify(coms_to_verify, &proof.opening_proof, &mut challenger)
+        .map_err(|_| VerificationError::PcsVerificationFailed)?;
+
+    if !constraints_hold {
+        return Err(VerificationError::ConstraintVerificationFailed);
+    }
+
+    // The verified shares of every chip on the bus cancel out
+    let contributions: Vec<BusContribution<Challenge<SC>>> = vks
+        .iter()
+        .zip(&proof.chips)
+        .filter_map(|(vk, chip_proof)| BusContribution::from_chip_proof(vk, chip_proof))
+        .collect();
+    check_bus_balance(&contributions).map_err(|_| VerificationError::UnbalancedBus)
+}
@@ -0,0 +1,253 @@
+//! Validating AIRs without a PCS
+//!
+//! Proving is slow to iterate on while an AIR is being written: a mistake only shows up
+//! as a failed verification. [`MockProver`] runs the parts of the prover that produce
+//! witness values, sampling pseudo-random challenges and building the auxiliary trace
+//! from them, then checks every constraint on every row, without committing to or
+//! opening anything.
+
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_field::{BasedVectorSpace, ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::air::has_aux_phase;
+use crate::check_constraints::collect_constraint_violations;
+use crate::openings::check_boundary_constraints;
+use crate::statements::check_statement_layout;
+use crate::{
+    AuxTraces, ChallengeSet, ConstraintViolation, ConstraintViolations, DebugConstraintBuilder,
+    MultiTraceAir, ZeroDenominator, ZeroDenominatorPolicy,
+};
+
+/// Seed of the pseudo-challenges, fixed so that runs are reproducible
+const PSEUDO_CHALLENGE_SEED: u64 = 0x6d6f_636b_6368_616c;
+
+/// Inputs [`MockProver::run`] can't check the constraints on, as the prover would
+/// reject or panic on them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockProverError {
+    /// The main trace isn't as wide as the AIR
+    MainWidthMismatch {
+        /// The AIR's width
+        expected: usize,
+        /// Width of the supplied trace
+        actual: usize,
+    },
+    /// The number of public values doesn't match the AIR's `num_public_values()`
+    PublicValuesMismatch {
+        /// Number of public values the AIR expects
+        expected: usize,
+        /// Number of public values supplied
+        actual: usize,
+    },
+    /// The AIR's statements can't be laid out in this trace
+    InvalidStatements(&'static str),
+    /// The AIR's boundary constraints don't fit this trace and its public values
+    InvalidBoundaryConstraints(&'static str),
+    /// `build_aux_traces` returned columns or exposed values other than the AIR declares
+    AuxShapeMismatch(&'static str),
+    /// A denominator of the auxiliary trace vanished on every attempt the AIR's
+    /// [`zero_denominator_policy`](crate::AuxTraceBuilder::zero_denominator_policy)
+    /// allows
+    ZeroDenominator(ZeroDenominator),
+}
+
+/// The witness of one AIR run on concrete values, and the constraints it violates.
+///
+/// Like halo2's `MockProver`: [`run`](Self::run) it on a main trace, then
+/// [`verify`](Self::verify) it.
+///
+/// ```ignore
+/// let prover = MockProver::run(&air, &main_trace, &public_values)?;
+/// if let Err(violations) = prover.verify() {
+///     panic!("{}", violations.report(&provenance));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MockProver<F, EF> {
+    /// Pseudo-challenges, in the order of `challenge_names`
+    challenges: Vec<EF>,
+
+    /// Auxiliary columns built from the pseudo-challenges
+    aux_traces: AuxTraces<F, EF>,
+
+    /// Constraints that didn't vanish, in row order
+    violations: Vec<ConstraintViolation<EF>>,
+}
+
+impl<F: Field, EF: ExtensionField<F>> MockProver<F, EF> {
+    /// Sample pseudo-challenges, build the auxiliary trace and evaluate every constraint
+    /// of `air` on every row of the traces.
+    ///
+    /// The pseudo-challenges come from a fixed seed rather than a transcript, so they are
+    /// the same from run to run, and lie outside the base field when `EF` is a proper
+    /// extension. They are derived as
+    /// [`challenge_sampling`](crate::AuxTraceBuilder::challenge_sampling) says. Nothing
+    /// binds them, so when a denominator vanishes the next ones are drawn if the AIR's
+    /// [`zero_denominator_policy`](crate::AuxTraceBuilder::zero_denominator_policy)
+    /// allows. Rows punctured by [`PuncturedAir`](crate::PuncturedAir) are not checked.
+    ///
+    /// # Errors
+    /// See [`MockProverError`]. Constraint violations are not errors of `run`: they are
+    /// reported by [`verify`](Self::verify).
+    pub fn run<A>(
+        air: &A,
+        main_trace: &RowMajorMatrix<F>,
+        public_values: &[F],
+    ) -> Result<Self, MockProverError>
+    where
+        A: MultiTraceAir<F, EF> + for<'a> Air<DebugConstraintBuilder<'a, F, EF>>,
+    {
+        if main_trace.width() != air.width() {
+            return Err(MockProverError::MainWidthMismatch {
+                expected: air.width(),
+                actual: main_trace.width(),
+            });
+        }
+        if public_values.len() != air.num_public_values() {
+            return Err(MockProverError::PublicValuesMismatch {
+                expected: air.num_public_values(),
+                actual: public_values.len(),
+            });
+        }
+        check_statement_layout(
+            air.num_statements(),
+            public_values.len(),
+            main_trace.height(),
+        )
+        .map_err(MockProverError::InvalidStatements)?;
+        check_boundary_constraints(
+            &air.boundary_constraints(),
+            main_trace.height(),
+            air.width(),
+            public_values.len(),
+        )
+        .map_err(MockProverError::InvalidBoundaryConstraints)?;
+
+        let names = air.challenge_names();
+        let mut state = PSEUDO_CHALLENGE_SEED;
+        let max_attempts = match air.zero_denominator_policy() {
+            ZeroDenominatorPolicy::Fail => 1,
+            ZeroDenominatorPolicy::Resample { max_attempts } => max_attempts.max(1),
+        };
+        let mut attempt = 1;
+        let (challenges, aux_traces) = loop {
+            let challenges: Vec<EF> = air
+                .challenge_sampling()
+                .sample(names.len(), || pseudo_challenge(&mut state));
+            if !has_aux_phase(air) {
+                let aux_traces = AuxTraces {
+                    ext: RowMajorMatrix::new(Vec::new(), 0),
+                    base: RowMajorMatrix::new(Vec::new(), 0),
+                    exposed: Vec::new(),
+                };
+                break (challenges, aux_traces);
+            }
+            match air.try_build_aux_traces(main_trace, &ChallengeSet::new(&names, &challenges)) {
+                Ok(aux_traces) => break (challenges, aux_traces),
+                Err(_) if attempt < max_attempts => attempt += 1,
+                Err(zero) => return Err(MockProverError::ZeroDenominator(zero)),
+            }
+        };
+        if has_aux_phase(air) {
+            check_aux_shape(air, &aux_traces, main_trace.height())
+                .map_err(MockProverError::AuxShapeMismatch)?;
+        }
+        let challenge_set = ChallengeSet::new(&names, &challenges);
+
+        let violations = collect_constraint_violations(
+            air,
+            main_trace,
+            Some(&aux_traces.ext),
+            Some(&aux_traces.base),
+            challenge_set,
+            &aux_traces.exposed,
+            public_values,
+        );
+        Ok(Self {
+            challenges,
+            aux_traces,
+            violations,
+        })
+    }
+
+    /// The pseudo-challenges the auxiliary trace was built with, in the order of
+    /// [`challenge_names`](crate::AuxTraceBuilder::challenge_names).
+    pub fn challenges(&self) -> &[EF] {
+        &self.challenges
+    }
+
+    /// The auxiliary columns and exposed values built from the pseudo-challenges.
+    pub const fn aux_traces(&self) -> &AuxTraces<F, EF> {
+        &self.aux_traces
+    }
+
+    /// Check the traces satisfy every constraint.
+    ///
+    /// # Errors
+    /// Every constraint that doesn't vanish, on every row, in row order.
+    /// [`ConstraintViolations::report`] renders them for humans.
+    pub fn verify(&self) -> Result<(), ConstraintViolations<EF>> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ConstraintViolations(self.violations.clone()))
+        }
+    }
+}
+
+/// Check the built auxiliary traces have the shape `air` declares.
+fn check_aux_shape<F, EF, A>(
+    air: &A,
+    aux_traces: &AuxTraces<F, EF>,
+    height: usize,
+) -> Result<(), &'static str>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    if aux_traces.ext.width() != air.aux_width() {
+        return Err("the auxiliary trace isn't aux_width() wide");
+    }
+    if aux_traces.base.width() != air.aux_base_width() {
+        return Err("the base-field auxiliary trace isn't aux_base_width() wide");
+    }
+    // Challenge-free columns are committed with the main trace, on its domain
+    let aux_height = aux_traces.height(height)?;
+    if air.challenge_free_aux_width() > 0 && aux_height != height {
+        return Err("challenge-free auxiliary columns aren't as tall as the main trace");
+    }
+    if aux_traces.exposed.len() != air.num_aux_exposed_values() {
+        return Err("the number of exposed values isn't num_aux_exposed_values()");
+    }
+    Ok(())
+}
+
+/// Next output of the SplitMix64 stream at `state`.
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Next pseudo-random element from a SplitMix64 stream at `state`.
+///
+/// Nothing is committed before the challenges are drawn, so they need to be spread over
+/// the field rather than unpredictable.
+fn pseudo_challenge<F: Field, EF: ExtensionField<F>>(state: &mut u64) -> EF {
+    let mut next = || F::from_u64(splitmix64(state));
+    loop {
+        let challenge = <EF as BasedVectorSpace<F>>::from_basis_coefficients_fn(|_| next());
+        // As `ExtensionSampling::BaseSamplesRejecting`, so that e.g. LogUp denominators
+        // can't vanish on base-field trace values
+        if <EF as BasedVectorSpace<F>>::DIMENSION == 1 || !challenge.is_in_basefield() {
+            return challenge;
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! Points each committed trace is opened at
+//!
+//! Every trace is opened at the out-of-domain point ζ. Constraints reading the next row
+//! also need it opened at ζ·g, those reading the previous row at ζ·g⁻¹, those reading
+//! rows further away at ζ·gᵏ for each offset `k`, and boundary checks on accumulators can
+//! need the last row's point. An [`OpeningSchedule`] lists which of these a trace needs,
+//! so proofs only carry the openings its AIR reads; see
+//! [`AirLayout::main_openings`](crate::AirLayout::main_openings) and
+//! [`AirLayout::aux_openings`](crate::AirLayout::aux_openings). Quotient chunks
+//! are always opened at ζ alone.
+//!
+//! The main trace is also opened at the rows its [`BoundaryConstraint`]s pin, so the
+//! verifier reads those cells directly rather than through a selector.
+
+use alloc::vec::Vec;
+
+use serde::{Deserialize, Serialize};
+
+/// Points a committed trace is opened at, besides ζ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpeningSchedule {
+    /// Open at ζ·g, so constraints can read the next row
+    pub next_row: bool,
+
+    /// Open at ζ·g⁻¹, so constraints can read the previous row
+    ///
+    /// The previous row of the first row is the last row, as the next row of the last row
+    /// is the first. Requires a PCS whose domain points are field elements, see
+    /// [`TraceDomain`](crate::TraceDomain).
+    pub prev_row: bool,
+
+    /// Open at ζ·gᵏ for each further offset `k`, so constraints can read rows further
+    /// from the current one
+    ///
+    /// Rows wrap around the trace as the next and previous rows do. Requires a PCS whose
+    /// domain points are field elements.
+    pub rotations: Rotations,
+
+    /// Open at the last row's point, so constraints can read the last row on every row
+    ///
+    /// Requires a PCS whose domain points are field elements, see
+    /// [`TraceDomain`](crate::TraceDomain).
+    pub last_row: bool,
+}
+
+impl OpeningSchedule {
+    /// Only ζ: constraints on the current row alone
+    pub const LOCAL: Self = Self {
+        next_row: false,
+        prev_row: false,
+        rotations: Rotations::NONE,
+        last_row: false,
+    };
+
+    /// ζ and ζ·g: constraints on pairs of consecutive rows
+    pub const TRANSITION: Self = Self {
+        next_row: true,
+        prev_row: false,
+        rotations: Rotations::NONE,
+        last_row: false,
+    };
+
+    /// Also open at the previous row's point, ζ·g⁻¹.
+    #[must_use]
+    pub const fn with_prev_row(self) -> Self {
+        Self {
+            prev_row: true,
+            ..self
+        }
+    }
+
+    /// Also open at the row `offset` rows after the current one, before it if negative.
+    ///
+    /// Offsets 1 and -1 are the next and previous rows, and 0 the current one.
+    ///
+    /// # Panics
+    /// If `offset` lies outside [`Rotations::MIN_OFFSET`]`..=`[`Rotations::MAX_OFFSET`].
+    #[must_use]
+    pub const fn with_rotation(self, offset: isize) -> Self {
+        match offset {
+            0 => self,
+            1 => Self {
+                next_row: true,
+                ..self
+            },
+            -1 => self.with_prev_row(),
+            _ => Self {
+                rotations: self.rotations.with(offset),
+                ..self
+            },
+        }
+    }
+
+    /// Also open at each row of `offsets`, as [`with_rotation`](Self::with_rotation)
+    /// does, e.g. `&[1, 2, 3]` for constraints on windows of four rows.
+    #[must_use]
+    pub const fn with_rotations(self, offsets: &[isize]) -> Self {
+        let mut schedule = self;
+        let mut i = 0;
+        while i < offsets.len() {
+            schedule = schedule.with_rotation(offsets[i]);
+            i += 1;
+        }
+        schedule
+    }
+
+    /// Also open at the last row's point.
+    #[must_use]
+    pub const fn with_last_row(self) -> Self {
+        Self {
+            last_row: true,
+            ..self
+        }
+    }
+
+    /// Number of points the trace is opened at, ζ included.
+    pub const fn num_points(&self) -> usize {
+        1 + self.next_row as usize
+            + self.prev_row as usize
+            + self.rotations.len()
+            + self.last_row as usize
+    }
+
+    /// The scheduled points, in opening order: ζ, then ζ·g, ζ·g⁻¹, ζ·gᵏ for each further
+    /// rotation `k` in increasing order, and the last row's.
+    ///
+    /// `window.prev`, the rotated points and `last_row` are only read if the schedule
+    /// opens those rows.
+    pub(crate) fn points<EF: Copy>(&self, window: &OodWindow<EF>, last_row: Option<EF>) -> Vec<EF> {
+        let mut points = Vec::with_capacity(self.num_points());
+        points.push(window.zeta);
+        if self.next_row {
+            points.push(window.next);
+        }
+        if self.prev_row {
+            points.extend(window.prev);
+        }
+        points.extend(
+            window
+                .rotated
+                .iter()
+                .filter(|&&(offset, _)| self.rotations.contains(offset))
+                .map(|&(_, point)| point),
+        );
+        if self.last_row {
+            points.extend(last_row);
+        }
+        points
+    }
+
+    /// Split values opened at [`points`](Self::points) by row, the unscheduled ones
+    /// empty.
+    pub(crate) fn split_openings<T: Clone>(&self, opened: &[Vec<T>]) -> RowOpenings<T> {
+        let mut opened = opened.iter().cloned();
+        let local = opened.next().unwrap_or_default();
+        let mut scheduled = |opened_there: bool| {
+            if opened_there {
+                opened.next().unwrap_or_default()
+            } else {
+                Vec::new()
+            }
+        };
+        let next = scheduled(self.next_row);
+        let prev = scheduled(self.prev_row);
+        let rotated = (0..self.rotations.len()).map(|_| scheduled(true)).collect();
+        let last = scheduled(self.last_row);
+        RowOpenings {
+            local,
+            next,
+            prev,
+            rotated,
+            last,
+        }
+    }
+
+    /// The scheduled points at each out-of-domain point of `windows`, in opening order:
+    /// the [`points`](Self::points) of the first, then those of each further one but
+    /// the last row's.
+    ///
+    /// The last row is the same whatever the out-of-domain point, so it is only opened
+    /// once.
+    pub(crate) fn points_at<EF: Copy>(
+        &self,
+        windows: &[OodWindow<EF>],
+        last_row: Option<EF>,
+    ) -> Vec<EF> {
+        let Some((first, further)) = windows.split_first() else {
+            return Vec::new();
+        };
+        let mut points = self.points(first, last_row);
+        let window = self.window();
+        for further in further {
+            points.extend(window.points(further, None));
+        }
+        points
+    }
+
+    /// Split values opened at [`points_at`](Self::points_at) into those at the first
+    /// out-of-domain point, as [`split_openings`](Self::split_openings) does, and those at
+    /// each further one, whose last row is empty.
+    pub(crate) fn split_openings_at<T: Clone>(
+        &self,
+        opened: &[Vec<T>],
+    ) -> (RowOpenings<T>, Vec<RowOpenings<T>>) {
+        let (first, further) = opened.split_at(self.num_points().min(opened.len()));
+        let window = self.window();
+        let further = further
+            .chunks(window.num_points())
+            .map(|values| window.split_openings(values))
+            .collect();
+        (self.split_openings(first), further)
+    }
+
+    /// The rows around ζ this schedule opens, without the last row.
+    const fn window(&self) -> Self {
+        Self {
+            last_row: false,
+            ..*self
+        }
+    }
+}
+
+impl Default for OpeningSchedule {
+    /// [`Self::TRANSITION`]
+    fn default() -> Self {
+        Self::TRANSITION
+    }
+}
+
+/// Offsets of the rows a trace is opened at beyond the current, next and previous ones,
+/// see [`OpeningSchedule::rotations`].
+///
+/// Offsets lie in [`MIN_OFFSET`](Self::MIN_OFFSET)`..=`[`MAX_OFFSET`](Self::MAX_OFFSET),
+/// and are listed in increasing order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Rotations(u64);
+
+impl Rotations {
+    /// No further rows
+    pub const NONE: Self = Self(0);
+
+    /// Lowest offset a trace can be opened at
+    pub const MIN_OFFSET: isize = -32;
+
+    /// Highest offset a trace can be opened at
+    pub const MAX_OFFSET: isize = 31;
+
+    /// Bits of the offsets -1, 0 and 1, which are never rotations
+    const WINDOW: u64 = 0b111 << (-1 - Self::MIN_OFFSET);
+
+    /// These offsets and `offset`.
+    ///
+    /// # Panics
+    /// If `offset` is -1, 0 or 1, or lies outside `MIN_OFFSET..=MAX_OFFSET`.
+    #[must_use]
+    pub const fn with(self, offset: isize) -> Self {
+        assert!(
+            Self::MIN_OFFSET <= offset && offset <= Self::MAX_OFFSET,
+            "rotation offset out of range"
+        );
+        assert!(
+            offset < -1 || offset > 1,
+            "the current, next and previous rows aren't rotations"
+        );
+        Self(self.0 | (1 << (offset - Self::MIN_OFFSET)))
+    }
+
+    /// Whether `offset` is one of these offsets.
+    pub const fn contains(&self, offset: isize) -> bool {
+        Self::MIN_OFFSET <= offset
+            && offset <= Self::MAX_OFFSET
+            && self.bits() & (1 << (offset - Self::MIN_OFFSET)) != 0
+    }
+
+    /// Number of offsets.
+    pub const fn len(&self) -> usize {
+        self.bits().count_ones() as usize
+    }
+
+    /// Whether there are no offsets.
+    pub const fn is_empty(&self) -> bool {
+        self.bits() == 0
+    }
+
+    /// The offsets, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = isize> {
+        let bits = self.bits();
+        (0..u64::BITS as isize)
+            .filter(move |bit| bits & (1 << bit) != 0)
+            .map(|bit| bit + Self::MIN_OFFSET)
+    }
+
+    /// Position of `offset` among these offsets, if it is one.
+    pub const fn position(&self, offset: isize) -> Option<usize> {
+        if !self.contains(offset) {
+            return None;
+        }
+        let below = self.bits() & ((1 << (offset - Self::MIN_OFFSET)) - 1);
+        Some(below.count_ones() as usize)
+    }
+
+    /// The offsets of both `self` and `other`.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// The offset bits, without any deserialized bits for the rows around ζ.
+    const fn bits(&self) -> u64 {
+        self.0 & !Self::WINDOW
+    }
+}
+
+/// Values a trace was opened at around one out-of-domain point, split by row.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RowOpenings<T> {
+    /// At ζ
+    pub local: Vec<T>,
+
+    /// At ζ·g, if scheduled
+    pub next: Vec<T>,
+
+    /// At ζ·g⁻¹, if scheduled
+    pub prev: Vec<T>,
+
+    /// At ζ·gᵏ, for each of the schedule's [`Rotations`]
+    pub rotated: Vec<Vec<T>>,
+
+    /// At the last row's point, if scheduled
+    pub last: Vec<T>,
+}
+
+impl<T> RowOpenings<T> {
+    /// Append the columns of `other` to each row.
+    pub fn append(&mut self, other: Self) {
+        self.local.extend(other.local);
+        self.next.extend(other.next);
+        self.prev.extend(other.prev);
+        if self.rotated.is_empty() {
+            self.rotated = other.rotated;
+        } else {
+            for (row, columns) in self.rotated.iter_mut().zip(other.rotated) {
+                row.extend(columns);
+            }
+        }
+        self.last.extend(other.last);
+    }
+
+    /// Split off the columns from `at` on of each row, unopened rows staying empty.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let split = |row: &mut Vec<T>| row.split_off(at.min(row.len()));
+        Self {
+            local: split(&mut self.local),
+            next: split(&mut self.next),
+            prev: split(&mut self.prev),
+            rotated: self.rotated.iter_mut().map(split).collect(),
+            last: split(&mut self.last),
+        }
+    }
+}
+
+/// An out-of-domain point ζ and the points of the rows around it.
+#[derive(Clone, Debug)]
+pub(crate) struct OodWindow<EF> {
+    /// ζ
+    pub zeta: EF,
+
+    /// ζ·g
+    pub next: EF,
+
+    /// ζ·g⁻¹, if a trace is opened at the previous row
+    pub prev: Option<EF>,
+
+    /// ζ·gᵏ for every further rotation `k` a trace is opened at, in increasing order
+    pub rotated: Vec<(isize, EF)>,
+}
+
+impl<EF: Copy> OodWindow<EF> {
+    /// The window with every point mapped by `f`, e.g. onto the domain of a shorter
+    /// trace.
+    pub(crate) fn map(&self, f: impl Fn(EF) -> EF) -> Self {
+        Self {
+            zeta: f(self.zeta),
+            next: f(self.next),
+            prev: self.prev.map(&f),
+            rotated: self
+                .rotated
+                .iter()
+                .map(|&(offset, point)| (offset, f(point)))
+                .collect(),
+        }
+    }
+}
+
+/// A main trace cell that must hold a public value: `main[row][column] ==
+/// public_values[public_value]`.
+///
+/// The prover opens the main trace at the row's domain point and the verifier compares the
+/// opened value with the public value, so the row can be anywhere in the trace, e.g. for
+/// inputs and outputs placed at fixed positions. Requires a PCS whose domain points are
+/// field elements, see [`TraceDomain`](crate::TraceDomain).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoundaryConstraint {
+    /// Row of the cell, counted from the first row
+    pub row: usize,
+
+    /// Main trace column of the cell
+    pub column: usize,
+
+    /// Index of the public value the cell must hold
+    pub public_value: usize,
+}
+
+impl BoundaryConstraint {
+    /// Constrain `main[row][column]` to the public value at index `public_value`.
+    pub const fn new(row: usize, column: usize, public_value: usize) -> Self {
+        Self {
+            row,
+            column,
+            public_value,
+        }
+    }
+}
+
+/// The distinct rows `constraints` pin, in increasing order: the main trace is opened at
+/// each once, whatever the number of cells constrained on it.
+pub(crate) fn boundary_rows(constraints: &[BoundaryConstraint]) -> Vec<usize> {
+    let mut rows: Vec<usize> = constraints
+        .iter()
+        .map(|constraint| constraint.row)
+        .collect();
+    rows.sort_unstable();
+    rows.dedup();
+    rows
+}
+
+/// Check every constraint of `constraints` pins a cell of a `height` by `width` main trace
+/// to one of `num_public_values` public values.
+pub(crate) fn check_boundary_constraints(
+    constraints: &[BoundaryConstraint],
+    height: usize,
+    width: usize,
+    num_public_values: usize,
+) -> Result<(), &'static str> {
+    for constraint in constraints {
+        if constraint.row >= height {
+            return Err("boundary constraint row outside the trace");
+        }
+        if constraint.column >= width {
+            return Err("boundary constraint column outside the main trace");
+        }
+        if constraint.public_value >= num_public_values {
+            return Err("boundary constraint public value out of range");
+        }
+    }
+    Ok(())
+}
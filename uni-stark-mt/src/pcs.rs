@@ -0,0 +1,231 @@
+//! Domain properties the prover and verifier rely on beyond the upstream
+//! [`PolynomialSpace`] trait
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "circle")]
+use p3_circle::{CircleDomain, CirclePcs};
+use p3_commit::PolynomialSpace;
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::coset::TwoAdicMultiplicativeCoset;
+#[cfg(feature = "circle")]
+use p3_field::extension::ComplexExtendable;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing, TwoAdicField};
+#[cfg(feature = "fri")]
+use p3_fri::TwoAdicFriPcs;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_util::log2_strict_usize;
+
+use crate::PcsId;
+
+/// The trace domains of a PCS, as the prover and verifier use them beyond
+/// [`PolynomialSpace`].
+///
+/// Every method has a conservative default: no row of the domain can be addressed on its
+/// own, so AIRs puncturing rows, pinning cells, opening rotated rows or splitting the
+/// trace into statements are rejected with a typed error, and the domain size is only
+/// bounded by the configuration's
+/// [`max_log_degree`](crate::StarkGenericConfig::max_log_degree). A PCS over a new kind
+/// of domain works with `impl TraceDomain for MyDomain {}` and overrides what its domain
+/// supports. Implemented for the multiplicative cosets of two-adic fields, which
+/// `TwoAdicFriPcs` and every other two-adic PCS use, and for the circle domains of the
+/// circle PCS (feature `circle`).
+///
+/// An AIR with [`num_punctured_rows`](crate::PuncturedAir::num_punctured_rows) `k` only
+/// constrains the trace domain minus its last `k` points, so the quotient divides by
+/// `Z_H(X) / prod_j (X - p_j)` over those points `p_j`.
+///
+/// The same per-row access gives the Lagrange polynomials of individual rows, from which
+/// the statement boundary selectors of [`num_statements`](crate::AirLayout::num_statements)
+/// are built, and the points a trace is opened at to read rows other than the next, see
+/// [`OpeningSchedule::prev_row`](crate::OpeningSchedule::prev_row). It also lets an
+/// auxiliary trace shorter than the main trace live on its own, smaller domain, see
+/// [`Proof::log_aux_degree`](crate::Proof::log_aux_degree).
+pub trait TraceDomain: PolynomialSpace {
+    /// log2 of the size of the largest domain of this kind, if it has one.
+    ///
+    /// The quotient domain is larger than the trace domain, so this bounds the tallest
+    /// trace that can be proven. Without a bound, verifiers of untrusted proofs should
+    /// cap the trace height with
+    /// [`max_log_degree`](crate::StarkGenericConfig::max_log_degree), since the PCS may
+    /// panic building a domain too large for it.
+    const MAX_LOG_SIZE: Option<usize> = None;
+
+    /// Points of the last `num_rows` rows of the trace domain starting at `first_point`
+    /// with `size` rows, last row first.
+    ///
+    /// Returns `None` if `num_rows > 0` and the domain can't be punctured.
+    fn last_row_points(
+        _first_point: Self::Val,
+        _size: usize,
+        num_rows: usize,
+    ) -> Option<Vec<Self::Val>> {
+        (num_rows == 0).then(Vec::new)
+    }
+
+    /// Point `p` and weight `1 / Z_H'(p)` of each of `rows` of the trace domain starting at
+    /// `first_point` with `size` rows, so that row `r`'s Lagrange polynomial is
+    /// `weight_r · Z_H(X) / (X - p_r)`.
+    ///
+    /// Returns `None` if `rows` is nonempty and the domain's points aren't field elements.
+    fn row_lagrange_points(
+        _first_point: Self::Val,
+        _size: usize,
+        rows: &[usize],
+    ) -> Option<Vec<(Self::Val, Self::Val)>> {
+        rows.is_empty().then(Vec::new)
+    }
+
+    /// The point `rows` rows after `point` (before it, if negative) in a trace domain with
+    /// `size` rows, e.g. ζ·g⁻¹ for `rows = -1`.
+    ///
+    /// Returns `None` if `rows` isn't 0 and the domain's points aren't field elements.
+    fn rotate_point<EF: ExtensionField<Self::Val>>(
+        _size: usize,
+        point: EF,
+        rows: isize,
+    ) -> Option<EF> {
+        (rows == 0).then_some(point)
+    }
+
+    /// The point of the trace domain with `period` rows at which a trace repeated every
+    /// `period` rows of the trace domain with `size` rows takes its value at `point`.
+    ///
+    /// Row `i` of the longer domain maps to row `i mod period` of the shorter one, so the
+    /// next, previous and last rows of the two domains correspond.
+    ///
+    /// Returns `None` if `period` isn't `size` and the domains can't be mapped this way.
+    fn periodic_point<EF: ExtensionField<Self::Val>>(
+        size: usize,
+        period: usize,
+        point: EF,
+    ) -> Option<EF> {
+        (period == size).then_some(point)
+    }
+
+    /// Evaluations over the quotient domain with `quotient_size` points starting at
+    /// `quotient_first_point`, for a trace domain with `size` rows, of `trace` repeated
+    /// every `trace.height()` rows.
+    ///
+    /// Returns `None` if the domains can't be mapped as for
+    /// [`periodic_point`](Self::periodic_point).
+    fn periodic_evaluations(
+        _trace: RowMajorMatrix<Self::Val>,
+        _size: usize,
+        _quotient_first_point: Self::Val,
+        _quotient_size: usize,
+    ) -> Option<RowMajorMatrix<Self::Val>> {
+        None
+    }
+}
+
+/// log2 of the tallest trace the domains of `D` can hold with a quotient domain
+/// `2^log_quotient_degree` times larger, and that fits in a `usize`.
+pub(crate) fn max_log_trace_size<D: TraceDomain>(log_quotient_degree: usize) -> usize {
+    D::MAX_LOG_SIZE
+        .unwrap_or(usize::MAX)
+        .min(usize::BITS as usize - 1)
+        .saturating_sub(log_quotient_degree)
+}
+
+impl<Val: TwoAdicField> TraceDomain for TwoAdicMultiplicativeCoset<Val> {
+    const MAX_LOG_SIZE: Option<usize> = Some(Val::TWO_ADICITY);
+
+    fn last_row_points(first_point: Val, size: usize, num_rows: usize) -> Option<Vec<Val>> {
+        // Row i is first_point * g^i, so row size - j is first_point * g^-j
+        let g_inv = Val::two_adic_generator(log2_strict_usize(size)).inverse();
+        Some(
+            g_inv
+                .shifted_powers(first_point * g_inv)
+                .take(num_rows)
+                .collect(),
+        )
+    }
+
+    fn row_lagrange_points(
+        first_point: Val,
+        size: usize,
+        rows: &[usize],
+    ) -> Option<Vec<(Val, Val)>> {
+        // Z_H(X) = (X / first_point)^n - 1, so Z_H'(p) = n / p on the domain
+        let g = Val::two_adic_generator(log2_strict_usize(size));
+        let inv_size = Val::from_usize(size).inverse();
+        Some(
+            rows.iter()
+                .map(|&row| {
+                    let point = first_point * g.exp_u64(row as u64);
+                    (point, point * inv_size)
+                })
+                .collect(),
+        )
+    }
+
+    fn rotate_point<EF: ExtensionField<Val>>(size: usize, point: EF, rows: isize) -> Option<EF> {
+        let g = Val::two_adic_generator(log2_strict_usize(size));
+        let shift = g.exp_u64(rows.unsigned_abs() as u64);
+        Some(if rows < 0 {
+            point * shift.inverse()
+        } else {
+            point * shift
+        })
+    }
+
+    fn periodic_point<EF: ExtensionField<Val>>(
+        size: usize,
+        period: usize,
+        point: EF,
+    ) -> Option<EF> {
+        // Trace domains are subgroups, and x ↦ x^(size / period) maps g^i to g'^i for
+        // the generator g' of the smaller one
+        Some(point.exp_u64((size / period) as u64))
+    }
+
+    fn periodic_evaluations(
+        trace: RowMajorMatrix<Val>,
+        size: usize,
+        quotient_first_point: Val,
+        quotient_size: usize,
+    ) -> Option<RowMajorMatrix<Val>> {
+        // The quotient domain maps onto a coset as many times smaller as the trace is,
+        // which the quotient domain then runs through `size / period` times
+        let repeats = size / trace.height();
+        let added_bits = log2_strict_usize(quotient_size / size);
+        let shift = quotient_first_point.exp_u64(repeats as u64);
+        let width = trace.width();
+        let evaluations = Radix2Dit::default()
+            .coset_lde_batch(trace, added_bits, shift)
+            .to_row_major_matrix();
+        Some(RowMajorMatrix::new(
+            evaluations.values.repeat(repeats),
+            width,
+        ))
+    }
+}
+
+#[cfg(feature = "circle")]
+impl<Val: ComplexExtendable> TraceDomain for CircleDomain<Val> {
+    // Circle domain points aren't field elements, so no row can be addressed on its own
+    // and a shorter trace can't be mapped onto a longer one's twin cosets
+    const MAX_LOG_SIZE: Option<usize> = Some(Val::CIRCLE_TWO_ADICITY);
+}
+
+/// A PCS with a stable identifier, written into encoded proofs so they are never decoded
+/// for another PCS, see [`Proof::to_bytes`](crate::Proof::to_bytes).
+pub trait IdentifiedPcs {
+    /// Identifier of the PCS
+    const PCS_ID: PcsId;
+}
+
+#[cfg(feature = "fri")]
+impl<Val, Dft, InputMmcs, FriMmcs> IdentifiedPcs for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+    const PCS_ID: PcsId = PcsId::TwoAdicFri;
+}
+
+#[cfg(feature = "circle")]
+impl<Val, InputMmcs, FriMmcs> IdentifiedPcs for CirclePcs<Val, InputMmcs, FriMmcs>
+where
+    Val: Field,
+{
+    const PCS_ID: PcsId = PcsId::CircleFri;
+}
@@ -0,0 +1,465 @@
+//! Constraint programs, evaluated by interpretation
+//!
+//! A [`ConstraintProgram`] is an AIR's constraints as straight-line code over registers,
+//! each register computed from a [`Leaf`] (a trace cell, public value, challenge,
+//! exposed value, selector or constant) or from earlier registers. It is plain data: it
+//! can be serialized alongside a [`VerifyingKey`] and handed to a verifier that has no
+//! access to the AIR's Rust type, be it in another language, on chain or in a recursion
+//! host. [`verify_program`](crate::verify_program) verifies proofs against the pair:
+//!
+//! ```ignore
+//! let program = compile_air::<Val, Challenge, _>(&air).program().clone();
+//! let vk = keygen(&config, &air);
+//! // ... ship `program` and `vk` to the verifier, then
+//! verify_program(&config, &vk, &program, &proof, &public_values)?;
+//! ```
+//!
+//! Programs are compiled from the symbolic constraints of an AIR, see
+//! [`compile_air`](crate::compile_air); only their interpretation is available under the
+//! `minimal` feature.
+
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{Field, PrimeCharacteristicRing};
+use p3_matrix::Matrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{AuxBuilder, OpeningSchedule, StatementBuilder, TranscriptPhase, VerifyingKey};
+
+/// Row a trace variable is read from: one around the current row, or the last row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RowOffset {
+    /// The current row
+    Local,
+    /// The row after the current one
+    Next,
+    /// The row before the current one
+    Prev,
+    /// The row this many rows after the current one, before it if negative, for offsets
+    /// beyond the next and previous rows (see
+    /// [`OpeningSchedule::rotations`](crate::OpeningSchedule::rotations))
+    Rotated(isize),
+    /// The trace's last row, the same for every row
+    Last,
+}
+
+/// A value a [`ConstraintProgram`] reads rather than computes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Leaf<F> {
+    /// Main trace column
+    Main {
+        /// Row of the window
+        row: RowOffset,
+        /// Column index
+        column: usize,
+    },
+
+    /// Extension-field auxiliary trace column
+    Aux {
+        /// Row of the window
+        row: RowOffset,
+        /// Column index
+        column: usize,
+    },
+
+    /// Base-field auxiliary trace column
+    AuxBase {
+        /// Row of the window
+        row: RowOffset,
+        /// Column index
+        column: usize,
+    },
+
+    /// Public value at this index
+    Public(usize),
+
+    /// Challenge at this index of the AIR's
+    /// [`challenge_names`](crate::AuxTraceBuilder::challenge_names)
+    Challenge(usize),
+
+    /// Exposed auxiliary value at this index
+    AuxExposed(usize),
+
+    /// Public value at this index of the statement owning the row
+    StatementPublic(usize),
+
+    /// 1 on the first row, 0 elsewhere
+    IsFirstRow,
+
+    /// 1 on the last row, 0 elsewhere
+    IsLastRow,
+
+    /// 1 on every row but the last, 0 on the last
+    IsTransition,
+
+    /// 1 on the first row of each statement
+    IsFirstStatementRow,
+
+    /// 1 on the last row of each statement
+    IsLastStatementRow,
+
+    /// 1 on every row but the last of each statement
+    IsStatementTransition,
+
+    /// A base-field constant
+    Constant(F),
+}
+
+impl<F> Leaf<F> {
+    /// Whether the leaf takes extension-field values in the protocol.
+    pub const fn is_extension(&self) -> bool {
+        matches!(
+            self,
+            Self::Aux { .. } | Self::Challenge(_) | Self::AuxExposed(_)
+        )
+    }
+
+    /// Check the leaf reads something an AIR with verifying key `vk` has.
+    fn check(&self, vk: &VerifyingKey) -> Result<(), &'static str> {
+        let opened = |row: RowOffset, schedule: OpeningSchedule| match row {
+            RowOffset::Local => true,
+            RowOffset::Next => schedule.next_row,
+            RowOffset::Prev => schedule.prev_row,
+            RowOffset::Rotated(offset) => schedule.rotations.contains(offset),
+            RowOffset::Last => schedule.last_row,
+        };
+        let in_range = match *self {
+            Self::Main { row, column } => column < vk.main_width && opened(row, vk.main_openings),
+            Self::Aux { row, column } => column < vk.aux_width && opened(row, vk.aux_openings),
+            Self::AuxBase { row, column } => {
+                column < vk.aux_base_width && opened(row, vk.aux_openings)
+            }
+            Self::Public(index) => index < vk.num_public_values,
+            Self::Challenge(index) => index < vk.num_challenges(TranscriptPhase::Aux),
+            Self::AuxExposed(index) => index < vk.num_aux_exposed_values,
+            Self::StatementPublic(index) => index < vk.num_public_values / vk.num_statements.max(1),
+            _ => true,
+        };
+        if in_range {
+            Ok(())
+        } else {
+            Err("leaf reads a value the AIR doesn't have")
+        }
+    }
+}
+
+/// One step of a [`ConstraintProgram`], computing one register from earlier ones.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Instruction<F> {
+    /// A variable, selector or constant
+    Leaf(Leaf<F>),
+
+    /// Sum of two registers
+    Add(usize, usize),
+
+    /// Difference of two registers
+    Sub(usize, usize),
+
+    /// Negation of a register
+    Neg(usize),
+
+    /// Product of two registers
+    Mul(usize, usize),
+}
+
+/// A constraint of a [`ConstraintProgram`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompiledConstraint {
+    /// Register holding the constraint polynomial
+    pub register: usize,
+
+    /// Whether it is asserted over the extension field
+    pub extension: bool,
+}
+
+/// The value of a register or constraint, in the field it is computed over.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstraintValue<E, EE> {
+    /// A base-field value
+    Base(E),
+
+    /// An extension-field value
+    Extension(EE),
+}
+
+impl<E: Clone, EE: From<E> + Clone> ConstraintValue<E, EE> {
+    /// The value, embedded into the extension field if it is a base-field one.
+    fn to_extension(&self) -> EE {
+        match self {
+            Self::Base(x) => EE::from(x.clone()),
+            Self::Extension(x) => x.clone(),
+        }
+    }
+}
+
+/// Constraints compiled to straight-line code over registers.
+///
+/// Instruction `i` writes register `i` and only reads registers below `i`. No two
+/// instructions compute the same thing, so a subexpression shared by several
+/// constraints, or written out several times by one, is evaluated once per row.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintProgram<F> {
+    /// Instructions, in execution order
+    pub instructions: Vec<Instruction<F>>,
+
+    /// Whether each register holds extension-field values: it reads an auxiliary
+    /// column, a challenge or an exposed value
+    pub extension: Vec<bool>,
+
+    /// Constraints, in the order `Air::eval` asserts them
+    pub constraints: Vec<CompiledConstraint>,
+}
+
+impl<F: Field> ConstraintProgram<F> {
+    /// Number of registers, one per instruction.
+    pub fn num_registers(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Check the program is well formed for an AIR with verifying key `vk`.
+    ///
+    /// A program that passes runs without panicking on any builder for that AIR: every
+    /// instruction only reads earlier registers, every leaf reads a value the AIR has,
+    /// the register fields are consistent, and it asserts as many constraints as `vk`
+    /// records. Programs received from elsewhere, e.g. deserialized, should be checked
+    /// before they are run.
+    ///
+    /// # Errors
+    /// What is wrong with the program.
+    pub fn check(&self, vk: &VerifyingKey) -> Result<(), &'static str> {
+        if self.extension.len() != self.instructions.len() {
+            return Err("one register field is needed per instruction");
+        }
+        for (register, instruction) in self.instructions.iter().enumerate() {
+            let operand = |read: usize| {
+                if read < register {
+                    Ok(self.extension[read])
+                } else {
+                    Err("instruction reads a register that isn't computed yet")
+                }
+            };
+            let is_extension = match instruction {
+                Instruction::Leaf(leaf) => {
+                    leaf.check(vk)?;
+                    leaf.is_extension()
+                }
+                Instruction::Add(a, b) | Instruction::Sub(a, b) | Instruction::Mul(a, b) => {
+                    operand(*a)? | operand(*b)?
+                }
+                Instruction::Neg(a) => operand(*a)?,
+            };
+            if self.extension[register] != is_extension {
+                return Err("register field doesn't match its instruction");
+            }
+        }
+        if self.constraints.len() != vk.num_constraints {
+            return Err("wrong number of constraints");
+        }
+        for constraint in &self.constraints {
+            match self.extension.get(constraint.register) {
+                None => return Err("constraint reads a register that doesn't exist"),
+                Some(&true) if !constraint.extension => {
+                    return Err("extension-field register asserted over the base field")
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the program, returning the value of every constraint in order.
+    ///
+    /// `base` gives the value of base-field leaves and `extension` that of leaves reading
+    /// an auxiliary column, a challenge or an exposed value. Base-field registers are
+    /// embedded into the extension field where an extension-field instruction or
+    /// constraint reads them.
+    pub fn run<E, EE>(
+        &self,
+        base: impl Fn(&Leaf<F>) -> E,
+        extension: impl Fn(&Leaf<F>) -> EE,
+    ) -> Vec<ConstraintValue<E, EE>>
+    where
+        E: PrimeCharacteristicRing,
+        EE: PrimeCharacteristicRing + From<E>,
+    {
+        let mut registers: Vec<ConstraintValue<E, EE>> =
+            Vec::with_capacity(self.instructions.len());
+        for (instruction, &is_extension) in self.instructions.iter().zip(&self.extension) {
+            let value = if is_extension {
+                let operand = |register: usize| registers[register].to_extension();
+                ConstraintValue::Extension(match instruction {
+                    Instruction::Leaf(leaf) => extension(leaf),
+                    Instruction::Add(a, b) => operand(*a) + operand(*b),
+                    Instruction::Sub(a, b) => operand(*a) - operand(*b),
+                    Instruction::Neg(a) => -operand(*a),
+                    Instruction::Mul(a, b) => operand(*a) * operand(*b),
+                })
+            } else {
+                let operand = |register: usize| match &registers[register] {
+                    ConstraintValue::Base(x) => x.clone(),
+                    ConstraintValue::Extension(_) => {
+                        unreachable!("base-field instructions only read base-field registers")
+                    }
+                };
+                ConstraintValue::Base(match instruction {
+                    Instruction::Leaf(leaf) => base(leaf),
+                    Instruction::Add(a, b) => operand(*a) + operand(*b),
+                    Instruction::Sub(a, b) => operand(*a) - operand(*b),
+                    Instruction::Neg(a) => -operand(*a),
+                    Instruction::Mul(a, b) => operand(*a) * operand(*b),
+                })
+            };
+            registers.push(value);
+        }
+        self.constraints
+            .iter()
+            .map(|constraint| {
+                let value = &registers[constraint.register];
+                if constraint.extension {
+                    ConstraintValue::Extension(value.to_extension())
+                } else {
+                    value.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The local and next rows of `matrix`, the next one empty if it isn't opened.
+fn window<T: Clone + Send + Sync>(matrix: &impl Matrix<T>) -> [Vec<T>; 2] {
+    [0, 1].map(|r| {
+        matrix
+            .row_slice(r)
+            .map(|row| row.to_vec())
+            .unwrap_or_default()
+    })
+}
+
+/// The value of column `column` on `row`, read from the matching opened row of `rows`,
+/// given as the local, next, previous and last rows, or of `rotated` at its offset.
+fn window_value<'a, T: Copy + 'a>(
+    rows: [&'a [T]; 4],
+    rotated: impl Fn(isize) -> &'a [T],
+    row: RowOffset,
+    column: usize,
+) -> T {
+    let [local, next, prev, last] = rows;
+    match row {
+        RowOffset::Local => local[column],
+        RowOffset::Next => next[column],
+        RowOffset::Prev => prev[column],
+        RowOffset::Rotated(offset) => rotated(offset)[column],
+        RowOffset::Last => last[column],
+    }
+}
+
+/// Run `program` on the builder's row and assert every constraint, in order.
+pub(crate) fn eval_program<AB>(program: &ConstraintProgram<AB::F>, builder: &mut AB)
+where
+    AB: AuxBuilder + StatementBuilder,
+    AB::F: Field,
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    let [main_local, main_next] = window(&builder.main());
+    let [aux_base_local, aux_base_next] = window(&builder.aux_base());
+    let [aux_local, aux_next] = window(&builder.aux());
+
+    let values = {
+        let builder = &*builder;
+        let base = |leaf: &Leaf<AB::F>| -> AB::Expr {
+            match *leaf {
+                Leaf::Main { row, column } => window_value(
+                    [
+                        &main_local,
+                        &main_next,
+                        builder.main_prev_row(),
+                        builder.main_last_row(),
+                    ],
+                    |offset| builder.main_rotated_row(offset),
+                    row,
+                    column,
+                )
+                .into(),
+                Leaf::AuxBase { row, column } => window_value(
+                    [
+                        &aux_base_local,
+                        &aux_base_next,
+                        builder.aux_base_prev_row(),
+                        builder.aux_base_last_row(),
+                    ],
+                    |offset| builder.aux_base_rotated_row(offset),
+                    row,
+                    column,
+                )
+                .into(),
+                Leaf::Public(index) => builder.public_values()[index].into(),
+                Leaf::StatementPublic(index) => builder.statement_public_values()[index].clone(),
+                Leaf::IsFirstRow => builder.is_first_row(),
+                Leaf::IsLastRow => builder.is_last_row(),
+                Leaf::IsTransition => builder.is_transition(),
+                Leaf::IsFirstStatementRow => builder.is_first_statement_row(),
+                Leaf::IsLastStatementRow => builder.is_last_statement_row(),
+                Leaf::IsStatementTransition => builder.is_statement_transition(),
+                Leaf::Constant(c) => AB::Expr::from(c),
+                Leaf::Aux { .. } | Leaf::Challenge(_) | Leaf::AuxExposed(_) => {
+                    unreachable!("extension-field leaves are never base-field registers")
+                }
+            }
+        };
+        let extension = |leaf: &Leaf<AB::F>| -> AB::ExprEF {
+            match *leaf {
+                Leaf::Aux { row, column } => window_value(
+                    [
+                        &aux_local,
+                        &aux_next,
+                        builder.aux_prev_row(),
+                        builder.aux_last_row(),
+                    ],
+                    |offset| builder.aux_rotated_row(offset),
+                    row,
+                    column,
+                )
+                .into(),
+                Leaf::Challenge(index) => builder.challenges()[index].into(),
+                Leaf::AuxExposed(index) => builder.aux_exposed_values()[index].into(),
+                _ => unreachable!("base-field leaves are never extension-field registers"),
+            }
+        };
+        program.run(base, extension)
+    };
+
+    for value in values {
+        match value {
+            ConstraintValue::Base(x) => builder.assert_zero(x),
+            ConstraintValue::Extension(x) => builder.assert_zero_ext(x),
+        }
+    }
+}
+
+/// An AIR whose constraints are a [`ConstraintProgram`], with nothing else known of it
+/// but its main trace width.
+pub(crate) struct ProgramAir<'a, F> {
+    /// The constraints
+    pub(crate) program: &'a ConstraintProgram<F>,
+
+    /// Number of main trace columns
+    pub(crate) main_width: usize,
+}
+
+impl<F: Sync> BaseAir<F> for ProgramAir<'_, F> {
+    fn width(&self) -> usize {
+        self.main_width
+    }
+}
+
+impl<AB> Air<AB> for ProgramAir<'_, AB::F>
+where
+    AB: AuxBuilder + StatementBuilder,
+    AB::F: Field,
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        eval_program(self.program, builder);
+    }
+}
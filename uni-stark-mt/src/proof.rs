@@ -2,11 +2,30 @@
 
 use alloc::vec::Vec;
 
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    trace_digest, Commitment, ConfigDigest, PcsProof, ProtocolId, StarkGenericConfig, TraceDigest,
+    Val,
+};
+
 /// A multi-trace STARK proof.
-#[derive(Clone)]
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct Proof<SC: crate::StarkGenericConfig> {
-    /// Commitment to the main trace
-    pub main_commit: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment,
+    /// Commitments to the main trace, one per column batch
+    ///
+    /// A single commitment unless the config splits wide traces, see
+    /// [`StarkGenericConfig::main_commit_batch_width`](crate::StarkGenericConfig::main_commit_batch_width).
+    pub main_commits: Vec<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
+
+    /// Commitment to the challenge-free auxiliary columns, made with the main trace
+    ///
+    /// None unless the AIR declares some, see
+    /// [`AuxTraceBuilder::challenge_free_aux_width`](crate::AuxTraceBuilder::challenge_free_aux_width).
+    pub challenge_free_commit:
+        Option<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
 
     /// Commitment to the auxiliary trace (None if no aux trace)
     pub aux_commit: Option<<SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Commitment>,
@@ -17,22 +36,565 @@ pub struct Proof<SC: crate::StarkGenericConfig> {
     /// Opened values of main trace at ζ (out-of-domain point)
     pub main_local: Vec<SC::Challenge>,
 
-    /// Opened values of main trace at ζ·g (next row)
+    /// Opened values of main trace at ζ·g (next row, if the AIR opens it there)
     pub main_next: Vec<SC::Challenge>,
 
+    /// Opened values of main trace at ζ·g⁻¹ (previous row, if the AIR opens it there)
+    pub main_prev: Vec<SC::Challenge>,
+
+    /// Opened values of main trace at ζ·gᵏ for each further rotation `k` the AIR opens
+    /// it at, in increasing order (see
+    /// [`OpeningSchedule::rotations`](crate::OpeningSchedule::rotations))
+    pub main_rotated: Vec<Vec<SC::Challenge>>,
+
+    /// Opened values of main trace at the last row's point (if the AIR opens it there)
+    pub main_last: Vec<SC::Challenge>,
+
+    /// Opened values of main trace at each row its boundary constraints pin, in
+    /// increasing row order (see
+    /// [`AirLayout::boundary_constraints`](crate::AirLayout::boundary_constraints))
+    pub main_boundary: Vec<Vec<SC::Challenge>>,
+
     /// Opened values of aux trace at ζ (if aux trace exists)
     pub aux_local: Vec<SC::Challenge>,
 
-    /// Opened values of aux trace at ζ·g (if aux trace exists)
+    /// Opened values of aux trace at ζ·g (if aux trace exists and the AIR opens it there)
     pub aux_next: Vec<SC::Challenge>,
 
+    /// Opened values of aux trace at ζ·g⁻¹ (if aux trace exists and the AIR opens it
+    /// there)
+    pub aux_prev: Vec<SC::Challenge>,
+
+    /// Opened values of aux trace at ζ·gᵏ for each further rotation `k` the AIR opens it
+    /// at, in increasing order
+    pub aux_rotated: Vec<Vec<SC::Challenge>>,
+
+    /// Opened values of the base-field aux columns at ζ, challenge-free columns first
+    pub aux_base_local: Vec<SC::Challenge>,
+
+    /// Opened values of the base-field aux columns at ζ·g
+    pub aux_base_next: Vec<SC::Challenge>,
+
+    /// Opened values of the base-field aux columns at ζ·g⁻¹
+    pub aux_base_prev: Vec<SC::Challenge>,
+
+    /// Opened values of the base-field aux columns at ζ·gᵏ for each further rotation `k`
+    pub aux_base_rotated: Vec<Vec<SC::Challenge>>,
+
+    /// Opened values of aux trace at the last row's point (if the AIR opens it there)
+    pub aux_last: Vec<SC::Challenge>,
+
+    /// Opened values of the base-field aux columns at the last row's point
+    pub aux_base_last: Vec<SC::Challenge>,
+
+    /// Values exposed by the auxiliary phase (e.g. final accumulator sums)
+    pub aux_exposed_values: Vec<SC::Challenge>,
+
     /// Opened values of quotient chunks at ζ
     /// Each chunk is a Vec<Challenge> (all columns in that chunk at zeta)
     pub quotient_chunks: Vec<Vec<SC::Challenge>>,
 
+    /// Opened values at each further out-of-domain point, see
+    /// [`StarkGenericConfig::num_ood_points`](crate::StarkGenericConfig::num_ood_points)
+    ///
+    /// Empty with a single point.
+    pub extra_ood_openings: Vec<OodOpening<SC::Challenge>>,
+
+    /// Proof-of-work witness ground before the out-of-domain points, see
+    /// [`StarkGenericConfig::pow_bits`](crate::StarkGenericConfig::pow_bits)
+    ///
+    /// Zero without grinding.
+    pub pow_witness: Val<SC>,
+
     /// PCS opening proof
     pub opening_proof: <SC::Pcs as p3_commit::Pcs<SC::Challenge, SC::Challenger>>::Proof,
 
     /// Degree (log2 of trace height)
     pub log_degree: u8,
+
+    /// log2 of the auxiliary trace's height, at most [`log_degree`](Self::log_degree)
+    ///
+    /// Equal to it unless the AIR builds a shorter auxiliary trace, committed on a domain
+    /// of its own (see [`AuxTraces::height`](crate::AuxTraces::height)).
+    pub log_aux_degree: u8,
+
+    /// Protocol variant the proof was produced with
+    pub protocol_id: crate::ProtocolId,
+
+    /// Keyed digest of the main trace, for storage auditing (not bound by the transcript)
+    ///
+    /// See [`Proof::attach_trace_digest`].
+    pub trace_digest: Option<TraceDigest>,
+
+    /// Digest of the parameters the proof was made with, see
+    /// [`config_digest`](crate::config_digest)
+    ///
+    /// The verifier rejects a proof made under other parameters than its own with
+    /// [`VerificationError::ConfigMismatch`](crate::VerificationError::ConfigMismatch).
+    /// `None` for configurations that don't record their security parameters, and in
+    /// proofs encoded before it existed, see [`Proof::from_bytes`](crate::Proof::from_bytes).
+    pub config_digest: Option<ConfigDigest>,
+}
+
+// Not derived, as the derive would require `SC: Clone`, which configurations needn't be
+impl<SC: crate::StarkGenericConfig> Clone for Proof<SC> {
+    fn clone(&self) -> Self {
+        Self {
+            main_commits: self.main_commits.clone(),
+            challenge_free_commit: self.challenge_free_commit.clone(),
+            aux_commit: self.aux_commit.clone(),
+            quotient_commit: self.quotient_commit.clone(),
+            main_local: self.main_local.clone(),
+            main_next: self.main_next.clone(),
+            main_prev: self.main_prev.clone(),
+            main_rotated: self.main_rotated.clone(),
+            main_last: self.main_last.clone(),
+            main_boundary: self.main_boundary.clone(),
+            aux_local: self.aux_local.clone(),
+            aux_next: self.aux_next.clone(),
+            aux_prev: self.aux_prev.clone(),
+            aux_rotated: self.aux_rotated.clone(),
+            aux_base_local: self.aux_base_local.clone(),
+            aux_base_next: self.aux_base_next.clone(),
+            aux_base_prev: self.aux_base_prev.clone(),
+            aux_base_rotated: self.aux_base_rotated.clone(),
+            aux_last: self.aux_last.clone(),
+            aux_base_last: self.aux_base_last.clone(),
+            aux_exposed_values: self.aux_exposed_values.clone(),
+            quotient_chunks: self.quotient_chunks.clone(),
+            extra_ood_openings: self.extra_ood_openings.clone(),
+            pow_witness: self.pow_witness,
+            opening_proof: self.opening_proof.clone(),
+            log_degree: self.log_degree,
+            log_aux_degree: self.log_aux_degree,
+            protocol_id: self.protocol_id,
+            trace_digest: self.trace_digest,
+            config_digest: self.config_digest,
+        }
+    }
+}
+
+/// Everything in a [`Proof`] but the PCS opening proof: the commitments, the claimed
+/// opened values and the metadata.
+///
+/// Aggregation schemes can check the claimed values algebraically with
+/// [`verify_claims`](crate::verify_claims), and defer checking the [`OpeningProof`] with
+/// [`verify_openings`](crate::verify_openings).
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Claims<SC: StarkGenericConfig> {
+    /// See [`Proof::main_commits`]
+    pub main_commits: Vec<Commitment<SC>>,
+    /// See [`Proof::challenge_free_commit`]
+    pub challenge_free_commit: Option<Commitment<SC>>,
+    /// See [`Proof::aux_commit`]
+    pub aux_commit: Option<Commitment<SC>>,
+    /// See [`Proof::quotient_commit`]
+    pub quotient_commit: Commitment<SC>,
+    /// See [`Proof::main_local`]
+    pub main_local: Vec<SC::Challenge>,
+    /// See [`Proof::main_next`]
+    pub main_next: Vec<SC::Challenge>,
+    /// See [`Proof::main_prev`]
+    pub main_prev: Vec<SC::Challenge>,
+    /// See [`Proof::main_rotated`]
+    pub main_rotated: Vec<Vec<SC::Challenge>>,
+    /// See [`Proof::main_last`]
+    pub main_last: Vec<SC::Challenge>,
+    /// See [`Proof::main_boundary`]
+    pub main_boundary: Vec<Vec<SC::Challenge>>,
+    /// See [`Proof::aux_local`]
+    pub aux_local: Vec<SC::Challenge>,
+    /// See [`Proof::aux_next`]
+    pub aux_next: Vec<SC::Challenge>,
+    /// See [`Proof::aux_prev`]
+    pub aux_prev: Vec<SC::Challenge>,
+    /// See [`Proof::aux_rotated`]
+    pub aux_rotated: Vec<Vec<SC::Challenge>>,
+    /// See [`Proof::aux_base_local`]
+    pub aux_base_local: Vec<SC::Challenge>,
+    /// See [`Proof::aux_base_next`]
+    pub aux_base_next: Vec<SC::Challenge>,
+    /// See [`Proof::aux_base_prev`]
+    pub aux_base_prev: Vec<SC::Challenge>,
+    /// See [`Proof::aux_base_rotated`]
+    pub aux_base_rotated: Vec<Vec<SC::Challenge>>,
+    /// See [`Proof::aux_last`]
+    pub aux_last: Vec<SC::Challenge>,
+    /// See [`Proof::aux_base_last`]
+    pub aux_base_last: Vec<SC::Challenge>,
+    /// See [`Proof::aux_exposed_values`]
+    pub aux_exposed_values: Vec<SC::Challenge>,
+    /// See [`Proof::quotient_chunks`]
+    pub quotient_chunks: Vec<Vec<SC::Challenge>>,
+    /// See [`Proof::extra_ood_openings`]
+    pub extra_ood_openings: Vec<OodOpening<SC::Challenge>>,
+    /// See [`Proof::pow_witness`]
+    pub pow_witness: Val<SC>,
+    /// See [`Proof::log_degree`]
+    pub log_degree: u8,
+    /// See [`Proof::log_aux_degree`]
+    pub log_aux_degree: u8,
+    /// See [`Proof::protocol_id`]
+    pub protocol_id: ProtocolId,
+    /// See [`Proof::trace_digest`]
+    pub trace_digest: Option<TraceDigest>,
+    /// See [`Proof::config_digest`]
+    pub config_digest: Option<ConfigDigest>,
+}
+
+impl<SC: StarkGenericConfig> Clone for Claims<SC> {
+    fn clone(&self) -> Self {
+        Self {
+            main_commits: self.main_commits.clone(),
+            challenge_free_commit: self.challenge_free_commit.clone(),
+            aux_commit: self.aux_commit.clone(),
+            quotient_commit: self.quotient_commit.clone(),
+            main_local: self.main_local.clone(),
+            main_next: self.main_next.clone(),
+            main_prev: self.main_prev.clone(),
+            main_rotated: self.main_rotated.clone(),
+            main_last: self.main_last.clone(),
+            main_boundary: self.main_boundary.clone(),
+            aux_local: self.aux_local.clone(),
+            aux_next: self.aux_next.clone(),
+            aux_prev: self.aux_prev.clone(),
+            aux_rotated: self.aux_rotated.clone(),
+            aux_base_local: self.aux_base_local.clone(),
+            aux_base_next: self.aux_base_next.clone(),
+            aux_base_prev: self.aux_base_prev.clone(),
+            aux_base_rotated: self.aux_base_rotated.clone(),
+            aux_last: self.aux_last.clone(),
+            aux_base_last: self.aux_base_last.clone(),
+            aux_exposed_values: self.aux_exposed_values.clone(),
+            quotient_chunks: self.quotient_chunks.clone(),
+            extra_ood_openings: self.extra_ood_openings.clone(),
+            pow_witness: self.pow_witness,
+            log_degree: self.log_degree,
+            log_aux_degree: self.log_aux_degree,
+            protocol_id: self.protocol_id,
+            trace_digest: self.trace_digest,
+            config_digest: self.config_digest,
+        }
+    }
+}
+
+/// Values opened at an out-of-domain point ζ' other than the first, ζ.
+///
+/// The last row is opened once, with ζ, so only the rows around ζ' are opened again.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OodOpening<EF> {
+    /// Opened values of main trace at ζ'
+    pub main_local: Vec<EF>,
+
+    /// Opened values of main trace at ζ'·g (if the AIR opens the next row)
+    pub main_next: Vec<EF>,
+
+    /// Opened values of main trace at ζ'·g⁻¹ (if the AIR opens the previous row)
+    pub main_prev: Vec<EF>,
+
+    /// Opened values of main trace at ζ'·gᵏ for each further rotation `k`
+    pub main_rotated: Vec<Vec<EF>>,
+
+    /// Opened values of aux trace at ζ'
+    pub aux_local: Vec<EF>,
+
+    /// Opened values of aux trace at ζ'·g
+    pub aux_next: Vec<EF>,
+
+    /// Opened values of aux trace at ζ'·g⁻¹
+    pub aux_prev: Vec<EF>,
+
+    /// Opened values of aux trace at ζ'·gᵏ for each further rotation `k`
+    pub aux_rotated: Vec<Vec<EF>>,
+
+    /// Opened values of the base-field aux columns at ζ', challenge-free columns first
+    pub aux_base_local: Vec<EF>,
+
+    /// Opened values of the base-field aux columns at ζ'·g
+    pub aux_base_next: Vec<EF>,
+
+    /// Opened values of the base-field aux columns at ζ'·g⁻¹
+    pub aux_base_prev: Vec<EF>,
+
+    /// Opened values of the base-field aux columns at ζ'·gᵏ for each further rotation
+    /// `k`
+    pub aux_base_rotated: Vec<Vec<EF>>,
+
+    /// Opened values of quotient chunks at ζ'
+    pub quotient_chunks: Vec<Vec<EF>>,
+}
+
+/// The PCS opening proof of a [`Proof`], detached from its [`Claims`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct OpeningProof<SC: StarkGenericConfig>(pub PcsProof<SC>);
+
+impl<SC: StarkGenericConfig> Clone for OpeningProof<SC> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Serialized size of each component of a [`Proof`], in bytes.
+///
+/// Sizes are those of the compact `postcard` encoding; the components add up to
+/// [`Proof::size_in_bytes`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofSizeBreakdown {
+    /// Main, auxiliary and quotient commitments
+    pub commitments: usize,
+
+    /// Opened trace and quotient values, and exposed auxiliary values
+    pub opened_values: usize,
+
+    /// PCS opening proof
+    pub opening_proof: usize,
+
+    /// Degrees, proof-of-work witness, protocol identifier, trace digest and configuration
+    /// digest
+    pub metadata: usize,
+}
+
+impl ProofSizeBreakdown {
+    /// Total proof size in bytes.
+    pub const fn total(&self) -> usize {
+        self.commitments + self.opened_values + self.opening_proof + self.metadata
+    }
+}
+
+/// Length of the `postcard` encoding of `value`.
+fn serialized_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    postcard::to_allocvec(value)
+        .expect("proof components are always serializable")
+        .len()
+}
+
+impl<SC: crate::StarkGenericConfig> Proof<SC> {
+    /// Serialized size of the proof in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.size_breakdown().total()
+    }
+
+    /// Serialized size of each proof component in bytes.
+    pub fn size_breakdown(&self) -> ProofSizeBreakdown {
+        ProofSizeBreakdown {
+            commitments: serialized_len(&self.main_commits)
+                + serialized_len(&self.challenge_free_commit)
+                + serialized_len(&self.aux_commit)
+                + serialized_len(&self.quotient_commit),
+            opened_values: serialized_len(&self.main_local)
+                + serialized_len(&self.main_next)
+                + serialized_len(&self.main_prev)
+                + serialized_len(&self.main_rotated)
+                + serialized_len(&self.main_last)
+                + serialized_len(&self.main_boundary)
+                + serialized_len(&self.aux_local)
+                + serialized_len(&self.aux_next)
+                + serialized_len(&self.aux_prev)
+                + serialized_len(&self.aux_rotated)
+                + serialized_len(&self.aux_base_local)
+                + serialized_len(&self.aux_base_next)
+                + serialized_len(&self.aux_base_prev)
+                + serialized_len(&self.aux_base_rotated)
+                + serialized_len(&self.aux_last)
+                + serialized_len(&self.aux_base_last)
+                + serialized_len(&self.aux_exposed_values)
+                + serialized_len(&self.quotient_chunks)
+                + serialized_len(&self.extra_ood_openings),
+            opening_proof: serialized_len(&self.opening_proof),
+            metadata: serialized_len(&self.log_degree)
+                + serialized_len(&self.log_aux_degree)
+                + serialized_len(&self.pow_witness)
+                + serialized_len(&self.protocol_id)
+                + serialized_len(&self.trace_digest)
+                + serialized_len(&self.config_digest),
+        }
+    }
+
+    /// A copy of everything in the proof but the opening proof.
+    pub fn claims(&self) -> Claims<SC> {
+        Claims {
+            main_commits: self.main_commits.clone(),
+            challenge_free_commit: self.challenge_free_commit.clone(),
+            aux_commit: self.aux_commit.clone(),
+            quotient_commit: self.quotient_commit.clone(),
+            main_local: self.main_local.clone(),
+            main_next: self.main_next.clone(),
+            main_prev: self.main_prev.clone(),
+            main_rotated: self.main_rotated.clone(),
+            main_last: self.main_last.clone(),
+            main_boundary: self.main_boundary.clone(),
+            aux_local: self.aux_local.clone(),
+            aux_next: self.aux_next.clone(),
+            aux_prev: self.aux_prev.clone(),
+            aux_rotated: self.aux_rotated.clone(),
+            aux_base_local: self.aux_base_local.clone(),
+            aux_base_next: self.aux_base_next.clone(),
+            aux_base_prev: self.aux_base_prev.clone(),
+            aux_base_rotated: self.aux_base_rotated.clone(),
+            aux_last: self.aux_last.clone(),
+            aux_base_last: self.aux_base_last.clone(),
+            aux_exposed_values: self.aux_exposed_values.clone(),
+            quotient_chunks: self.quotient_chunks.clone(),
+            extra_ood_openings: self.extra_ood_openings.clone(),
+            pow_witness: self.pow_witness,
+            log_degree: self.log_degree,
+            log_aux_degree: self.log_aux_degree,
+            protocol_id: self.protocol_id,
+            trace_digest: self.trace_digest,
+            config_digest: self.config_digest,
+        }
+    }
+
+    /// Split the proof into its claims and its opening proof, to serialize, send or
+    /// verify them separately.
+    pub fn split(self) -> (Claims<SC>, OpeningProof<SC>) {
+        let Self {
+            main_commits,
+            challenge_free_commit,
+            aux_commit,
+            quotient_commit,
+            main_local,
+            main_next,
+            main_prev,
+            main_rotated,
+            main_last,
+            main_boundary,
+            aux_local,
+            aux_next,
+            aux_prev,
+            aux_rotated,
+            aux_base_local,
+            aux_base_next,
+            aux_base_prev,
+            aux_base_rotated,
+            aux_last,
+            aux_base_last,
+            aux_exposed_values,
+            quotient_chunks,
+            extra_ood_openings,
+            pow_witness,
+            opening_proof,
+            log_degree,
+            log_aux_degree,
+            protocol_id,
+            trace_digest,
+            config_digest,
+        } = self;
+        let claims = Claims {
+            main_commits,
+            challenge_free_commit,
+            aux_commit,
+            quotient_commit,
+            main_local,
+            main_next,
+            main_prev,
+            main_rotated,
+            main_last,
+            main_boundary,
+            aux_local,
+            aux_next,
+            aux_prev,
+            aux_rotated,
+            aux_base_local,
+            aux_base_next,
+            aux_base_prev,
+            aux_base_rotated,
+            aux_last,
+            aux_base_last,
+            aux_exposed_values,
+            quotient_chunks,
+            extra_ood_openings,
+            pow_witness,
+            log_degree,
+            log_aux_degree,
+            protocol_id,
+            trace_digest,
+            config_digest,
+        };
+        (claims, OpeningProof(opening_proof))
+    }
+
+    /// Reassemble a proof [`split`](Self::split) into `claims` and `opening_proof`.
+    pub fn from_parts(claims: Claims<SC>, opening_proof: OpeningProof<SC>) -> Self {
+        let Claims {
+            main_commits,
+            challenge_free_commit,
+            aux_commit,
+            quotient_commit,
+            main_local,
+            main_next,
+            main_prev,
+            main_rotated,
+            main_last,
+            main_boundary,
+            aux_local,
+            aux_next,
+            aux_prev,
+            aux_rotated,
+            aux_base_local,
+            aux_base_next,
+            aux_base_prev,
+            aux_base_rotated,
+            aux_last,
+            aux_base_last,
+            aux_exposed_values,
+            quotient_chunks,
+            extra_ood_openings,
+            pow_witness,
+            log_degree,
+            log_aux_degree,
+            protocol_id,
+            trace_digest,
+            config_digest,
+        } = claims;
+        Self {
+            main_commits,
+            challenge_free_commit,
+            aux_commit,
+            quotient_commit,
+            main_local,
+            main_next,
+            main_prev,
+            main_rotated,
+            main_last,
+            main_boundary,
+            aux_local,
+            aux_next,
+            aux_prev,
+            aux_rotated,
+            aux_base_local,
+            aux_base_next,
+            aux_base_prev,
+            aux_base_rotated,
+            aux_last,
+            aux_base_last,
+            aux_exposed_values,
+            quotient_chunks,
+            extra_ood_openings,
+            pow_witness,
+            opening_proof: opening_proof.0,
+            log_degree,
+            log_aux_degree,
+            protocol_id,
+            trace_digest,
+            config_digest,
+        }
+    }
+
+    /// Store the keyed digest of the main trace `trace` in the proof envelope.
+    ///
+    /// The digest is not part of the transcript, so the proof verifies the same with or
+    /// without it.
+    pub fn attach_trace_digest(&mut self, trace: &RowMajorMatrix<Val<SC>>, key: &[u8]) {
+        self.trace_digest = Some(trace_digest(trace, key));
+    }
+
+    /// Whether the proof carries the keyed digest of `trace`.
+    ///
+    /// Returns `false` if no digest is attached.
+    pub fn matches_trace(&self, trace: &RowMajorMatrix<Val<SC>>, key: &[u8]) -> bool {
+        self.trace_digest == Some(trace_digest(trace, key))
+    }
 }
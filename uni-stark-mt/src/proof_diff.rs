@@ -0,0 +1,206 @@
+//! Structural comparison of proofs
+//!
+//! [`diff_proofs`] reports which components of two proofs differ, down to individual
+//! opened values. Proving is deterministic, so two honest provers given the same AIR,
+//! trace and configuration produce identical proofs; a nonempty diff points at
+//! nondeterminism or drift between prover versions, and at where it first shows.
+
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::{Proof, ProofSizeBreakdown, StarkGenericConfig};
+
+/// How two lists of opened values differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueDiff {
+    /// The lists have different lengths; their values are not compared
+    Length {
+        /// Length in the first proof
+        a: usize,
+        /// Length in the second proof
+        b: usize,
+    },
+
+    /// The values at `index` differ
+    Value {
+        /// Position in the list
+        index: usize,
+    },
+}
+
+/// A difference between the opened values of two proofs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpenedValueDiff {
+    /// Proof field holding the values, e.g. `"main_local"` or `"quotient_chunks"`
+    pub component: &'static str,
+
+    /// Quotient chunk holding the values, for `"quotient_chunks"`
+    pub chunk: Option<usize>,
+
+    /// What differs
+    pub diff: ValueDiff,
+}
+
+/// Components that differ between two proofs, see [`diff_proofs`].
+///
+/// Commitments and the opening proof are compared by their `postcard` encoding, so the
+/// PCS types don't need to implement `PartialEq`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofDiff {
+    /// Whether the main trace commitments differ, including the challenge-free auxiliary one
+    pub main_commit: bool,
+
+    /// Whether the auxiliary trace commitments differ, including presence
+    pub aux_commit: bool,
+
+    /// Whether the quotient commitments differ
+    pub quotient_commit: bool,
+
+    /// Differing opened values, in proof field order
+    pub opened_values: Vec<OpenedValueDiff>,
+
+    /// Numbers of quotient chunks, if they differ
+    pub quotient_chunk_counts: Option<(usize, usize)>,
+
+    /// Whether the values opened at further out-of-domain points differ
+    pub extra_ood_openings: bool,
+
+    /// Whether the values opened at the rows boundary constraints pin differ
+    pub main_boundary: bool,
+
+    /// Whether the values opened at further rotations differ
+    pub rotated: bool,
+
+    /// Whether the proof-of-work witnesses differ
+    pub pow_witness: bool,
+
+    /// Whether the PCS opening proofs differ
+    pub opening_proof: bool,
+
+    /// Degrees (log2 of trace height), if they differ
+    pub log_degree: Option<(u8, u8)>,
+
+    /// Auxiliary trace degrees (log2 of its height), if they differ
+    pub log_aux_degree: Option<(u8, u8)>,
+
+    /// Whether the protocol identifiers differ
+    pub protocol_id: bool,
+
+    /// Whether the attached trace digests differ, including presence
+    pub trace_digest: bool,
+
+    /// Whether the configuration digests differ, including presence
+    pub config_digest: bool,
+
+    /// Serialized sizes, if they differ
+    pub sizes: Option<(ProofSizeBreakdown, ProofSizeBreakdown)>,
+}
+
+impl ProofDiff {
+    /// Whether the proofs are identical.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Compare `a` and `b` component by component.
+pub fn diff_proofs<SC: StarkGenericConfig>(a: &Proof<SC>, b: &Proof<SC>) -> ProofDiff {
+    let mut opened_values = Vec::new();
+    for (component, values_a, values_b) in [
+        ("main_local", &a.main_local, &b.main_local),
+        ("main_next", &a.main_next, &b.main_next),
+        ("main_prev", &a.main_prev, &b.main_prev),
+        ("main_last", &a.main_last, &b.main_last),
+        ("aux_local", &a.aux_local, &b.aux_local),
+        ("aux_next", &a.aux_next, &b.aux_next),
+        ("aux_prev", &a.aux_prev, &b.aux_prev),
+        ("aux_base_local", &a.aux_base_local, &b.aux_base_local),
+        ("aux_base_next", &a.aux_base_next, &b.aux_base_next),
+        ("aux_base_prev", &a.aux_base_prev, &b.aux_base_prev),
+        ("aux_last", &a.aux_last, &b.aux_last),
+        ("aux_base_last", &a.aux_base_last, &b.aux_base_last),
+        (
+            "aux_exposed_values",
+            &a.aux_exposed_values,
+            &b.aux_exposed_values,
+        ),
+    ] {
+        diff_values(component, None, values_a, values_b, &mut opened_values);
+    }
+    for (chunk, (values_a, values_b)) in
+        a.quotient_chunks.iter().zip(&b.quotient_chunks).enumerate()
+    {
+        diff_values(
+            "quotient_chunks",
+            Some(chunk),
+            values_a,
+            values_b,
+            &mut opened_values,
+        );
+    }
+
+    let (sizes_a, sizes_b) = (a.size_breakdown(), b.size_breakdown());
+    ProofDiff {
+        main_commit: !same_encoding(
+            &(&a.main_commits, &a.challenge_free_commit),
+            &(&b.main_commits, &b.challenge_free_commit),
+        ),
+        aux_commit: !same_encoding(&a.aux_commit, &b.aux_commit),
+        quotient_commit: !same_encoding(&a.quotient_commit, &b.quotient_commit),
+        opened_values,
+        quotient_chunk_counts: (a.quotient_chunks.len() != b.quotient_chunks.len())
+            .then(|| (a.quotient_chunks.len(), b.quotient_chunks.len())),
+        extra_ood_openings: a.extra_ood_openings != b.extra_ood_openings,
+        main_boundary: a.main_boundary != b.main_boundary,
+        rotated: (&a.main_rotated, &a.aux_rotated, &a.aux_base_rotated)
+            != (&b.main_rotated, &b.aux_rotated, &b.aux_base_rotated),
+        pow_witness: a.pow_witness != b.pow_witness,
+        opening_proof: !same_encoding(&a.opening_proof, &b.opening_proof),
+        log_degree: (a.log_degree != b.log_degree).then_some((a.log_degree, b.log_degree)),
+        log_aux_degree: (a.log_aux_degree != b.log_aux_degree)
+            .then_some((a.log_aux_degree, b.log_aux_degree)),
+        protocol_id: a.protocol_id != b.protocol_id,
+        trace_digest: a.trace_digest != b.trace_digest,
+        config_digest: a.config_digest != b.config_digest,
+        sizes: (sizes_a != sizes_b).then_some((sizes_a, sizes_b)),
+    }
+}
+
+/// Record how `a` and `b` differ into `diffs`.
+fn diff_values<T: PartialEq>(
+    component: &'static str,
+    chunk: Option<usize>,
+    a: &[T],
+    b: &[T],
+    diffs: &mut Vec<OpenedValueDiff>,
+) {
+    if a.len() != b.len() {
+        diffs.push(OpenedValueDiff {
+            component,
+            chunk,
+            diff: ValueDiff::Length {
+                a: a.len(),
+                b: b.len(),
+            },
+        });
+        return;
+    }
+    diffs.extend(
+        a.iter()
+            .zip(b)
+            .enumerate()
+            .filter(|(_, (value_a, value_b))| value_a != value_b)
+            .map(|(index, _)| OpenedValueDiff {
+                component,
+                chunk,
+                diff: ValueDiff::Value { index },
+            }),
+    );
+}
+
+/// Whether `a` and `b` have the same `postcard` encoding.
+fn same_encoding<T: Serialize>(a: &T, b: &T) -> bool {
+    postcard::to_allocvec(a).expect("proof components are always serializable")
+        == postcard::to_allocvec(b).expect("proof components are always serializable")
+}
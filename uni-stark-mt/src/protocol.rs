@@ -0,0 +1,174 @@
+//! Protocol identification
+//!
+//! A [`ProtocolId`] records which variant of the protocol produced a proof. It is carried
+//! in the proof and observed at the start of the transcript, so a proof produced under
+//! different protocol semantics can never cross-verify.
+
+use p3_field::PrimeCharacteristicRing;
+use serde::{Deserialize, Serialize};
+
+use crate::air::has_aux_phase;
+use crate::{Challenge, ExtensionSampling, MultiTraceAir, StarkGenericConfig, Val};
+
+/// Version of the proving protocol implemented by this crate.
+///
+/// Bump this whenever the transcript schedule or proof layout changes meaning.
+pub const PROTOCOL_VERSION: u32 = 5;
+
+/// Identifies the protocol version and the optional features a proof relies on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolId {
+    /// Protocol version, see [`PROTOCOL_VERSION`]
+    pub version: u32,
+
+    /// Whether the PCS is zero-knowledge
+    pub zk: bool,
+
+    /// Proof-of-work bits ground into the transcript (0 if grinding is disabled)
+    pub pow_bits: u32,
+
+    /// Number of auxiliary commitment phases
+    pub num_aux_phases: u32,
+
+    /// Number of auxiliary accumulator values exposed by the proof
+    pub num_accumulators: u32,
+
+    /// How extension-field challenges are drawn
+    pub extension_sampling: ExtensionSampling,
+
+    /// Number of out-of-domain points the constraints are checked at
+    pub num_ood_points: u32,
+}
+
+/// The first [`ProtocolId`] field on which a proof and the verifier disagree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProtocolMismatch {
+    /// Different protocol versions
+    Version { expected: u32, actual: u32 },
+    /// One side uses a zero-knowledge PCS and the other doesn't
+    Zk { expected: bool, actual: bool },
+    /// Different grinding difficulty
+    PowBits { expected: u32, actual: u32 },
+    /// Different number of auxiliary phases
+    AuxPhases { expected: u32, actual: u32 },
+    /// Different number of exposed accumulators
+    Accumulators { expected: u32, actual: u32 },
+    /// Extension-field challenges drawn differently
+    ExtensionSampling {
+        expected: ExtensionSampling,
+        actual: ExtensionSampling,
+    },
+    /// Constraints checked at a different number of out-of-domain points
+    OodPoints { expected: u32, actual: u32 },
+}
+
+impl core::fmt::Display for ProtocolMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Version { expected, actual } => {
+                write!(f, "expected protocol version {expected}, got {actual}")
+            }
+            Self::Zk { expected, actual } => {
+                write!(f, "expected zero knowledge {expected}, got {actual}")
+            }
+            Self::PowBits { expected, actual } => {
+                write!(f, "expected {expected} grinding bits, got {actual}")
+            }
+            Self::AuxPhases { expected, actual } => {
+                write!(f, "expected {expected} auxiliary phases, got {actual}")
+            }
+            Self::Accumulators { expected, actual } => {
+                write!(f, "expected {expected} exposed accumulators, got {actual}")
+            }
+            Self::ExtensionSampling { expected, actual } => write!(
+                f,
+                "expected extension sampling {expected:?}, got {actual:?}"
+            ),
+            Self::OodPoints { expected, actual } => {
+                write!(f, "expected {expected} out-of-domain points, got {actual}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProtocolMismatch {}
+
+impl ProtocolId {
+    /// The protocol used to prove `air` under `config` with this version of the crate.
+    pub fn new<SC, A>(config: &SC, air: &A) -> Self
+    where
+        SC: StarkGenericConfig,
+        A: MultiTraceAir<Val<SC>, Challenge<SC>> + ?Sized,
+    {
+        Self {
+            version: PROTOCOL_VERSION,
+            zk: config.is_zk() != 0,
+            pow_bits: config.pow_bits() as u32,
+            num_aux_phases: (has_aux_phase(air) || config.uniform_aux_phase()) as u32,
+            num_accumulators: air.num_aux_exposed_values() as u32,
+            extension_sampling: config.extension_sampling(),
+            num_ood_points: config.num_ood_points() as u32,
+        }
+    }
+
+    /// Compare against the locally expected protocol, reporting the first difference.
+    pub fn check(&self, expected: &Self) -> Result<(), ProtocolMismatch> {
+        if self.version != expected.version {
+            return Err(ProtocolMismatch::Version {
+                expected: expected.version,
+                actual: self.version,
+            });
+        }
+        if self.zk != expected.zk {
+            return Err(ProtocolMismatch::Zk {
+                expected: expected.zk,
+                actual: self.zk,
+            });
+        }
+        if self.pow_bits != expected.pow_bits {
+            return Err(ProtocolMismatch::PowBits {
+                expected: expected.pow_bits,
+                actual: self.pow_bits,
+            });
+        }
+        if self.num_aux_phases != expected.num_aux_phases {
+            return Err(ProtocolMismatch::AuxPhases {
+                expected: expected.num_aux_phases,
+                actual: self.num_aux_phases,
+            });
+        }
+        if self.num_accumulators != expected.num_accumulators {
+            return Err(ProtocolMismatch::Accumulators {
+                expected: expected.num_accumulators,
+                actual: self.num_accumulators,
+            });
+        }
+        if self.extension_sampling != expected.extension_sampling {
+            return Err(ProtocolMismatch::ExtensionSampling {
+                expected: expected.extension_sampling,
+                actual: self.extension_sampling,
+            });
+        }
+        if self.num_ood_points != expected.num_ood_points {
+            return Err(ProtocolMismatch::OodPoints {
+                expected: expected.num_ood_points,
+                actual: self.num_ood_points,
+            });
+        }
+        Ok(())
+    }
+
+    /// Encoding observed into the transcript.
+    pub fn to_field_elements<F: PrimeCharacteristicRing>(&self) -> [F; 7] {
+        [
+            F::from_u32(self.version),
+            F::from_bool(self.zk),
+            F::from_u32(self.pow_bits),
+            F::from_u32(self.num_aux_phases),
+            F::from_u32(self.num_accumulators),
+            F::from_u32(self.extension_sampling.id()),
+            F::from_u32(self.num_ood_points),
+        ]
+    }
+}
@@ -0,0 +1,49 @@
+//! Row provenance for constraint diagnostics
+//!
+//! A violation at "row 183201" says little about which step of the computation went
+//! wrong. Trace builders can record, alongside the trace, where its rows came from (e.g.
+//! which instruction produced them) in a [`TraceProvenance`];
+//! [`ConstraintViolations::report`](crate::ConstraintViolations::report) then names the
+//! origin of every failing row.
+//!
+//! Provenance is only recorded in debug builds: in release builds [`TraceProvenance::tag`]
+//! is a no-op, so instrumented trace builders cost nothing in production.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// Origin of each row of a trace, as labels attached to row ranges.
+///
+/// Each [`tag`](Self::tag) labels the rows from the tagged one up to the next tagged row,
+/// so a builder emitting several rows per step only tags each step's first row.
+#[derive(Clone, Debug, Default)]
+pub struct TraceProvenance {
+    /// Label of the rows starting at each tagged row
+    starts: BTreeMap<usize, String>,
+}
+
+impl TraceProvenance {
+    /// An empty record, labelling no rows.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Label rows from `row` onwards, up to the next tagged row, with `label`.
+    ///
+    /// Tagging the same row again replaces its label. Does nothing in release builds.
+    pub fn tag(&mut self, row: usize, label: impl Into<String>) {
+        if cfg!(debug_assertions) {
+            self.starts.insert(row, label.into());
+        }
+    }
+
+    /// Label of `row`, if a row at or before it was tagged.
+    ///
+    /// Always `None` in release builds.
+    pub fn label(&self, row: usize) -> Option<&str> {
+        self.starts
+            .range(..=row)
+            .next_back()
+            .map(|(_, label)| label.as_str())
+    }
+}
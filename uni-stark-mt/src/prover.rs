@@ -1,55 +1,438 @@
 //! Prover implementation for multi-trace STARK
 
+use alloc::borrow::Cow;
 use alloc::vec;
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use p3_air::Air;
-use p3_challenger::{CanObserve, CanSample};
-use p3_commit::{Pcs, PolynomialSpace};
-use p3_field::{PackedField, PrimeCharacteristicRing};
+use p3_challenger::{CanObserve, CanSample, FieldChallenger, GrindingChallenger};
+use p3_commit::{LagrangeSelectors, Pcs, PolynomialSpace};
+use p3_field::{BasedVectorSpace, ExtensionField, Field, PackedField, PrimeCharacteristicRing};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
-use crate::{Challenge, MultiTraceAir, Proof, ProverFolder, Val};
+use crate::air::{has_aux_phase, has_aux_trace};
+use crate::openings::{boundary_rows, check_boundary_constraints, OodWindow, RowOpenings};
+use crate::pcs::max_log_trace_size;
+use crate::rows::trace_row;
+use crate::statements::{check_statement_layout, StatementBoundaries, StatementValues};
+#[cfg(feature = "std")]
+use crate::telemetry::AllocationTracker;
+use crate::telemetry::{record_proof, ProverPhase};
+use crate::{
+    config_digest, constraint_alpha_powers, domain_separator_elements, keygen, main_commit_batches,
+    placeholder_aux_commitment, AuxTraces, Challenge, ChallengeSet, Commitment,
+    DebugConstraintBuilder, Domain, MultiTraceAir, OodOpening, OpeningSchedule, Proof, ProverData,
+    ProverFolder, ProverMetrics, QuotientRow, RotatedRows, Rotations, StatementSelectors,
+    TraceDomain, TraceRow, TranscriptPhase, Val, VerifyingKey, ZeroDenominator,
+};
+
+/// log2 of the smallest quotient domain blowup over the trace domain.
+///
+/// AIRs whose constraints have a degree above 5 use a larger one, see
+/// [`VerifyingKey::log_quotient_degree`](crate::VerifyingKey::log_quotient_degree).
+pub(crate) const LOG_QUOTIENT_DEGREE: usize = 2;
+
+/// Prover error types
+#[derive(Debug)]
+pub enum ProverError {
+    /// The trace is too tall for the PCS's largest domain (the field's two-adic subgroup,
+    /// or its circle group) to hold its quotient domain, or for the configuration's
+    /// [`max_log_degree`](crate::StarkGenericConfig::max_log_degree)
+    DegreeExceedsFieldTwoAdicity {
+        /// Height of the supplied trace
+        height: usize,
+        /// Largest trace height supported by the field, PCS and configuration
+        max_height: usize,
+    },
+    /// The number of public values doesn't match the AIR's `num_public_values()`
+    PublicValuesMismatch {
+        /// Number of public values the AIR expects
+        expected: usize,
+        /// Number of public values supplied
+        actual: usize,
+    },
+    /// The AIR's punctured rows can't be removed from this trace domain
+    InvalidPunctures(&'static str),
+    /// The AIR's statements can't be laid out in this trace
+    InvalidStatements(&'static str),
+    /// The AIR opens a trace at the last row, which the PCS can't open at
+    LastRowOpeningUnsupported,
+    /// The AIR opens a trace at the previous row or a further rotation, which the PCS
+    /// can't open at
+    RotationOpeningUnsupported,
+    /// The auxiliary trace is shorter than the main trace, which the PCS can't commit on
+    /// a domain of its own, see [`AuxTraces::height`]
+    ShortAuxTraceUnsupported,
+    /// The AIR's boundary constraints don't fit the trace, or pin rows the PCS can't open
+    /// at
+    InvalidBoundaryConstraints(&'static str),
+    /// The AIR builds auxiliary columns from the whole main trace, which a streamed
+    /// commitment doesn't keep, see [`commit_main_streaming`]
+    MainTraceRequired,
+    /// A denominator of the auxiliary trace vanished for the sampled challenges, see
+    /// [`AuxTraceBuilder::try_build_aux_traces`](crate::AuxTraceBuilder::try_build_aux_traces)
+    ZeroDenominator(ZeroDenominator),
+    /// The chips or configuration don't make a machine, see
+    /// [`prove_machine`](crate::prove_machine)
+    InvalidMachine(&'static str),
+}
+
+impl core::fmt::Display for ProverError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::DegreeExceedsFieldTwoAdicity { height, max_height } => write!(
+                f,
+                "trace height {height} exceeds the largest supported, {max_height}"
+            ),
+            Self::PublicValuesMismatch { expected, actual } => {
+                write!(f, "expected {expected} public values, got {actual}")
+            }
+            Self::InvalidPunctures(reason) => write!(f, "invalid punctured rows: {reason}"),
+            Self::InvalidStatements(reason) => write!(f, "invalid statement layout: {reason}"),
+            Self::LastRowOpeningUnsupported => {
+                f.write_str("the PCS can't open traces at the last row")
+            }
+            Self::RotationOpeningUnsupported => {
+                f.write_str("the PCS can't open traces at rotated rows")
+            }
+            Self::ShortAuxTraceUnsupported => {
+                f.write_str("the PCS can't commit a shorter auxiliary trace")
+            }
+            Self::InvalidBoundaryConstraints(reason) => {
+                write!(f, "invalid boundary constraints: {reason}")
+            }
+            Self::MainTraceRequired => {
+                f.write_str("the AIR's auxiliary columns need the whole main trace")
+            }
+            Self::ZeroDenominator(zero) => write!(f, "building the auxiliary trace failed: {zero}"),
+            Self::InvalidMachine(reason) => write!(f, "invalid machine: {reason}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProverError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ZeroDenominator(zero) => Some(zero),
+            _ => None,
+        }
+    }
+}
+
+/// A main trace handed to the prover, owned or borrowed.
+///
+/// The prover only reads the trace, so callers that need it afterwards, e.g. to debug or
+/// re-prove, lend it rather than clone it. The PCS takes ownership of what it commits, so
+/// committing the trace copies it once either way.
+pub trait IntoMainTrace<'a, F: Clone> {
+    /// The trace, borrowed if it was lent.
+    fn into_main_trace(self) -> Cow<'a, RowMajorMatrix<F>>;
+}
+
+impl<'a, F: Clone> IntoMainTrace<'a, F> for RowMajorMatrix<F> {
+    fn into_main_trace(self) -> Cow<'a, RowMajorMatrix<F>> {
+        Cow::Owned(self)
+    }
+}
+
+impl<'a, F: Clone> IntoMainTrace<'a, F> for &'a RowMajorMatrix<F> {
+    fn into_main_trace(self) -> Cow<'a, RowMajorMatrix<F>> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl<'a, F: Clone> IntoMainTrace<'a, F> for Cow<'a, RowMajorMatrix<F>> {
+    fn into_main_trace(self) -> Cow<'a, RowMajorMatrix<F>> {
+        self
+    }
+}
 
 /// Prove a computation using a multi-trace AIR.
 ///
 /// # Arguments
 /// - `config`: STARK configuration (PCS, challenger)
 /// - `air`: The AIR defining the computation
-/// - `main_trace`: The main execution trace
+/// - `main_trace`: The main execution trace, owned or borrowed (see [`IntoMainTrace`])
 /// - `public_values`: Public input/output values
 ///
 /// # Returns
 /// A proof that can be verified with [`crate::verify`]
 ///
+/// # Errors
+/// - [`ProverError::DegreeExceedsFieldTwoAdicity`] if the quotient domain for this trace
+///   height would not fit in the PCS's largest domain (see
+///   [`TraceDomain::MAX_LOG_SIZE`]), or the trace is taller than the configuration's
+///   [`max_log_degree`](crate::StarkGenericConfig::max_log_degree)
+/// - [`ProverError::PublicValuesMismatch`] if `public_values` doesn't have the length the
+///   AIR expects
+/// - [`ProverError::InvalidPunctures`] if the AIR punctures every row, or punctures rows
+///   of a domain the PCS can't puncture (see [`TraceDomain`])
+/// - [`ProverError::InvalidStatements`] if the AIR's statements don't split the trace and
+///   public values evenly, or the trace encodes several statements in a domain the PCS
+///   can't address rows of (see [`num_statements`](crate::AirLayout::num_statements))
+/// - [`ProverError::LastRowOpeningUnsupported`] if the AIR opens a trace at the last row
+///   of a domain whose points aren't field elements (see
+///   [`OpeningSchedule::last_row`](crate::OpeningSchedule::last_row))
+/// - [`ProverError::RotationOpeningUnsupported`] if the AIR opens a trace at the previous
+///   row or a further rotation of a domain whose points aren't field elements (see
+///   [`OpeningSchedule::rotations`](crate::OpeningSchedule::rotations))
+/// - [`ProverError::ShortAuxTraceUnsupported`] if the auxiliary trace is shorter than the
+///   main trace and the PCS's domains can't be mapped onto each other (see
+///   [`AuxTraces::height`])
+/// - [`ProverError::InvalidBoundaryConstraints`] if a boundary constraint names a row,
+///   column or public value out of range, or pins rows of a domain whose points aren't
+///   field elements (see
+///   [`boundary_constraints`](crate::AirLayout::boundary_constraints))
+/// - [`ProverError::ZeroDenominator`] if a denominator of the auxiliary trace vanishes
+///   for the sampled challenges (see
+///   [`try_build_aux_traces`](crate::AuxTraceBuilder::try_build_aux_traces))
+///
 /// # Panics
 /// - If trace dimensions don't match AIR width
 /// - If auxiliary trace building fails
-#[instrument(skip_all, fields(trace_height = main_trace.height()))]
-pub fn prove<SC, A>(
+#[instrument(skip_all, fields(trace_height = tracing::field::Empty))]
+pub fn prove<'t, SC, A>(
     config: &SC,
     air: &A,
-    main_trace: RowMajorMatrix<Val<SC>>,
+    main_trace: impl IntoMainTrace<'t, Val<SC>>,
     public_values: &[Val<SC>],
-) -> Proof<SC>
+) -> Result<Proof<SC>, ProverError>
 where
     SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
     Val<SC>: PackedField,
     A: MultiTraceAir<Val<SC>, Challenge<SC>>
         + for<'a> Air<ProverFolder<'a, SC>>
-        + for<'a> Air<crate::VerifierFolder<'a, SC>>,
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
 {
-    assert_eq!(main_trace.width(), air.width(), "Main trace width mismatch");
+    let main_trace = main_trace.into_main_trace();
+    tracing::Span::current().record("trace_height", main_trace.height());
+    let committed = commit_main(config, air, main_trace)?;
+    prove_with_committed_main(config, air, &committed, public_values)
+}
 
-    let pcs = config.pcs();
-    let mut challenger = config.initialise_challenger();
+/// Like [`prove`], also returning a per-phase [`ProverMetrics`] report.
+///
+/// [`ProverMetrics::peak_allocated_bytes`] is only filled in when
+/// [`CountingAllocator`](crate::CountingAllocator) is the global allocator.
+///
+/// # Errors
+/// As for [`prove`].
+///
+/// # Panics
+/// As for [`prove`].
+#[cfg(feature = "std")]
+#[instrument(skip_all, fields(trace_height = tracing::field::Empty))]
+pub fn prove_with_metrics<'t, SC, A>(
+    config: &SC,
+    air: &A,
+    main_trace: impl IntoMainTrace<'t, Val<SC>>,
+    public_values: &[Val<SC>],
+) -> Result<(Proof<SC>, ProverMetrics), ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<crate::VerifierFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    let allocations = AllocationTracker::start();
+    let mut metrics = ProverMetrics::default();
+    let main_trace = main_trace.into_main_trace();
+    tracing::Span::current().record("trace_height", main_trace.height());
+
+    let committed = commit_main_measured(config, air, main_trace, &mut metrics)?;
+    check_public_values(air, public_values)?;
+    check_statements(air, committed.trace.height(), public_values)?;
+    check_boundaries(air, committed.trace.height(), public_values)?;
+    debug_check_main_trace(air, &committed, public_values);
+    let proof = prove_after_main_commit(
+        config,
+        air,
+        MainPhase::committed(&committed),
+        public_values,
+        &mut ProvingCache::default(),
+        &mut metrics,
+    )?;
+
+    metrics.proof_size_bytes = proof.size_in_bytes();
+    metrics.peak_allocated_bytes = allocations.peak();
+    Ok((proof, metrics))
+}
+
+/// A prover for one AIR under one configuration, keeping what doesn't depend on the
+/// trace between proofs.
+///
+/// [`prove`] derives the AIR's [`VerifyingKey`] (its constraint count, which sizes the
+/// powers of the constraint combiner, its constraint degree and its fingerprint) and the
+/// selectors of the trace domain over the quotient domain on every call. A `Prover`
+/// derives the key once, in [`new`](Self::new), and the selectors once per trace height,
+/// so servers proving the same AIR at the same size over and over only pay for the
+/// trace. The PCS's DFT keeps its twiddles between proofs under the same `config`.
+///
+/// ```ignore
+/// let mut prover = Prover::new(&config, &air);
+/// for (trace, public_values) in jobs {
+///     let proof = prover.prove(trace, &public_values)?;
+/// }
+/// ```
+///
+/// Proofs are the ones [`prove`] makes.
+pub struct Prover<'a, SC: crate::StarkGenericConfig, A> {
+    config: &'a SC,
+    air: &'a A,
+    cache: ProvingCache<SC>,
+}
+
+/// What proving an AIR derives that doesn't depend on the trace, see [`Prover`].
+pub(crate) struct ProvingCache<SC: crate::StarkGenericConfig> {
+    /// The AIR's verifying key
+    vk: Option<VerifyingKey>,
+    /// The trace height last proven, and the selectors of its trace domain over its
+    /// quotient domain
+    selectors: Option<(usize, LagrangeSelectors<Vec<Val<SC>>>)>,
+}
+
+impl<SC: crate::StarkGenericConfig> Default for ProvingCache<SC> {
+    fn default() -> Self {
+        Self {
+            vk: None,
+            selectors: None,
+        }
+    }
+}
+
+impl<'a, SC, A> Prover<'a, SC, A>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'b> Air<ProverFolder<'b, SC>>
+        + for<'b> Air<crate::VerifierFolder<'b, SC>>
+        + for<'b> Air<DebugConstraintBuilder<'b, Val<SC>, Challenge<SC>>>,
+{
+    /// A prover for `air` under `config`, deriving the AIR's verifying key.
+    pub fn new(config: &'a SC, air: &'a A) -> Self {
+        Self {
+            config,
+            air,
+            cache: ProvingCache {
+                vk: Some(keygen(config, air)),
+                selectors: None,
+            },
+        }
+    }
+
+    /// The verifying key of the AIR, as [`keygen`] derives it.
+    pub fn vk(&self) -> &VerifyingKey {
+        self.cache
+            .vk
+            .as_ref()
+            .expect("the key is derived on creation")
+    }
+
+    /// Prove `main_trace` with `public_values`, as [`prove`] does.
+    ///
+    /// # Errors
+    /// As for [`prove`].
+    ///
+    /// # Panics
+    /// As for [`prove`].
+    #[instrument(skip_all, fields(trace_height = tracing::field::Empty))]
+    pub fn prove<'t>(
+        &mut self,
+        main_trace: impl IntoMainTrace<'t, Val<SC>>,
+        public_values: &[Val<SC>],
+    ) -> Result<Proof<SC>, ProverError> {
+        let main_trace = main_trace.into_main_trace();
+        tracing::Span::current().record("trace_height", main_trace.height());
+        let committed = commit_main(self.config, self.air, main_trace)?;
+        prove_committed(
+            self.config,
+            self.air,
+            &committed,
+            public_values,
+            &mut self.cache,
+        )
+    }
+}
+
+/// A main trace together with its PCS commitments.
+///
+/// The main trace is committed before any public value enters the transcript, so its
+/// commitment doesn't depend on them. Produced by [`commit_main`] and consumed by
+/// [`prove_with_committed_main`].
+pub struct CommittedMainTrace<'a, SC: crate::StarkGenericConfig> {
+    /// The main execution trace, borrowed if the caller lent it
+    pub trace: Cow<'a, RowMajorMatrix<Val<SC>>>,
+
+    /// Commitments to the main trace, one per column batch, see
+    /// [`StarkGenericConfig::main_commit_batch_width`](crate::StarkGenericConfig::main_commit_batch_width)
+    pub commits: Vec<Commitment<SC>>,
+
+    /// PCS prover data for each column batch (its LDE and Merkle tree)
+    pub data: Vec<ProverData<SC>>,
+
+    /// Challenge-free auxiliary columns, see
+    /// [`AuxTraceBuilder::challenge_free_aux_width`](crate::AuxTraceBuilder::challenge_free_aux_width)
+    pub challenge_free_aux: RowMajorMatrix<Val<SC>>,
+
+    /// Commitment to the challenge-free auxiliary columns and its PCS prover data, if
+    /// the AIR has any
+    pub challenge_free_commit: Option<(Commitment<SC>, ProverData<SC>)>,
+}
+
+/// Commit to the main trace, and the challenge-free auxiliary columns if the AIR has
+/// any, without proving anything yet.
+///
+/// # Errors
+/// - [`ProverError::DegreeExceedsFieldTwoAdicity`] as for [`prove`]
+///
+/// # Panics
+/// - If trace dimensions don't match AIR width
+#[instrument(skip_all, fields(trace_height = tracing::field::Empty))]
+pub fn commit_main<'t, SC, A>(
+    config: &SC,
+    air: &A,
+    main_trace: impl IntoMainTrace<'t, Val<SC>>,
+) -> Result<CommittedMainTrace<'t, SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let main_trace = main_trace.into_main_trace();
+    tracing::Span::current().record("trace_height", main_trace.height());
+    commit_main_measured(config, air, main_trace, &mut ProverMetrics::default())
+}
+
+/// [`commit_main`], timing the commitment into `metrics`.
+fn commit_main_measured<'t, SC, A>(
+    config: &SC,
+    air: &A,
+    main_trace: Cow<'t, RowMajorMatrix<Val<SC>>>,
+    metrics: &mut ProverMetrics,
+) -> Result<CommittedMainTrace<'t, SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    assert_eq!(main_trace.width(), air.width(), "Main trace width mismatch");
 
-    // Trace dimensions
     let height = main_trace.height();
-    let log_degree = log2_strict_usize(height) as u8;
+    check_trace_height(config, height, LOG_QUOTIENT_DEGREE)?;
+
+    let pcs = config.pcs();
     let trace_domain = pcs.natural_domain_for_degree(height);
 
     // ==================== PHASE 1: Main Trace ====================
@@ -57,56 +440,800 @@ where
         tracing::info!("Committing main trace (height={})", height);
     });
 
-    let (main_commit, main_data) =
-        info_span!("pcs_commit_main").in_scope(|| pcs.commit([(trace_domain, main_trace.clone())]));
+    // One Merkle tree per column batch, built one after the other
+    let batches = main_commit_batches(config, main_trace.width());
+    let (commits, data) = info_span!("pcs_commit_main").in_scope(|| {
+        metrics.time(ProverPhase::CommitMain, || {
+            batches
+                .into_iter()
+                .map(|columns| pcs.commit([(trace_domain, column_batch(&*main_trace, columns))]))
+                .unzip()
+        })
+    });
+
+    Ok(with_challenge_free_aux(
+        config, air, main_trace, commits, data, metrics,
+    ))
+}
+
+/// Build and commit the challenge-free auxiliary columns of `air` next to the committed
+/// main trace.
+fn with_challenge_free_aux<'t, SC, A>(
+    config: &SC,
+    air: &A,
+    main_trace: Cow<'t, RowMajorMatrix<Val<SC>>>,
+    commits: Vec<Commitment<SC>>,
+    data: Vec<ProverData<SC>>,
+    metrics: &mut ProverMetrics,
+) -> CommittedMainTrace<'t, SC>
+where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let width = air.challenge_free_aux_width();
+    assert!(
+        width <= air.aux_base_width(),
+        "More challenge-free auxiliary columns than base-field auxiliary columns"
+    );
+    if width == 0 {
+        return CommittedMainTrace {
+            trace: main_trace,
+            commits,
+            data,
+            challenge_free_aux: RowMajorMatrix::new(Vec::new(), 0),
+            challenge_free_commit: None,
+        };
+    }
+
+    let height = main_trace.height();
+    let challenge_free_aux = air.build_challenge_free_aux_trace(&*main_trace);
+    assert_eq!(
+        challenge_free_aux.width, width,
+        "Challenge-free auxiliary trace width mismatch"
+    );
+    assert_eq!(
+        challenge_free_aux.height(),
+        height,
+        "Challenge-free auxiliary trace height mismatch"
+    );
+
+    let pcs = config.pcs();
+    let trace_domain = pcs.natural_domain_for_degree(height);
+    let committed = info_span!("pcs_commit_challenge_free_aux").in_scope(|| {
+        metrics.time(ProverPhase::CommitMain, || {
+            pcs.commit([(trace_domain, challenge_free_aux.clone())])
+        })
+    });
+
+    CommittedMainTrace {
+        trace: main_trace,
+        commits,
+        data,
+        challenge_free_aux,
+        challenge_free_commit: Some(committed),
+    }
+}
+
+/// Prove against an already committed main trace.
+///
+/// Only the phases after public values are absorbed are run: auxiliary trace, quotient
+/// and openings. When iterating on public values over a fixed trace, commit once with
+/// [`commit_main`] and call this for each public-value vector; the main-trace LDE and
+/// Merkle tree are reused. The resulting proof is identical to the one [`prove`] returns.
+///
+/// # Errors
+/// - [`ProverError::PublicValuesMismatch`] as for [`prove`]
+/// - [`ProverError::InvalidPunctures`] as for [`prove`]
+/// - [`ProverError::InvalidStatements`] as for [`prove`]
+/// - [`ProverError::LastRowOpeningUnsupported`] as for [`prove`]
+/// - [`ProverError::RotationOpeningUnsupported`] as for [`prove`]
+/// - [`ProverError::ShortAuxTraceUnsupported`] as for [`prove`]
+/// - [`ProverError::InvalidBoundaryConstraints`] as for [`prove`]
+/// - [`ProverError::ZeroDenominator`] as for [`prove`]
+///
+/// # Panics
+/// - If auxiliary trace building fails
+#[instrument(skip_all, fields(trace_height = committed.trace.height()))]
+pub fn prove_with_committed_main<SC, A>(
+    config: &SC,
+    air: &A,
+    committed: &CommittedMainTrace<'_, SC>,
+    public_values: &[Val<SC>],
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    prove_committed(
+        config,
+        air,
+        committed,
+        public_values,
+        &mut ProvingCache::default(),
+    )
+}
+
+/// [`prove_with_committed_main`], reusing what `cache` holds from earlier proofs.
+fn prove_committed<SC, A>(
+    config: &SC,
+    air: &A,
+    committed: &CommittedMainTrace<'_, SC>,
+    public_values: &[Val<SC>],
+    cache: &mut ProvingCache<SC>,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    check_public_values(air, public_values)?;
+    check_statements(air, committed.trace.height(), public_values)?;
+    check_boundaries(air, committed.trace.height(), public_values)?;
+    debug_check_main_trace(air, committed, public_values);
+    prove_after_main_commit(
+        config,
+        air,
+        MainPhase::committed(committed),
+        public_values,
+        cache,
+        &mut ProverMetrics::default(),
+    )
+}
+
+/// A main trace committed column batch by column batch, without ever being held whole.
+///
+/// Produced by [`commit_main_streaming`] and consumed by [`prove_with_streamed_main`].
+pub struct StreamedMainTrace<SC: crate::StarkGenericConfig> {
+    /// Number of rows of the trace
+    pub height: usize,
+
+    /// The last row of the trace, read by AIRs opening the main trace there
+    pub last_row: Vec<Val<SC>>,
+
+    /// Commitments to the main trace, one per column batch, see
+    /// [`StarkGenericConfig::main_commit_batch_width`](crate::StarkGenericConfig::main_commit_batch_width)
+    pub commits: Vec<Commitment<SC>>,
+
+    /// PCS prover data for each column batch (its LDE and Merkle tree)
+    pub data: Vec<ProverData<SC>>,
+}
+
+/// Commit to a main trace of `height` rows supplied one column batch at a time.
+///
+/// For traces that don't fit in memory alongside their LDEs: `columns` is called once
+/// per batch of
+/// [`main_commit_batch_width`](crate::StarkGenericConfig::main_commit_batch_width)
+/// columns, in column order, and returns those columns of the trace, e.g. generated on
+/// the fly or read from disk. Each batch is committed and dropped before the next one
+/// is asked for, so at most one batch of the natural-order trace is in memory at a
+/// time, next to the LDEs already committed. The quotient phase then reads the
+/// committed LDEs through the PCS rather than the trace.
+///
+/// Without a batch width the whole trace is asked for at once. The proof is the same as
+/// [`prove`] gives for the whole trace.
+///
+/// # Errors
+/// - [`ProverError::DegreeExceedsFieldTwoAdicity`] as for [`prove`]
+/// - [`ProverError::MainTraceRequired`] if the AIR has auxiliary columns, which are
+///   built from the whole main trace
+///
+/// # Panics
+/// - If a batch isn't as wide as asked for or `height` rows tall
+#[instrument(skip_all, fields(trace_height = height))]
+pub fn commit_main_streaming<SC, A>(
+    config: &SC,
+    air: &A,
+    height: usize,
+    mut columns: impl FnMut(Range<usize>) -> RowMajorMatrix<Val<SC>>,
+) -> Result<StreamedMainTrace<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    if has_aux_phase(air) || air.challenge_free_aux_width() > 0 {
+        return Err(ProverError::MainTraceRequired);
+    }
+    check_trace_height(config, height, LOG_QUOTIENT_DEGREE)?;
+
+    let pcs = config.pcs();
+    let trace_domain = pcs.natural_domain_for_degree(height);
+    let mut last_row = Vec::with_capacity(air.width());
+    let (commits, data) = info_span!("pcs_commit_main_streaming").in_scope(|| {
+        main_commit_batches(config, air.width())
+            .into_iter()
+            .map(|range| {
+                let batch = columns(range.clone());
+                assert_eq!(batch.width(), range.len(), "Column batch width mismatch");
+                assert!(
+                    range.is_empty() || batch.height() == height,
+                    "Column batch height mismatch"
+                );
+                last_row.extend_from_slice(trace_row(&batch, TraceRow::last(height)));
+                pcs.commit([(trace_domain, batch)])
+            })
+            .unzip()
+    });
+
+    Ok(StreamedMainTrace {
+        height,
+        last_row,
+        commits,
+        data,
+    })
+}
 
-    // Observe main trace commitment
-    challenger.observe(main_commit.clone());
+/// Prove against a main trace committed by [`commit_main_streaming`].
+///
+/// The resulting proof is identical to the one [`prove`] returns for the whole trace.
+/// The constraints aren't checked against the trace in debug builds, as it isn't
+/// available: check the trace with the [`MockProver`](crate::MockProver) instead.
+///
+/// # Errors
+/// - [`ProverError::PublicValuesMismatch`] as for [`prove`]
+/// - [`ProverError::InvalidPunctures`] as for [`prove`]
+/// - [`ProverError::InvalidStatements`] as for [`prove`]
+/// - [`ProverError::LastRowOpeningUnsupported`] as for [`prove`]
+/// - [`ProverError::RotationOpeningUnsupported`] as for [`prove`]
+/// - [`ProverError::ShortAuxTraceUnsupported`] as for [`prove`]
+/// - [`ProverError::InvalidBoundaryConstraints`] as for [`prove`]
+/// - [`ProverError::MainTraceRequired`] as for [`commit_main_streaming`]
+#[instrument(skip_all, fields(trace_height = streamed.height))]
+pub fn prove_with_streamed_main<SC, A>(
+    config: &SC,
+    air: &A,
+    streamed: &StreamedMainTrace<SC>,
+    public_values: &[Val<SC>],
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    if has_aux_phase(air) || air.challenge_free_aux_width() > 0 {
+        return Err(ProverError::MainTraceRequired);
+    }
+    check_public_values(air, public_values)?;
+    check_statements(air, streamed.height, public_values)?;
+    check_boundaries(air, streamed.height, public_values)?;
+    let main = MainPhase {
+        trace: None,
+        height: streamed.height,
+        last_row: &streamed.last_row,
+        commits: &streamed.commits,
+        data: &streamed.data,
+        challenge_free_aux: None,
+        challenge_free_commit: None,
+    };
+    prove_after_main_commit(
+        config,
+        air,
+        main,
+        public_values,
+        &mut ProvingCache::default(),
+        &mut ProverMetrics::default(),
+    )
+}
+
+/// The `columns` of `matrix`, as a matrix of their own.
+fn column_batch<F: Clone + Send + Sync>(
+    matrix: &RowMajorMatrix<F>,
+    columns: Range<usize>,
+) -> RowMajorMatrix<F> {
+    if columns == (0..matrix.width) {
+        return matrix.clone();
+    }
+    let width = columns.len();
+    let values = matrix
+        .values
+        .chunks_exact(matrix.width)
+        .flat_map(|row| row[columns.clone()].iter().cloned())
+        .collect();
+    RowMajorMatrix::new(values, width)
+}
+
+/// log2 of the tallest trace whose quotient domain, `2^log_quotient_degree` times larger,
+/// fits in the PCS's domains (and in a `usize`), and that `config` accepts.
+pub(crate) fn max_log_degree<SC>(config: &SC, log_quotient_degree: usize) -> usize
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    let supported = max_log_trace_size::<Domain<SC>>(log_quotient_degree);
+    config
+        .max_log_degree()
+        .map_or(supported, |max_log_degree| max_log_degree.min(supported))
+}
+
+/// Check that `config` accepts a trace of this height, see [`max_log_degree`].
+pub(crate) fn check_trace_height<SC>(
+    config: &SC,
+    height: usize,
+    log_quotient_degree: usize,
+) -> Result<(), ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    let log_degree = log2_strict_usize(height);
+    let max_log_degree = max_log_degree(config, log_quotient_degree);
+    if log_degree > max_log_degree {
+        return Err(ProverError::DegreeExceedsFieldTwoAdicity {
+            height,
+            max_height: 1 << max_log_degree,
+        });
+    }
+    Ok(())
+}
+
+/// Check that `public_values` has the length `air` expects.
+fn check_public_values<F, EF, A>(air: &A, public_values: &[F]) -> Result<(), ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    if public_values.len() != air.num_public_values() {
+        return Err(ProverError::PublicValuesMismatch {
+            expected: air.num_public_values(),
+            actual: public_values.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Check that `air`'s statements split a trace of `height` rows and `public_values` evenly.
+fn check_statements<F, EF, A>(
+    air: &A,
+    height: usize,
+    public_values: &[F],
+) -> Result<(), ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    check_statement_layout(air.num_statements(), public_values.len(), height)
+        .map_err(ProverError::InvalidStatements)
+}
+
+/// Check that `air`'s boundary constraints pin cells of a trace of `height` rows to
+/// `public_values`.
+fn check_boundaries<F, EF, A>(
+    air: &A,
+    height: usize,
+    public_values: &[F],
+) -> Result<(), ProverError>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF>,
+{
+    check_boundary_constraints(
+        &air.boundary_constraints(),
+        height,
+        air.width(),
+        public_values.len(),
+    )
+    .map_err(ProverError::InvalidBoundaryConstraints)
+}
+
+/// Single-phase AIRs have their full witness once the main trace and challenge-free
+/// columns are committed: check it there.
+fn debug_check_main_trace<SC, A>(
+    air: &A,
+    committed: &CommittedMainTrace<'_, SC>,
+    public_values: &[Val<SC>],
+) where
+    SC: crate::StarkGenericConfig,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    #[cfg(all(debug_assertions, not(feature = "minimal")))]
+    if !has_aux_phase(air) {
+        crate::check_constraints::debug_assert_constraints(
+            air,
+            &*committed.trace,
+            None,
+            Some(&committed.challenge_free_aux),
+            ChallengeSet::empty(),
+            &[],
+            public_values,
+        );
+    }
+    #[cfg(not(all(debug_assertions, not(feature = "minimal"))))]
+    let _ = (air, committed, public_values);
+}
+
+/// Points of the rows `air` punctures from `trace_domain`, see [`PuncturedAir`](crate::PuncturedAir).
+fn puncture_points<SC, A>(
+    air: &A,
+    trace_domain: crate::Domain<SC>,
+) -> Result<Vec<Val<SC>>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let num_rows = air.num_punctured_rows();
+    if num_rows >= trace_domain.size() {
+        return Err(ProverError::InvalidPunctures(
+            "at least one row must stay constrained",
+        ));
+    }
+    <Domain<SC> as TraceDomain>::last_row_points(
+        trace_domain.first_point(),
+        trace_domain.size(),
+        num_rows,
+    )
+    .ok_or(ProverError::InvalidPunctures(
+        "the PCS's trace domains can't be punctured",
+    ))
+}
+
+/// Point of the last row of `trace_domain`, if `air` opens one of its traces there, see
+/// [`OpeningSchedule::last_row`](crate::OpeningSchedule::last_row).
+fn last_row_point<SC, A>(
+    air: &A,
+    trace_domain: crate::Domain<SC>,
+) -> Result<Option<Val<SC>>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let aux_last_row = has_aux_trace(air) && air.aux_openings().last_row;
+    if !(air.main_openings().last_row || aux_last_row) {
+        return Ok(None);
+    }
+    <Domain<SC> as TraceDomain>::last_row_points(trace_domain.first_point(), trace_domain.size(), 1)
+        .map(|points| Some(points[0]))
+        .ok_or(ProverError::LastRowOpeningUnsupported)
+}
+
+/// Whether `air` opens one of its traces at the previous row, and the further rotations
+/// it opens any of them at, see
+/// [`OpeningSchedule::rotations`](crate::OpeningSchedule::rotations).
+fn opened_rotations<SC, A>(
+    air: &A,
+    trace_domain: crate::Domain<SC>,
+) -> Result<(bool, Rotations), ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    let main = air.main_openings();
+    let aux = if has_aux_trace(air) {
+        air.aux_openings()
+    } else {
+        OpeningSchedule::LOCAL
+    };
+    let prev_row = main.prev_row || aux.prev_row;
+    let rotations = main.rotations.union(aux.rotations);
+    let rotates = <Domain<SC> as TraceDomain>::rotate_point(
+        trace_domain.size(),
+        Challenge::<SC>::from(trace_domain.first_point()),
+        -1,
+    )
+    .is_some();
+    if (prev_row || !rotations.is_empty()) && !rotates {
+        return Err(ProverError::RotationOpeningUnsupported);
+    }
+    Ok((prev_row, rotations))
+}
+
+/// Points of the rows `air`'s boundary constraints pin in `trace_domain`, in increasing
+/// row order, see [`AirLayout::boundary_constraints`](crate::AirLayout::boundary_constraints).
+fn boundary_points<SC, A>(
+    air: &A,
+    trace_domain: crate::Domain<SC>,
+) -> Result<Vec<Val<SC>>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>,
+{
+    <Domain<SC> as TraceDomain>::row_lagrange_points(
+        trace_domain.first_point(),
+        trace_domain.size(),
+        &boundary_rows(&air.boundary_constraints()),
+    )
+    .map(|points| points.into_iter().map(|(point, _)| point).collect())
+    .ok_or(ProverError::InvalidBoundaryConstraints(
+        "the PCS's trace domains can't be opened at arbitrary rows",
+    ))
+}
+
+/// What the phases after the main commitment read of the committed main trace.
+struct MainPhase<'c, SC: crate::StarkGenericConfig> {
+    /// The whole trace, `None` if it was streamed
+    trace: Option<&'c RowMajorMatrix<Val<SC>>>,
+    height: usize,
+    last_row: &'c [Val<SC>],
+    commits: &'c [Commitment<SC>],
+    data: &'c [ProverData<SC>],
+    /// Challenge-free auxiliary columns, `None` if the trace was streamed
+    challenge_free_aux: Option<&'c RowMajorMatrix<Val<SC>>>,
+    challenge_free_commit: Option<&'c (Commitment<SC>, ProverData<SC>)>,
+}
+
+impl<'c, SC: crate::StarkGenericConfig> MainPhase<'c, SC> {
+    fn committed(committed: &'c CommittedMainTrace<'_, SC>) -> Self {
+        let height = committed.trace.height();
+        Self {
+            trace: Some(&*committed.trace),
+            height,
+            last_row: trace_row(&committed.trace, TraceRow::last(height)),
+            commits: &committed.commits,
+            data: &committed.data,
+            challenge_free_aux: Some(&committed.challenge_free_aux),
+            challenge_free_commit: committed.challenge_free_commit.as_ref(),
+        }
+    }
+}
+
+/// Run every phase after the main trace has been committed, timing them into `metrics`.
+fn prove_after_main_commit<SC, A>(
+    config: &SC,
+    air: &A,
+    main: MainPhase<'_, SC>,
+    public_values: &[Val<SC>],
+    cache: &mut ProvingCache<SC>,
+    metrics: &mut ProverMetrics,
+) -> Result<Proof<SC>, ProverError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    let pcs = config.pcs();
+    let mut challenger = config.initialise_challenger();
+    let main_data = main.data;
+    let challenge_free_data = main.challenge_free_commit.map(|(_, data)| data);
+
+    // Trace dimensions
+    let height = main.height;
+    let log_degree = log2_strict_usize(height) as u8;
+    let trace_domain = pcs.natural_domain_for_degree(height);
+    metrics.trace_height = height;
+    let punctures = puncture_points::<SC, A>(air, trace_domain)?;
+    let last_point = last_row_point::<SC, A>(air, trace_domain)?;
+    let (opens_prev_row, rotations) = opened_rotations::<SC, A>(air, trace_domain)?;
+    let boundary_points = boundary_points::<SC, A>(air, trace_domain)?;
+    let (main_schedule, aux_schedule) = (air.main_openings(), air.aux_openings());
+    let main_last_row = if main_schedule.last_row {
+        main.last_row.to_vec()
+    } else {
+        Vec::new()
+    };
+    let statements =
+        StatementBoundaries::new(air.num_statements(), public_values.len(), trace_domain)
+            .map_err(ProverError::InvalidStatements)?;
+
+    // Constraints of a higher degree need a larger quotient domain
+    let ProvingCache { vk, selectors } = cache;
+    let vk = &*vk.get_or_insert_with(|| keygen(config, air));
+    check_trace_height(config, height, vk.log_quotient_degree)?;
+
+    // Bind the application, protocol variant and AIR before anything else
+    challenger.observe_slice(&domain_separator_elements::<Val<SC>>(
+        config.domain_separator(),
+    ));
+    let protocol_id = vk.protocol_id;
+    let extension_sampling = config.extension_sampling();
+    challenger.observe_slice(&protocol_id.to_field_elements::<Val<SC>>());
+    challenger.observe_slice(&vk.fingerprint.map(Val::<SC>::from_u8));
+
+    // Observe the main trace commitments, in column order, then the challenge-free
+    // auxiliary commitment made with them
+    challenger.observe(TranscriptPhase::Main.to_field_element::<Val<SC>>());
+    let main_commits = main.commits.to_vec();
+    for commit in &main_commits {
+        challenger.observe(commit.clone());
+    }
+    let challenge_free_commit = main.challenge_free_commit.map(|(commit, _)| commit.clone());
+    if let Some(commit) = &challenge_free_commit {
+        challenger.observe(commit.clone());
+    }
     challenger.observe_slice(public_values);
 
     // ==================== PHASE 2: Auxiliary Trace ====================
-    let (aux_commit, aux_data, _aux_trace) = if air.aux_width() > 0 {
+    let challenge_names = air.challenge_names();
+    let (
+        aux_commit,
+        aux_data,
+        challenge_values,
+        aux_exposed_values,
+        aux_last_row,
+        aux_base_last_row,
+        aux_height,
+        short_aux_trace,
+    ) = if has_aux_phase(air) {
         info_span!("auxiliary phase").in_scope(|| {
-            // Sample challenges
-            let num_challenges = air.num_challenges();
-            let challenges: Vec<Challenge<SC>> =
-                (0..num_challenges).map(|_| challenger.sample()).collect();
+            metrics.time(ProverPhase::Aux, || {
+                let (Some(main_trace), Some(challenge_free_aux)) =
+                    (main.trace, main.challenge_free_aux)
+                else {
+                    unreachable!("streamed main traces are refused for auxiliary phases");
+                };
 
-            tracing::info!("Sampled {} challenges for auxiliary trace", num_challenges);
+                // Sample the declared challenges, as the AIR asks
+                challenger.observe(TranscriptPhase::Aux.to_field_element::<Val<SC>>());
+                let challenge_values: Vec<Challenge<SC>> =
+                    air.challenge_sampling().sample(challenge_names.len(), || {
+                        extension_sampling.draw::<Val<SC>, _, _>(&mut challenger)
+                    });
+                let challenges = ChallengeSet::new(&challenge_names, &challenge_values);
 
-            // Build auxiliary trace using challenges
-            // Pass the original main_trace (not LDE) to build_aux_trace
-            let aux_trace = air.build_aux_trace(&main_trace, &challenges);
+                tracing::info!(
+                    "Sampled {} challenges for auxiliary trace",
+                    challenges.len()
+                );
 
-            assert_eq!(
-                aux_trace.width,
-                air.aux_width(),
-                "Auxiliary trace width mismatch"
-            );
-            assert_eq!(
-                aux_trace.height(),
-                height,
-                "Auxiliary trace height mismatch"
-            );
+                // Build both auxiliary segments using challenges
+                // The builders see the logical main trace, in natural order on the trace
+                // domain, not the bit-reversed LDE the commitment holds
+                let aux_traces = air
+                    .try_build_aux_traces(main_trace, &challenges)
+                    .map_err(ProverError::ZeroDenominator)?;
+                let aux_height = aux_traces
+                    .height(height)
+                    .unwrap_or_else(|message| panic!("{message}"));
+                let AuxTraces {
+                    ext: aux_trace,
+                    base: aux_base_trace,
+                    exposed: aux_exposed_values,
+                } = aux_traces;
 
-            tracing::info!(
-                "Built auxiliary trace ({}x{})",
-                aux_trace.height(),
-                aux_trace.width
-            );
+                assert_eq!(
+                    aux_trace.width,
+                    air.aux_width(),
+                    "Auxiliary trace width mismatch"
+                );
+                assert_eq!(
+                    aux_base_trace.width,
+                    air.aux_base_width(),
+                    "Base-field auxiliary trace width mismatch"
+                );
+                // A shorter trace lives on a domain of its own, whose rows the trace
+                // domain's map onto
+                if aux_height < height
+                    && <Domain<SC> as TraceDomain>::periodic_point(
+                        height,
+                        aux_height,
+                        Challenge::<SC>::ONE,
+                    )
+                    .is_none()
+                {
+                    return Err(ProverError::ShortAuxTraceUnsupported);
+                }
+                assert_eq!(
+                    aux_exposed_values.len(),
+                    air.num_aux_exposed_values(),
+                    "Exposed auxiliary value count mismatch"
+                );
+                if let Some(index) = air.bus_contribution_index() {
+                    assert!(
+                        index < aux_exposed_values.len(),
+                        "Bus contribution index out of range of the exposed values"
+                    );
+                }
 
-            // Commit auxiliary trace (flatten to base field first)
-            let aux_trace_flat = aux_trace.clone().flatten_to_base();
-            let (aux_commit, aux_data) = info_span!("pcs_commit_aux")
-                .in_scope(|| pcs.commit([(trace_domain, aux_trace_flat)]));
+                tracing::info!(
+                    "Built auxiliary trace ({}x{} extension, {}x{} base)",
+                    aux_height,
+                    aux_trace.width,
+                    aux_height,
+                    aux_base_trace.width
+                );
 
-            // Observe auxiliary commitment
-            challenger.observe(aux_commit.clone());
+                #[cfg(all(debug_assertions, not(feature = "minimal")))]
+                crate::check_constraints::debug_assert_constraints(
+                    air,
+                    main_trace,
+                    Some(&aux_trace),
+                    Some(&aux_base_trace),
+                    challenges,
+                    &aux_exposed_values,
+                    public_values,
+                );
 
-            (Some(aux_commit), Some(aux_data), Some(aux_trace))
-        })
+                let challenge_free_width = challenge_free_aux.width;
+                if challenge_free_width > 0 {
+                    assert!(
+                        aux_base_trace.height() == challenge_free_aux.height()
+                            && aux_base_trace
+                                .rows()
+                                .zip(challenge_free_aux.rows())
+                                .all(|(row, free)| row.take(challenge_free_width).eq(free)),
+                        "Challenge-free auxiliary columns changed in the auxiliary phase"
+                    );
+                }
+
+                // Constraints read the last row directly when it is opened
+                let (aux_last_row, aux_base_last_row) = if aux_schedule.last_row {
+                    (
+                        trace_row(&aux_trace, TraceRow::last(aux_height)).to_vec(),
+                        trace_row(&aux_base_trace, TraceRow::last(aux_height)).to_vec(),
+                    )
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+
+                // Commit the extension columns flattened to the base field, then the
+                // base-field columns not committed yet, as one matrix
+                let aux_base_dependent =
+                    column_batch(&aux_base_trace, challenge_free_width..aux_base_trace.width);
+                let aux_committed =
+                    committed_aux_trace(&aux_trace, &aux_base_dependent, aux_height);
+                // The quotient reads a shorter trace repeated, which the PCS doesn't extend
+                let short_aux_trace = (aux_height < height).then(|| aux_committed.clone());
+                let aux_domain = pcs.natural_domain_for_degree(aux_height);
+                let (aux_commit, aux_data) = info_span!("pcs_commit_aux")
+                    .in_scope(|| pcs.commit([(aux_domain, aux_committed)]));
+
+                // Observe auxiliary commitment, then the exposed values
+                challenger.observe(aux_commit.clone());
+                for value in &aux_exposed_values {
+                    challenger.observe_algebra_element(*value);
+                }
+
+                Ok((
+                    Some(aux_commit),
+                    Some(aux_data),
+                    challenge_values,
+                    aux_exposed_values,
+                    aux_last_row,
+                    aux_base_last_row,
+                    aux_height,
+                    short_aux_trace,
+                ))
+            })
+        })?
     } else {
-        (None, None, None)
+        // Only challenge-free auxiliary columns, if any
+        let aux_base_last_row = match main.challenge_free_aux {
+            Some(free) if aux_schedule.last_row && free.width > 0 => {
+                trace_row(free, TraceRow::last(height)).to_vec()
+            }
+            _ => Vec::new(),
+        };
+        // An auxiliary phase without challenges or columns, if the config asks for one
+        let aux_commit = config.uniform_aux_phase().then(|| {
+            challenger.observe(TranscriptPhase::Aux.to_field_element::<Val<SC>>());
+            let commit = placeholder_aux_commitment(config);
+            challenger.observe(commit.clone());
+            commit
+        });
+        (
+            aux_commit,
+            None,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            aux_base_last_row,
+            height,
+            None,
+        )
+    };
+    let challenges = if aux_data.is_some() {
+        ChallengeSet::new(&challenge_names, &challenge_values)
+    } else {
+        ChallengeSet::empty()
     };
 
     // ==================== PHASE 3: Quotient Polynomial ====================
@@ -115,32 +1242,87 @@ where
     });
 
     // Sample challenge for combining constraints
-    let alpha: Challenge<SC> = challenger.sample();
+    challenger.observe(TranscriptPhase::Quotient.to_field_element::<Val<SC>>());
+    let alpha: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
 
-    // Compute constraint polynomial degree
-    // TODO: For now using a simple heuristic; should compute symbolically
-    let constraint_degree = 2; // Most common case
-    let quotient_degree = 1 << constraint_degree;
+    let quotient_degree = 1 << vk.log_quotient_degree;
 
     // Create larger domain for quotient evaluation
     let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
+    metrics.quotient_domain_size = quotient_domain.size();
+    if !matches!(selectors, Some((cached, _)) if *cached == height) {
+        *selectors = Some((height, trace_domain.selectors_on_coset(quotient_domain)));
+    }
+    let (_, selectors) = selectors
+        .as_ref()
+        .expect("the selectors were just computed");
+    // Constraint i is weighted by alpha^(num_constraints - 1 - i)
+    let alpha_powers = constraint_alpha_powers(alpha, vk.num_constraints);
 
     // Get trace evaluations on quotient domain
-    let main_on_quotient = pcs.get_evaluations_on_domain(&main_data, 0, quotient_domain);
-    let aux_on_quotient = aux_data
-        .as_ref()
-        .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+    let main_on_quotient: Vec<_> = main_data
+        .iter()
+        .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain))
+        .collect();
+    let challenge_free_on_quotient =
+        challenge_free_data.map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+    // A shorter auxiliary trace repeats over the trace domain, and so over the quotient
+    // domain
+    let short_aux_on_quotient = short_aux_trace.map(|trace| {
+        <Domain<SC> as TraceDomain>::periodic_evaluations(
+            trace,
+            height,
+            quotient_domain.first_point(),
+            quotient_domain.size(),
+        )
+        .expect("the PCS maps short traces, checked when committing them")
+    });
 
     // Compute quotient values
-    let quotient_values = compute_quotient_values(
-        air,
-        trace_domain,
-        quotient_domain,
-        &main_on_quotient,
-        aux_on_quotient.as_ref(),
-        alpha,
-        public_values,
-    );
+    let quotient_values = metrics.time(ProverPhase::Quotient, || {
+        if let Some(aux_on_quotient) = &short_aux_on_quotient {
+            compute_quotient_values(
+                air,
+                trace_domain,
+                quotient_domain,
+                selectors,
+                &main_on_quotient,
+                challenge_free_on_quotient.as_ref(),
+                Some(aux_on_quotient),
+                challenges,
+                &aux_exposed_values,
+                &main_last_row,
+                &aux_last_row,
+                &aux_base_last_row,
+                &alpha_powers,
+                public_values,
+                &punctures,
+                statements.as_ref(),
+            )
+        } else {
+            let aux_on_quotient = aux_data
+                .as_ref()
+                .map(|data| pcs.get_evaluations_on_domain(data, 0, quotient_domain));
+            compute_quotient_values(
+                air,
+                trace_domain,
+                quotient_domain,
+                selectors,
+                &main_on_quotient,
+                challenge_free_on_quotient.as_ref(),
+                aux_on_quotient.as_ref(),
+                challenges,
+                &aux_exposed_values,
+                &main_last_row,
+                &aux_last_row,
+                &aux_base_last_row,
+                &alpha_powers,
+                public_values,
+                &punctures,
+                statements.as_ref(),
+            )
+        }
+    });
 
     // Commit to quotient polynomial chunks
     let quotient_flat = RowMajorMatrix::new_col(quotient_values).flatten_to_base();
@@ -149,13 +1331,15 @@ where
 
     // Commit all chunks together (not separately)
     let (quotient_commit, quotient_data) = info_span!("pcs_commit_quotient").in_scope(|| {
-        pcs.commit(
-            quotient_chunk_domains
-                .iter()
-                .copied()
-                .zip(quotient_chunks.into_iter())
-                .collect::<Vec<_>>(),
-        )
+        metrics.time(ProverPhase::CommitQuotient, || {
+            pcs.commit(
+                quotient_chunk_domains
+                    .iter()
+                    .copied()
+                    .zip(quotient_chunks.into_iter())
+                    .collect::<Vec<_>>(),
+            )
+        })
     });
 
     // Observe quotient commitment
@@ -166,161 +1350,463 @@ where
         tracing::info!("Computing opening proofs");
     });
 
-    // Sample out-of-domain evaluation point
-    let zeta: Challenge<SC> = challenger.sample();
-    let zeta_next = trace_domain
-        .next_point(zeta)
-        .expect("domain must support next_point");
+    // Grind, then sample the out-of-domain evaluation points
+    challenger.observe(TranscriptPhase::Opening.to_field_element::<Val<SC>>());
+    let pow_witness = match config.pow_bits() {
+        0 => Val::<SC>::ZERO,
+        bits => info_span!("grind").in_scope(|| challenger.grind(bits)),
+    };
+    let zetas: Vec<OodWindow<Challenge<SC>>> = (0..config.num_ood_points())
+        .map(|_| {
+            let zeta: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
+            let next = trace_domain
+                .next_point(zeta)
+                .expect("domain must support next_point");
+            let rotate = |offset| {
+                <Domain<SC> as TraceDomain>::rotate_point(trace_domain.size(), zeta, offset)
+                    .expect("the PCS rotates points, see opened_rotations")
+            };
+            OodWindow {
+                zeta,
+                next,
+                prev: opens_prev_row.then(|| rotate(-1)),
+                rotated: rotations
+                    .iter()
+                    .map(|offset| (offset, rotate(offset)))
+                    .collect(),
+            }
+        })
+        .collect();
 
-    // Open all committed polynomials
-    let mut opening_points = vec![(&main_data, vec![vec![zeta, zeta_next]])];
+    // Open each trace at the points its schedule asks for, at every out-of-domain point
+    let last_point = last_point.map(Challenge::<SC>::from);
+    // Every main batch is opened at the main trace's points, then at the pinned rows
+    let mut main_points = main_schedule.points_at(&zetas, last_point);
+    main_points.extend(
+        boundary_points
+            .iter()
+            .map(|&point| Challenge::<SC>::from(point)),
+    );
+    let mut opening_points: Vec<_> = main_data
+        .iter()
+        .map(|data| (data, vec![main_points.clone()]))
+        .collect();
 
+    // Challenge-free auxiliary columns are read alongside the other auxiliary columns. A
+    // shorter auxiliary trace is opened where the points' rows map to on its domain
+    let periodic = |point: Challenge<SC>| {
+        <Domain<SC> as TraceDomain>::periodic_point(height, aux_height, point)
+            .expect("the PCS maps short traces, checked when committing them")
+    };
+    let aux_zetas: Vec<OodWindow<Challenge<SC>>> =
+        zetas.iter().map(|window| window.map(periodic)).collect();
+    let aux_points = aux_schedule.points_at(&aux_zetas, last_point.map(periodic));
+    if let Some(data) = challenge_free_data {
+        opening_points.push((data, vec![aux_points.clone()]));
+    }
     if let Some(ref aux_data) = aux_data {
-        opening_points.push((aux_data, vec![vec![zeta, zeta_next]]));
+        opening_points.push((aux_data, vec![aux_points]));
     }
 
-    // Open all quotient chunks at zeta (they're all in one commitment now)
-    let quotient_opening_points: Vec<Vec<Challenge<SC>>> =
-        quotient_chunk_domains.iter().map(|_| vec![zeta]).collect();
+    // Open all quotient chunks at the out-of-domain points (they're all in one
+    // commitment now)
+    let quotient_opening_points: Vec<Vec<Challenge<SC>>> = quotient_chunk_domains
+        .iter()
+        .map(|_| zetas.iter().map(|window| window.zeta).collect())
+        .collect();
     opening_points.push((&quotient_data, quotient_opening_points));
 
-    let (opened_values, opening_proof) = pcs.open(opening_points, &mut challenger);
+    let (opened_values, opening_proof) = metrics.time(ProverPhase::Open, || {
+        pcs.open(opening_points, &mut challenger)
+    });
 
     // Extract opened values
     let mut values_iter = opened_values.into_iter();
+    // Main trace openings, the batches' columns joined back in order
+    let num_further = zetas.len() - 1;
+    let mut main = RowOpenings::default();
+    let mut main_further = vec![RowOpenings::default(); num_further];
+    let mut main_boundary = vec![Vec::new(); boundary_points.len()];
+    for batch in values_iter.by_ref().take(main_data.len()) {
+        let (scheduled, pinned) = batch[0].split_at(batch[0].len() - boundary_points.len());
+        for (row, values) in main_boundary.iter_mut().zip(pinned) {
+            row.extend_from_slice(values);
+        }
+        let (first, further) = main_schedule.split_openings_at(scheduled);
+        main.append(first);
+        for (joined, openings) in main_further.iter_mut().zip(further) {
+            joined.append(openings);
+        }
+    }
+
+    // Challenge-free auxiliary openings, the leading base-field auxiliary columns
+    let (mut aux_base, mut aux_base_further) = if challenge_free_data.is_some() {
+        aux_schedule.split_openings_at(&values_iter.next().unwrap()[0])
+    } else {
+        Default::default()
+    };
+    aux_base_further.resize_with(num_further, RowOpenings::default);
 
-    // Main trace openings
-    let main_openings = values_iter.next().unwrap();
-    let main_local = main_openings[0][0].clone();
-    let main_next = main_openings[0][1].clone();
+    // Auxiliary trace openings (if present), split into the flattened extension columns
+    // and the base-field columns committed after them
+    let aux_ext_width = air.aux_width() * <Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let (mut aux, mut aux_further) = if aux_data.is_some() {
+        let (mut first, mut further) =
+            aux_schedule.split_openings_at(&values_iter.next().unwrap()[0]);
+        aux_base.append(first.split_off(aux_ext_width));
+        for (base, openings) in aux_base_further.iter_mut().zip(&mut further) {
+            base.append(openings.split_off(aux_ext_width));
+        }
+        (first, further)
+    } else {
+        Default::default()
+    };
+    aux_further.resize_with(num_further, RowOpenings::default);
 
-    // Auxiliary trace openings (if present)
-    let (aux_local, aux_next) = if aux_data.is_some() {
-        let aux_openings = values_iter.next().unwrap();
-        (aux_openings[0][0].clone(), aux_openings[0][1].clone())
+    // An AIR with auxiliary columns lists a row per rotation for both segments, empty if
+    // it has no columns in one
+    let num_aux_rotations = if has_aux_trace(air) {
+        aux_schedule.rotations.len()
     } else {
-        (vec![], vec![])
+        0
     };
+    for openings in [&mut aux, &mut aux_base]
+        .into_iter()
+        .chain(&mut aux_further)
+        .chain(&mut aux_base_further)
+    {
+        openings.rotated.resize(num_aux_rotations, Vec::new());
+    }
+
+    let mut extra_ood_openings: Vec<OodOpening<Challenge<SC>>> = main_further
+        .into_iter()
+        .zip(aux_further)
+        .zip(aux_base_further)
+        .map(|((main, aux), aux_base)| OodOpening {
+            main_local: main.local,
+            main_next: main.next,
+            main_prev: main.prev,
+            main_rotated: main.rotated,
+            aux_local: aux.local,
+            aux_next: aux.next,
+            aux_prev: aux.prev,
+            aux_rotated: aux.rotated,
+            aux_base_local: aux_base.local,
+            aux_base_next: aux_base.next,
+            aux_base_prev: aux_base.prev,
+            aux_base_rotated: aux_base.rotated,
+            quotient_chunks: Vec::new(),
+        })
+        .collect();
 
     // Quotient chunk openings
-    // All quotient chunks were in one commitment, opened at multiple rounds (one per chunk)
+    // All quotient chunks were in one commitment, opened at multiple rounds (one per
+    // chunk), each at every out-of-domain point
     let quotient_openings = values_iter.next().unwrap();
     let quotient_chunks: Vec<Vec<Challenge<SC>>> = quotient_openings
         .iter()
         .map(|round| round[0].clone())
         .collect();
+    for (i, opening) in extra_ood_openings.iter_mut().enumerate() {
+        opening.quotient_chunks = quotient_openings
+            .iter()
+            .map(|round| round[i + 1].clone())
+            .collect();
+    }
 
-    Proof {
-        main_commit,
+    let proof = Proof {
+        main_commits,
+        challenge_free_commit,
         aux_commit,
         quotient_commit,
-        main_local,
-        main_next,
-        aux_local,
-        aux_next,
+        main_local: main.local,
+        main_next: main.next,
+        main_prev: main.prev,
+        main_rotated: main.rotated,
+        main_last: main.last,
+        main_boundary,
+        aux_local: aux.local,
+        aux_next: aux.next,
+        aux_prev: aux.prev,
+        aux_rotated: aux.rotated,
+        aux_base_local: aux_base.local,
+        aux_base_next: aux_base.next,
+        aux_base_prev: aux_base.prev,
+        aux_base_rotated: aux_base.rotated,
+        aux_last: aux.last,
+        aux_base_last: aux_base.last,
+        aux_exposed_values,
         quotient_chunks,
+        extra_ood_openings,
+        pow_witness,
         opening_proof,
         log_degree,
+        log_aux_degree: log2_strict_usize(aux_height) as u8,
+        protocol_id,
+        trace_digest: None,
+        config_digest: config_digest(config),
+    };
+    record_proof(&proof);
+    Ok(proof)
+}
+
+/// The auxiliary trace as committed: the extension-field columns flattened to the base
+/// field, followed by the base-field columns.
+pub(crate) fn committed_aux_trace<F, EF>(
+    aux_trace: &RowMajorMatrix<EF>,
+    aux_base_trace: &RowMajorMatrix<F>,
+    height: usize,
+) -> RowMajorMatrix<F>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    let (ext_width, base_width) = (aux_trace.width, aux_base_trace.width);
+    let width = ext_width * <EF as BasedVectorSpace<F>>::DIMENSION + base_width;
+    let mut values = Vec::with_capacity(width * height);
+    for row in 0..height {
+        values.extend(
+            aux_trace.values[row * ext_width..(row + 1) * ext_width]
+                .iter()
+                .flat_map(|x| {
+                    BasedVectorSpace::<F>::as_basis_coefficients_slice(x)
+                        .iter()
+                        .copied()
+                }),
+        );
+        values.extend_from_slice(&aux_base_trace.values[row * base_width..(row + 1) * base_width]);
     }
+    RowMajorMatrix::new(values, width)
+}
+
+/// Recombine extension-field values from their flattened base-field coefficients.
+fn recombine_extension<F, EF>(flat: &[F]) -> Vec<EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    flat.chunks_exact(<EF as BasedVectorSpace<F>>::DIMENSION)
+        .map(|coefficients| {
+            <EF as BasedVectorSpace<F>>::from_basis_coefficients_fn(|k| coefficients[k])
+        })
+        .collect()
 }
 
 /// Compute quotient polynomial values by evaluating constraints on the quotient domain.
 #[instrument(skip_all)]
-fn compute_quotient_values<SC, A, M>(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn compute_quotient_values<SC, A, M, MA>(
     air: &A,
     trace_domain: crate::Domain<SC>,
     quotient_domain: crate::Domain<SC>,
-    main_on_quotient: &M,
-    _aux_on_quotient: Option<&M>,
-    alpha: Challenge<SC>,
-    _public_values: &[Val<SC>],
+    selectors: &LagrangeSelectors<Vec<Val<SC>>>,
+    main_on_quotient: &[M],
+    challenge_free_on_quotient: Option<&M>,
+    aux_on_quotient: Option<&MA>,
+    challenges: ChallengeSet<'_, Challenge<SC>>,
+    aux_exposed_values: &[Challenge<SC>],
+    main_last_row: &[Val<SC>],
+    aux_last_row: &[Challenge<SC>],
+    aux_base_last_row: &[Val<SC>],
+    alpha_powers: &[Challenge<SC>],
+    public_values: &[Val<SC>],
+    punctures: &[Val<SC>],
+    statements: Option<&StatementBoundaries<Val<SC>>>,
 ) -> Vec<Challenge<SC>>
 where
     SC: crate::StarkGenericConfig,
     Val<SC>: PackedField,
-    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<ProverFolder<'a, SC>>,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>
+        + ?Sized,
     M: p3_matrix::Matrix<Val<SC>> + Sync,
+    MA: p3_matrix::Matrix<Val<SC>> + Sync,
 {
     let quotient_size = quotient_domain.size();
-    let width_main = main_on_quotient.width();
-    let _width_aux = 0; // TODO: Implement proper aux trace handling
+    let width_main = main_on_quotient.iter().map(|batch| batch.width()).sum();
+    // Extension-field auxiliary columns are committed flattened, followed by the
+    // base-field auxiliary columns
+    let aux_width = air.aux_width();
+    let aux_ext_width = aux_width * <Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION;
+    let (main_schedule, aux_schedule) = (air.main_openings(), air.aux_openings());
+
+    // Dividing by Z_H / prod_j (X - p_j) is multiplying 1 / Z_H by the punctured roots
+    let puncture_factors: Option<Vec<Val<SC>>> = (!punctures.is_empty()).then(|| {
+        core::iter::successors(Some(quotient_domain.first_point()), |&x| {
+            quotient_domain.next_point(x)
+        })
+        .take(quotient_size)
+        .map(|x| punctures.iter().map(|&p| x - p).product())
+        .collect()
+    });
 
-    // Compute selectors
-    let selectors = trace_domain.selectors_on_coset(quotient_domain);
+    // Statement selectors, when the trace encodes several statements
+    let statement_values: Option<Vec<StatementValues<Val<SC>>>> = statements.map(|boundaries| {
+        core::iter::successors(Some(quotient_domain.first_point()), |&x| {
+            quotient_domain.next_point(x)
+        })
+        .take(quotient_size)
+        .map(|x| boundaries.values_at(x, trace_domain.vanishing_poly_at_point(x), public_values))
+        .collect()
+    });
 
-    // Calculate step size between consecutive trace points in quotient domain LDE
     // quotient_domain is quotient_degree times larger than trace_domain
     let log_quotient_degree =
         p3_util::log2_strict_usize(quotient_size) - p3_util::log2_strict_usize(trace_domain.size());
-    let next_step = 1 << log_quotient_degree;
 
     // Evaluate constraints at each point in quotient domain
     // For simplicity, we'll do this in a single-threaded manner
     // TODO: Add parallel evaluation
     let mut quotient_values = Vec::with_capacity(quotient_size);
 
-    // First pass: count constraints by doing a dry run on first point
-    let main_local: Vec<_> = main_on_quotient.row_slice(0).unwrap().to_vec();
-    let main_next: Vec<_> = main_on_quotient
-        .row_slice(next_step % quotient_size)
-        .unwrap()
-        .to_vec();
-    let main_view =
-        p3_matrix::dense::RowMajorMatrix::new([main_local, main_next].concat(), width_main);
-    let aux_view = p3_matrix::dense::RowMajorMatrix::new(vec![], 0);
-
-    // Create dummy alpha powers for counting (won't be used, just need something)
-    let dummy_alpha_powers = vec![SC::Challenge::ZERO; 100];
-    let mut constraint_counter = ProverFolder {
-        main: main_view.as_view(),
-        aux: aux_view.as_view(),
-        is_first_row: selectors.is_first_row[0],
-        is_last_row: selectors.is_last_row[0],
-        is_transition: selectors.is_transition[0],
-        alpha_powers: &dummy_alpha_powers,
-        accumulator: SC::Challenge::ZERO,
-        constraint_index: 0,
-    };
-    air.eval(&mut constraint_counter);
-    let constraint_count = constraint_counter.constraint_index;
-
-    // Compute exact number of alpha powers and reverse
-    let mut alpha_powers: Vec<Challenge<SC>> = Vec::with_capacity(constraint_count);
-    let mut power = SC::Challenge::ONE;
-    for _ in 0..constraint_count {
-        alpha_powers.push(power);
-        power *= alpha;
-    }
-    alpha_powers.reverse();
-
     for i in 0..quotient_size {
         let is_first_row = selectors.is_first_row[i];
         let is_last_row = selectors.is_last_row[i];
         let is_transition = selectors.is_transition[i];
-        let inv_vanishing = selectors.inv_vanishing[i];
+        let inv_vanishing = match &puncture_factors {
+            Some(factors) => selectors.inv_vanishing[i] * factors[i],
+            None => selectors.inv_vanishing[i],
+        };
+        let statements = statement_values.as_ref().map_or(
+            StatementSelectors {
+                is_first_row,
+                is_last_row,
+                is_transition,
+                public_values,
+            },
+            |values| values[i].selectors(),
+        );
 
         // Get local and next row values
-        // Next row is next_step away, not just i+1, because quotient domain LDE
+        // Next row is a trace step away, not just i+1, because quotient domain LDE
         // interleaves trace points with intermediate evaluation points
-        let main_local: Vec<_> = main_on_quotient.row_slice(i).unwrap().to_vec();
-        let main_next_idx = (i + next_step) % quotient_size;
-        let main_next: Vec<_> = main_on_quotient.row_slice(main_next_idx).unwrap().to_vec();
+        // Traces not opened at ζ·g only get a one-row window, as for the verifier
+        let row = QuotientRow(i);
+        let next = row.next_trace_step(log_quotient_degree, quotient_size);
+        let main_rows: &[usize] = if main_schedule.next_row {
+            &[row.0, next.0]
+        } else {
+            &[row.0]
+        };
+        let aux_rows: &[usize] = if aux_schedule.next_row {
+            &[row.0, next.0]
+        } else {
+            &[row.0]
+        };
+        // The previous row is as far back as the next row is ahead
+        let prev = row.prev_trace_step(log_quotient_degree, quotient_size);
+        let main_prev_rows: &[usize] = if main_schedule.prev_row {
+            &[prev.0]
+        } else {
+            &[]
+        };
+        let aux_prev_rows: &[usize] = if aux_schedule.prev_row {
+            &[prev.0]
+        } else {
+            &[]
+        };
+
+        let main_values = |rows: &[usize]| -> Vec<Val<SC>> {
+            rows.iter()
+                .flat_map(|&row| {
+                    main_on_quotient
+                        .iter()
+                        .flat_map(move |batch| batch.row_slice(row).unwrap().to_vec())
+                })
+                .collect()
+        };
+        let main_view = RowMajorMatrix::new(main_values(main_rows), width_main);
+        let main_prev_row = main_values(main_prev_rows);
+
+        // Recombine each extension-field column from its flattened base-field columns
+        let aux_values = |rows: &[usize]| -> Vec<Challenge<SC>> {
+            aux_on_quotient.map_or_else(Vec::new, |aux| {
+                rows.iter()
+                    .flat_map(|&row| {
+                        recombine_extension::<Val<SC>, Challenge<SC>>(
+                            &aux.row_slice(row).unwrap()[..aux_ext_width],
+                        )
+                    })
+                    .collect()
+            })
+        };
+        let aux_view = if aux_on_quotient.is_some() {
+            RowMajorMatrix::new(aux_values(aux_rows), aux_width)
+        } else {
+            RowMajorMatrix::new(vec![], 0)
+        };
+        let aux_prev_row = aux_values(aux_prev_rows);
 
-        let main_view =
-            p3_matrix::dense::RowMajorMatrix::new([main_local, main_next].concat(), width_main);
+        // Challenge-free columns come first, from their own commitment
+        let aux_base_values = |rows: &[usize]| -> Vec<Val<SC>> {
+            rows.iter()
+                .flat_map(|&row| {
+                    let free = challenge_free_on_quotient
+                        .map(|free| free.row_slice(row).unwrap().to_vec())
+                        .unwrap_or_default();
+                    let dependent = aux_on_quotient
+                        .map(|aux| aux.row_slice(row).unwrap()[aux_ext_width..].to_vec())
+                        .unwrap_or_default();
+                    [free, dependent].concat()
+                })
+                .collect()
+        };
+        let aux_base_view = RowMajorMatrix::new(aux_base_values(aux_rows), air.aux_base_width());
+        let aux_base_prev_row = aux_base_values(aux_prev_rows);
 
-        // TODO: Implement proper aux trace handling
-        // For now, use empty aux view
-        let aux_view = p3_matrix::dense::RowMajorMatrix::new(vec![], 0);
+        // Further rotations are as many trace steps away as their offset
+        let rotated = |offset| {
+            row.rotated_trace_step(offset, log_quotient_degree, quotient_size)
+                .0
+        };
+        let main_rotated: Vec<_> = main_schedule
+            .rotations
+            .iter()
+            .map(|offset| main_values(&[rotated(offset)]))
+            .collect();
+        let aux_rotated: Vec<_> = aux_schedule
+            .rotations
+            .iter()
+            .map(|offset| aux_values(&[rotated(offset)]))
+            .collect();
+        let aux_base_rotated: Vec<_> = aux_schedule
+            .rotations
+            .iter()
+            .map(|offset| aux_base_values(&[rotated(offset)]))
+            .collect();
 
         // Evaluate constraints
         let mut folder = ProverFolder {
             main: main_view.as_view(),
             aux: aux_view.as_view(),
+            aux_base: aux_base_view.as_view(),
+            challenges,
+            aux_exposed_values,
+            aux_prev_row: &aux_prev_row,
+            aux_base_prev_row: &aux_base_prev_row,
+            main_prev_row: &main_prev_row,
+            aux_rotated: RotatedRows {
+                offsets: aux_schedule.rotations,
+                rows: &aux_rotated,
+            },
+            aux_base_rotated: RotatedRows {
+                offsets: aux_schedule.rotations,
+                rows: &aux_base_rotated,
+            },
+            main_rotated: RotatedRows {
+                offsets: main_schedule.rotations,
+                rows: &main_rotated,
+            },
+            aux_last_row,
+            aux_base_last_row,
+            main_last_row,
+            public_values,
             is_first_row,
             is_last_row,
             is_transition,
-            alpha_powers: &alpha_powers,
+            statements,
+            alpha_powers,
             accumulator: SC::Challenge::ZERO,
             constraint_index: 0,
         };
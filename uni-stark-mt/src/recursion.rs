@@ -0,0 +1,482 @@
+//! Constraint folding over symbolic values, for in-circuit verification
+//!
+//! [`VerifierFolder`](crate::VerifierFolder) evaluates an AIR on concrete opened values.
+//! [`RecursiveVerifierFolder`] performs the same Horner fold, but every opened value,
+//! selector and the combination challenge are variables/expressions of some target
+//! builder (e.g. the extension-field variables of a recursion AIR). The resulting
+//! accumulator is the verifier's constraint sum `C(ζ)`, expressed in the target
+//! builder, which can then be constrained against the recombined quotient.
+
+use core::marker::PhantomData;
+use core::ops::{Add, Mul, Sub};
+
+use p3_air::{AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PermutationAirBuilder};
+use p3_commit::LagrangeSelectors;
+use p3_field::{Algebra, ExtensionField, Field};
+
+use crate::{
+    AuxBuilder, ChallengeSet, OpeningSchedule, RotatedRows, StatementBuilder, StatementSelectors,
+    VerifierView,
+};
+
+/// Variable type usable by [`RecursiveVerifierFolder`].
+///
+/// These are exactly the arithmetic requirements [`AirBuilder::Var`] places on a
+/// variable whose expressions are `Expr` over the field `F`.
+pub trait RecursiveVar<F, Expr>:
+    Into<Expr>
+    + Copy
+    + Send
+    + Sync
+    + Add<F, Output = Expr>
+    + Add<Self, Output = Expr>
+    + Add<Expr, Output = Expr>
+    + Sub<F, Output = Expr>
+    + Sub<Self, Output = Expr>
+    + Sub<Expr, Output = Expr>
+    + Mul<F, Output = Expr>
+    + Mul<Self, Output = Expr>
+    + Mul<Expr, Output = Expr>
+{
+}
+
+impl<F, Expr, T> RecursiveVar<F, Expr> for T where
+    T: Into<Expr>
+        + Copy
+        + Send
+        + Sync
+        + Add<F, Output = Expr>
+        + Add<T, Output = Expr>
+        + Add<Expr, Output = Expr>
+        + Sub<F, Output = Expr>
+        + Sub<T, Output = Expr>
+        + Sub<Expr, Output = Expr>
+        + Mul<F, Output = Expr>
+        + Mul<T, Output = Expr>
+        + Mul<Expr, Output = Expr>
+{
+}
+
+/// Verifier folder whose values are variables of a target builder.
+///
+/// `F`/`EF` are the base and extension fields of the proof being verified. `Var` and
+/// `Expr` are the target builder's representations of extension-field elements: every
+/// opened value and public value is a `Var`, and selectors, α and the accumulator are
+/// `Expr`s.
+pub struct RecursiveVerifierFolder<'a, F, EF, Var, Expr> {
+    /// Main trace values (local row)
+    pub main_local: &'a [Var],
+
+    /// Main trace values (next row)
+    pub main_next: &'a [Var],
+
+    /// Auxiliary trace values (local row), one per extension-field column
+    ///
+    /// See [`recompose_aux_openings`](crate::recompose_aux_openings).
+    pub aux_local: &'a [Var],
+
+    /// Auxiliary trace values (next row), one per extension-field column
+    pub aux_next: &'a [Var],
+
+    /// Base-field auxiliary trace values (local row)
+    pub aux_base_local: &'a [Var],
+
+    /// Base-field auxiliary trace values (next row)
+    pub aux_base_next: &'a [Var],
+
+    /// Challenges sampled for the auxiliary phase
+    pub challenges: ChallengeSet<'a, Var>,
+
+    /// Values exposed by the auxiliary phase
+    pub aux_exposed_values: &'a [Var],
+
+    /// Auxiliary trace values on the previous row, one per extension-field column, if
+    /// the AIR opens them
+    pub aux_prev_row: &'a [Var],
+
+    /// Base-field auxiliary trace values on the previous row, if the AIR opens them
+    pub aux_base_prev_row: &'a [Var],
+
+    /// Main trace values on the previous row, if the AIR opens them
+    pub main_prev_row: &'a [Var],
+
+    /// Auxiliary trace values on the rows of the AIR's further rotations, one per
+    /// extension-field column
+    pub aux_rotated: RotatedRows<'a, Var>,
+
+    /// Base-field auxiliary trace values on the rows of the AIR's further rotations
+    pub aux_base_rotated: RotatedRows<'a, Var>,
+
+    /// Main trace values on the rows of the AIR's further rotations
+    pub main_rotated: RotatedRows<'a, Var>,
+
+    /// Auxiliary trace values on the last row, one per extension-field column, if the
+    /// AIR opens them
+    pub aux_last_row: &'a [Var],
+
+    /// Base-field auxiliary trace values on the last row, if the AIR opens them
+    pub aux_base_last_row: &'a [Var],
+
+    /// Main trace values on the last row, if the AIR opens them
+    pub main_last_row: &'a [Var],
+
+    /// Points the main trace was opened at
+    pub main_openings: OpeningSchedule,
+
+    /// Points the auxiliary trace was opened at
+    pub aux_openings: OpeningSchedule,
+
+    /// Public input/output values
+    pub public_values: &'a [Var],
+
+    /// Selector: 1 on first row, 0 elsewhere
+    pub is_first_row: Expr,
+
+    /// Selector: 1 on last row, 0 elsewhere
+    pub is_last_row: Expr,
+
+    /// Selector: 1 on all rows except last, 0 on last
+    pub is_transition: Expr,
+
+    /// Statement boundary selectors and public values
+    pub statements: StatementSelectors<'a, Expr>,
+
+    /// Randomness for combining constraints
+    pub alpha: Expr,
+
+    /// Accumulated constraint value
+    pub accumulator: Expr,
+
+    _phantom: PhantomData<(F, EF)>,
+}
+
+impl<'a, F, EF, Var, Expr> RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    Expr: Default + Clone,
+{
+    /// Create a folder over opened values, selectors at ζ and the combination challenge.
+    ///
+    /// The `inv_vanishing` selector is not needed for folding and is ignored. The folder
+    /// has no base-field auxiliary columns, challenges, exposed values, previous-row,
+    /// rotated-row or last-row openings; add them with [`Self::with_aux_base`],
+    /// [`Self::with_challenges`], [`Self::with_aux_exposed_values`],
+    /// [`Self::with_prev_rows`], [`Self::with_rotated_rows`] and
+    /// [`Self::with_last_rows`]. Both traces are
+    /// taken to be opened at ζ and ζ·g; change that with [`Self::with_openings`]. Its
+    /// statement selectors are the trace's, with no
+    /// statement public values; set them with [`Self::with_statements`].
+    pub fn new(
+        main_local: &'a [Var],
+        main_next: &'a [Var],
+        aux_local: &'a [Var],
+        aux_next: &'a [Var],
+        public_values: &'a [Var],
+        selectors: LagrangeSelectors<Expr>,
+        alpha: Expr,
+    ) -> Self {
+        Self {
+            main_local,
+            main_next,
+            aux_local,
+            aux_next,
+            aux_base_local: &[],
+            aux_base_next: &[],
+            challenges: ChallengeSet::empty(),
+            aux_exposed_values: &[],
+            aux_prev_row: &[],
+            aux_base_prev_row: &[],
+            main_prev_row: &[],
+            aux_rotated: RotatedRows::empty(),
+            aux_base_rotated: RotatedRows::empty(),
+            main_rotated: RotatedRows::empty(),
+            aux_last_row: &[],
+            aux_base_last_row: &[],
+            main_last_row: &[],
+            main_openings: OpeningSchedule::TRANSITION,
+            aux_openings: OpeningSchedule::TRANSITION,
+            public_values,
+            statements: StatementSelectors {
+                is_first_row: selectors.is_first_row.clone(),
+                is_last_row: selectors.is_last_row.clone(),
+                is_transition: selectors.is_transition.clone(),
+                public_values: &[],
+            },
+            is_first_row: selectors.is_first_row,
+            is_last_row: selectors.is_last_row,
+            is_transition: selectors.is_transition,
+            alpha,
+            accumulator: Expr::default(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set the opened values of the base-field auxiliary columns.
+    #[must_use]
+    pub fn with_aux_base(mut self, aux_base_local: &'a [Var], aux_base_next: &'a [Var]) -> Self {
+        self.aux_base_local = aux_base_local;
+        self.aux_base_next = aux_base_next;
+        self
+    }
+
+    /// Set the challenges of the auxiliary phase, as variables of the target builder.
+    #[must_use]
+    pub fn with_challenges(mut self, challenges: ChallengeSet<'a, Var>) -> Self {
+        self.challenges = challenges;
+        self
+    }
+
+    /// Set the values exposed by the auxiliary phase.
+    #[must_use]
+    pub fn with_aux_exposed_values(mut self, aux_exposed_values: &'a [Var]) -> Self {
+        self.aux_exposed_values = aux_exposed_values;
+        self
+    }
+
+    /// Set the trace values opened at the previous row, see [`OpeningSchedule::prev_row`].
+    #[must_use]
+    pub fn with_prev_rows(
+        mut self,
+        main_prev_row: &'a [Var],
+        aux_prev_row: &'a [Var],
+        aux_base_prev_row: &'a [Var],
+    ) -> Self {
+        self.main_prev_row = main_prev_row;
+        self.aux_prev_row = aux_prev_row;
+        self.aux_base_prev_row = aux_base_prev_row;
+        self
+    }
+
+    /// Set the trace values opened at further rotations, see
+    /// [`OpeningSchedule::rotations`].
+    #[must_use]
+    pub fn with_rotated_rows(
+        mut self,
+        main_rotated: RotatedRows<'a, Var>,
+        aux_rotated: RotatedRows<'a, Var>,
+        aux_base_rotated: RotatedRows<'a, Var>,
+    ) -> Self {
+        self.main_rotated = main_rotated;
+        self.aux_rotated = aux_rotated;
+        self.aux_base_rotated = aux_base_rotated;
+        self
+    }
+
+    /// Set the trace values opened at the last row, see [`OpeningSchedule::last_row`].
+    #[must_use]
+    pub fn with_last_rows(
+        mut self,
+        main_last_row: &'a [Var],
+        aux_last_row: &'a [Var],
+        aux_base_last_row: &'a [Var],
+    ) -> Self {
+        self.main_last_row = main_last_row;
+        self.aux_last_row = aux_last_row;
+        self.aux_base_last_row = aux_base_last_row;
+        self
+    }
+
+    /// Set the points the main and auxiliary traces were opened at.
+    #[must_use]
+    pub fn with_openings(
+        mut self,
+        main_openings: OpeningSchedule,
+        aux_openings: OpeningSchedule,
+    ) -> Self {
+        self.main_openings = main_openings;
+        self.aux_openings = aux_openings;
+        self
+    }
+
+    /// Set the statement selectors and public values at ζ, as expressions of the target
+    /// builder.
+    #[must_use]
+    pub fn with_statements(mut self, statements: StatementSelectors<'a, Expr>) -> Self {
+        self.statements = statements;
+        self
+    }
+
+    fn fold(&mut self, x: Expr)
+    where
+        Expr: Clone + Add<Output = Expr> + Mul<Output = Expr>,
+    {
+        let accumulator = core::mem::take(&mut self.accumulator);
+        self.accumulator = accumulator * self.alpha.clone() + x;
+    }
+}
+
+impl<'a, F, EF, Var, Expr> AirBuilder for RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Expr: Algebra<F> + Algebra<EF> + Algebra<Var>,
+    Var: RecursiveVar<F, Expr>,
+{
+    type F = F;
+    type Expr = Expr;
+    type Var = Var;
+    type M = VerifierView<'a, Var>;
+
+    fn main(&self) -> Self::M {
+        VerifierView::scheduled(self.main_local, self.main_next, self.main_openings.next_row)
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.is_first_row.clone()
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.is_last_row.clone()
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        assert_eq!(size, 2, "Only window size 2 is supported");
+        self.is_transition.clone()
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.fold(x.into());
+    }
+}
+
+impl<'a, F, EF, Var, Expr> AirBuilderWithPublicValues
+    for RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Expr: Algebra<F> + Algebra<EF> + Algebra<Var>,
+    Var: RecursiveVar<F, Expr>,
+{
+    type PublicVar = Var;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+impl<'a, F, EF, Var, Expr> StatementBuilder for RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Expr: Algebra<F> + Algebra<EF> + Algebra<Var>,
+    Var: RecursiveVar<F, Expr>,
+{
+    fn is_first_statement_row(&self) -> Self::Expr {
+        self.statements.is_first_row.clone()
+    }
+
+    fn is_last_statement_row(&self) -> Self::Expr {
+        self.statements.is_last_row.clone()
+    }
+
+    fn is_statement_transition(&self) -> Self::Expr {
+        self.statements.is_transition.clone()
+    }
+
+    fn statement_public_values(&self) -> &[Self::Expr] {
+        self.statements.public_values
+    }
+}
+
+impl<'a, F, EF, Var, Expr> ExtensionBuilder for RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Expr: Algebra<F> + Algebra<EF> + Algebra<Var>,
+    Var: RecursiveVar<F, Expr>,
+{
+    type EF = EF;
+    type ExprEF = Expr;
+    type VarEF = Var;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.fold(x.into());
+    }
+}
+
+impl<'a, F, EF, Var, Expr> PermutationAirBuilder for RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Expr: Algebra<F> + Algebra<EF> + Algebra<Var>,
+    Var: RecursiveVar<F, Expr>,
+{
+    type MP = VerifierView<'a, Var>;
+    type RandomVar = Self::VarEF;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.challenges()
+    }
+}
+
+impl<'a, F, EF, Var, Expr> AuxBuilder for RecursiveVerifierFolder<'a, F, EF, Var, Expr>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Expr: Algebra<F> + Algebra<EF> + Algebra<Var>,
+    Var: RecursiveVar<F, Expr>,
+{
+    type MAux = VerifierView<'a, Var>;
+
+    fn aux(&self) -> Self::MAux {
+        VerifierView::scheduled(self.aux_local, self.aux_next, self.aux_openings.next_row)
+    }
+
+    fn aux_base(&self) -> Self::M {
+        VerifierView::scheduled(
+            self.aux_base_local,
+            self.aux_base_next,
+            self.aux_openings.next_row,
+        )
+    }
+
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF> {
+        self.challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
+
+    fn aux_prev_row(&self) -> &[Self::VarEF] {
+        self.aux_prev_row
+    }
+
+    fn aux_base_prev_row(&self) -> &[Self::Var] {
+        self.aux_base_prev_row
+    }
+
+    fn main_prev_row(&self) -> &[Self::Var] {
+        self.main_prev_row
+    }
+
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF] {
+        self.aux_rotated.row(offset)
+    }
+
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.aux_base_rotated.row(offset)
+    }
+
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.main_rotated.row(offset)
+    }
+
+    fn aux_last_row(&self) -> &[Self::VarEF] {
+        self.aux_last_row
+    }
+
+    fn aux_base_last_row(&self) -> &[Self::Var] {
+        self.aux_base_last_row
+    }
+
+    fn main_last_row(&self) -> &[Self::Var] {
+        self.main_last_row
+    }
+}
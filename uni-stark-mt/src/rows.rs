@@ -0,0 +1,134 @@
+//! Row indices of the prover's evaluation domains
+//!
+//! The prover indexes rows of three domains: the trace domain, the quotient domain it
+//! evaluates constraints over, and the LDE domains the PCS commits to, in the PCS's row
+//! ordering. Mixing them up gives wrong results rather than errors, so each has its own
+//! index type, and moving between them goes through the conversions here.
+
+use p3_matrix::dense::RowMajorMatrix;
+use p3_util::reverse_bits_len;
+
+/// Row ordering of a low-degree extension.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LdeOrdering {
+    /// Row `i` holds the evaluation at the `i`-th point of the LDE coset
+    Natural,
+    /// Rows are permuted by bit-reversing their index
+    BitReversed,
+}
+
+/// A row of the trace domain, `0..height`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TraceRow(pub usize);
+
+/// A row of the quotient domain, a coset `2^log_quotient_degree` times larger than the
+/// trace domain, in natural order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QuotientRow(pub usize);
+
+/// A row of a committed low-degree extension, in the PCS's [`LdeOrdering`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LdeRow(pub usize);
+
+impl TraceRow {
+    /// The last row of a trace of `height` rows.
+    ///
+    /// # Panics
+    /// If `height` is 0.
+    pub const fn last(height: usize) -> Self {
+        assert!(height > 0, "an empty trace has no last row");
+        Self(height - 1)
+    }
+
+    /// The next row of a trace of `height` rows, wrapping from the last row to the first.
+    pub const fn next(self, height: usize) -> Self {
+        Self((self.0 + 1) % height)
+    }
+
+    /// The previous row of a trace of `height` rows, wrapping from the first row to the
+    /// last.
+    pub const fn prev(self, height: usize) -> Self {
+        Self((self.0 + height - 1) % height)
+    }
+
+    /// The row `offset` rows after this one, before it if negative, in a trace of
+    /// `height` rows, wrapping around.
+    pub const fn rotate(self, offset: isize, height: usize) -> Self {
+        Self((self.0 as isize + offset).rem_euclid(height as isize) as usize)
+    }
+
+    /// The quotient domain row at the same power of the domain generators.
+    pub const fn to_quotient(self, log_quotient_degree: usize) -> QuotientRow {
+        QuotientRow(self.0 << log_quotient_degree)
+    }
+
+    /// The row of an LDE by `2^log_blowup` of a trace of `2^log_height` rows at the same
+    /// power of the domain generators, in `ordering`.
+    pub fn to_lde(self, log_height: usize, log_blowup: usize, ordering: LdeOrdering) -> LdeRow {
+        LdeRow::from_natural(self.0 << log_blowup, log_height + log_blowup, ordering)
+    }
+}
+
+impl QuotientRow {
+    /// The row one trace row later, at `2^log_quotient_degree` quotient rows, wrapping
+    /// around a quotient domain of `quotient_size` rows.
+    ///
+    /// This is where the constraints' next row is read on the quotient domain: the
+    /// quotient domain interleaves `2^log_quotient_degree - 1` points between consecutive
+    /// trace steps.
+    pub const fn next_trace_step(self, log_quotient_degree: usize, quotient_size: usize) -> Self {
+        Self((self.0 + (1 << log_quotient_degree)) % quotient_size)
+    }
+
+    /// The row one trace row earlier, wrapping around a quotient domain of
+    /// `quotient_size` rows, where the constraints' previous row is read.
+    pub const fn prev_trace_step(self, log_quotient_degree: usize, quotient_size: usize) -> Self {
+        Self((self.0 + quotient_size - (1 << log_quotient_degree)) % quotient_size)
+    }
+
+    /// The row `offset` trace rows later, earlier if negative, wrapping around a quotient
+    /// domain of `quotient_size` rows, where the constraints' rotated rows are read.
+    pub const fn rotated_trace_step(
+        self,
+        offset: isize,
+        log_quotient_degree: usize,
+        quotient_size: usize,
+    ) -> Self {
+        let shift = offset * (1 << log_quotient_degree);
+        Self((self.0 as isize + shift).rem_euclid(quotient_size as isize) as usize)
+    }
+
+    /// The trace row at the same power of the domain generators, if the row lies on one.
+    pub const fn to_trace(self, log_quotient_degree: usize) -> Option<TraceRow> {
+        if self.0 % (1 << log_quotient_degree) == 0 {
+            Some(TraceRow(self.0 >> log_quotient_degree))
+        } else {
+            None
+        }
+    }
+}
+
+impl LdeRow {
+    /// The row of a `2^log_lde_height`-row LDE in `ordering` holding the evaluation at
+    /// the `natural`-th point of the LDE domain.
+    pub fn from_natural(natural: usize, log_lde_height: usize, ordering: LdeOrdering) -> Self {
+        match ordering {
+            LdeOrdering::Natural => Self(natural),
+            LdeOrdering::BitReversed => Self(reverse_bits_len(natural, log_lde_height)),
+        }
+    }
+
+    /// Index of this row's point in the LDE domain, undoing `ordering`.
+    pub fn to_natural(self, log_lde_height: usize, ordering: LdeOrdering) -> usize {
+        match ordering {
+            LdeOrdering::Natural => self.0,
+            // Bit reversal is an involution
+            LdeOrdering::BitReversed => reverse_bits_len(self.0, log_lde_height),
+        }
+    }
+}
+
+/// The values of `trace` on `row`.
+pub(crate) fn trace_row<T>(trace: &RowMajorMatrix<T>, row: TraceRow) -> &[T] {
+    &trace.values[row.0 * trace.width..(row.0 + 1) * trace.width]
+}
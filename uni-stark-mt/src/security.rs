@@ -0,0 +1,207 @@
+//! Soundness estimates for a configuration
+//!
+//! A proof's soundness error is dominated by the FRI queries, and bounded below by the
+//! size of the challenge field the out-of-domain points and folding challenges are drawn
+//! from. [`SecurityParameters`] collects what these depend on, and
+//! [`SecurityParameters::security_level`] turns it into bits of security, under the
+//! usual conjecture and under what is proven.
+//!
+//! [`StarkConfig::validate`](crate::StarkConfig::validate) checks a configuration against
+//! a [`SecurityTarget`], and explains a shortfall with the [`SecurityBound`] at fault.
+//!
+//! The estimates follow the ethSTARK analysis without its lower-order terms: they are
+//! meant to compare configurations and answer audits, not to replace a careful analysis
+//! of a deployment.
+
+use serde::{Deserialize, Serialize};
+
+use crate::prover::LOG_QUOTIENT_DEGREE;
+
+/// The parameters of a configuration its soundness depends on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityParameters {
+    /// log2 of the FRI blowup factor
+    pub log_blowup: usize,
+
+    /// Number of FRI queries
+    pub num_queries: usize,
+
+    /// Proof-of-work bits FRI grinds before drawing its queries
+    pub query_pow_bits: usize,
+
+    /// Proof-of-work bits ground before the out-of-domain points, see
+    /// [`StarkGenericConfig::pow_bits`](crate::StarkGenericConfig::pow_bits)
+    ///
+    /// [`StarkConfig::security_bits`](crate::StarkConfig::security_bits) takes them from
+    /// the configuration.
+    pub ood_pow_bits: usize,
+
+    /// log2 of the size of the challenge field, rounded down
+    pub log_challenge_field_size: usize,
+}
+
+/// Bits of security of a configuration, see [`SecurityParameters::security_level`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityLevel {
+    /// Bits of security under the ethSTARK conjecture that each FRI query catches a far
+    /// codeword with probability `1 - ρ`, for the code rate `ρ`
+    pub conjectured: usize,
+
+    /// Bits of security proven up to the Johnson bound, where each query only catches a
+    /// far codeword with probability `1 - √ρ`
+    pub proven: usize,
+}
+
+/// Security level a configuration must reach, see
+/// [`StarkConfig::validate`](crate::StarkConfig::validate).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecurityTarget {
+    /// At least this many bits of [conjectured](SecurityLevel::conjectured) security
+    Bits(usize),
+    /// At least this many bits of [proven](SecurityLevel::proven) security
+    ProvenBits(usize),
+}
+
+/// A term a [`SecurityLevel`] is the smallest of, see
+/// [`SecurityParameters::security_level`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SecurityBound {
+    /// The FRI queries, raised by a larger blowup, more queries or query grinding
+    Queries,
+    /// The FRI folding challenges, raised by a larger challenge field
+    FoldingChallenges,
+    /// The out-of-domain points, raised by more points, grinding or a larger challenge
+    /// field
+    OodPoints,
+}
+
+/// Why a configuration doesn't reach a [`SecurityTarget`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SecurityError {
+    /// The configuration doesn't record its
+    /// [`SecurityParameters`], so its security can't be estimated
+    UnknownParameters,
+    /// The configuration falls short of the target
+    Insufficient {
+        /// The target missed
+        target: SecurityTarget,
+        /// Bits of security the configuration reaches, of the kind the target asks for
+        achieved: usize,
+        /// The term that limits the configuration to `achieved` bits
+        limited_by: SecurityBound,
+    },
+}
+
+impl core::fmt::Display for SecurityBound {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Queries => "the FRI queries (raise the blowup, queries or grinding)",
+            Self::FoldingChallenges => "the FRI folding challenges (use a larger field)",
+            Self::OodPoints => {
+                "the out-of-domain points (add points or grinding, or use a larger field)"
+            }
+        })
+    }
+}
+
+impl core::fmt::Display for SecurityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownParameters => {
+                f.write_str("the configuration doesn't record its security parameters")
+            }
+            Self::Insufficient {
+                target,
+                achieved,
+                limited_by,
+            } => {
+                let (kind, bits) = match target {
+                    SecurityTarget::Bits(bits) => ("conjectured", bits),
+                    SecurityTarget::ProvenBits(bits) => ("proven", bits),
+                };
+                write!(
+                    f,
+                    "{achieved} bits of {kind} security, short of {bits}, limited by {limited_by}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SecurityError {}
+
+impl SecurityParameters {
+    /// Bits of security of proofs of traces at most `2^log_max_height` rows tall,
+    /// checked at `num_ood_points` out-of-domain points.
+    ///
+    /// Each level is the smallest of three terms, see [`SecurityBound`]:
+    /// - the queries: `log_blowup` bits per query under the conjecture, half as many
+    ///   proven, plus the query grinding bits;
+    /// - the FRI folding challenges, which err with probability about the LDE size over
+    ///   the field size under the conjecture, and its square over the field size proven;
+    /// - the out-of-domain points, each erring with probability about the quotient's
+    ///   degree over the field size, plus the out-of-domain grinding bits.
+    pub fn security_level(&self, log_max_height: usize, num_ood_points: usize) -> SecurityLevel {
+        let level = |proven| self.weakest_bound(log_max_height, num_ood_points, proven).1;
+        SecurityLevel {
+            conjectured: level(false),
+            proven: level(true),
+        }
+    }
+
+    /// Check proofs of traces at most `2^log_max_height` rows tall, checked at
+    /// `num_ood_points` out-of-domain points, reach `target`.
+    ///
+    /// # Errors
+    /// [`SecurityError::Insufficient`] with the term at fault if they don't.
+    pub fn validate(
+        &self,
+        log_max_height: usize,
+        num_ood_points: usize,
+        target: SecurityTarget,
+    ) -> Result<SecurityLevel, SecurityError> {
+        let (proven, bits) = match target {
+            SecurityTarget::Bits(bits) => (false, bits),
+            SecurityTarget::ProvenBits(bits) => (true, bits),
+        };
+        let (limited_by, achieved) = self.weakest_bound(log_max_height, num_ood_points, proven);
+        if achieved < bits {
+            return Err(SecurityError::Insufficient {
+                target,
+                achieved,
+                limited_by,
+            });
+        }
+        Ok(self.security_level(log_max_height, num_ood_points))
+    }
+
+    /// The smallest term of the conjectured or `proven` level, and its bits.
+    fn weakest_bound(
+        &self,
+        log_max_height: usize,
+        num_ood_points: usize,
+        proven: bool,
+    ) -> (SecurityBound, usize) {
+        let field = self.log_challenge_field_size;
+        let log_lde_size = log_max_height + self.log_blowup;
+        let (query_bits, log_folding_error) = if proven {
+            (self.num_queries * self.log_blowup / 2, 2 * log_lde_size)
+        } else {
+            (self.num_queries * self.log_blowup, log_lde_size)
+        };
+        let ood = num_ood_points * field.saturating_sub(log_max_height + LOG_QUOTIENT_DEGREE)
+            + self.ood_pow_bits;
+        [
+            (SecurityBound::Queries, query_bits + self.query_pow_bits),
+            (
+                SecurityBound::FoldingChallenges,
+                field.saturating_sub(log_folding_error),
+            ),
+            (SecurityBound::OodPoints, ood),
+        ]
+        .into_iter()
+        .min_by_key(|&(_, bits)| bits)
+        .expect("there are three bounds")
+    }
+}
@@ -0,0 +1,223 @@
+//! Proving long executions in shards
+//!
+//! An execution too long for one trace is split into shards, consecutive pieces of the
+//! execution each proven on its own, possibly in parallel or on other machines. Each
+//! shard's public values carry a digest of the state the shard starts from and of the one
+//! it ends in, where its AIR declares them with
+//! [`AirLayout::state_digests`](crate::AirLayout::state_digests).
+//! [`verify_chain`] verifies every shard and checks each one starts in the state the
+//! previous one ended in, returning the states the whole execution starts from and ends
+//! in for the application to check.
+//!
+//! A digest must cover everything a shard's constraints rely on from the previous one,
+//! e.g. the program counter, registers, a memory root and a clock: shards are only as
+//! chained as their digests.
+
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_field::PackedField;
+use p3_matrix::dense::RowMajorMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    keygen, prove, verify_with_vk, Challenge, DebugConstraintBuilder, Domain, MultiTraceAir, Proof,
+    ProverError, ProverFolder, StarkGenericConfig, TraceDomain, Val, VerificationError,
+    VerifierFolder,
+};
+
+/// Position of a shard's state digests among its public values, see
+/// [`AirLayout::state_digests`](crate::AirLayout::state_digests).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDigests {
+    /// Index of the first public value of the digest of the state the shard starts from
+    pub input_offset: usize,
+
+    /// Index of the first public value of the digest of the state the shard ends in
+    pub output_offset: usize,
+
+    /// Number of public values in each digest
+    pub len: usize,
+}
+
+impl StateDigests {
+    /// The digest of the state the shard starts from, if it lies within `public_values`.
+    pub fn input_digest<'a, F>(&self, public_values: &'a [F]) -> Option<&'a [F]> {
+        public_values.get(self.input_offset..self.input_offset.checked_add(self.len)?)
+    }
+
+    /// The digest of the state the shard ends in, if it lies within `public_values`.
+    pub fn output_digest<'a, F>(&self, public_values: &'a [F]) -> Option<&'a [F]> {
+        public_values.get(self.output_offset..self.output_offset.checked_add(self.len)?)
+    }
+}
+
+/// A shard's proof, with the public values it was proven with.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ShardProof<SC: StarkGenericConfig> {
+    /// The shard's proof
+    pub proof: Proof<SC>,
+
+    /// The shard's public values, its state digests among them
+    pub public_values: Vec<Val<SC>>,
+}
+
+impl<SC: StarkGenericConfig> Clone for ShardProof<SC> {
+    fn clone(&self) -> Self {
+        Self {
+            proof: self.proof.clone(),
+            public_values: self.public_values.clone(),
+        }
+    }
+}
+
+/// States a chain of shards starts from and ends in, see [`verify_chain`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainStates<F> {
+    /// Digest of the state the first shard starts from
+    pub input: Vec<F>,
+
+    /// Digest of the state the last shard ends in
+    pub output: Vec<F>,
+}
+
+/// Why a chain of shards doesn't verify.
+#[derive(Debug)]
+pub enum ChainError {
+    /// The chain has no shards
+    Empty,
+    /// The AIR declares no state digests, or digests outside its public values
+    InvalidStateDigests,
+    /// A shard's proof doesn't verify
+    Shard {
+        /// Position of the shard in the chain
+        index: usize,
+        /// Why its proof doesn't verify
+        error: VerificationError,
+    },
+    /// A shard doesn't start in the state the previous one ends in
+    Discontinuity {
+        /// Position of the shard in the chain
+        index: usize,
+    },
+}
+
+impl core::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("the chain has no shards"),
+            Self::InvalidStateDigests => {
+                f.write_str("the AIR declares no state digests within its public values")
+            }
+            Self::Shard { index, error } => write!(f, "shard {index} doesn't verify: {error}"),
+            Self::Discontinuity { index } => write!(
+                f,
+                "shard {index} doesn't start in the state shard {} ends in",
+                index - 1
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Shard { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Prove each shard of an execution, given as its main trace and public values, in order.
+///
+/// # Errors
+/// The first error [`prove`] returns for a shard.
+pub fn prove_shards<SC, A>(
+    config: &SC,
+    air: &A,
+    shards: impl IntoIterator<Item = (RowMajorMatrix<Val<SC>>, Vec<Val<SC>>)>,
+) -> Result<Vec<ShardProof<SC>>, ProverError>
+where
+    SC: StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    Val<SC>: PackedField,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<ProverFolder<'a, SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    shards
+        .into_iter()
+        .map(|(trace, public_values)| {
+            let proof = prove(config, air, trace, &public_values)?;
+            Ok(ShardProof {
+                proof,
+                public_values,
+            })
+        })
+        .collect()
+}
+
+/// Verify a chain of shards of one execution, given in order.
+///
+/// Every shard is verified against `air`, and each must start in the state the previous
+/// one ends in. The states the chain starts from and ends in are returned: the
+/// application checks they are the execution's initial and final states.
+///
+/// # Errors
+/// - [`ChainError::Empty`] if there are no shards
+/// - [`ChainError::InvalidStateDigests`] if `air` declares no state digests, or digests
+///   outside its public values
+/// - [`ChainError::Shard`] with the first shard whose proof doesn't verify
+/// - [`ChainError::Discontinuity`] with the first shard not starting where the previous
+///   one ends
+pub fn verify_chain<SC, A>(
+    config: &SC,
+    air: &A,
+    shards: &[ShardProof<SC>],
+) -> Result<ChainStates<Val<SC>>, ChainError>
+where
+    SC: StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    let vk = keygen(config, air);
+    let digests = vk.state_digests.ok_or(ChainError::InvalidStateDigests)?;
+    let within = |offset: usize| {
+        offset
+            .checked_add(digests.len)
+            .is_some_and(|end| end <= vk.num_public_values)
+    };
+    if !within(digests.input_offset) || !within(digests.output_offset) {
+        return Err(ChainError::InvalidStateDigests);
+    }
+
+    let mut states: Option<ChainStates<Val<SC>>> = None;
+    for (index, shard) in shards.iter().enumerate() {
+        verify_with_vk(config, &vk, air, &shard.proof, &shard.public_values)
+            .map_err(|error| ChainError::Shard { index, error })?;
+        // The verified public values have the length the key expects
+        let input = digests
+            .input_digest(&shard.public_values)
+            .expect("the digests lie within the public values");
+        let output = digests
+            .output_digest(&shard.public_values)
+            .expect("the digests lie within the public values");
+        states = match states {
+            None => Some(ChainStates {
+                input: input.to_vec(),
+                output: output.to_vec(),
+            }),
+            Some(mut states) if states.output == input => {
+                states.output = output.to_vec();
+                Some(states)
+            }
+            Some(_) => return Err(ChainError::Discontinuity { index }),
+        };
+    }
+    states.ok_or(ChainError::Empty)
+}
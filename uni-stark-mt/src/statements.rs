@@ -0,0 +1,199 @@
+//! Several independent statements in one trace
+//!
+//! A batch prover can encode `B` statements of the same AIR in one trace, one segment of
+//! rows each, and get one proof for all of them (see
+//! [`num_statements`](crate::AirLayout::num_statements)). Each statement's boundary
+//! rows and public values are exposed to constraints through [`StatementBuilder`]; the
+//! selectors are sums of row Lagrange polynomials, evaluated by the prover on the quotient
+//! domain and by the verifier at ζ.
+//!
+//! Like `is_first_row`, the statement selectors are polynomials of degree below the trace
+//! height, so a constraint filtered by one has its degree raised by one.
+
+use alloc::vec::Vec;
+
+use p3_air::{AirBuilderWithPublicValues, FilteredAirBuilder};
+use p3_field::{batch_multiplicative_inverse, ExtensionField, Field, PrimeCharacteristicRing};
+
+use crate::TraceDomain;
+
+/// Extension trait for constraints over traces encoding several statements.
+///
+/// With a single statement, the selectors are the usual first-row, last-row and
+/// transition selectors and the statement public values are the public values.
+pub trait StatementBuilder: AirBuilderWithPublicValues {
+    /// Selector: 1 on the first row of every statement, 0 elsewhere
+    fn is_first_statement_row(&self) -> Self::Expr;
+
+    /// Selector: 1 on the last row of every statement, 0 elsewhere
+    fn is_last_statement_row(&self) -> Self::Expr;
+
+    /// Selector: 0 on the last row of every statement, 1 elsewhere
+    fn is_statement_transition(&self) -> Self::Expr;
+
+    /// Public values of the statement owning the current row.
+    ///
+    /// Only meaningful on a statement's first and last rows: use them under
+    /// [`when_first_statement_row`](Self::when_first_statement_row) or
+    /// [`when_last_statement_row`](Self::when_last_statement_row).
+    fn statement_public_values(&self) -> &[Self::Expr];
+
+    /// Builder whose constraints only apply on the first row of every statement.
+    fn when_first_statement_row(&mut self) -> FilteredAirBuilder<'_, Self> {
+        self.when(self.is_first_statement_row())
+    }
+
+    /// Builder whose constraints only apply on the last row of every statement.
+    fn when_last_statement_row(&mut self) -> FilteredAirBuilder<'_, Self> {
+        self.when(self.is_last_statement_row())
+    }
+
+    /// Builder whose constraints apply between consecutive rows of the same statement.
+    fn when_statement_transition(&mut self) -> FilteredAirBuilder<'_, Self> {
+        self.when(self.is_statement_transition())
+    }
+}
+
+/// Statement boundary selectors and public values at one point, see [`StatementBuilder`].
+#[derive(Clone, Copy, Debug)]
+pub struct StatementSelectors<'a, T> {
+    /// Selector: 1 on the first row of every statement, 0 elsewhere
+    pub is_first_row: T,
+
+    /// Selector: 1 on the last row of every statement, 0 elsewhere
+    pub is_last_row: T,
+
+    /// Selector: 0 on the last row of every statement, 1 elsewhere
+    pub is_transition: T,
+
+    /// Public values of the statement owning the point's row, on boundary rows
+    pub public_values: &'a [T],
+}
+
+/// Statement selectors evaluated at one point, owning their public values.
+pub(crate) struct StatementValues<T> {
+    is_first_row: T,
+    is_last_row: T,
+    public_values: Vec<T>,
+}
+
+impl<T: PrimeCharacteristicRing + Copy> StatementValues<T> {
+    pub(crate) fn selectors(&self) -> StatementSelectors<'_, T> {
+        StatementSelectors {
+            is_first_row: self.is_first_row,
+            is_last_row: self.is_last_row,
+            is_transition: T::ONE - self.is_last_row,
+            public_values: &self.public_values,
+        }
+    }
+}
+
+/// The boundary rows of the statements of a trace encoding more than one.
+pub(crate) struct StatementBoundaries<F> {
+    /// Point and Lagrange weight of each statement's first row
+    first_rows: Vec<(F, F)>,
+
+    /// Point and Lagrange weight of each statement's last row
+    last_rows: Vec<(F, F)>,
+}
+
+impl<F: Field> StatementBoundaries<F> {
+    /// Locate the boundaries of `num_statements` statements in `trace_domain`.
+    ///
+    /// Returns `Ok(None)` for a single statement, whose selectors are the trace domain's.
+    ///
+    /// # Errors
+    /// If the statements don't split the trace and public values evenly, with at least
+    /// two rows each, or the PCS can't address individual rows of its domains.
+    pub(crate) fn new<D>(
+        num_statements: usize,
+        num_public_values: usize,
+        trace_domain: D,
+    ) -> Result<Option<Self>, &'static str>
+    where
+        D: TraceDomain<Val = F>,
+    {
+        check_statement_layout(num_statements, num_public_values, trace_domain.size())?;
+        if num_statements == 1 {
+            return Ok(None);
+        }
+        let size = trace_domain.size();
+        let height = size / num_statements;
+        let rows = |offset: usize| -> Vec<usize> {
+            (0..num_statements).map(|j| j * height + offset).collect()
+        };
+        let points = |rows: &[usize]| {
+            D::row_lagrange_points(trace_domain.first_point(), size, rows)
+                .ok_or("the PCS's trace domains can't be split into statements")
+        };
+        Ok(Some(Self {
+            first_rows: points(&rows(0))?,
+            last_rows: points(&rows(height - 1))?,
+        }))
+    }
+
+    /// Selectors and statement public values at `x`, where the trace domain's vanishing
+    /// polynomial is `z_h`.
+    ///
+    /// `x` must lie outside the trace domain.
+    pub(crate) fn values_at<EF: ExtensionField<F>>(
+        &self,
+        x: EF,
+        z_h: EF,
+        public_values: &[F],
+    ) -> StatementValues<EF> {
+        let num_statements = self.first_rows.len();
+        let per_statement = public_values.len() / num_statements;
+        let inverses = batch_multiplicative_inverse(
+            &self
+                .first_rows
+                .iter()
+                .chain(&self.last_rows)
+                .map(|&(point, _)| x - point)
+                .collect::<Vec<_>>(),
+        );
+        let lagrange: Vec<EF> = self
+            .first_rows
+            .iter()
+            .chain(&self.last_rows)
+            .zip(inverses)
+            .map(|(&(_, weight), inverse)| z_h * inverse * weight)
+            .collect();
+        let (first, last) = lagrange.split_at(num_statements);
+
+        // Each statement's values, on its first and last rows only
+        let mut statement_public_values = EF::zero_vec(per_statement);
+        if per_statement > 0 {
+            for (j, values) in public_values.chunks_exact(per_statement).enumerate() {
+                let boundary = first[j] + last[j];
+                for (acc, &value) in statement_public_values.iter_mut().zip(values) {
+                    *acc += boundary * value;
+                }
+            }
+        }
+        StatementValues {
+            is_first_row: first.iter().copied().sum(),
+            is_last_row: last.iter().copied().sum(),
+            public_values: statement_public_values,
+        }
+    }
+}
+
+/// Check that `num_statements` statements split a trace of `height` rows and
+/// `num_public_values` public values evenly, with at least two rows each.
+pub(crate) fn check_statement_layout(
+    num_statements: usize,
+    num_public_values: usize,
+    height: usize,
+) -> Result<(), &'static str> {
+    if num_statements == 0 {
+        return Err("an AIR encodes at least one statement");
+    }
+    if height % num_statements != 0 || height / num_statements < 2 {
+        return Err("statements must split the trace into segments of at least two rows");
+    }
+    if num_public_values % num_statements != 0 {
+        return Err("statements must split the public values evenly");
+    }
+    Ok(())
+}
@@ -0,0 +1,1044 @@
+//! Symbolic evaluation of constraints, for export to formal tools
+//!
+//! [`symbolic_constraints`] evaluates an AIR once over [`SymbolicExpression`]s instead of
+//! field elements, so every asserted constraint comes back as a polynomial over named
+//! variables: trace cells on the rows each trace is opened at (see [`OpeningSchedule`]),
+//! public values, challenges, exposed auxiliary values and the row selectors. The result
+//! can be printed in infix form for a computer algebra system ([`SymbolicConstraints`] implements `Display`) or as an SMT-LIB
+//! script over the prime field ([`SymbolicConstraints::to_smtlib`]), so constraint
+//! completeness and soundness can be analyzed outside Rust.
+//!
+//! The same constraints serve analyses of the AIR: [`SymbolicConstraints::max_degree`]
+//! bounds the quotient blowup, [`SymbolicExpression::entries`] lists the trace cells and
+//! values a constraint reads, and [`SymbolicExpression::evaluate`] interprets it in any
+//! ring, e.g. over concrete values or another builder's expressions.
+//!
+//! The builder's extension field is the base field itself: auxiliary values and
+//! challenges are plain variables, and each constraint records whether it was asserted
+//! over the extension field. Constants are therefore always base-field elements.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter, Write};
+use core::iter::{Product, Sum};
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use p3_air::{
+    Air, AirBuilder, AirBuilderWithPublicValues, ExtensionBuilder, PermutationAirBuilder,
+};
+use p3_field::{Algebra, ExtensionField, Field, PrimeCharacteristicRing, PrimeField64};
+
+use crate::{
+    AuxBuilder, ChallengeSet, MultiTraceAir, OpeningSchedule, RotatedRows, RowOffset,
+    StatementBuilder, VerifierView,
+};
+
+/// What a [`SymbolicVariable`] stands for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Entry {
+    /// Main trace column
+    Main {
+        /// Row of the window
+        row: RowOffset,
+        /// Column index
+        column: usize,
+    },
+
+    /// Extension-field auxiliary trace column
+    Aux {
+        /// Row of the window
+        row: RowOffset,
+        /// Column index
+        column: usize,
+    },
+
+    /// Base-field auxiliary trace column
+    AuxBase {
+        /// Row of the window
+        row: RowOffset,
+        /// Column index
+        column: usize,
+    },
+
+    /// Public value at this index
+    Public(usize),
+
+    /// Challenge declared under this name
+    Challenge(&'static str),
+
+    /// Exposed auxiliary value at this index
+    AuxExposed(usize),
+
+    /// Public value at this index of the statement owning the row
+    StatementPublic(usize),
+}
+
+impl Entry {
+    /// Identifier of the variable, e.g. `main_next_1`, `main_ahead3_0` or
+    /// `challenge_logup_alpha`.
+    pub fn name(&self) -> String {
+        let row = |row: &RowOffset| -> String {
+            match *row {
+                RowOffset::Local => "local".into(),
+                RowOffset::Next => "next".into(),
+                RowOffset::Prev => "prev".into(),
+                RowOffset::Rotated(offset) if offset < 0 => format!("behind{}", -offset),
+                RowOffset::Rotated(offset) => format!("ahead{offset}"),
+                RowOffset::Last => "last".into(),
+            }
+        };
+        match self {
+            Self::Main { row: r, column } => format!("main_{}_{column}", row(r)),
+            Self::Aux { row: r, column } => format!("aux_{}_{column}", row(r)),
+            Self::AuxBase { row: r, column } => format!("aux_base_{}_{column}", row(r)),
+            Self::Public(index) => format!("public_{index}"),
+            Self::Challenge(name) => format!("challenge_{name}"),
+            Self::AuxExposed(index) => format!("exposed_{index}"),
+            Self::StatementPublic(index) => format!("statement_public_{index}"),
+        }
+    }
+
+    /// Row of the trace the variable is read from, for trace cells.
+    pub const fn row(&self) -> Option<RowOffset> {
+        match self {
+            Self::Main { row, .. } | Self::Aux { row, .. } | Self::AuxBase { row, .. } => {
+                Some(*row)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the variable takes extension-field values in the protocol.
+    pub const fn is_extension(&self) -> bool {
+        matches!(
+            self,
+            Self::Aux { .. } | Self::Challenge(_) | Self::AuxExposed(_)
+        )
+    }
+}
+
+/// A named variable of a [`SymbolicExpression`].
+pub struct SymbolicVariable<F> {
+    /// What the variable stands for
+    pub entry: Entry,
+    _phantom: PhantomData<F>,
+}
+
+impl<F> SymbolicVariable<F> {
+    /// The variable standing for `entry`.
+    pub const fn new(entry: Entry) -> Self {
+        Self {
+            entry,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F> Clone for SymbolicVariable<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for SymbolicVariable<F> {}
+
+impl<F> PartialEq for SymbolicVariable<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entry == other.entry
+    }
+}
+
+impl<F> Eq for SymbolicVariable<F> {}
+
+impl<F> fmt::Debug for SymbolicVariable<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.entry.name())
+    }
+}
+
+/// A constraint polynomial, as an expression tree over [`SymbolicVariable`]s.
+///
+/// Operations on constants are folded, and adding zero or multiplying by zero or one is
+/// simplified away, so exported constraints stay close to how the AIR wrote them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SymbolicExpression<F> {
+    /// A variable
+    Variable(SymbolicVariable<F>),
+
+    /// Selector: 1 on the first row, 0 elsewhere
+    IsFirstRow,
+
+    /// Selector: 1 on the last row, 0 elsewhere
+    IsLastRow,
+
+    /// Selector: 1 on all rows except the last, 0 on the last
+    IsTransition,
+
+    /// Selector: 1 on the first row of every statement, 0 elsewhere
+    IsFirstStatementRow,
+
+    /// Selector: 1 on the last row of every statement, 0 elsewhere
+    IsLastStatementRow,
+
+    /// Selector: 0 on the last row of every statement, 1 elsewhere
+    IsStatementTransition,
+
+    /// A constant
+    Constant(F),
+
+    /// Sum of two expressions
+    Add(Arc<Self>, Arc<Self>),
+
+    /// Difference of two expressions
+    Sub(Arc<Self>, Arc<Self>),
+
+    /// Negation of an expression
+    Neg(Arc<Self>),
+
+    /// Product of two expressions
+    Mul(Arc<Self>, Arc<Self>),
+}
+
+/// Identifiers of the row selectors, as declared in exported scripts.
+const SELECTOR_NAMES: [&str; 6] = [
+    "is_first_row",
+    "is_last_row",
+    "is_transition",
+    "is_first_statement_row",
+    "is_last_statement_row",
+    "is_statement_transition",
+];
+
+impl<F> SymbolicExpression<F> {
+    /// Degree of `self` over the trace domain, in multiples of the trace height.
+    ///
+    /// Trace cells of the local, next, previous and rotated rows count one, as do the
+    /// first- and last-row selectors of the trace and of its statements. The transition
+    /// selector, last-row cells, public values, challenges and exposed values are constant
+    /// or of negligible degree and count zero. The quotient of a constraint of degree `d`
+    /// needs a domain `d - 1` times larger than the trace.
+    pub fn degree(&self) -> usize {
+        match self {
+            Self::Variable(v) => match v.entry {
+                Entry::Main { row, .. } | Entry::Aux { row, .. } | Entry::AuxBase { row, .. } => {
+                    usize::from(row != RowOffset::Last)
+                }
+                _ => 0,
+            },
+            Self::IsTransition | Self::Constant(_) => 0,
+            Self::IsFirstRow
+            | Self::IsLastRow
+            | Self::IsFirstStatementRow
+            | Self::IsLastStatementRow
+            | Self::IsStatementTransition => 1,
+            Self::Add(a, b) | Self::Sub(a, b) => a.degree().max(b.degree()),
+            Self::Neg(a) => a.degree(),
+            Self::Mul(a, b) => a.degree() + b.degree(),
+        }
+    }
+
+    /// Every variable `self` reads, in order of first appearance.
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        self.collect_entries(&mut entries);
+        entries
+    }
+
+    /// Append the variables `self` reads that `entries` lacks.
+    fn collect_entries(&self, entries: &mut Vec<Entry>) {
+        match self {
+            Self::Variable(v) => {
+                if !entries.contains(&v.entry) {
+                    entries.push(v.entry);
+                }
+            }
+            Self::Add(a, b) | Self::Sub(a, b) | Self::Mul(a, b) => {
+                a.collect_entries(entries);
+                b.collect_entries(entries);
+            }
+            Self::Neg(a) => a.collect_entries(entries),
+            _ => {}
+        }
+    }
+
+    /// Evaluate `self` in `T`, with `leaf` giving the value of every variable, selector
+    /// and constant.
+    pub fn evaluate<T: PrimeCharacteristicRing>(&self, leaf: &impl Fn(&Self) -> T) -> T {
+        match self {
+            Self::Add(a, b) => a.evaluate(leaf) + b.evaluate(leaf),
+            Self::Sub(a, b) => a.evaluate(leaf) - b.evaluate(leaf),
+            Self::Neg(a) => -a.evaluate(leaf),
+            Self::Mul(a, b) => a.evaluate(leaf) * b.evaluate(leaf),
+            _ => leaf(self),
+        }
+    }
+
+    /// Identifier of `self`, if it is a row selector.
+    const fn selector_name(&self) -> Option<&'static str> {
+        let index = match self {
+            Self::IsFirstRow => 0,
+            Self::IsLastRow => 1,
+            Self::IsTransition => 2,
+            Self::IsFirstStatementRow => 3,
+            Self::IsLastStatementRow => 4,
+            Self::IsStatementTransition => 5,
+            _ => return None,
+        };
+        Some(SELECTOR_NAMES[index])
+    }
+}
+
+impl<F: Field + Display> SymbolicExpression<F> {
+    /// Binding strength of the top-level operation, for parenthesizing infix output.
+    const fn precedence(&self) -> u8 {
+        match self {
+            Self::Add(..) | Self::Sub(..) => 1,
+            Self::Mul(..) => 2,
+            Self::Neg(_) => 3,
+            _ => 4,
+        }
+    }
+
+    /// Write `self` in infix form, parenthesized if it binds weaker than `parent`.
+    fn fmt_infix(&self, f: &mut Formatter<'_>, parent: u8) -> fmt::Result {
+        let parenthesize = self.precedence() < parent;
+        if parenthesize {
+            f.write_char('(')?;
+        }
+        match self {
+            Self::Variable(v) => f.write_str(&v.entry.name())?,
+            Self::Constant(c) => write!(f, "{c}")?,
+            Self::Add(a, b) => {
+                a.fmt_infix(f, 1)?;
+                f.write_str(" + ")?;
+                b.fmt_infix(f, 1)?;
+            }
+            Self::Sub(a, b) => {
+                a.fmt_infix(f, 1)?;
+                f.write_str(" - ")?;
+                b.fmt_infix(f, 2)?;
+            }
+            Self::Neg(a) => {
+                f.write_char('-')?;
+                a.fmt_infix(f, 3)?;
+            }
+            Self::Mul(a, b) => {
+                a.fmt_infix(f, 2)?;
+                f.write_str(" * ")?;
+                b.fmt_infix(f, 2)?;
+            }
+            selector => f.write_str(selector.selector_name().expect("only selectors remain"))?,
+        }
+        if parenthesize {
+            f.write_char(')')?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField64> SymbolicExpression<F> {
+    /// Write `self` as an SMT-LIB term over the finite field sort `F`.
+    fn write_smtlib(&self, out: &mut String) {
+        match self {
+            Self::Variable(v) => out.push_str(&v.entry.name()),
+            Self::Constant(c) => {
+                write!(out, "(as ff{} F)", c.as_canonical_u64())
+                    .expect("writing to a String can't fail");
+            }
+            Self::Add(a, b) => write_smtlib_application(out, "ff.add", a, Some(b)),
+            Self::Sub(a, b) => {
+                // SMT-LIB's finite field theory has no subtraction
+                let negated = Self::Neg(b.clone());
+                write_smtlib_application(out, "ff.add", a, Some(&negated));
+            }
+            Self::Neg(a) => write_smtlib_application(out, "ff.neg", a, None),
+            Self::Mul(a, b) => write_smtlib_application(out, "ff.mul", a, Some(b)),
+            selector => out.push_str(selector.selector_name().expect("only selectors remain")),
+        }
+    }
+}
+
+/// Write the application of `op` to `a` (and `b`) as an SMT-LIB term.
+fn write_smtlib_application<F: PrimeField64>(
+    out: &mut String,
+    op: &str,
+    a: &SymbolicExpression<F>,
+    b: Option<&SymbolicExpression<F>>,
+) {
+    write!(out, "({op} ").expect("writing to a String can't fail");
+    a.write_smtlib(out);
+    if let Some(b) = b {
+        out.push(' ');
+        b.write_smtlib(out);
+    }
+    out.push(')');
+}
+
+impl<F: Field + Display> Display for SymbolicExpression<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.fmt_infix(f, 0)
+    }
+}
+
+impl<F: Field> Default for SymbolicExpression<F> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<F: Field> From<F> for SymbolicExpression<F> {
+    fn from(value: F) -> Self {
+        Self::Constant(value)
+    }
+}
+
+impl<F: Field> From<SymbolicVariable<F>> for SymbolicExpression<F> {
+    fn from(value: SymbolicVariable<F>) -> Self {
+        Self::Variable(value)
+    }
+}
+
+impl<F: Field> PrimeCharacteristicRing for SymbolicExpression<F> {
+    type PrimeSubfield = F::PrimeSubfield;
+
+    const ZERO: Self = Self::Constant(F::ZERO);
+    const ONE: Self = Self::Constant(F::ONE);
+    const TWO: Self = Self::Constant(F::TWO);
+    const NEG_ONE: Self = Self::Constant(F::NEG_ONE);
+
+    fn from_prime_subfield(f: Self::PrimeSubfield) -> Self {
+        F::from_prime_subfield(f).into()
+    }
+}
+
+impl<F: Field> Algebra<F> for SymbolicExpression<F> {}
+
+impl<F: Field> Algebra<SymbolicVariable<F>> for SymbolicExpression<F> {}
+
+impl<F: Field> Add for SymbolicExpression<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a + b),
+            (x, Self::Constant(c)) | (Self::Constant(c), x) if c == F::ZERO => x,
+            (a, b) => Self::Add(Arc::new(a), Arc::new(b)),
+        }
+    }
+}
+
+impl<F: Field> Sub for SymbolicExpression<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a - b),
+            (x, Self::Constant(c)) if c == F::ZERO => x,
+            (Self::Constant(c), x) if c == F::ZERO => -x,
+            (a, b) => Self::Sub(Arc::new(a), Arc::new(b)),
+        }
+    }
+}
+
+impl<F: Field> Neg for SymbolicExpression<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        match self {
+            Self::Constant(c) => Self::Constant(-c),
+            x => Self::Neg(Arc::new(x)),
+        }
+    }
+}
+
+impl<F: Field> Mul for SymbolicExpression<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Self::Constant(a), Self::Constant(b)) => Self::Constant(a * b),
+            (_, Self::Constant(c)) | (Self::Constant(c), _) if c == F::ZERO => Self::ZERO,
+            (x, Self::Constant(c)) | (Self::Constant(c), x) if c == F::ONE => x,
+            (a, b) => Self::Mul(Arc::new(a), Arc::new(b)),
+        }
+    }
+}
+
+impl<F: Field> AddAssign for SymbolicExpression<F> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = core::mem::take(self) + rhs;
+    }
+}
+
+impl<F: Field> SubAssign for SymbolicExpression<F> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = core::mem::take(self) - rhs;
+    }
+}
+
+impl<F: Field> MulAssign for SymbolicExpression<F> {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = core::mem::take(self) * rhs;
+    }
+}
+
+impl<F: Field> Sum for SymbolicExpression<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, |acc, x| acc + x)
+    }
+}
+
+impl<F: Field> Product for SymbolicExpression<F> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, |acc, x| acc * x)
+    }
+}
+
+/// Arithmetic of expressions with constants and variables, through [`From`].
+macro_rules! impl_expression_ops {
+    ($rhs:ty) => {
+        impl<F: Field> Add<$rhs> for SymbolicExpression<F> {
+            type Output = Self;
+
+            fn add(self, rhs: $rhs) -> Self {
+                self + Self::from(rhs)
+            }
+        }
+
+        impl<F: Field> Sub<$rhs> for SymbolicExpression<F> {
+            type Output = Self;
+
+            fn sub(self, rhs: $rhs) -> Self {
+                self - Self::from(rhs)
+            }
+        }
+
+        impl<F: Field> Mul<$rhs> for SymbolicExpression<F> {
+            type Output = Self;
+
+            fn mul(self, rhs: $rhs) -> Self {
+                self * Self::from(rhs)
+            }
+        }
+
+        impl<F: Field> AddAssign<$rhs> for SymbolicExpression<F> {
+            fn add_assign(&mut self, rhs: $rhs) {
+                *self += Self::from(rhs);
+            }
+        }
+
+        impl<F: Field> SubAssign<$rhs> for SymbolicExpression<F> {
+            fn sub_assign(&mut self, rhs: $rhs) {
+                *self -= Self::from(rhs);
+            }
+        }
+
+        impl<F: Field> MulAssign<$rhs> for SymbolicExpression<F> {
+            fn mul_assign(&mut self, rhs: $rhs) {
+                *self *= Self::from(rhs);
+            }
+        }
+    };
+}
+
+impl_expression_ops!(F);
+impl_expression_ops!(SymbolicVariable<F>);
+
+/// Arithmetic of variables, producing expressions.
+macro_rules! impl_variable_ops {
+    ($rhs:ty) => {
+        impl<F: Field> Add<$rhs> for SymbolicVariable<F> {
+            type Output = SymbolicExpression<F>;
+
+            fn add(self, rhs: $rhs) -> SymbolicExpression<F> {
+                SymbolicExpression::from(self) + rhs
+            }
+        }
+
+        impl<F: Field> Sub<$rhs> for SymbolicVariable<F> {
+            type Output = SymbolicExpression<F>;
+
+            fn sub(self, rhs: $rhs) -> SymbolicExpression<F> {
+                SymbolicExpression::from(self) - rhs
+            }
+        }
+
+        impl<F: Field> Mul<$rhs> for SymbolicVariable<F> {
+            type Output = SymbolicExpression<F>;
+
+            fn mul(self, rhs: $rhs) -> SymbolicExpression<F> {
+                SymbolicExpression::from(self) * rhs
+            }
+        }
+    };
+}
+
+impl_variable_ops!(F);
+impl_variable_ops!(SymbolicVariable<F>);
+impl_variable_ops!(SymbolicExpression<F>);
+
+/// A constraint asserted by the AIR, as a polynomial that must vanish on every row.
+#[derive(Clone, Debug)]
+pub struct SymbolicConstraint<F> {
+    /// The constraint polynomial
+    pub expr: SymbolicExpression<F>,
+
+    /// Whether it was asserted over the extension field
+    pub extension: bool,
+}
+
+impl<F> SymbolicConstraint<F> {
+    /// Degree of the constraint polynomial, see [`SymbolicExpression::degree`].
+    pub fn degree(&self) -> usize {
+        self.expr.degree()
+    }
+}
+
+/// Every constraint of an AIR, with the variables they range over.
+///
+/// `Display` prints one constraint per line as `<expr> = 0`, in infix form.
+#[derive(Clone, Debug)]
+pub struct SymbolicConstraints<F> {
+    /// Constraints, in the order `Air::eval` asserts them
+    pub constraints: Vec<SymbolicConstraint<F>>,
+
+    /// Every variable the constraints may use, in declaration order
+    pub variables: Vec<Entry>,
+}
+
+impl<F> SymbolicConstraints<F> {
+    /// Highest degree of any constraint, 0 without constraints.
+    ///
+    /// Bounds the blowup of the quotient domain: constraints of degree `d` need it at
+    /// least `d - 1` times larger than the trace domain.
+    pub fn max_degree(&self) -> usize {
+        self.constraints
+            .iter()
+            .map(SymbolicConstraint::degree)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Variables of [`variables`](Self::variables) no constraint reads.
+    pub fn unused_variables(&self) -> Vec<Entry> {
+        let used: Vec<Entry> = self
+            .constraints
+            .iter()
+            .flat_map(|constraint| constraint.expr.entries())
+            .collect();
+        self.variables
+            .iter()
+            .filter(|entry| !used.contains(entry))
+            .copied()
+            .collect()
+    }
+}
+
+impl<F: Field + Display> Display for SymbolicConstraints<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for constraint in &self.constraints {
+            writeln!(f, "{} = 0", constraint.expr)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField64> SymbolicConstraints<F> {
+    /// An SMT-LIB script asserting every constraint, over the finite field theory
+    /// (`QF_FF`, as supported by cvc5).
+    ///
+    /// All variables, including the row selectors, are declared in the prime field `F`.
+    /// Variables that take extension-field values in the protocol are marked with an
+    /// `; extension` comment, as are constraints asserted over the extension field, so a
+    /// tool can re-declare them over its own model of the extension.
+    pub fn to_smtlib(&self) -> String {
+        let mut out = String::new();
+        let mut line = |text: &str| {
+            out.push_str(text);
+            out.push('\n');
+        };
+        line("(set-logic QF_FF)");
+        line(&format!(
+            "(define-sort F () (_ FiniteField {}))",
+            F::ORDER_U64
+        ));
+        for selector in SELECTOR_NAMES {
+            line(&format!("(declare-const {selector} F)"));
+        }
+        for entry in &self.variables {
+            let comment = if entry.is_extension() {
+                " ; extension"
+            } else {
+                ""
+            };
+            line(&format!("(declare-const {} F){comment}", entry.name()));
+        }
+        for (i, constraint) in self.constraints.iter().enumerate() {
+            let mut term = String::new();
+            constraint.expr.write_smtlib(&mut term);
+            let comment = if constraint.extension {
+                " ; extension"
+            } else {
+                ""
+            };
+            line(&format!(
+                "(assert (= {term} (as ff0 F))){comment}  ; constraint {i}"
+            ));
+        }
+        out
+    }
+}
+
+/// Builder evaluating constraints over [`SymbolicExpression`]s.
+///
+/// Every asserted constraint is recorded as-is, without being combined with the others.
+pub struct SymbolicAirBuilder<'a, F: Field> {
+    /// Main trace variables (local and next rows)
+    main: VerifierView<'a, SymbolicVariable<F>>,
+
+    /// Auxiliary trace variables (local and next rows)
+    aux: VerifierView<'a, SymbolicVariable<F>>,
+
+    /// Base-field auxiliary trace variables (local and next rows)
+    aux_base: VerifierView<'a, SymbolicVariable<F>>,
+
+    /// Challenge variables, by name
+    challenges: ChallengeSet<'a, SymbolicVariable<F>>,
+
+    /// Exposed auxiliary value variables
+    aux_exposed_values: &'a [SymbolicVariable<F>],
+
+    /// Auxiliary trace variables on the previous row, if the AIR opens it
+    aux_prev_row: &'a [SymbolicVariable<F>],
+
+    /// Base-field auxiliary trace variables on the previous row, if the AIR opens it
+    aux_base_prev_row: &'a [SymbolicVariable<F>],
+
+    /// Main trace variables on the previous row, if the AIR opens it
+    main_prev_row: &'a [SymbolicVariable<F>],
+
+    /// Auxiliary trace variables on the rows of the AIR's further rotations
+    aux_rotated: RotatedRows<'a, SymbolicVariable<F>>,
+
+    /// Base-field auxiliary trace variables on the rows of the AIR's further rotations
+    aux_base_rotated: RotatedRows<'a, SymbolicVariable<F>>,
+
+    /// Main trace variables on the rows of the AIR's further rotations
+    main_rotated: RotatedRows<'a, SymbolicVariable<F>>,
+
+    /// Auxiliary trace variables on the last row, if the AIR opens it
+    aux_last_row: &'a [SymbolicVariable<F>],
+
+    /// Base-field auxiliary trace variables on the last row, if the AIR opens it
+    aux_base_last_row: &'a [SymbolicVariable<F>],
+
+    /// Main trace variables on the last row, if the AIR opens it
+    main_last_row: &'a [SymbolicVariable<F>],
+
+    /// Public value variables
+    public_values: &'a [SymbolicVariable<F>],
+
+    /// Statement public value variables, as expressions
+    statement_public_values: Vec<SymbolicExpression<F>>,
+
+    /// Constraints asserted so far
+    constraints: Vec<SymbolicConstraint<F>>,
+}
+
+impl<'a, F: Field> AirBuilder for SymbolicAirBuilder<'a, F> {
+    type F = F;
+    type Expr = SymbolicExpression<F>;
+    type Var = SymbolicVariable<F>;
+    type M = VerifierView<'a, SymbolicVariable<F>>;
+
+    fn main(&self) -> Self::M {
+        self.main
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        SymbolicExpression::IsFirstRow
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        SymbolicExpression::IsLastRow
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        assert_eq!(size, 2, "Only window size 2 is supported");
+        SymbolicExpression::IsTransition
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        self.constraints.push(SymbolicConstraint {
+            expr: x.into(),
+            extension: false,
+        });
+    }
+}
+
+impl<'a, F: Field> AirBuilderWithPublicValues for SymbolicAirBuilder<'a, F> {
+    type PublicVar = SymbolicVariable<F>;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+impl<'a, F: Field> StatementBuilder for SymbolicAirBuilder<'a, F> {
+    fn is_first_statement_row(&self) -> Self::Expr {
+        SymbolicExpression::IsFirstStatementRow
+    }
+
+    fn is_last_statement_row(&self) -> Self::Expr {
+        SymbolicExpression::IsLastStatementRow
+    }
+
+    fn is_statement_transition(&self) -> Self::Expr {
+        SymbolicExpression::IsStatementTransition
+    }
+
+    fn statement_public_values(&self) -> &[Self::Expr] {
+        &self.statement_public_values
+    }
+}
+
+impl<'a, F: Field> ExtensionBuilder for SymbolicAirBuilder<'a, F> {
+    type EF = F;
+    type ExprEF = SymbolicExpression<F>;
+    type VarEF = SymbolicVariable<F>;
+
+    fn assert_zero_ext<I>(&mut self, x: I)
+    where
+        I: Into<Self::ExprEF>,
+    {
+        self.constraints.push(SymbolicConstraint {
+            expr: x.into(),
+            extension: true,
+        });
+    }
+}
+
+impl<'a, F: Field> PermutationAirBuilder for SymbolicAirBuilder<'a, F> {
+    type MP = VerifierView<'a, SymbolicVariable<F>>;
+    type RandomVar = Self::VarEF;
+
+    fn permutation(&self) -> Self::MP {
+        self.aux()
+    }
+
+    fn permutation_randomness(&self) -> &[Self::RandomVar] {
+        self.challenges()
+    }
+}
+
+impl<'a, F: Field> AuxBuilder for SymbolicAirBuilder<'a, F> {
+    type MAux = VerifierView<'a, SymbolicVariable<F>>;
+
+    fn aux(&self) -> Self::MAux {
+        self.aux
+    }
+
+    fn aux_base(&self) -> Self::M {
+        self.aux_base
+    }
+
+    fn challenge_set(&self) -> ChallengeSet<'_, Self::VarEF> {
+        self.challenges
+    }
+
+    fn aux_exposed_values(&self) -> &[Self::VarEF] {
+        self.aux_exposed_values
+    }
+
+    fn aux_prev_row(&self) -> &[Self::VarEF] {
+        self.aux_prev_row
+    }
+
+    fn aux_base_prev_row(&self) -> &[Self::Var] {
+        self.aux_base_prev_row
+    }
+
+    fn main_prev_row(&self) -> &[Self::Var] {
+        self.main_prev_row
+    }
+
+    fn aux_rotated_row(&self, offset: isize) -> &[Self::VarEF] {
+        self.aux_rotated.row(offset)
+    }
+
+    fn aux_base_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.aux_base_rotated.row(offset)
+    }
+
+    fn main_rotated_row(&self, offset: isize) -> &[Self::Var] {
+        self.main_rotated.row(offset)
+    }
+
+    fn aux_last_row(&self) -> &[Self::VarEF] {
+        self.aux_last_row
+    }
+
+    fn aux_base_last_row(&self) -> &[Self::Var] {
+        self.aux_base_last_row
+    }
+
+    fn main_last_row(&self) -> &[Self::Var] {
+        self.main_last_row
+    }
+}
+
+/// Variables for `width` trace columns on the local, next, previous and last rows, the
+/// rows `openings` doesn't open having none.
+fn window_variables<F>(
+    width: usize,
+    openings: OpeningSchedule,
+    entry: impl Fn(RowOffset, usize) -> Entry,
+) -> [Vec<SymbolicVariable<F>>; 4] {
+    [
+        (RowOffset::Local, true),
+        (RowOffset::Next, openings.next_row),
+        (RowOffset::Prev, openings.prev_row),
+        (RowOffset::Last, openings.last_row),
+    ]
+    .map(|(row, opened)| {
+        let width = if opened { width } else { 0 };
+        (0..width)
+            .map(|column| SymbolicVariable::new(entry(row, column)))
+            .collect()
+    })
+}
+
+/// Variables for `width` trace columns on the row of each further rotation `openings`
+/// opens, in increasing offset order.
+fn rotated_variables<F>(
+    width: usize,
+    openings: OpeningSchedule,
+    entry: impl Fn(RowOffset, usize) -> Entry,
+) -> Vec<Vec<SymbolicVariable<F>>> {
+    openings
+        .rotations
+        .iter()
+        .map(|offset| {
+            (0..width)
+                .map(|column| SymbolicVariable::new(entry(RowOffset::Rotated(offset), column)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Evaluate `air` symbolically, returning every constraint as a polynomial.
+///
+/// `EF` is only used to query the AIR's layout (widths, challenge names, exposed value
+/// count); the constraints themselves are evaluated with the base field as extension.
+pub fn symbolic_constraints<F, EF, A>(air: &A) -> SymbolicConstraints<F>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    A: MultiTraceAir<F, EF> + for<'a> Air<SymbolicAirBuilder<'a, F>>,
+{
+    let (main_openings, aux_openings) = (air.main_openings(), air.aux_openings());
+    let [main_local, main_next, main_prev_row, main_last_row] =
+        window_variables(air.width(), main_openings, |row, column| Entry::Main {
+            row,
+            column,
+        });
+    let [aux_local, aux_next, aux_prev_row, aux_last_row] =
+        window_variables(air.aux_width(), aux_openings, |row, column| Entry::Aux {
+            row,
+            column,
+        });
+    let [aux_base_local, aux_base_next, aux_base_prev_row, aux_base_last_row] =
+        window_variables(air.aux_base_width(), aux_openings, |row, column| {
+            Entry::AuxBase { row, column }
+        });
+    let main_rotated = rotated_variables(air.width(), main_openings, |row, column| Entry::Main {
+        row,
+        column,
+    });
+    let aux_rotated = rotated_variables(air.aux_width(), aux_openings, |row, column| Entry::Aux {
+        row,
+        column,
+    });
+    let aux_base_rotated = rotated_variables(air.aux_base_width(), aux_openings, |row, column| {
+        Entry::AuxBase { row, column }
+    });
+    let public_values: Vec<_> = (0..air.num_public_values())
+        .map(|i| SymbolicVariable::new(Entry::Public(i)))
+        .collect();
+    let challenge_names = air.challenge_names();
+    let challenge_values: Vec<_> = challenge_names
+        .iter()
+        .map(|&name| SymbolicVariable::new(Entry::Challenge(name)))
+        .collect();
+    let aux_exposed_values: Vec<_> = (0..air.num_aux_exposed_values())
+        .map(|i| SymbolicVariable::new(Entry::AuxExposed(i)))
+        .collect();
+    let statement_public_values: Vec<_> = (0..air.num_public_values()
+        / air.num_statements().max(1))
+        .map(|i| SymbolicVariable::new(Entry::StatementPublic(i)))
+        .collect();
+
+    let mut builder = SymbolicAirBuilder {
+        main: VerifierView::scheduled(&main_local, &main_next, main_openings.next_row),
+        aux: VerifierView::scheduled(&aux_local, &aux_next, aux_openings.next_row),
+        aux_base: VerifierView::scheduled(&aux_base_local, &aux_base_next, aux_openings.next_row),
+        challenges: ChallengeSet::new(&challenge_names, &challenge_values),
+        aux_exposed_values: &aux_exposed_values,
+        aux_prev_row: &aux_prev_row,
+        aux_base_prev_row: &aux_base_prev_row,
+        main_prev_row: &main_prev_row,
+        aux_rotated: RotatedRows {
+            offsets: aux_openings.rotations,
+            rows: &aux_rotated,
+        },
+        aux_base_rotated: RotatedRows {
+            offsets: aux_openings.rotations,
+            rows: &aux_base_rotated,
+        },
+        main_rotated: RotatedRows {
+            offsets: main_openings.rotations,
+            rows: &main_rotated,
+        },
+        aux_last_row: &aux_last_row,
+        aux_base_last_row: &aux_base_last_row,
+        main_last_row: &main_last_row,
+        public_values: &public_values,
+        statement_public_values: statement_public_values.iter().map(|&v| v.into()).collect(),
+        constraints: Vec::new(),
+    };
+    air.eval(&mut builder);
+    let constraints = builder.constraints;
+
+    let variables = [
+        main_local,
+        main_next,
+        aux_local,
+        aux_next,
+        aux_base_local,
+        aux_base_next,
+        main_prev_row,
+        aux_prev_row,
+        aux_base_prev_row,
+        main_rotated.concat(),
+        aux_rotated.concat(),
+        aux_base_rotated.concat(),
+        main_last_row,
+        aux_last_row,
+        aux_base_last_row,
+        public_values,
+        challenge_values,
+        aux_exposed_values,
+        statement_public_values,
+    ]
+    .into_iter()
+    .flatten()
+    .map(|v| v.entry)
+    .collect();
+    SymbolicConstraints {
+        constraints,
+        variables,
+    }
+}
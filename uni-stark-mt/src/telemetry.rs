@@ -0,0 +1,261 @@
+//! Prover and verifier metrics
+//!
+//! [`prove_with_metrics`](crate::prove_with_metrics) returns a [`ProverMetrics`] report
+//! alongside each proof (requires the `std` feature).
+//!
+//! With the `metrics` feature, the prover and verifier report through the [`metrics`]
+//! facade, so any installed recorder (Prometheus, OpenTelemetry, ...) picks them up.
+//! Without the feature every hook compiles to nothing.
+//!
+//! Names follow OpenTelemetry conventions: snake case with a unit suffix. Phase durations
+//! carry a `phase` label, verification outcomes an `outcome` label.
+
+use core::time::Duration;
+
+use crate::{Proof, StarkGenericConfig, VerificationError};
+
+/// Histogram of prover phase durations, labelled by `phase`.
+pub const PROVER_PHASE_DURATION_SECONDS: &str = "uni_stark_mt_prover_phase_duration_seconds";
+
+/// Counter of proofs produced.
+pub const PROOFS_TOTAL: &str = "uni_stark_mt_proofs_total";
+
+/// Histogram of serialized proof sizes.
+pub const PROOF_SIZE_BYTES: &str = "uni_stark_mt_proof_size_bytes";
+
+/// Counter of verification calls, labelled by `outcome`.
+pub const VERIFICATIONS_TOTAL: &str = "uni_stark_mt_verifications_total";
+
+/// A timed phase of the prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ProverPhase {
+    CommitMain,
+    Aux,
+    Quotient,
+    CommitQuotient,
+    Open,
+}
+
+impl ProverPhase {
+    /// Value of the `phase` label.
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    const fn name(self) -> &'static str {
+        match self {
+            Self::CommitMain => "commit_main",
+            Self::Aux => "aux",
+            Self::Quotient => "quotient",
+            Self::CommitQuotient => "commit_quotient",
+            Self::Open => "open",
+        }
+    }
+}
+
+/// Per-phase report of a single proof, returned by
+/// [`prove_with_metrics`](crate::prove_with_metrics).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverMetrics {
+    /// Time spent extending and committing the main trace
+    pub commit_main: Duration,
+
+    /// Time spent building and committing the auxiliary trace (zero if there is none)
+    pub aux: Duration,
+
+    /// Time spent evaluating constraints over the quotient domain
+    pub quotient: Duration,
+
+    /// Time spent committing the quotient chunks
+    pub commit_quotient: Duration,
+
+    /// Time spent computing the opening proof
+    pub open: Duration,
+
+    /// Number of rows of the trace
+    pub trace_height: usize,
+
+    /// Number of points the constraints were evaluated on
+    pub quotient_domain_size: usize,
+
+    /// Serialized size of the proof, in bytes
+    pub proof_size_bytes: usize,
+
+    /// Peak heap usage above the starting point while proving, in bytes
+    ///
+    /// `None` unless [`CountingAllocator`] is the global allocator.
+    pub peak_allocated_bytes: Option<usize>,
+}
+
+impl ProverMetrics {
+    /// Total time spent in all phases.
+    pub fn total(&self) -> Duration {
+        self.commit_main + self.aux + self.quotient + self.commit_quotient + self.open
+    }
+
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    fn duration_mut(&mut self, phase: ProverPhase) -> &mut Duration {
+        match phase {
+            ProverPhase::CommitMain => &mut self.commit_main,
+            ProverPhase::Aux => &mut self.aux,
+            ProverPhase::Quotient => &mut self.quotient,
+            ProverPhase::CommitQuotient => &mut self.commit_quotient,
+            ProverPhase::Open => &mut self.open,
+        }
+    }
+
+    /// Run `f`, adding its wall-clock duration to `phase`.
+    ///
+    /// Durations are only measured with the `std` feature; the `metrics` facade also
+    /// receives them when that feature is enabled.
+    #[cfg(feature = "std")]
+    pub(crate) fn time<R>(&mut self, phase: ProverPhase, f: impl FnOnce() -> R) -> R {
+        let start = std::time::Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        *self.duration_mut(phase) += elapsed;
+        #[cfg(feature = "metrics")]
+        metrics::histogram!(PROVER_PHASE_DURATION_SECONDS, "phase" => phase.name())
+            .record(elapsed.as_secs_f64());
+        result
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline(always)]
+    pub(crate) fn time<R>(&mut self, phase: ProverPhase, f: impl FnOnce() -> R) -> R {
+        let _ = phase;
+        f()
+    }
+}
+
+/// Global allocator wrapper that tracks heap usage for [`ProverMetrics`].
+///
+/// Install it to get [`ProverMetrics::peak_allocated_bytes`]:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOC: CountingAllocator = CountingAllocator::new(std::alloc::System);
+/// ```
+///
+/// Counters are process-wide, so proofs produced concurrently see each other's
+/// allocations.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = std::alloc::System> {
+    inner: A,
+}
+
+#[cfg(feature = "std")]
+mod counters {
+    use core::sync::atomic::{AtomicBool, AtomicUsize};
+
+    pub(super) static INSTALLED: AtomicBool = AtomicBool::new(false);
+    pub(super) static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+    pub(super) static PEAK: AtomicUsize = AtomicUsize::new(0);
+}
+
+#[cfg(feature = "std")]
+impl<A> CountingAllocator<A> {
+    /// Wrap `inner`, counting every allocation made through it.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    fn grow(size: usize) {
+        use core::sync::atomic::Ordering::Relaxed;
+        counters::INSTALLED.store(true, Relaxed);
+        let now = counters::ALLOCATED.fetch_add(size, Relaxed) + size;
+        counters::PEAK.fetch_max(now, Relaxed);
+    }
+
+    fn shrink(size: usize) {
+        counters::ALLOCATED.fetch_sub(size, core::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "std")]
+unsafe impl<A: core::alloc::GlobalAlloc> core::alloc::GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: core::alloc::Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: core::alloc::Layout) {
+        self.inner.dealloc(ptr, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: core::alloc::Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::shrink(layout.size());
+            Self::grow(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Measures peak heap usage between its creation and [`AllocationTracker::peak`].
+#[cfg(feature = "std")]
+pub(crate) struct AllocationTracker {
+    baseline: usize,
+}
+
+#[cfg(feature = "std")]
+impl AllocationTracker {
+    pub(crate) fn start() -> Self {
+        use core::sync::atomic::Ordering::Relaxed;
+        let baseline = counters::ALLOCATED.load(Relaxed);
+        counters::PEAK.store(baseline, Relaxed);
+        Self { baseline }
+    }
+
+    /// Peak usage above the baseline, or `None` if [`CountingAllocator`] isn't installed.
+    pub(crate) fn peak(&self) -> Option<usize> {
+        use core::sync::atomic::Ordering::Relaxed;
+        counters::INSTALLED
+            .load(Relaxed)
+            .then(|| counters::PEAK.load(Relaxed).saturating_sub(self.baseline))
+    }
+}
+
+/// Record a freshly produced proof.
+#[cfg(feature = "metrics")]
+pub(crate) fn record_proof<SC: StarkGenericConfig>(proof: &Proof<SC>) {
+    metrics::counter!(PROOFS_TOTAL).increment(1);
+    metrics::histogram!(PROOF_SIZE_BYTES).record(proof.size_in_bytes() as f64);
+}
+
+#[cfg(not(feature = "metrics"))]
+#[inline(always)]
+pub(crate) fn record_proof<SC: StarkGenericConfig>(_proof: &Proof<SC>) {}
+
+/// Record the outcome of a verification call.
+pub(crate) fn record_verification(result: &Result<(), VerificationError>) {
+    #[cfg(feature = "metrics")]
+    {
+        let outcome = match result {
+            Ok(()) => "ok",
+            Err(VerificationError::PcsVerificationFailed) => "pcs_failed",
+            Err(VerificationError::ConstraintVerificationFailed) => "constraint_failed",
+            Err(VerificationError::InvalidProof(_)) => "invalid_proof",
+            Err(VerificationError::PublicValuesMismatch { .. }) => "public_values_mismatch",
+            Err(VerificationError::ProtocolMismatch(_)) => "protocol_mismatch",
+            Err(VerificationError::InvalidConstraintProgram(_)) => "invalid_constraint_program",
+            Err(VerificationError::InvalidPowWitness) => "invalid_pow_witness",
+            Err(VerificationError::InvalidMachine(_)) => "invalid_machine",
+            Err(VerificationError::UnbalancedBus) => "unbalanced_bus",
+            Err(VerificationError::ConfigMismatch { .. }) => "config_mismatch",
+            Err(VerificationError::LogDegreeOutOfRange { .. }) => "log_degree_out_of_range",
+            Err(VerificationError::QuotientChunkCount { .. }) => "quotient_chunk_count",
+        };
+        metrics::counter!(VERIFICATIONS_TOTAL, "outcome" => outcome).increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = result;
+}
@@ -0,0 +1,108 @@
+//! Helpers for testing AIRs and the proof system, behind the `test-utils` feature
+//!
+//! [`roundtrip()`] proves and verifies a trace under a ready-made configuration (with the
+//! `presets` feature), so testing an AIR needs no configuration code. Not meant for
+//! production provers or verifiers: a [`SeededConfig`] transcript differs from the one of
+//! the configuration it wraps.
+//!
+//! [`FibonacciAir`] is the smallest AIR worth proving, for tests of the proof system
+//! itself.
+
+mod fibonacci;
+pub mod mutate;
+#[cfg(feature = "presets")]
+mod roundtrip;
+
+pub use fibonacci::*;
+#[cfg(feature = "presets")]
+pub use roundtrip::*;
+
+use p3_challenger::CanObserve;
+use p3_field::PrimeCharacteristicRing;
+
+use crate::{StarkGenericConfig, Val};
+
+/// A configuration whose challenger is seeded with a fixed value.
+///
+/// The seed is absorbed by every fresh challenger before anything else, so all challenges
+/// are a function of the seed and the proof alone: prove/verify round trips and golden
+/// proofs are bit-for-bit reproducible across runs and machines, and distinct seeds give
+/// independent transcripts for the same statement. Prover and verifier must use the same
+/// seed.
+#[derive(Clone, Debug)]
+pub struct SeededConfig<SC> {
+    inner: SC,
+    seed: u64,
+}
+
+impl<SC> SeededConfig<SC> {
+    /// Seed the challengers of `inner` with `seed`.
+    pub const fn new(inner: SC, seed: u64) -> Self {
+        Self { inner, seed }
+    }
+
+    /// The seed absorbed by every challenger.
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The wrapped configuration.
+    pub const fn inner(&self) -> &SC {
+        &self.inner
+    }
+}
+
+impl<SC: StarkGenericConfig> StarkGenericConfig for SeededConfig<SC> {
+    type Pcs = SC::Pcs;
+    type Challenge = SC::Challenge;
+    type Challenger = SC::Challenger;
+
+    fn pcs(&self) -> &Self::Pcs {
+        self.inner.pcs()
+    }
+
+    fn initialise_challenger(&self) -> Self::Challenger {
+        let mut challenger = self.inner.initialise_challenger();
+        // In 16-bit limbs, so distinct seeds stay distinct in every supported field
+        for limb in self.seed.to_le_bytes().chunks(2) {
+            challenger.observe(Val::<SC>::from_u16(u16::from_le_bytes([limb[0], limb[1]])));
+        }
+        challenger
+    }
+
+    fn is_zk(&self) -> usize {
+        self.inner.is_zk()
+    }
+
+    fn main_commit_batch_width(&self) -> Option<usize> {
+        self.inner.main_commit_batch_width()
+    }
+
+    fn domain_separator(&self) -> &[u8] {
+        self.inner.domain_separator()
+    }
+
+    fn extension_sampling(&self) -> crate::ExtensionSampling {
+        self.inner.extension_sampling()
+    }
+
+    fn uniform_aux_phase(&self) -> bool {
+        self.inner.uniform_aux_phase()
+    }
+
+    fn num_ood_points(&self) -> usize {
+        self.inner.num_ood_points()
+    }
+
+    fn pow_bits(&self) -> usize {
+        self.inner.pow_bits()
+    }
+
+    fn security_parameters(&self) -> Option<crate::SecurityParameters> {
+        self.inner.security_parameters()
+    }
+
+    fn max_log_degree(&self) -> Option<usize> {
+        self.inner.max_log_degree()
+    }
+}
@@ -0,0 +1,69 @@
+//! A minimal AIR for tests of the proof system
+
+use alloc::vec::Vec;
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::{AirLayout, AuxTraceBuilder, PuncturedAir, TraceGenerator};
+
+/// Fibonacci AIR: `(0, 1)` on the first row, `(a, b) -> (b, a + b)` on transitions
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for FibonacciAir {}
+
+impl<F> PuncturedAir<F> for FibonacciAir {}
+
+impl<F> AirLayout<F> for FibonacciAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for FibonacciAir {}
+
+impl<AB: AirBuilder> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+
+        builder.when_first_row().assert_zero(local[0].clone());
+        builder.when_first_row().assert_one(local[1].clone());
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local[1].clone(), next[0].clone());
+        when_transition.assert_eq(local[0].clone() + local[1].clone(), next[1].clone());
+    }
+}
+
+impl<F: Field> TraceGenerator<F> for FibonacciAir {
+    /// Number of rows
+    type Inputs = usize;
+
+    fn height(&self, height: &usize) -> usize {
+        *height
+    }
+
+    fn generate(&self, &height: &usize) -> RowMajorMatrix<F> {
+        fibonacci_trace(height)
+    }
+}
+
+/// The `n` rows of [`FibonacciAir`]'s trace.
+pub fn fibonacci_trace<F: Field>(n: usize) -> RowMajorMatrix<F> {
+    let mut values = Vec::with_capacity(2 * n);
+    let (mut left, mut right) = (F::ZERO, F::ONE);
+    for _ in 0..n {
+        values.extend([left, right]);
+        (left, right) = (right, left + right);
+    }
+    RowMajorMatrix::new(values, 2)
+}
@@ -0,0 +1,232 @@
+//! Proof mutations for negative tests
+//!
+//! A [`Mutation`] corrupts one component of a proof. [`mutations`] lists every single
+//! mutation that applies to a proof, so a soundness regression test can check `verify`
+//! rejects each of them:
+//!
+//! ```ignore
+//! for mutation in mutations(&proof) {
+//!     let mut bad = proof.clone();
+//!     mutation.apply(&mut bad);
+//!     assert!(verify(&config, &air, &bad, &public_values).is_err(), "{mutation:?}");
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use p3_field::PrimeCharacteristicRing;
+
+use crate::{Proof, StarkGenericConfig};
+
+/// A list of opened values in a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opening {
+    /// [`Proof::main_local`]
+    MainLocal,
+    /// [`Proof::main_next`]
+    MainNext,
+    /// [`Proof::main_prev`]
+    MainPrev,
+    /// [`Proof::main_last`]
+    MainLast,
+    /// [`Proof::aux_local`]
+    AuxLocal,
+    /// [`Proof::aux_next`]
+    AuxNext,
+    /// [`Proof::aux_prev`]
+    AuxPrev,
+    /// [`Proof::aux_base_local`]
+    AuxBaseLocal,
+    /// [`Proof::aux_base_next`]
+    AuxBaseNext,
+    /// [`Proof::aux_base_prev`]
+    AuxBasePrev,
+    /// [`Proof::aux_last`]
+    AuxLast,
+    /// [`Proof::aux_base_last`]
+    AuxBaseLast,
+    /// [`Proof::aux_exposed_values`]
+    AuxExposedValues,
+    /// The given chunk of [`Proof::quotient_chunks`]
+    QuotientChunk(usize),
+}
+
+impl Opening {
+    /// Every list a proof with `num_quotient_chunks` quotient chunks has, in proof field
+    /// order.
+    pub fn all(num_quotient_chunks: usize) -> impl Iterator<Item = Self> {
+        [
+            Self::MainLocal,
+            Self::MainNext,
+            Self::MainPrev,
+            Self::MainLast,
+            Self::AuxLocal,
+            Self::AuxNext,
+            Self::AuxPrev,
+            Self::AuxBaseLocal,
+            Self::AuxBaseNext,
+            Self::AuxBasePrev,
+            Self::AuxLast,
+            Self::AuxBaseLast,
+            Self::AuxExposedValues,
+        ]
+        .into_iter()
+        .chain((0..num_quotient_chunks).map(Self::QuotientChunk))
+    }
+
+    /// The values of this list in `proof`.
+    ///
+    /// # Panics
+    /// If `proof` has no such quotient chunk.
+    pub fn values<SC: StarkGenericConfig>(self, proof: &Proof<SC>) -> &Vec<SC::Challenge> {
+        match self {
+            Self::MainLocal => &proof.main_local,
+            Self::MainNext => &proof.main_next,
+            Self::MainPrev => &proof.main_prev,
+            Self::MainLast => &proof.main_last,
+            Self::AuxLocal => &proof.aux_local,
+            Self::AuxNext => &proof.aux_next,
+            Self::AuxPrev => &proof.aux_prev,
+            Self::AuxBaseLocal => &proof.aux_base_local,
+            Self::AuxBaseNext => &proof.aux_base_next,
+            Self::AuxBasePrev => &proof.aux_base_prev,
+            Self::AuxLast => &proof.aux_last,
+            Self::AuxBaseLast => &proof.aux_base_last,
+            Self::AuxExposedValues => &proof.aux_exposed_values,
+            Self::QuotientChunk(chunk) => &proof.quotient_chunks[chunk],
+        }
+    }
+
+    /// The values of this list in `proof`, mutably.
+    ///
+    /// # Panics
+    /// If `proof` has no such quotient chunk.
+    pub fn values_mut<SC: StarkGenericConfig>(
+        self,
+        proof: &mut Proof<SC>,
+    ) -> &mut Vec<SC::Challenge> {
+        match self {
+            Self::MainLocal => &mut proof.main_local,
+            Self::MainNext => &mut proof.main_next,
+            Self::MainPrev => &mut proof.main_prev,
+            Self::MainLast => &mut proof.main_last,
+            Self::AuxLocal => &mut proof.aux_local,
+            Self::AuxNext => &mut proof.aux_next,
+            Self::AuxPrev => &mut proof.aux_prev,
+            Self::AuxBaseLocal => &mut proof.aux_base_local,
+            Self::AuxBaseNext => &mut proof.aux_base_next,
+            Self::AuxBasePrev => &mut proof.aux_base_prev,
+            Self::AuxLast => &mut proof.aux_last,
+            Self::AuxBaseLast => &mut proof.aux_base_last,
+            Self::AuxExposedValues => &mut proof.aux_exposed_values,
+            Self::QuotientChunk(chunk) => &mut proof.quotient_chunks[chunk],
+        }
+    }
+}
+
+/// A commitment in a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitmentSlot {
+    /// The given entry of [`Proof::main_commits`]
+    Main(usize),
+    /// [`Proof::challenge_free_commit`]
+    ChallengeFree,
+    /// [`Proof::aux_commit`]
+    Aux,
+    /// [`Proof::quotient_commit`]
+    Quotient,
+}
+
+impl CommitmentSlot {
+    /// Every commitment `proof` carries, in proof field order.
+    pub fn all<SC: StarkGenericConfig>(proof: &Proof<SC>) -> Vec<Self> {
+        let mut slots: Vec<_> = (0..proof.main_commits.len()).map(Self::Main).collect();
+        if proof.challenge_free_commit.is_some() {
+            slots.push(Self::ChallengeFree);
+        }
+        if proof.aux_commit.is_some() {
+            slots.push(Self::Aux);
+        }
+        slots.push(Self::Quotient);
+        slots
+    }
+
+    /// The commitment in this slot of `proof`, mutably.
+    ///
+    /// # Panics
+    /// If `proof` has no commitment in this slot.
+    pub fn commitment_mut<SC: StarkGenericConfig>(
+        self,
+        proof: &mut Proof<SC>,
+    ) -> &mut crate::Commitment<SC> {
+        match self {
+            Self::Main(batch) => &mut proof.main_commits[batch],
+            Self::ChallengeFree => proof
+                .challenge_free_commit
+                .as_mut()
+                .expect("proof has no challenge-free commitment"),
+            Self::Aux => proof
+                .aux_commit
+                .as_mut()
+                .expect("proof has no aux commitment"),
+            Self::Quotient => &mut proof.quotient_commit,
+        }
+    }
+}
+
+/// A corruption of one proof component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Add one to an opened value
+    FlipOpenedValue {
+        /// List holding the value
+        opening: Opening,
+        /// Position in the list
+        index: usize,
+    },
+    /// Exchange two commitments
+    SwapCommitments(CommitmentSlot, CommitmentSlot),
+    /// Add `delta` to the degree, wrapping around
+    ChangeLogDegree(i8),
+}
+
+impl Mutation {
+    /// Corrupt `proof`.
+    ///
+    /// # Panics
+    /// If `proof` lacks the component the mutation targets.
+    pub fn apply<SC: StarkGenericConfig>(self, proof: &mut Proof<SC>) {
+        match self {
+            Self::FlipOpenedValue { opening, index } => {
+                opening.values_mut(proof)[index] += SC::Challenge::ONE;
+            }
+            Self::SwapCommitments(a, b) => {
+                let a_commit = a.commitment_mut(proof).clone();
+                let b_commit = core::mem::replace(b.commitment_mut(proof), a_commit);
+                *a.commitment_mut(proof) = b_commit;
+            }
+            Self::ChangeLogDegree(delta) => {
+                proof.log_degree = proof.log_degree.wrapping_add_signed(delta);
+            }
+        }
+    }
+}
+
+/// Every single mutation that applies to `proof`: each opened value flipped, each pair of
+/// commitments swapped, and the degree one lower and one higher.
+pub fn mutations<SC: StarkGenericConfig>(proof: &Proof<SC>) -> Vec<Mutation> {
+    let opened = Opening::all(proof.quotient_chunks.len()).flat_map(|opening| {
+        (0..opening.values(proof).len())
+            .map(move |index| Mutation::FlipOpenedValue { opening, index })
+    });
+    let slots = CommitmentSlot::all(proof);
+    let swaps = slots.iter().enumerate().flat_map(|(i, &a)| {
+        slots[i + 1..]
+            .iter()
+            .map(move |&b| Mutation::SwapCommitments(a, b))
+    });
+    opened
+        .chain(swaps)
+        .chain([Mutation::ChangeLogDegree(-1), Mutation::ChangeLogDegree(1)])
+        .collect()
+}
@@ -0,0 +1,103 @@
+//! Prove/verify round trips under a ready-made configuration
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use p3_air::Air;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use super::SeededConfig;
+use crate::config::baby_bear_poseidon2::{Challenge, Config, Val};
+use crate::statements::check_statement_layout;
+use crate::{
+    check_constraints, prove, verify, ConstraintViolations, DebugConstraintBuilder, MultiTraceAir,
+    Proof, ProverError, ProverFolder, StarkConfigBuilder, StarkGenericConfig, TraceProvenance,
+    VerificationError, VerifierFolder,
+};
+
+/// Configuration of [`roundtrip`]: BabyBear + Poseidon2 with few FRI queries, seeded so
+/// round trips are reproducible.
+pub type RoundtripConfig = SeededConfig<Config>;
+
+/// Seed of [`roundtrip_config`]
+const ROUNDTRIP_SEED: u64 = 0x5eed;
+
+/// FRI queries of [`roundtrip_config`]: enough to catch a broken AIR, not for security
+const ROUNDTRIP_QUERIES: usize = 16;
+
+/// The configuration [`roundtrip`] proves and verifies under.
+pub fn roundtrip_config() -> RoundtripConfig {
+    let config = StarkConfigBuilder::baby_bear()
+        .queries(ROUNDTRIP_QUERIES)
+        .build()
+        .expect("round trip parameters are valid");
+    SeededConfig::new(config, ROUNDTRIP_SEED)
+}
+
+/// Why a [`roundtrip`] failed.
+#[derive(Debug)]
+pub enum RoundtripError {
+    /// The trace doesn't satisfy the AIR, so nothing was proven
+    Constraints {
+        /// Every violated constraint, on every row
+        violations: ConstraintViolations<Challenge>,
+        /// The violations, one per line
+        report: String,
+    },
+
+    /// Proving failed, e.g. on malformed public values
+    Prove(ProverError),
+
+    /// The proof didn't verify although the trace satisfies the AIR, which points at a
+    /// mismatch between the AIR's prover and verifier sides
+    Verify(VerificationError),
+}
+
+/// Prove `trace` with `public_values` for `air` under [`roundtrip_config`], then verify
+/// the proof.
+///
+/// The trace is checked against the constraints first, with challenges drawn from the
+/// configuration's challenger, so a broken trace is reported row by row rather than as
+/// a failed proof.
+///
+/// # Errors
+/// See [`RoundtripError`].
+pub fn roundtrip<A>(
+    air: &A,
+    trace: RowMajorMatrix<Val>,
+    public_values: &[Val],
+) -> Result<Proof<RoundtripConfig>, RoundtripError>
+where
+    A: MultiTraceAir<Val, Challenge>
+        + for<'a> Air<ProverFolder<'a, RoundtripConfig>>
+        + for<'a> Air<VerifierFolder<'a, RoundtripConfig>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val, Challenge>>,
+{
+    let config = roundtrip_config();
+
+    // Malformed public values or statements are the prover's to report
+    let well_formed = public_values.len() == air.num_public_values()
+        && check_statement_layout(air.num_statements(), public_values.len(), trace.height())
+            .is_ok();
+    if well_formed {
+        let mut challenger = config.initialise_challenger();
+        let challenges: Vec<Challenge> =
+            air.challenge_sampling()
+                .sample(air.challenge_names().len(), || {
+                    config
+                        .extension_sampling()
+                        .draw::<Val, _, _>(&mut challenger)
+                });
+        check_constraints(air, &trace, None, &challenges, public_values).map_err(|violations| {
+            RoundtripError::Constraints {
+                report: violations.report(&TraceProvenance::new()),
+                violations,
+            }
+        })?;
+    }
+
+    let proof = prove(&config, air, trace, public_values).map_err(RoundtripError::Prove)?;
+    verify(&config, air, &proof, public_values).map_err(RoundtripError::Verify)?;
+    Ok(proof)
+}
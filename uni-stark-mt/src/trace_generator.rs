@@ -0,0 +1,67 @@
+//! Main trace generation
+//!
+//! [`TraceGenerator`] is the standard way for an AIR to produce its main trace from the
+//! inputs of a computation. AIRs whose rows only depend on the inputs and the row index
+//! implement [`generate_row`](TraceGenerator::generate_row), and get a trace filled in
+//! parallel under the `parallel` feature. AIRs with rows depending on earlier rows, such
+//! as Fibonacci, override [`generate`](TraceGenerator::generate) instead.
+
+use p3_air::BaseAir;
+use p3_field::Field;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+
+/// Produces the main trace of an AIR from the inputs of a computation.
+///
+/// # Example
+///
+/// ```ignore
+/// impl<F: Field> TraceGenerator<F> for CounterAir {
+///     type Inputs = usize;
+///
+///     fn height(&self, rows: &usize) -> usize {
+///         *rows
+///     }
+///
+///     fn generate_row(&self, _rows: &usize, index: usize, row: &mut [F]) {
+///         row[0] = F::from_usize(index);
+///     }
+/// }
+///
+/// let trace = CounterAir.generate(&8);
+/// ```
+pub trait TraceGenerator<F: Field>: BaseAir<F> + Sync {
+    /// Inputs of the computation the trace is generated for
+    type Inputs: ?Sized + Sync;
+
+    /// Number of rows of the trace of `inputs`, a power of two.
+    fn height(&self, inputs: &Self::Inputs) -> usize;
+
+    /// Fill `row`, row `index` of the trace of `inputs`.
+    ///
+    /// `row` is [`width()`](BaseAir::width) wide and zeroed. Rows are filled in no
+    /// particular order, and in parallel under the `parallel` feature.
+    ///
+    /// # Panics
+    /// The default panics: implement either this or [`generate`](Self::generate).
+    fn generate_row(&self, inputs: &Self::Inputs, index: usize, row: &mut [F]) {
+        let _ = (inputs, index, row);
+        unimplemented!("implement generate_row or generate");
+    }
+
+    /// The main trace of `inputs`.
+    ///
+    /// The default allocates a zeroed trace of [`height`](Self::height) rows and fills
+    /// each with [`generate_row`](Self::generate_row).
+    fn generate(&self, inputs: &Self::Inputs) -> RowMajorMatrix<F> {
+        let (width, height) = (self.width(), self.height(inputs));
+        let mut values = F::zero_vec(width * height);
+        if width > 0 {
+            values
+                .par_chunks_mut(width)
+                .enumerate()
+                .for_each(|(index, row)| self.generate_row(inputs, index, row));
+        }
+        RowMajorMatrix::new(values, width)
+    }
+}
@@ -0,0 +1,57 @@
+//! Fiat-Shamir transcript helpers shared by the prover and verifier
+//!
+//! Commitments are absorbed through the challenger's own `CanObserve` impl for the
+//! PCS's commitment type, which is where their canonical encoding lives: field-element
+//! digests (e.g. of a Poseidon2 Merkle tree) are absorbed element by element into a
+//! duplex challenger, while byte digests (e.g. of Keccak) go through the serializing
+//! challenger's byte layer. The prover and verifier absorb the same commitment values in
+//! the same order, so their transcripts agree for every MMCS.
+
+use alloc::vec::Vec;
+
+use p3_field::PrimeCharacteristicRing;
+
+/// Phases of the proof transcript.
+///
+/// The prover and verifier observe each phase's tag before its first message, so
+/// transcripts of differently shaped proofs (e.g. with and without an auxiliary phase)
+/// can't be aligned with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptPhase {
+    /// Main trace commitments and public values
+    Main,
+    /// Auxiliary challenges, commitment and exposed values
+    Aux,
+    /// Constraint combination challenge and quotient commitment
+    Quotient,
+    /// Out-of-domain point and PCS opening
+    Opening,
+}
+
+impl TranscriptPhase {
+    /// Tag of the phase, its name in four ASCII bytes read as a big-endian integer.
+    ///
+    /// Tags stay below 2^31 so they are distinct in every field the crate supports.
+    pub const fn tag(self) -> u32 {
+        u32::from_be_bytes(match self {
+            Self::Main => *b"MAIN",
+            Self::Aux => *b"AUXT",
+            Self::Quotient => *b"QUOT",
+            Self::Opening => *b"OPEN",
+        })
+    }
+
+    /// The tag as a field element, as the transcript absorbs it.
+    pub fn to_field_element<F: PrimeCharacteristicRing>(self) -> F {
+        F::from_u32(self.tag())
+    }
+}
+
+/// Encode an application domain separator, see
+/// [`StarkGenericConfig::domain_separator`](crate::StarkGenericConfig::domain_separator),
+/// as field elements: its length, then one element per byte.
+pub(crate) fn domain_separator_elements<F: PrimeCharacteristicRing>(separator: &[u8]) -> Vec<F> {
+    core::iter::once(F::from_usize(separator.len()))
+        .chain(separator.iter().map(|&byte| F::from_u8(byte)))
+        .collect()
+}
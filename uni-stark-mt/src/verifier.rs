@@ -2,17 +2,34 @@
 
 use alloc::vec;
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use itertools::Itertools;
-use p3_air::Air;
-use p3_challenger::{CanObserve, CanSample};
+use p3_air::{Air, BaseAir};
+use p3_challenger::{CanObserve, CanSample, FieldChallenger, GrindingChallenger};
 use p3_commit::{Pcs, PolynomialSpace};
 use p3_field::{BasedVectorSpace, Field, PrimeCharacteristicRing};
+#[cfg(feature = "tracing")]
 use tracing::instrument;
 
-use crate::{Challenge, Domain, MultiTraceAir, Proof, Val, VerifierFolder};
+use crate::openings::{boundary_rows, check_boundary_constraints, OodWindow, RowOpenings};
+use crate::program::ProgramAir;
+use crate::prover::max_log_degree;
+use crate::statements::StatementBoundaries;
+use crate::telemetry::record_verification;
+use crate::{
+    config_digest, domain_separator_elements, keygen, main_commit_batches,
+    placeholder_aux_commitment, Challenge, ChallengeSet, Challenger, Claims, Commitment,
+    ConfigDigest, ConstraintProgram, DebugConstraintBuilder, Domain, MultiTraceAir, OodOpening,
+    OpeningProof, OpeningSchedule, PcsProof, Proof, ProtocolMismatch, RotatedRows,
+    StatementSelectors, TraceDomain, TranscriptPhase, Val, VerifierFolder, VerifyingKey,
+};
 
 /// Verification error types
+///
+/// Verification never panics on a malformed proof; every structural problem is reported
+/// as [`VerificationError::InvalidProof`]. See [`verify_with_vk`] for the exact
+/// guarantee.
 #[derive(Debug)]
 pub enum VerificationError {
     /// PCS verification failed
@@ -21,6 +38,100 @@ pub enum VerificationError {
     ConstraintVerificationFailed,
     /// Invalid proof structure
     InvalidProof(&'static str),
+    /// The number of public values doesn't match the verifying key
+    PublicValuesMismatch {
+        /// Number of public values the AIR expects
+        expected: usize,
+        /// Number of public values supplied
+        actual: usize,
+    },
+    /// The proof was produced under a different protocol variant
+    ProtocolMismatch(ProtocolMismatch),
+    /// The constraint program doesn't fit the verifying key, see
+    /// [`ConstraintProgram::check`]
+    InvalidConstraintProgram(&'static str),
+    /// The proof-of-work witness doesn't meet the grinding difficulty, see
+    /// [`StarkGenericConfig::pow_bits`](crate::StarkGenericConfig::pow_bits)
+    InvalidPowWitness,
+    /// The chips or configuration don't make a machine, see
+    /// [`verify_machine`](crate::verify_machine)
+    InvalidMachine(&'static str),
+    /// The bus contributions of a machine's chips don't add up to zero, see
+    /// [`check_bus_balance`](crate::check_bus_balance)
+    UnbalancedBus,
+    /// The proof was made under other parameters than the verifier's configuration, see
+    /// [`Proof::config_digest`]
+    ConfigMismatch {
+        /// Digest of the verifier's configuration
+        expected: ConfigDigest,
+        /// Digest the proof carries
+        actual: ConfigDigest,
+    },
+    /// The proof claims a trace taller than the verifier accepts
+    LogDegreeOutOfRange {
+        /// log2 of the trace height the proof claims
+        log_degree: u8,
+        /// log2 of the tallest trace the PCS supports for the AIR and the configuration
+        /// accepts, see [`StarkGenericConfig::max_log_degree`](crate::StarkGenericConfig::max_log_degree)
+        max_log_degree: usize,
+    },
+    /// The proof doesn't carry one quotient chunk per unit of the AIR's quotient degree, see
+    /// [`VerifyingKey::log_quotient_degree`]
+    QuotientChunkCount {
+        /// Number of chunks the AIR's constraint degree calls for
+        expected: usize,
+        /// Number of chunks in the proof
+        actual: usize,
+    },
+}
+
+impl core::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::PcsVerificationFailed => f.write_str("the PCS opening proof doesn't verify"),
+            Self::ConstraintVerificationFailed => {
+                f.write_str("the opened values don't satisfy the constraints")
+            }
+            Self::InvalidProof(reason) => write!(f, "invalid proof: {reason}"),
+            Self::PublicValuesMismatch { expected, actual } => {
+                write!(f, "expected {expected} public values, got {actual}")
+            }
+            Self::ProtocolMismatch(mismatch) => write!(f, "protocol mismatch: {mismatch}"),
+            Self::InvalidConstraintProgram(reason) => {
+                write!(f, "invalid constraint program: {reason}")
+            }
+            Self::InvalidPowWitness => {
+                f.write_str("the proof-of-work witness doesn't meet the grinding difficulty")
+            }
+            Self::InvalidMachine(reason) => write!(f, "invalid machine: {reason}"),
+            Self::UnbalancedBus => f.write_str("the chips' bus contributions don't add up to zero"),
+            Self::ConfigMismatch { expected, actual } => write!(
+                f,
+                "the proof was made under other parameters (configuration digest {actual:02x?}, \
+                 expected {expected:02x?})"
+            ),
+            Self::LogDegreeOutOfRange {
+                log_degree,
+                max_log_degree,
+            } => write!(
+                f,
+                "trace height 2^{log_degree} exceeds the largest accepted, 2^{max_log_degree}"
+            ),
+            Self::QuotientChunkCount { expected, actual } => {
+                write!(f, "expected {expected} quotient chunks, got {actual}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerificationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ProtocolMismatch(mismatch) => Some(mismatch),
+            _ => None,
+        }
+    }
 }
 
 /// Recomposes the quotient polynomial from its chunks evaluated at a point.
@@ -71,6 +182,183 @@ where
         .sum::<Challenge<SC>>()
 }
 
+/// Recombines opened extension-field columns from the openings of their flattened
+/// base-field columns.
+///
+/// Extension-field auxiliary columns are committed as `DIMENSION` base-field columns each,
+/// so the PCS opens `DIMENSION` values per column; this returns one value per column, as
+/// the constraints read it.
+pub fn recompose_aux_openings<SC>(flat: &[Challenge<SC>]) -> Vec<Challenge<SC>>
+where
+    SC: crate::StarkGenericConfig,
+{
+    flat.chunks_exact(<Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION)
+        .map(|coefficients| {
+            coefficients
+                .iter()
+                .enumerate()
+                .map(|(e_i, &c)| Challenge::<SC>::ith_basis_element(e_i).unwrap() * c)
+                .sum()
+        })
+        .collect()
+}
+
+/// Check `config` accepts a trace of `2^log_degree` rows for the AIR of `vk`, see
+/// [`max_log_degree`].
+pub(crate) fn check_log_degree<SC>(
+    config: &SC,
+    vk: &VerifyingKey,
+    log_degree: u8,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    let max_log_degree = max_log_degree(config, vk.log_quotient_degree);
+    if log_degree as usize > max_log_degree {
+        return Err(VerificationError::LogDegreeOutOfRange {
+            log_degree,
+            max_log_degree,
+        });
+    }
+    Ok(())
+}
+
+/// Check that the opened values in `claims` have the dimensions implied by `vk`.
+///
+/// Everything after this check may index into the opened values without bounds errors.
+fn check_proof_shape<SC>(vk: &VerifyingKey, claims: &Claims<SC>) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    let ext_degree = <Challenge<SC> as BasedVectorSpace<Val<SC>>>::DIMENSION;
+
+    // Only auxiliary traces committed after the main trace, on their own, can be shorter
+    let aux_degree_fixed = !vk.has_aux_phase() || vk.has_challenge_free_aux();
+    if claims.log_aux_degree > claims.log_degree
+        || (aux_degree_fixed && claims.log_aux_degree != claims.log_degree)
+    {
+        return Err(VerificationError::InvalidProof(
+            "auxiliary trace degree out of range",
+        ));
+    }
+    // Rows a trace isn't opened at carry no values; rotated rows come one per rotation
+    let scheduled = |opened: bool, width: usize| if opened { width } else { 0 };
+    let rotated = |rows: &[Vec<Challenge<SC>>], num_rotations: usize, width: usize| {
+        rows.len() == num_rotations && rows.iter().all(|row| row.len() == width)
+    };
+    let main = vk.main_openings;
+    if claims.main_local.len() != vk.main_width
+        || claims.main_next.len() != scheduled(main.next_row, vk.main_width)
+        || claims.main_prev.len() != scheduled(main.prev_row, vk.main_width)
+        || !rotated(&claims.main_rotated, main.rotations.len(), vk.main_width)
+        || claims.main_last.len() != scheduled(main.last_row, vk.main_width)
+    {
+        return Err(VerificationError::InvalidProof(
+            "main trace openings have the wrong width",
+        ));
+    }
+    // The auxiliary trace is committed flattened to base-field columns,
+    // and an AIR without one opens it at no rotation
+    let aux = vk.aux_openings;
+    let aux_width = vk.aux_width * ext_degree;
+    let aux_rotations = if vk.has_aux_trace() {
+        aux.rotations.len()
+    } else {
+        0
+    };
+    if claims.aux_local.len() != aux_width
+        || claims.aux_next.len() != scheduled(aux.next_row, aux_width)
+        || claims.aux_prev.len() != scheduled(aux.prev_row, aux_width)
+        || !rotated(&claims.aux_rotated, aux_rotations, aux_width)
+        || claims.aux_last.len() != scheduled(aux.last_row, aux_width)
+    {
+        return Err(VerificationError::InvalidProof(
+            "auxiliary trace openings have the wrong width",
+        ));
+    }
+    if claims.aux_base_local.len() != vk.aux_base_width
+        || claims.aux_base_next.len() != scheduled(aux.next_row, vk.aux_base_width)
+        || claims.aux_base_prev.len() != scheduled(aux.prev_row, vk.aux_base_width)
+        || !rotated(&claims.aux_base_rotated, aux_rotations, vk.aux_base_width)
+        || claims.aux_base_last.len() != scheduled(aux.last_row, vk.aux_base_width)
+    {
+        return Err(VerificationError::InvalidProof(
+            "base-field auxiliary trace openings have the wrong width",
+        ));
+    }
+    // Pinned rows open the whole main trace, once per row
+    if claims.main_boundary.len() != boundary_rows(&vk.boundary_constraints).len()
+        || claims
+            .main_boundary
+            .iter()
+            .any(|row| row.len() != vk.main_width)
+    {
+        return Err(VerificationError::InvalidProof(
+            "boundary row openings have the wrong shape",
+        ));
+    }
+    if claims.aux_exposed_values.len() != vk.num_aux_exposed_values {
+        return Err(VerificationError::InvalidProof(
+            "wrong number of exposed auxiliary values",
+        ));
+    }
+    if claims.quotient_chunks.len() != 1 << vk.log_quotient_degree {
+        return Err(VerificationError::QuotientChunkCount {
+            expected: 1 << vk.log_quotient_degree,
+            actual: claims.quotient_chunks.len(),
+        });
+    }
+    if claims
+        .quotient_chunks
+        .iter()
+        .any(|chunk| chunk.len() != ext_degree)
+    {
+        return Err(VerificationError::InvalidProof(
+            "quotient chunk has the wrong width",
+        ));
+    }
+
+    // Further out-of-domain points open the window around ζ of every trace again
+    let num_ood_points = vk.protocol_id.num_ood_points as usize;
+    if claims.extra_ood_openings.len() != num_ood_points.saturating_sub(1) {
+        return Err(VerificationError::InvalidProof(
+            "wrong number of out-of-domain openings",
+        ));
+    }
+    for opening in &claims.extra_ood_openings {
+        if opening.main_local.len() != claims.main_local.len()
+            || opening.main_next.len() != claims.main_next.len()
+            || opening.main_prev.len() != claims.main_prev.len()
+            || !rotated(&opening.main_rotated, main.rotations.len(), vk.main_width)
+            || opening.aux_local.len() != claims.aux_local.len()
+            || opening.aux_next.len() != claims.aux_next.len()
+            || opening.aux_prev.len() != claims.aux_prev.len()
+            || !rotated(&opening.aux_rotated, aux_rotations, aux_width)
+            || opening.aux_base_local.len() != claims.aux_base_local.len()
+            || opening.aux_base_next.len() != claims.aux_base_next.len()
+            || opening.aux_base_prev.len() != claims.aux_base_prev.len()
+            || !rotated(&opening.aux_base_rotated, aux_rotations, vk.aux_base_width)
+        {
+            return Err(VerificationError::InvalidProof(
+                "out-of-domain openings have the wrong width",
+            ));
+        }
+        if opening.quotient_chunks.len() != claims.quotient_chunks.len()
+            || opening
+                .quotient_chunks
+                .iter()
+                .any(|chunk| chunk.len() != ext_degree)
+        {
+            return Err(VerificationError::InvalidProof(
+                "out-of-domain quotient chunks have the wrong shape",
+            ));
+        }
+    }
+    Ok(())
+}
+
 /// Verify a multi-trace STARK proof.
 ///
 /// # Arguments
@@ -82,7 +370,6 @@ where
 /// # Returns
 /// - `Ok(())` if the proof is valid
 /// - `Err(VerificationError)` if verification fails
-#[instrument(skip_all, fields(log_degree = proof.log_degree))]
 pub fn verify<SC, A>(
     config: &SC,
     air: &A,
@@ -91,131 +378,943 @@ pub fn verify<SC, A>(
 ) -> Result<(), VerificationError>
 where
     SC: crate::StarkGenericConfig,
-    A: MultiTraceAir<Val<SC>, Challenge<SC>> + for<'a> Air<VerifierFolder<'a, SC>>,
+    Domain<SC>: TraceDomain,
+    A: MultiTraceAir<Val<SC>, Challenge<SC>>
+        + for<'a> Air<VerifierFolder<'a, SC>>
+        + for<'a> Air<DebugConstraintBuilder<'a, Val<SC>, Challenge<SC>>>,
+{
+    let vk = keygen(config, air);
+    verify_with_vk(config, &vk, air, proof, public_values)
+}
+
+/// Verify a multi-trace STARK proof against a precomputed [`VerifyingKey`].
+///
+/// All structural data (widths, challenge and public value counts, punctured rows,
+/// quotient degree, protocol variant, fingerprint) is taken from `vk`; `air` is only used to evaluate the constraints
+/// at the out-of-domain point.
+///
+/// # Concurrency
+/// Verification keeps no shared or global state: every call builds its own challenger
+/// from `config` and only reads its other arguments, and its allocations are bounded by
+/// the size of `proof`. Any number of proofs can therefore be verified in parallel
+/// against the same `config`, `vk` and `air`.
+///
+/// # Untrusted input
+/// `proof` and `public_values` may be adversarial: no value of them makes this function
+/// panic, overflow or allocate beyond the size of `proof`. Every malformed input is
+/// reported as a [`VerificationError`]. This covers the crate's own checks and
+/// arithmetic; the opening proof is handed to the PCS, which must uphold the same
+/// property for its `verify`. `config`, `vk` and `air` are trusted, but a key whose main
+/// width isn't the AIR's is still reported rather than read past.
+///
+/// # Returns
+/// - `Ok(())` if the proof is valid
+/// - `Err(VerificationError)` if verification fails
+#[cfg_attr(feature = "tracing", instrument(skip_all, fields(log_degree = proof.log_degree)))]
+pub fn verify_with_vk<SC, A>(
+    config: &SC,
+    vk: &VerifyingKey,
+    air: &A,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let result = verify_with_vk_inner(
+        config,
+        vk,
+        air,
+        proof,
+        public_values,
+        &mut VerifyingCache::default(),
+    );
+    record_verification(&result);
+    result
+}
+
+/// Verify a multi-trace STARK proof against a [`VerifyingKey`] and the AIR's constraints
+/// as a [`ConstraintProgram`], without the AIR itself.
+///
+/// Verifiers receiving `vk` and `program` as data, e.g. deserialized from the prover's
+/// side, need no Rust type implementing `Air`: the constraints are interpreted at the
+/// out-of-domain point. The verdict is the one [`verify_with_vk`] reaches with the AIR
+/// `program` was compiled from.
+///
+/// # Errors
+/// As [`verify_with_vk`], and [`VerificationError::InvalidConstraintProgram`] if
+/// `program` doesn't fit `vk`.
+pub fn verify_program<SC>(
+    config: &SC,
+    vk: &VerifyingKey,
+    program: &ConstraintProgram<Val<SC>>,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    program
+        .check(vk)
+        .map_err(VerificationError::InvalidConstraintProgram)?;
+    let air = ProgramAir {
+        program,
+        main_width: vk.main_width,
+    };
+    verify_with_vk(config, vk, &air, proof, public_values)
+}
+
+/// A verifier for one key under one configuration, keeping what doesn't depend on the
+/// proof between verifications.
+///
+/// [`verify_with_vk`] derives the configuration's digest and placeholder commitment, and
+/// the trace, auxiliary and quotient chunk domains with the points of the punctured,
+/// last and pinned rows, on every call. A `Verifier` derives the former once, in
+/// [`new`](Self::new), and the latter once per trace height, so verifying many proofs of
+/// the same shape only pays for what depends on the proof: the transcript, the
+/// selectors at the out-of-domain points and the constraints there. Together with a key
+/// from [`keygen`] or a deserialized one, it doesn't redo the AIR's symbolic analysis
+/// either, as [`verify`] does.
+///
+/// Verifying takes `&mut self`; threads verifying in parallel keep a `Verifier` each.
+/// The verdicts are the ones [`verify_with_vk`] reaches.
+pub struct Verifier<'a, SC: crate::StarkGenericConfig> {
+    config: &'a SC,
+    vk: &'a VerifyingKey,
+    cache: VerifyingCache<SC>,
+}
+
+impl<'a, SC> Verifier<'a, SC>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    /// A verifier for proofs against `vk` under `config`, deriving the configuration's
+    /// digest and placeholder commitment.
+    pub fn new(config: &'a SC, vk: &'a VerifyingKey) -> Self {
+        Self {
+            config,
+            vk,
+            cache: VerifyingCache {
+                config_digest: Some(config_digest(config)),
+                placeholder: (!vk.has_aux_phase() && config.uniform_aux_phase())
+                    .then(|| placeholder_aux_commitment(config)),
+                domains: None,
+            },
+        }
+    }
+
+    /// The key proofs are verified against.
+    pub fn vk(&self) -> &VerifyingKey {
+        self.vk
+    }
+
+    /// Verify `proof` with `public_values`, as [`verify_with_vk`] does.
+    ///
+    /// # Errors
+    /// As for [`verify_with_vk`].
+    #[cfg_attr(feature = "tracing", instrument(skip_all, fields(log_degree = proof.log_degree)))]
+    pub fn verify<A>(
+        &mut self,
+        air: &A,
+        proof: &Proof<SC>,
+        public_values: &[Val<SC>],
+    ) -> Result<(), VerificationError>
+    where
+        A: for<'b> Air<VerifierFolder<'b, SC>>,
+    {
+        let result = verify_with_vk_inner(
+            self.config,
+            self.vk,
+            air,
+            proof,
+            public_values,
+            &mut self.cache,
+        );
+        record_verification(&result);
+        result
+    }
+}
+
+/// The PCS check [`verify_claims`] leaves to [`verify_openings`]: the commitments to
+/// open, the points and claimed values to open them at, and the transcript state the
+/// opening proof is checked in.
+pub struct DeferredOpening<SC: crate::StarkGenericConfig> {
+    /// Each commitment, with the points and claimed values of each of its domains
+    #[allow(clippy::type_complexity)]
+    coms_to_verify: Vec<(
+        Commitment<SC>,
+        Vec<(Domain<SC>, Vec<(Challenge<SC>, Vec<Challenge<SC>>)>)>,
+    )>,
+
+    /// Challenger after sampling the out-of-domain point
+    challenger: Challenger<SC>,
+}
+
+/// First stage of verification: check `claims` algebraically, deferring the PCS check.
+///
+/// Replays the transcript of `claims` and checks the constraints at the out-of-domain
+/// point against the claimed opened values, as [`verify_with_vk`] does. The claimed
+/// values are only trusted once [`verify_openings`] has checked the opening proof against
+/// the returned [`DeferredOpening`]; the two stages together accept exactly the proofs
+/// [`verify_with_vk`] accepts. When both stages fail, the errors may differ from
+/// [`verify_with_vk`]'s, which checks the openings first.
+///
+/// # Errors
+/// As [`verify_with_vk`], but never [`VerificationError::PcsVerificationFailed`].
+pub fn verify_claims<SC, A>(
+    config: &SC,
+    vk: &VerifyingKey,
+    air: &A,
+    claims: &Claims<SC>,
+    public_values: &[Val<SC>],
+) -> Result<DeferredOpening<SC>, VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let (deferred, constraints_hold) = check_claims(
+        config,
+        vk,
+        air,
+        claims,
+        public_values,
+        &mut VerifyingCache::default(),
+    )?;
+    if !constraints_hold {
+        return Err(VerificationError::ConstraintVerificationFailed);
+    }
+    Ok(deferred)
+}
+
+/// Second stage of verification: check `opening_proof` against the claims
+/// [`verify_claims`] checked.
+///
+/// # Errors
+/// [`VerificationError::PcsVerificationFailed`] if the opening proof doesn't open the
+/// commitments to the claimed values.
+pub fn verify_openings<SC>(
+    config: &SC,
+    deferred: DeferredOpening<SC>,
+    opening_proof: &OpeningProof<SC>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+{
+    check_deferred_opening(config, deferred, &opening_proof.0)
+}
+
+fn verify_with_vk_inner<SC, A>(
+    config: &SC,
+    vk: &VerifyingKey,
+    air: &A,
+    proof: &Proof<SC>,
+    public_values: &[Val<SC>],
+    cache: &mut VerifyingCache<SC>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: for<'a> Air<VerifierFolder<'a, SC>>,
+{
+    let (deferred, constraints_hold) =
+        check_claims(config, vk, air, &proof.claims(), public_values, cache)?;
+    // Check the openings first: claimed values that don't match the commitments are
+    // reported as such rather than as failing constraints
+    check_deferred_opening(config, deferred, &proof.opening_proof)?;
+    if !constraints_hold {
+        return Err(VerificationError::ConstraintVerificationFailed);
+    }
+    Ok(())
+}
+
+/// What verifying a proof of one trace height derives from the key and the
+/// configuration alone: the domains, and the points of the rows the key opens.
+struct DomainConstants<SC: crate::StarkGenericConfig> {
+    /// log2 of the trace height
+    log_degree: u8,
+    /// log2 of the auxiliary trace height
+    log_aux_degree: u8,
+    trace_domain: Domain<SC>,
+    aux_domain: Domain<SC>,
+    /// Points of the punctured rows
+    punctures: Vec<Val<SC>>,
+    /// Point of the last row, if a trace is opened there
+    last_point: Option<Challenge<SC>>,
+    statements: Option<StatementBoundaries<Val<SC>>>,
+    /// Rows pinned by boundary constraints, in order
+    boundary_rows: Vec<usize>,
+    /// Point and Lagrange weight of each pinned row
+    boundary_points: Vec<(Val<SC>, Val<SC>)>,
+    /// Domain of each quotient chunk
+    quotient_chunk_domains: Vec<Domain<SC>>,
+}
+
+impl<SC> DomainConstants<SC>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+{
+    /// Build the domains of a proof with traces of height `2^log_degree` and
+    /// `2^log_aux_degree`, which [`check_log_degree`] accepted.
+    fn new(
+        config: &SC,
+        vk: &VerifyingKey,
+        log_degree: u8,
+        log_aux_degree: u8,
+    ) -> Result<Self, VerificationError> {
+        let pcs = config.pcs();
+        let height = 1 << log_degree;
+        let trace_domain = pcs.natural_domain_for_degree(height);
+        if vk.num_punctured_rows >= height {
+            return Err(VerificationError::InvalidProof(
+                "trace is too short for the AIR's punctured rows",
+            ));
+        }
+        let punctures = <Domain<SC> as TraceDomain>::last_row_points(
+            trace_domain.first_point(),
+            height,
+            vk.num_punctured_rows,
+        )
+        .ok_or(VerificationError::InvalidProof(
+            "the PCS's trace domains can't be punctured",
+        ))?;
+        let opens_last_row =
+            vk.main_openings.last_row || (vk.aux_openings.last_row && vk.has_aux_trace());
+        let last_point = if opens_last_row {
+            let points =
+                <Domain<SC> as TraceDomain>::last_row_points(trace_domain.first_point(), height, 1)
+                    .ok_or(VerificationError::InvalidProof(
+                        "the PCS's trace domains can't be opened at the last row",
+                    ))?;
+            Some(Challenge::<SC>::from(points[0]))
+        } else {
+            None
+        };
+        // A shorter auxiliary trace is committed on its own domain, and opened where the
+        // trace domain's points map to on it
+        let aux_height = 1 << log_aux_degree;
+        let aux_domain = pcs.natural_domain_for_degree(aux_height);
+        if <Domain<SC> as TraceDomain>::periodic_point(height, aux_height, Challenge::<SC>::ONE)
+            .is_none()
+        {
+            return Err(VerificationError::InvalidProof(
+                "the PCS's trace domains can't hold a shorter auxiliary trace",
+            ));
+        }
+        let statements =
+            StatementBoundaries::new(vk.num_statements, vk.num_public_values, trace_domain)
+                .map_err(VerificationError::InvalidProof)?;
+        check_boundary_constraints(
+            &vk.boundary_constraints,
+            height,
+            vk.main_width,
+            vk.num_public_values,
+        )
+        .map_err(VerificationError::InvalidProof)?;
+        let boundary_rows = boundary_rows(&vk.boundary_constraints);
+        let boundary_points = <Domain<SC> as TraceDomain>::row_lagrange_points(
+            trace_domain.first_point(),
+            height,
+            &boundary_rows,
+        )
+        .ok_or(VerificationError::InvalidProof(
+            "the PCS's trace domains can't be opened at arbitrary rows",
+        ))?;
+
+        // Compute quotient degree and domains (must match prover)
+        let quotient_degree = 1 << vk.log_quotient_degree;
+        let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
+        Ok(Self {
+            log_degree,
+            log_aux_degree,
+            trace_domain,
+            aux_domain,
+            punctures,
+            last_point,
+            statements,
+            boundary_rows,
+            boundary_points,
+            quotient_chunk_domains: quotient_domain.split_domains(quotient_degree),
+        })
+    }
+}
+
+/// What verifying proofs against a key derives that doesn't depend on the proof, see
+/// [`Verifier`].
+pub(crate) struct VerifyingCache<SC: crate::StarkGenericConfig> {
+    /// The configuration's digest, if derived yet
+    config_digest: Option<Option<ConfigDigest>>,
+    /// The configuration's placeholder auxiliary commitment
+    placeholder: Option<Commitment<SC>>,
+    /// The domains of the trace height last verified
+    domains: Option<DomainConstants<SC>>,
+}
+
+impl<SC: crate::StarkGenericConfig> Default for VerifyingCache<SC> {
+    fn default() -> Self {
+        Self {
+            config_digest: None,
+            placeholder: None,
+            domains: None,
+        }
+    }
+}
+
+/// Replay the transcript of `claims` and evaluate the constraints at the out-of-domain
+/// point.
+///
+/// Returns the PCS check left to do, and whether the constraints hold. Structural
+/// problems are errors.
+fn check_claims<SC, A>(
+    config: &SC,
+    vk: &VerifyingKey,
+    air: &A,
+    claims: &Claims<SC>,
+    public_values: &[Val<SC>],
+    cache: &mut VerifyingCache<SC>,
+) -> Result<(DeferredOpening<SC>, bool), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+    Domain<SC>: TraceDomain,
+    A: for<'a> Air<VerifierFolder<'a, SC>>,
 {
     // Check basic proof structure
-    if air.aux_width() > 0 && proof.aux_commit.is_none() {
+    if vk.has_aux_phase() && claims.aux_commit.is_none() {
         return Err(VerificationError::InvalidProof(
             "AIR requires auxiliary trace but proof has none",
         ));
     }
 
-    if air.aux_width() == 0 && proof.aux_commit.is_some() {
+    if !vk.has_aux_phase() && claims.aux_commit.is_some() && !config.uniform_aux_phase() {
         return Err(VerificationError::InvalidProof(
             "AIR has no auxiliary trace but proof includes one",
         ));
     }
 
-    let pcs = config.pcs();
+    if vk.has_challenge_free_aux() != claims.challenge_free_commit.is_some() {
+        return Err(VerificationError::InvalidProof(
+            "challenge-free auxiliary commitment doesn't match the AIR",
+        ));
+    }
+
+    claims
+        .protocol_id
+        .check(&vk.protocol_id)
+        .map_err(VerificationError::ProtocolMismatch)?;
+    // Proofs made under other parameters fail later anyway, but not saying why
+    if let Some(actual) = claims.config_digest {
+        let expected = *cache
+            .config_digest
+            .get_or_insert_with(|| config_digest(config));
+        // A configuration without a digest can't tell
+        if let Some(expected) = expected.filter(|&expected| expected != actual) {
+            return Err(VerificationError::ConfigMismatch { expected, actual });
+        }
+    }
+
+    // Without an auxiliary phase of its own, the AIR gets the placeholder one
+    if !vk.has_aux_phase() && config.uniform_aux_phase() {
+        let placeholder = cache
+            .placeholder
+            .get_or_insert_with(|| placeholder_aux_commitment(config));
+        let encode = |commit: Option<&Commitment<SC>>| {
+            postcard::to_allocvec(&commit).expect("commitments are serializable")
+        };
+        if encode(claims.aux_commit.as_ref()) != encode(Some(&*placeholder)) {
+            return Err(VerificationError::InvalidProof(
+                "auxiliary commitment isn't the placeholder",
+            ));
+        }
+    }
+
+    if public_values.len() != vk.num_public_values {
+        return Err(VerificationError::PublicValuesMismatch {
+            expected: vk.num_public_values,
+            actual: public_values.len(),
+        });
+    }
+
+    // The openings are sized by `vk`, so they must be as wide as the AIR's rows
+    if BaseAir::<Val<SC>>::width(air) != vk.main_width {
+        return Err(VerificationError::InvalidProof(
+            "the AIR's main width doesn't match the verifying key",
+        ));
+    }
+    // Both the trace and the quotient domain must fit in the PCS's domains (and in a
+    // usize), or building them would panic or overflow
+    check_log_degree(config, vk, claims.log_degree)?;
+    check_proof_shape(vk, claims)?;
+    let main_batches = main_commit_batches(config, vk.main_width);
+    if claims.main_commits.len() != main_batches.len() {
+        return Err(VerificationError::InvalidProof(
+            "wrong number of main trace commitments",
+        ));
+    }
+
     let mut challenger = config.initialise_challenger();
+    challenger.observe_slice(&domain_separator_elements::<Val<SC>>(
+        config.domain_separator(),
+    ));
+    challenger.observe_slice(&vk.protocol_id.to_field_elements::<Val<SC>>());
+    challenger.observe_slice(&vk.fingerprint.map(Val::<SC>::from_u8));
+    // The protocol identifier check makes this the proof's sampling too
+    let extension_sampling = config.extension_sampling();
 
     // Reconstruct the verifier's view of the protocol
-    let height = 1 << proof.log_degree;
-    let trace_domain = pcs.natural_domain_for_degree(height);
+    let height = 1 << claims.log_degree;
+    let aux_height = 1 << claims.log_aux_degree;
+    let cached = cache.domains.as_ref().is_some_and(|domains| {
+        (domains.log_degree, domains.log_aux_degree) == (claims.log_degree, claims.log_aux_degree)
+    });
+    if !cached {
+        cache.domains = Some(DomainConstants::new(
+            config,
+            vk,
+            claims.log_degree,
+            claims.log_aux_degree,
+        )?);
+    }
+    let DomainConstants {
+        trace_domain,
+        aux_domain,
+        ref punctures,
+        last_point,
+        ref statements,
+        ref boundary_rows,
+        ref boundary_points,
+        ref quotient_chunk_domains,
+        ..
+    } = *cache.domains.as_ref().expect("the domains were just built");
+    let periodic = |point: Challenge<SC>| {
+        <Domain<SC> as TraceDomain>::periodic_point(height, aux_height, point)
+            .expect("checked with the domains")
+    };
+    let opens_prev_row =
+        vk.main_openings.prev_row || (vk.aux_openings.prev_row && vk.has_aux_trace());
+    let rotations = if vk.has_aux_trace() {
+        vk.main_openings.rotations.union(vk.aux_openings.rotations)
+    } else {
+        vk.main_openings.rotations
+    };
 
-    // Observe main trace commitment (same as prover)
-    challenger.observe(proof.main_commit.clone());
+    // Observe main trace commitments, then the challenge-free auxiliary one (same as
+    // prover)
+    challenger.observe(TranscriptPhase::Main.to_field_element::<Val<SC>>());
+    for commit in &claims.main_commits {
+        challenger.observe(commit.clone());
+    }
+    if let Some(ref commit) = claims.challenge_free_commit {
+        challenger.observe(commit.clone());
+    }
     challenger.observe_slice(public_values);
 
     // Observe auxiliary commitment if present
-    if let Some(ref aux_commit) = proof.aux_commit {
-        // Sample challenges (same as prover)
-        let num_challenges = air.num_challenges();
-        for _ in 0..num_challenges {
-            let _: Challenge<SC> = challenger.sample();
-        }
+    let mut challenge_values: Vec<Challenge<SC>> = Vec::new();
+    if let Some(ref aux_commit) = claims.aux_commit {
+        // Sample challenges (same as prover), none in a placeholder phase
+        challenger.observe(TranscriptPhase::Aux.to_field_element::<Val<SC>>());
+        let num_challenges = vk.num_challenges(TranscriptPhase::Aux);
+        challenge_values = vk.challenge_sampling.sample(num_challenges, || {
+            extension_sampling.draw::<Val<SC>, _, _>(&mut challenger)
+        });
 
         challenger.observe(aux_commit.clone());
+        for value in &claims.aux_exposed_values {
+            challenger.observe_algebra_element(*value);
+        }
     }
+    let challenge_names: Vec<&str> = vk
+        .challenge_names
+        .iter()
+        .map(|name| name.as_str())
+        .collect();
 
     // Sample alpha for constraint combination (same as prover - must be BEFORE quotient commits)
-    let alpha: Challenge<SC> = challenger.sample();
+    challenger.observe(TranscriptPhase::Quotient.to_field_element::<Val<SC>>());
+    let alpha: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
 
     // Observe quotient commitment
-    challenger.observe(proof.quotient_commit.clone());
+    challenger.observe(claims.quotient_commit.clone());
 
-    // Sample out-of-domain point (same as prover)
-    let zeta: Challenge<SC> = challenger.sample();
-    let _zeta_next = trace_domain
-        .next_point(zeta)
-        .expect("domain must support next_point");
+    // Check the grinding, then sample the out-of-domain points (same as prover)
+    challenger.observe(TranscriptPhase::Opening.to_field_element::<Val<SC>>());
+    match config.pow_bits() {
+        0 if claims.pow_witness != Val::<SC>::ZERO => {
+            return Err(VerificationError::InvalidProof(
+                "proof-of-work witness without grinding",
+            ));
+        }
+        0 => {}
+        bits => {
+            if !challenger.check_witness(bits, claims.pow_witness) {
+                return Err(VerificationError::InvalidPowWitness);
+            }
+        }
+    }
+    let zetas = (0..=claims.extra_ood_openings.len())
+        .map(|_| {
+            let zeta: Challenge<SC> = extension_sampling.draw::<Val<SC>, _, _>(&mut challenger);
+            let next = trace_domain
+                .next_point(zeta)
+                .ok_or(VerificationError::InvalidProof(
+                    "trace domain does not support next_point",
+                ))?;
+            let rotate = |rows: isize| {
+                <Domain<SC> as TraceDomain>::rotate_point(height, zeta, rows).ok_or(
+                    VerificationError::InvalidProof(
+                        "the PCS's trace domains can't be opened at a rotated row",
+                    ),
+                )
+            };
+            let prev = if opens_prev_row {
+                Some(rotate(-1)?)
+            } else {
+                None
+            };
+            let rotated = rotations
+                .iter()
+                .map(|offset| Ok((offset, rotate(offset)?)))
+                .collect::<Result<_, VerificationError>>()?;
+            Ok(OodWindow {
+                zeta,
+                next,
+                prev,
+                rotated,
+            })
+        })
+        .collect::<Result<Vec<OodWindow<Challenge<SC>>>, VerificationError>>()?;
 
-    // Compute quotient degree and domains (must match prover)
-    let constraint_degree = 2; // Must match prover's heuristic
-    let quotient_degree = 1 << constraint_degree;
-    let quotient_domain = trace_domain.create_disjoint_domain(height * quotient_degree);
-    let quotient_chunk_domains = quotient_domain.split_domains(quotient_degree);
+    let aux_zetas: Vec<OodWindow<Challenge<SC>>> =
+        zetas.iter().map(|window| window.map(periodic)).collect();
+    let aux_last_point = last_point.map(periodic);
 
     // Build PCS opening verification data
     // Format: Vec<(Commitment, Vec<(Domain, Vec<(Point, Values)>)>)>
-    let mut coms_to_verify = vec![(
-        proof.main_commit.clone(),
-        vec![(
-            trace_domain,
-            vec![
-                (zeta, proof.main_local.clone()),
-                (_zeta_next, proof.main_next.clone()),
-            ],
-        )],
-    )];
-
-    if let Some(ref aux_commit) = proof.aux_commit {
-        coms_to_verify.push((
-            aux_commit.clone(),
-            vec![(
-                trace_domain,
-                vec![
-                    (zeta, proof.aux_local.clone()),
-                    (_zeta_next, proof.aux_next.clone()),
-                ],
-            )],
-        ));
+    // Each trace is opened at the points of its schedule, at every out-of-domain point,
+    // in the prover's order
+    let scheduled_openings =
+        |schedule: OpeningSchedule,
+         windows: &[OodWindow<Challenge<SC>>],
+         last_point: Option<Challenge<SC>>,
+         first: RowOpenings<Challenge<SC>>,
+         further: Vec<RowOpenings<Challenge<SC>>>| {
+            let mut values = Vec::new();
+            let further = further.into_iter().map(|openings| (openings, false));
+            for (openings, last_row) in core::iter::once((first, schedule.last_row)).chain(further)
+            {
+                values.push(openings.local);
+                if schedule.next_row {
+                    values.push(openings.next);
+                }
+                if schedule.prev_row {
+                    values.push(openings.prev);
+                }
+                values.extend(openings.rotated);
+                if last_row {
+                    values.push(openings.last);
+                }
+            }
+            schedule
+                .points_at(windows, last_point)
+                .into_iter()
+                .zip(values)
+                .collect::<Vec<(Challenge<SC>, Vec<Challenge<SC>>)>>()
+        };
+    // Each main batch opens its own columns; unopened rows are empty. The pinned rows
+    // follow the scheduled ones
+    let columns = |values: &[Challenge<SC>], batch: &Range<usize>| {
+        values.get(batch.clone()).unwrap_or_default().to_vec()
+    };
+    let rotated_columns = |rows: &[Vec<Challenge<SC>>], batch: &Range<usize>| {
+        rows.iter().map(|row| columns(row, batch)).collect()
+    };
+    let pinned = |batch: &Range<usize>| {
+        boundary_points
+            .iter()
+            .zip(&claims.main_boundary)
+            .map(|(&(point, _), values)| (Challenge::<SC>::from(point), columns(values, batch)))
+            .collect::<Vec<_>>()
+    };
+    let extra = &claims.extra_ood_openings;
+    let mut coms_to_verify: Vec<_> = claims
+        .main_commits
+        .iter()
+        .zip(&main_batches)
+        .map(|(commit, batch)| {
+            let mut openings = scheduled_openings(
+                vk.main_openings,
+                &zetas,
+                last_point,
+                RowOpenings {
+                    local: columns(&claims.main_local, batch),
+                    next: columns(&claims.main_next, batch),
+                    prev: columns(&claims.main_prev, batch),
+                    rotated: rotated_columns(&claims.main_rotated, batch),
+                    last: columns(&claims.main_last, batch),
+                },
+                extra
+                    .iter()
+                    .map(|opening| RowOpenings {
+                        local: columns(&opening.main_local, batch),
+                        next: columns(&opening.main_next, batch),
+                        prev: columns(&opening.main_prev, batch),
+                        rotated: rotated_columns(&opening.main_rotated, batch),
+                        last: Vec::new(),
+                    })
+                    .collect(),
+            );
+            openings.extend(pinned(batch));
+            (commit.clone(), vec![(trace_domain, openings)])
+        })
+        .collect();
+
+    // The leading base-field auxiliary columns are challenge-free, committed on their own
+    let challenge_free = 0..vk.challenge_free_aux_width;
+    let dependent = vk.challenge_free_aux_width..vk.aux_base_width;
+    if let Some(ref commit) = claims.challenge_free_commit {
+        let openings = scheduled_openings(
+            vk.aux_openings,
+            &zetas,
+            last_point,
+            RowOpenings {
+                local: columns(&claims.aux_base_local, &challenge_free),
+                next: columns(&claims.aux_base_next, &challenge_free),
+                prev: columns(&claims.aux_base_prev, &challenge_free),
+                rotated: rotated_columns(&claims.aux_base_rotated, &challenge_free),
+                last: columns(&claims.aux_base_last, &challenge_free),
+            },
+            extra
+                .iter()
+                .map(|opening| RowOpenings {
+                    local: columns(&opening.aux_base_local, &challenge_free),
+                    next: columns(&opening.aux_base_next, &challenge_free),
+                    prev: columns(&opening.aux_base_prev, &challenge_free),
+                    rotated: rotated_columns(&opening.aux_base_rotated, &challenge_free),
+                    last: Vec::new(),
+                })
+                .collect(),
+        );
+        coms_to_verify.push((commit.clone(), vec![(trace_domain, openings)]));
+    }
+
+    // The placeholder commitment is never opened
+    if let Some(aux_commit) = claims.aux_commit.as_ref().filter(|_| vk.has_aux_phase()) {
+        // Both segments are committed as one matrix, base-field columns last
+        let joined = |ext: &[Challenge<SC>], base: &[Challenge<SC>]| {
+            [ext.to_vec(), columns(base, &dependent)].concat()
+        };
+        let joined_rotated = |ext: &[Vec<Challenge<SC>>], base: &[Vec<Challenge<SC>>]| {
+            ext.iter()
+                .zip(base)
+                .map(|(ext, base)| joined(ext, base))
+                .collect()
+        };
+        let aux_openings = scheduled_openings(
+            vk.aux_openings,
+            &aux_zetas,
+            aux_last_point,
+            RowOpenings {
+                local: joined(&claims.aux_local, &claims.aux_base_local),
+                next: joined(&claims.aux_next, &claims.aux_base_next),
+                prev: joined(&claims.aux_prev, &claims.aux_base_prev),
+                rotated: joined_rotated(&claims.aux_rotated, &claims.aux_base_rotated),
+                last: joined(&claims.aux_last, &claims.aux_base_last),
+            },
+            extra
+                .iter()
+                .map(|opening| RowOpenings {
+                    local: joined(&opening.aux_local, &opening.aux_base_local),
+                    next: joined(&opening.aux_next, &opening.aux_base_next),
+                    prev: joined(&opening.aux_prev, &opening.aux_base_prev),
+                    rotated: joined_rotated(&opening.aux_rotated, &opening.aux_base_rotated),
+                    last: Vec::new(),
+                })
+                .collect(),
+        );
+        coms_to_verify.push((aux_commit.clone(), vec![(aux_domain, aux_openings)]));
     }
 
     // Add quotient commitment with all chunks
-    // Each chunk is opened at zeta on its own domain
+    // Chunk i is opened at zeta on the i-th chunk domain, not the trace domain, so the
+    // PCS checks each opened value against the domain its recomposition weight uses
     let quotient_openings: Vec<(Domain<SC>, Vec<(Challenge<SC>, Vec<Challenge<SC>>)>)> =
         quotient_chunk_domains
             .iter()
             .enumerate()
-            .map(|(i, &domain)| (domain, vec![(zeta, proof.quotient_chunks[i].clone())]))
+            .map(|(i, &domain)| {
+                let values = core::iter::once(&claims.quotient_chunks)
+                    .chain(extra.iter().map(|opening| &opening.quotient_chunks))
+                    .map(|chunks| chunks[i].clone());
+                let openings = zetas.iter().map(|window| window.zeta).zip(values).collect();
+                (domain, openings)
+            })
             .collect();
 
-    coms_to_verify.push((proof.quotient_commit.clone(), quotient_openings));
-
-    // Verify PCS opening proofs
-    pcs.verify(coms_to_verify, &proof.opening_proof, &mut challenger)
-        .map_err(|_| VerificationError::PcsVerificationFailed)?;
-
-    // Compute selectors at zeta
-    let selectors = trace_domain.selectors_at_point(zeta);
-
-    // Evaluate constraints at zeta
-    let mut folder = VerifierFolder {
-        main_local: &proof.main_local,
-        main_next: &proof.main_next,
-        aux_local: &proof.aux_local,
-        aux_next: &proof.aux_next,
-        is_first_row: selectors.is_first_row,
-        is_last_row: selectors.is_last_row,
-        is_transition: selectors.is_transition,
-        alpha,
-        accumulator: SC::Challenge::ZERO,
+    coms_to_verify.push((claims.quotient_commit.clone(), quotient_openings));
+
+    // The PCS checks the opening proof from this transcript state on
+    let deferred = DeferredOpening {
+        coms_to_verify,
+        challenger,
     };
 
-    air.eval(&mut folder);
-    let constraints_at_zeta = folder.accumulator;
+    // Evaluate the constraints at one out-of-domain point, checking them against the
+    // quotient there
+    let public_values_ext: Vec<Challenge<SC>> =
+        public_values.iter().map(|&value| value.into()).collect();
+    let aux_last = recompose_aux_openings::<SC>(&claims.aux_last);
+    let constraints_hold_at = |zeta: Challenge<SC>, opening: &OodOpening<Challenge<SC>>| {
+        // Compute selectors at zeta
+        // 1/Z_H(zeta) is undefined if zeta landed in the trace domain
+        let vanishing_at_zeta = trace_domain.vanishing_poly_at_point(zeta);
+        if vanishing_at_zeta.is_zero() {
+            return Err(VerificationError::InvalidProof(
+                "out-of-domain point lies in the trace domain",
+            ));
+        }
+        let selectors = trace_domain.selectors_at_point(zeta);
+        let statement_values = statements
+            .as_ref()
+            .map(|boundaries| boundaries.values_at(zeta, vanishing_at_zeta, public_values));
+        let statement_selectors = statement_values.as_ref().map_or(
+            StatementSelectors {
+                is_first_row: selectors.is_first_row,
+                is_last_row: selectors.is_last_row,
+                is_transition: selectors.is_transition,
+                public_values: &public_values_ext,
+            },
+            |values| values.selectors(),
+        );
 
-    // Reconstruct quotient value from chunks using Lagrange interpolation
-    let quotient_at_zeta =
-        recompose_quotient_from_chunks::<SC>(&quotient_chunk_domains, &proof.quotient_chunks, zeta);
+        // Evaluate constraints at zeta, on the recombined extension-field aux columns
+        let aux_local = recompose_aux_openings::<SC>(&opening.aux_local);
+        let aux_next = recompose_aux_openings::<SC>(&opening.aux_next);
+        let aux_prev = recompose_aux_openings::<SC>(&opening.aux_prev);
+        let aux_rotated: Vec<Vec<Challenge<SC>>> = opening
+            .aux_rotated
+            .iter()
+            .map(|row| recompose_aux_openings::<SC>(row))
+            .collect();
+        let challenges = if vk.has_aux_phase() {
+            ChallengeSet::new(&challenge_names, &challenge_values)
+        } else {
+            ChallengeSet::empty()
+        };
+        let mut folder = VerifierFolder {
+            main_local: &opening.main_local,
+            main_next: &opening.main_next,
+            main_last_row: &claims.main_last,
+            main_openings: vk.main_openings,
+            aux_openings: vk.aux_openings,
+            aux_local: &aux_local,
+            aux_next: &aux_next,
+            aux_base_local: &opening.aux_base_local,
+            aux_base_next: &opening.aux_base_next,
+            challenges,
+            aux_exposed_values: &claims.aux_exposed_values,
+            aux_prev_row: &aux_prev,
+            aux_base_prev_row: &opening.aux_base_prev,
+            main_prev_row: &opening.main_prev,
+            aux_rotated: RotatedRows {
+                offsets: vk.aux_openings.rotations,
+                rows: &aux_rotated,
+            },
+            aux_base_rotated: RotatedRows {
+                offsets: vk.aux_openings.rotations,
+                rows: &opening.aux_base_rotated,
+            },
+            main_rotated: RotatedRows {
+                offsets: vk.main_openings.rotations,
+                rows: &opening.main_rotated,
+            },
+            aux_last_row: &aux_last,
+            aux_base_last_row: &claims.aux_base_last,
+            public_values,
+            is_first_row: selectors.is_first_row,
+            is_last_row: selectors.is_last_row,
+            is_transition: selectors.is_transition,
+            statements: statement_selectors,
+            alpha,
+            accumulator: SC::Challenge::ZERO,
+        };
 
-    // Check: C(zeta) / Z_H(zeta) == Q(zeta)
-    // Equivalently: C(zeta) * inv_Z_H(zeta) == Q(zeta)
-    // The selector provides inv_vanishing = 1/Z_H(zeta)
-    if constraints_at_zeta * selectors.inv_vanishing != quotient_at_zeta {
-        return Err(VerificationError::ConstraintVerificationFailed);
+        air.eval(&mut folder);
+        let constraints_at_zeta = folder.accumulator;
+
+        // Reconstruct quotient value from chunks using Lagrange interpolation
+        let quotient_at_zeta = recompose_quotient_from_chunks::<SC>(
+            quotient_chunk_domains,
+            &opening.quotient_chunks,
+            zeta,
+        );
+
+        // Punctured rows are roots of Z_H the constraints needn't vanish on:
+        // divide them back out, i.e. multiply 1/Z_H(zeta) by prod_j (zeta - p_j)
+        let puncture_at_zeta = punctures
+            .iter()
+            .map(|&p| zeta - p)
+            .product::<Challenge<SC>>();
+
+        // Check: C(zeta) / Z_H(zeta) == Q(zeta)
+        // Equivalently: C(zeta) * inv_Z_H(zeta) == Q(zeta)
+        // The selector provides inv_vanishing = 1/Z_H(zeta)
+        Ok(constraints_at_zeta * puncture_at_zeta * selectors.inv_vanishing == quotient_at_zeta)
+    };
+
+    // The first point's values are the proof's own, the further points' its extra openings
+    let first = OodOpening {
+        main_local: claims.main_local.clone(),
+        main_next: claims.main_next.clone(),
+        main_prev: claims.main_prev.clone(),
+        main_rotated: claims.main_rotated.clone(),
+        aux_local: claims.aux_local.clone(),
+        aux_next: claims.aux_next.clone(),
+        aux_prev: claims.aux_prev.clone(),
+        aux_rotated: claims.aux_rotated.clone(),
+        aux_base_local: claims.aux_base_local.clone(),
+        aux_base_next: claims.aux_base_next.clone(),
+        aux_base_prev: claims.aux_base_prev.clone(),
+        aux_base_rotated: claims.aux_base_rotated.clone(),
+        quotient_chunks: claims.quotient_chunks.clone(),
+    };
+    let mut constraints_hold = true;
+    for (window, opening) in zetas.iter().zip(core::iter::once(&first).chain(extra)) {
+        constraints_hold &= constraints_hold_at(window.zeta, opening)?;
     }
 
-    Ok(())
+    // Pinned cells hold their public values
+    for constraint in &vk.boundary_constraints {
+        let row = boundary_rows
+            .binary_search(&constraint.row)
+            .expect("every pinned row is opened");
+        constraints_hold &= claims.main_boundary[row][constraint.column]
+            == Challenge::<SC>::from(public_values[constraint.public_value]);
+    }
+
+    Ok((deferred, constraints_hold))
+}
+
+/// Check `opening_proof` opens the commitments `deferred` lists at its points to the
+/// claimed values.
+fn check_deferred_opening<SC>(
+    config: &SC,
+    deferred: DeferredOpening<SC>,
+    opening_proof: &PcsProof<SC>,
+) -> Result<(), VerificationError>
+where
+    SC: crate::StarkGenericConfig,
+{
+    let DeferredOpening {
+        coms_to_verify,
+        mut challenger,
+    } = deferred;
+    config
+        .pcs()
+        .verify(coms_to_verify, opening_proof, &mut challenger)
+        .map_err(|_| VerificationError::PcsVerificationFailed)
 }
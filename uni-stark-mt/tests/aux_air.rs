@@ -0,0 +1,1190 @@
+//! AIRs with an auxiliary trace
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues, PermutationAirBuilder};
+use p3_baby_bear::BabyBear;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::default_config;
+use p3_uni_stark_mt::{
+    check_constraints, keygen, prove, verify, AirLayout, AuxBuilder, AuxSegment, AuxTraceBuilder,
+    AuxTraces, ChallengeSampling, ChallengeSet, MockProver, MockProverError, OpeningSchedule,
+    PuncturedAir, TranscriptPhase,
+};
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+/// One main column `x`, mirrored into two auxiliary columns.
+///
+/// The first is constrained with `assert_eq_main_aux`, the second with
+/// `assert_eq_aux_main`.
+pub struct MirrorAir;
+
+impl<F> BaseAir<F> for MirrorAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for MirrorAir {}
+
+impl<F> PuncturedAir<F> for MirrorAir {}
+
+impl<F> AirLayout<F> for MirrorAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for MirrorAir {
+    fn aux_width(&self) -> usize {
+        2
+    }
+
+    fn build_aux_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        _challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        let values = main
+            .values
+            .iter()
+            .flat_map(|&x| [EF::from(x), EF::from(x)])
+            .collect();
+        RowMajorMatrix::new(values, 2)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for MirrorAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let x = main.row_slice(0).expect("Matrix is empty?")[0];
+        let aux_local = aux.row_slice(0).expect("Matrix is empty?");
+        let (y, z) = (aux_local[0], aux_local[1]);
+
+        builder.assert_eq_main_aux(x, y);
+        builder.assert_eq_aux_main(z, x);
+    }
+}
+
+fn main_trace(n: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col((0..n as u32).map(Val::from_u32).collect())
+}
+
+#[test]
+fn test_mirror_air_built_aux() {
+    let main = main_trace(8);
+    check_constraints::<Val, Challenge, _>(&MirrorAir, &main, None, &[], &[])
+        .expect("built auxiliary trace satisfies the AIR");
+}
+
+#[test]
+fn test_mirror_air_corrupted_aux() {
+    let main = main_trace(8);
+    let mut aux: RowMajorMatrix<Challenge> =
+        MirrorAir.build_aux_trace(&main, &ChallengeSet::empty());
+    aux.values[2 * 3] += Challenge::ONE;
+    aux.values[2 * 5 + 1] += Challenge::ONE;
+
+    let violations = check_constraints(&MirrorAir, &main, Some(&aux), &[], &[])
+        .expect_err("corrupted auxiliary trace must be rejected")
+        .0;
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].row, violations[0].constraint_index), (3, 0));
+    assert_eq!(violations[0].value, -Challenge::ONE);
+    assert_eq!((violations[1].row, violations[1].constraint_index), (5, 1));
+    assert_eq!(violations[1].value, Challenge::ONE);
+}
+
+/// One main column `x` and one base-field auxiliary column holding `x^2`.
+pub struct SquareAir;
+
+impl<F> BaseAir<F> for SquareAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SquareAir {}
+
+impl<F> PuncturedAir<F> for SquareAir {}
+
+impl<F> AirLayout<F> for SquareAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SquareAir {
+    fn aux_base_width(&self) -> usize {
+        1
+    }
+
+    fn build_aux_base_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        _challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new_col(main.values.iter().map(|&x| x.square()).collect())
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for SquareAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux_base = builder.aux_base();
+        let x = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let y = aux_base.row_slice(0).expect("Matrix is empty?")[0].clone();
+        builder.assert_eq(y, x.clone() * x);
+    }
+}
+
+#[test]
+fn test_square_air_base_aux() {
+    let config = default_config();
+    let main = main_trace(8);
+    check_constraints::<Val, Challenge, _>(&SquareAir, &main, None, &[], &[])
+        .expect("built base-field auxiliary trace satisfies the AIR");
+
+    let vk = keygen(&config, &SquareAir);
+    assert_eq!((vk.aux_width, vk.aux_base_width), (0, 1));
+
+    let proof = prove(&config, &SquareAir, main, &[]).expect("proving failed");
+    // Committed as a single base-field column rather than a flattened extension element
+    assert!(proof.aux_commit.is_some());
+    assert!(proof.aux_local.is_empty());
+    assert_eq!(proof.aux_base_local.len(), 1);
+    verify(&config, &SquareAir, &proof, &[]).expect("verification failed");
+}
+
+/// One main column `x`, a base-field auxiliary column `m = x + 1` and an extension
+/// column `s = r·m` for the phase challenge `r`, built together.
+pub struct ScaledAir;
+
+impl<F> BaseAir<F> for ScaledAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ScaledAir {}
+
+impl<F> PuncturedAir<F> for ScaledAir {}
+
+impl<F> AirLayout<F> for ScaledAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ScaledAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn aux_base_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["scale"]
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let r = challenges["scale"];
+        let base: Vec<F> = main.values.iter().map(|&x| x + F::ONE).collect();
+        let ext = base.iter().map(|&m| r * m).collect();
+        AuxTraces {
+            ext: RowMajorMatrix::new_col(ext),
+            base: RowMajorMatrix::new_col(base),
+            exposed: Vec::new(),
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ScaledAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let aux_base = builder.aux_base();
+        let x: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let m = aux_base.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let m_next = aux_base.row_slice(1).expect("Matrix only has 1 row?")[0].clone();
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        builder.assert_eq(m.clone(), x + AB::Expr::ONE);
+        // s_next·m = s·m_next, i.e. s/m is constant
+        let (m, m_next) = (builder.embed_main(m), builder.embed_main(m_next));
+        builder.assert_eq_ext(s_next * m, s * m_next);
+    }
+}
+
+#[test]
+fn test_scaled_air_mixed_aux() {
+    let main = main_trace(8);
+    let challenges = [Challenge::from_u32(7)];
+    check_constraints::<Val, Challenge, _>(&ScaledAir, &main, None, &challenges, &[])
+        .expect("jointly built auxiliary traces satisfy the AIR");
+
+    let AuxTraces { mut ext, base, .. } =
+        ScaledAir.build_aux_traces(&main, &ChallengeSet::new(&["scale"], &challenges));
+    assert_eq!((ext.width, base.width), (1, 1));
+    ext.values[4] += Challenge::ONE;
+    let violations = check_constraints(&ScaledAir, &main, Some(&ext), &challenges, &[])
+        .expect_err("corrupted extension column must be rejected")
+        .0;
+    // The ratio breaks on the transitions into and out of row 4
+    assert_eq!(violations.len(), 2);
+    assert_eq!((violations[0].row, violations[1].row), (3, 4));
+}
+
+#[test]
+fn test_uniform_aux_phase_keeps_real_one() {
+    // AIRs with an auxiliary phase of their own prove the same with the option on
+    let config = default_config();
+    let uniform = default_config().with_uniform_aux_phase(true);
+    let proof = prove(&config, &ScaledAir, main_trace(8), &[]).expect("proving failed");
+    let uniform_proof = prove(&uniform, &ScaledAir, main_trace(8), &[]).expect("proving failed");
+    verify(&uniform, &ScaledAir, &uniform_proof, &[]).expect("verification failed");
+    assert_eq!(
+        postcard::to_allocvec(&proof).unwrap(),
+        postcard::to_allocvec(&uniform_proof).unwrap()
+    );
+}
+
+/// Segment mirroring the main column `x` into the extension field
+pub struct MirrorSegment;
+
+impl<F: Field, EF: ExtensionField<F>> AuxSegment<F, EF> for MirrorSegment {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn build(
+        &self,
+        main: &RowMajorMatrix<F>,
+        _challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        RowMajorMatrix::new_col(main.values.iter().map(|&x| EF::from(x)).collect())
+    }
+}
+
+/// Segment holding `r·(x + 1)` for its own challenge `r`
+pub struct ScaleSegment;
+
+impl<F: Field, EF: ExtensionField<F>> AuxSegment<F, EF> for ScaleSegment {
+    fn width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["scale_r"]
+    }
+
+    fn build(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        let r = challenges["scale_r"];
+        RowMajorMatrix::new_col(main.values.iter().map(|&x| r * (x + F::ONE)).collect())
+    }
+}
+
+/// One main column `x` with two independent auxiliary segments.
+pub struct SegmentedAir;
+
+impl<F> BaseAir<F> for SegmentedAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SegmentedAir {}
+
+impl<F> PuncturedAir<F> for SegmentedAir {}
+
+impl<F> AirLayout<F> for SegmentedAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SegmentedAir {
+    fn aux_segments(&self) -> Vec<&dyn AuxSegment<F, EF>> {
+        vec![&MirrorSegment as &dyn AuxSegment<F, EF>, &ScaleSegment]
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for SegmentedAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let x: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0]
+            .clone()
+            .into();
+        let x_next: AB::Expr = main.row_slice(1).expect("Matrix only has 1 row?")[0]
+            .clone()
+            .into();
+        let (mirror, scaled) = {
+            let local = aux.row_slice(0).expect("Matrix is empty?");
+            (local[0], local[1])
+        };
+        let scaled_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[1].into();
+
+        builder.assert_eq_aux_main(mirror, x.clone());
+        // scaled / (x + 1) is the same on every row
+        let m = builder.embed_main(x + AB::Expr::ONE);
+        let m_next = builder.embed_main(x_next + AB::Expr::ONE);
+        let scaled: AB::ExprEF = scaled.into();
+        builder.assert_eq_ext(scaled_next * m, scaled * m_next);
+    }
+}
+
+#[test]
+fn test_segmented_air() {
+    let air = SegmentedAir;
+    assert_eq!(AuxTraceBuilder::<Val, Challenge>::aux_width(&air), 2);
+    assert_eq!(
+        AuxTraceBuilder::<Val, Challenge>::challenge_names(&air),
+        ["scale_r"]
+    );
+    assert_eq!(
+        AuxTraceBuilder::<Val, Challenge>::aux_segment_columns(&air, 1),
+        1..2
+    );
+
+    let main = main_trace(8);
+    let challenges = [Challenge::from_u32(7)];
+    let aux: RowMajorMatrix<Challenge> =
+        air.build_aux_trace(&main, &ChallengeSet::new(&["scale_r"], &challenges));
+    assert_eq!(aux.width, 2);
+    assert_eq!(aux.values[2 * 3], Challenge::from_u32(3));
+    assert_eq!(aux.values[2 * 3 + 1], Challenge::from_u32(28));
+
+    check_constraints::<Val, Challenge, _>(&air, &main, None, &challenges, &[])
+        .expect("segments satisfy the AIR");
+}
+
+#[test]
+fn test_challenge_set_lookup() {
+    let values = [Challenge::from_u32(3), Challenge::from_u32(5)];
+    let challenges = ChallengeSet::new(&["logup_alpha", "perm_beta"], &values);
+    assert_eq!(challenges["perm_beta"], values[1]);
+    assert_eq!(challenges.get("logup_alpha"), Some(&values[0]));
+    assert_eq!(challenges.get("logup_beta"), None);
+    assert_eq!(challenges.len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "declared twice")]
+fn test_challenge_set_duplicate_name() {
+    let values = [Challenge::ONE, Challenge::TWO];
+    ChallengeSet::new(&["alpha", "alpha"], &values);
+}
+
+/// LogUp-style running sum: `s_0 = 0` and `s_{i+1} = s_i + 1/(α - x_i)` for the challenge
+/// `α`, which the constraints read by name.
+pub struct RunningSumAir;
+
+impl<F> BaseAir<F> for RunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for RunningSumAir {}
+
+impl<F> PuncturedAir<F> for RunningSumAir {}
+
+impl<F> AirLayout<F> for RunningSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for RunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["logup_alpha"]
+    }
+
+    fn build_aux_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        let alpha = challenges["logup_alpha"];
+        let mut sum = EF::ZERO;
+        let values = main
+            .values
+            .iter()
+            .map(|&x| {
+                let current = sum;
+                sum += (alpha - x).inverse();
+                current
+            })
+            .collect();
+        RowMajorMatrix::new_col(values)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for RunningSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let alpha: AB::ExprEF = builder.challenge("logup_alpha").into();
+        let x = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        let is_first_row = builder.embed_main(builder.is_first_row());
+        let is_transition = builder.embed_main(builder.is_transition());
+        let x = builder.embed_main(x);
+        builder.assert_zero_ext(is_first_row * s.clone());
+        // (s_next - s)·(α - x) = 1 between consecutive rows
+        builder.assert_eq_ext(
+            is_transition.clone() * (s_next - s) * (alpha - x),
+            is_transition,
+        );
+    }
+}
+
+#[test]
+fn test_running_sum_challenge_in_eval() {
+    let main = main_trace(8);
+    let alpha = [Challenge::from_u32(1000)];
+    check_constraints::<Val, Challenge, _>(&RunningSumAir, &main, None, &alpha, &[])
+        .expect("running sum satisfies the AIR");
+
+    // The constraints use the challenge they were checked with, not the build one
+    let aux: RowMajorMatrix<Challenge> =
+        RunningSumAir.build_aux_trace(&main, &ChallengeSet::new(&["logup_alpha"], &alpha));
+    let other_alpha = [Challenge::from_u32(1001)];
+    assert!(check_constraints::<Val, Challenge, _>(
+        &RunningSumAir,
+        &main,
+        Some(&aux),
+        &other_alpha,
+        &[]
+    )
+    .is_err());
+
+    let config = default_config();
+    assert_eq!(
+        keygen(&config, &RunningSumAir).challenge_names,
+        ["logup_alpha"]
+    );
+    let proof = prove(&config, &RunningSumAir, main, &[]).expect("proving failed");
+    verify(&config, &RunningSumAir, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_extension_aux_prove_verify() {
+    let config = default_config();
+    let proof = prove(&config, &MirrorAir, main_trace(8), &[]).expect("proving failed");
+    // Each extension column is opened as its flattened base-field columns
+    assert_eq!(proof.aux_local.len(), 2 * 4);
+    verify(&config, &MirrorAir, &proof, &[]).expect("verification failed");
+
+    let proof = prove(&config, &ScaledAir, main_trace(8), &[]).expect("proving failed");
+    verify(&config, &ScaledAir, &proof, &[]).expect("verification failed");
+
+    let proof = prove(&config, &SegmentedAir, main_trace(8), &[]).expect("proving failed");
+    verify(&config, &SegmentedAir, &proof, &[]).expect("verification failed");
+}
+
+/// [`RunningSumAir`] written with filtered extension-field builders.
+pub struct FilteredRunningSumAir;
+
+impl<F> BaseAir<F> for FilteredRunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for FilteredRunningSumAir {}
+
+impl<F> PuncturedAir<F> for FilteredRunningSumAir {}
+
+impl<F> AirLayout<F> for FilteredRunningSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for FilteredRunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["logup_alpha"]
+    }
+
+    fn build_aux_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        RunningSumAir.build_aux_trace(main, challenges)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for FilteredRunningSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let alpha: AB::ExprEF = builder.challenge("logup_alpha").into();
+        let x = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        let x = builder.embed_main(x);
+        builder.when_first_row_ext().assert_zero_ext(s.clone());
+        builder
+            .when_transition_ext()
+            .assert_eq_ext((s_next - s) * (alpha - x), AB::ExprEF::ONE);
+    }
+}
+
+#[test]
+fn test_filtered_extension_constraints() {
+    let main = main_trace(8);
+    let alpha = [Challenge::from_u32(1000)];
+    check_constraints::<Val, Challenge, _>(&FilteredRunningSumAir, &main, None, &alpha, &[])
+        .expect("running sum satisfies the AIR");
+
+    // A filtered constraint still applies on its rows
+    let mut aux: RowMajorMatrix<Challenge> =
+        RunningSumAir.build_aux_trace(&main, &ChallengeSet::new(&["logup_alpha"], &alpha));
+    aux.values[3] += Challenge::ONE;
+    let violations = check_constraints::<Val, Challenge, _>(
+        &FilteredRunningSumAir,
+        &main,
+        Some(&aux),
+        &alpha,
+        &[],
+    )
+    .expect_err("a wrong partial sum must be caught");
+    let rows: Vec<usize> = violations.0.iter().map(|violation| violation.row).collect();
+    assert_eq!(rows, [2, 3]);
+
+    let config = default_config();
+    let proof = prove(&config, &FilteredRunningSumAir, main, &[]).expect("proving failed");
+    verify(&config, &FilteredRunningSumAir, &proof, &[]).expect("verification failed");
+}
+
+/// [`RunningSumAir`] written against p3-air's `PermutationAirBuilder`, as ecosystem AIRs
+/// are.
+pub struct PermutationRunningSumAir;
+
+impl<F> BaseAir<F> for PermutationRunningSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for PermutationRunningSumAir {}
+
+impl<F> PuncturedAir<F> for PermutationRunningSumAir {}
+
+impl<F> AirLayout<F> for PermutationRunningSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for PermutationRunningSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["logup_alpha"]
+    }
+
+    fn build_aux_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        RunningSumAir.build_aux_trace(main, challenges)
+    }
+}
+
+impl<AB: PermutationAirBuilder> Air<AB> for PermutationRunningSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let permutation = builder.permutation();
+        let alpha: AB::ExprEF = builder.permutation_randomness()[0].into();
+        let x: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s: AB::ExprEF = permutation.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF =
+            permutation.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        let is_first_row = AB::ExprEF::from(builder.is_first_row());
+        let is_transition = AB::ExprEF::from(builder.is_transition());
+        builder.assert_zero_ext(is_first_row * s.clone());
+        builder.assert_eq_ext(
+            is_transition.clone() * (s_next - s) * (alpha - AB::ExprEF::from(x)),
+            is_transition,
+        );
+    }
+}
+
+#[test]
+fn test_permutation_air_builder() {
+    let main = main_trace(8);
+    let alpha = [Challenge::from_u32(1000)];
+    check_constraints::<Val, Challenge, _>(&PermutationRunningSumAir, &main, None, &alpha, &[])
+        .expect("running sum satisfies the AIR");
+
+    let config = default_config();
+    let proof = prove(&config, &PermutationRunningSumAir, main, &[]).expect("proving failed");
+    verify(&config, &PermutationRunningSumAir, &proof, &[]).expect("verification failed");
+}
+
+/// [`RunningSumAir`] exposing its total `T = Σ 1/(α - x)` to the verifier.
+///
+/// The last row closes the sum: `(T - s)·(α - x) = 1`.
+pub struct TotalSumAir;
+
+impl<F> BaseAir<F> for TotalSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for TotalSumAir {}
+
+impl<F> PuncturedAir<F> for TotalSumAir {}
+
+impl<F> AirLayout<F> for TotalSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for TotalSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["logup_alpha"]
+    }
+
+    fn num_aux_exposed_values(&self) -> usize {
+        1
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let ext = RunningSumAir.build_aux_trace(main, challenges);
+        let alpha = challenges["logup_alpha"];
+        let last = *main.values.last().expect("Matrix is empty?");
+        let total = *ext.values.last().expect("Matrix is empty?") + (alpha - last).inverse();
+        AuxTraces {
+            ext,
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: vec![total],
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for TotalSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir.eval(builder);
+
+        let main = builder.main();
+        let aux = builder.aux();
+        let alpha: AB::ExprEF = builder.challenge("logup_alpha").into();
+        let total: AB::ExprEF = builder.aux_exposed_values()[0].into();
+        let x = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+
+        let is_last_row = builder.embed_main(builder.is_last_row());
+        let x = builder.embed_main(x);
+        builder.assert_eq_ext(is_last_row.clone() * (total - s) * (alpha - x), is_last_row);
+    }
+}
+
+#[test]
+fn test_exposed_total_sum() {
+    let main = main_trace(8);
+    let alpha = [Challenge::from_u32(1000)];
+    check_constraints::<Val, Challenge, _>(&TotalSumAir, &main, None, &alpha, &[])
+        .expect("exposed total closes the running sum");
+
+    let config = default_config();
+    assert_eq!(keygen(&config, &TotalSumAir).num_aux_exposed_values, 1);
+    let proof = prove(&config, &TotalSumAir, main, &[]).expect("proving failed");
+    assert_eq!(proof.aux_exposed_values.len(), 1);
+    verify(&config, &TotalSumAir, &proof, &[]).expect("verification failed");
+
+    let mut tampered = proof.clone();
+    tampered.aux_exposed_values[0] += Challenge::ONE;
+    assert!(verify(&config, &TotalSumAir, &tampered, &[]).is_err());
+
+    let mut truncated = proof;
+    truncated.aux_exposed_values.clear();
+    assert!(verify(&config, &TotalSumAir, &truncated, &[]).is_err());
+}
+
+/// [`RunningSumAir`] exposing its last accumulator value `s_{n-1}`, checked against the
+/// auxiliary trace opened at the last row rather than under `is_last_row`.
+pub struct LastRowSumAir;
+
+impl<F> BaseAir<F> for LastRowSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for LastRowSumAir {}
+
+impl<F> PuncturedAir<F> for LastRowSumAir {}
+
+impl<F> AirLayout<F> for LastRowSumAir {
+    fn aux_openings(&self) -> OpeningSchedule {
+        OpeningSchedule::TRANSITION.with_last_row()
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for LastRowSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["logup_alpha"]
+    }
+
+    fn num_aux_exposed_values(&self) -> usize {
+        1
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let ext = RunningSumAir.build_aux_trace(main, challenges);
+        let last = *ext.values.last().expect("Matrix is empty?");
+        AuxTraces {
+            ext,
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: vec![last],
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for LastRowSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        RunningSumAir.eval(builder);
+
+        let exposed = builder.aux_exposed_values()[0];
+        let last = builder.aux_last_row()[0];
+        builder.assert_eq_ext(last, exposed);
+    }
+}
+
+#[test]
+fn test_aux_last_row_opening() {
+    let main = main_trace(8);
+    let alpha = [Challenge::from_u32(1000)];
+    check_constraints::<Val, Challenge, _>(&LastRowSumAir, &main, None, &alpha, &[])
+        .expect("exposed value is the last accumulator");
+
+    let config = default_config();
+    assert!(keygen(&config, &LastRowSumAir).aux_openings.last_row);
+    let proof = prove(&config, &LastRowSumAir, main, &[]).expect("proving failed");
+    assert_eq!(proof.aux_last.len(), 4);
+    assert!(proof.aux_base_last.is_empty());
+    verify(&config, &LastRowSumAir, &proof, &[]).expect("verification failed");
+
+    let mut tampered = proof.clone();
+    tampered.aux_exposed_values[0] += Challenge::ONE;
+    assert!(verify(&config, &LastRowSumAir, &tampered, &[]).is_err());
+
+    // The opening is bound by the PCS, so the prover can't adjust it to match
+    let mut tampered = proof.clone();
+    tampered.aux_exposed_values[0] += Challenge::ONE;
+    tampered.aux_last[0] += Challenge::ONE;
+    assert!(verify(&config, &LastRowSumAir, &tampered, &[]).is_err());
+
+    let mut truncated = proof;
+    truncated.aux_last.clear();
+    assert!(verify(&config, &LastRowSumAir, &truncated, &[]).is_err());
+}
+
+#[test]
+fn test_aux_last_row_not_opened_by_default() {
+    let config = default_config();
+    let proof = prove(&config, &TotalSumAir, main_trace(8), &[]).expect("proving failed");
+    assert!(proof.aux_last.is_empty());
+    assert!(!keygen(&config, &TotalSumAir).aux_openings.last_row);
+}
+
+/// Challenges `β, β², β³` derived from one sampled element, checked by the constraints.
+///
+/// The auxiliary column holds `β` on every row.
+pub struct PowerChallengesAir;
+
+impl<F> BaseAir<F> for PowerChallengesAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for PowerChallengesAir {}
+
+impl<F> PuncturedAir<F> for PowerChallengesAir {}
+
+impl<F> AirLayout<F> for PowerChallengesAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for PowerChallengesAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["beta", "beta_2", "beta_3"]
+    }
+
+    fn challenge_sampling(&self) -> ChallengeSampling {
+        ChallengeSampling::Powers
+    }
+
+    fn build_aux_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
+        RowMajorMatrix::new_col(vec![challenges["beta"]; main.height()])
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for PowerChallengesAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let aux = builder.aux();
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let beta: AB::ExprEF = builder.challenge("beta").into();
+        let beta_2: AB::ExprEF = builder.challenge("beta_2").into();
+        let beta_3: AB::ExprEF = builder.challenge("beta_3").into();
+
+        builder.assert_eq_ext(s, beta.clone());
+        builder.assert_eq_ext(beta_2.clone(), beta.clone() * beta.clone());
+        builder.assert_eq_ext(beta_3, beta_2 * beta);
+    }
+}
+
+#[test]
+fn test_challenges_as_powers() {
+    let beta = Challenge::from_u32(5);
+    let powers = [beta, beta * beta, beta * beta * beta];
+    check_constraints::<Val, Challenge, _>(&PowerChallengesAir, &main_trace(8), None, &powers, &[])
+        .expect("challenges are powers of the first");
+    let independent = [beta, beta, beta];
+    assert!(check_constraints::<Val, Challenge, _>(
+        &PowerChallengesAir,
+        &main_trace(8),
+        None,
+        &independent,
+        &[]
+    )
+    .is_err());
+
+    let config = default_config();
+    let vk = keygen(&config, &PowerChallengesAir);
+    assert_eq!(vk.challenge_sampling, ChallengeSampling::Powers);
+    assert_eq!(vk.num_challenges(TranscriptPhase::Aux), 3);
+    assert_eq!(vk.num_challenges(TranscriptPhase::Main), 0);
+    let proof = prove(&config, &PowerChallengesAir, main_trace(8), &[]).expect("proving failed");
+    verify(&config, &PowerChallengesAir, &proof, &[]).expect("verification failed");
+
+    // The sampled element is bound by the transcript
+    let mut tampered = proof;
+    tampered.aux_local[0] += Challenge::ONE;
+    assert!(verify(&config, &PowerChallengesAir, &tampered, &[]).is_err());
+}
+
+#[test]
+fn test_challenges_independent_by_default() {
+    let config = default_config();
+    assert_eq!(
+        keygen(&config, &RunningSumAir).challenge_sampling,
+        ChallengeSampling::Independent
+    );
+}
+
+/// [`SquareAir`] with its square column committed before any challenge is sampled.
+pub struct ChallengeFreeSquareAir;
+
+impl<F> BaseAir<F> for ChallengeFreeSquareAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ChallengeFreeSquareAir {}
+
+impl<F> PuncturedAir<F> for ChallengeFreeSquareAir {}
+
+impl<F> AirLayout<F> for ChallengeFreeSquareAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ChallengeFreeSquareAir {
+    fn aux_base_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_free_aux_width(&self) -> usize {
+        1
+    }
+
+    fn build_challenge_free_aux_trace(&self, main: &RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new_col(main.values.iter().map(|&x| x.square()).collect())
+    }
+
+    fn build_aux_base_trace(
+        &self,
+        main: &RowMajorMatrix<F>,
+        _challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<F> {
+        self.build_challenge_free_aux_trace(main)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ChallengeFreeSquareAir {
+    fn eval(&self, builder: &mut AB) {
+        SquareAir.eval(builder);
+    }
+}
+
+#[test]
+fn test_challenge_free_aux_only() {
+    let config = default_config();
+    let vk = keygen(&config, &ChallengeFreeSquareAir);
+    assert_eq!(vk.challenge_free_aux_width, 1);
+    assert!(!vk.has_aux_phase());
+    assert_eq!(vk.protocol_id.num_aux_phases, 0);
+
+    let proof =
+        prove(&config, &ChallengeFreeSquareAir, main_trace(8), &[]).expect("proving failed");
+    // No auxiliary phase, the column is committed alongside the main trace
+    assert!(proof.aux_commit.is_none());
+    assert!(proof.challenge_free_commit.is_some());
+    assert_eq!(proof.aux_base_local.len(), 1);
+    verify(&config, &ChallengeFreeSquareAir, &proof, &[]).expect("verification failed");
+
+    let mut tampered = proof.clone();
+    tampered.aux_base_local[0] += Challenge::ONE;
+    assert!(verify(&config, &ChallengeFreeSquareAir, &tampered, &[]).is_err());
+
+    // The same column under a challenge-dependent commitment is a different AIR
+    assert!(verify(&config, &SquareAir, &proof, &[]).is_err());
+    let mut missing = proof;
+    missing.challenge_free_commit = None;
+    assert!(verify(&config, &ChallengeFreeSquareAir, &missing, &[]).is_err());
+}
+
+/// [`ScaledAir`] with the multiplicity column `m = x + 1` committed before `scale` is
+/// sampled.
+pub struct ChallengeFreeScaledAir;
+
+impl<F> BaseAir<F> for ChallengeFreeScaledAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ChallengeFreeScaledAir {}
+
+impl<F> PuncturedAir<F> for ChallengeFreeScaledAir {}
+
+impl<F> AirLayout<F> for ChallengeFreeScaledAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ChallengeFreeScaledAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn aux_base_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_free_aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["scale"]
+    }
+
+    fn build_challenge_free_aux_trace(&self, main: &RowMajorMatrix<F>) -> RowMajorMatrix<F> {
+        RowMajorMatrix::new_col(main.values.iter().map(|&x| x + F::ONE).collect())
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        ScaledAir.build_aux_traces(main, challenges)
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ChallengeFreeScaledAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        ScaledAir.eval(builder);
+    }
+}
+
+#[test]
+fn test_challenge_free_aux_with_aux_phase() {
+    let config = default_config();
+    let vk = keygen(&config, &ChallengeFreeScaledAir);
+    assert!(vk.has_aux_phase());
+    assert!(vk.has_challenge_free_aux());
+
+    let proof =
+        prove(&config, &ChallengeFreeScaledAir, main_trace(8), &[]).expect("proving failed");
+    assert!(proof.aux_commit.is_some());
+    assert!(proof.challenge_free_commit.is_some());
+    assert_eq!((proof.aux_local.len(), proof.aux_base_local.len()), (4, 1));
+    assert_eq!(proof.aux_base_next.len(), 1);
+    verify(&config, &ChallengeFreeScaledAir, &proof, &[]).expect("verification failed");
+
+    let mut tampered = proof.clone();
+    tampered.aux_base_next[0] += Challenge::ONE;
+    assert!(verify(&config, &ChallengeFreeScaledAir, &tampered, &[]).is_err());
+
+    // Observed before the challenges, so swapping in another commitment changes them
+    let mut swapped = proof;
+    swapped.challenge_free_commit = swapped.aux_commit.clone();
+    assert!(verify(&config, &ChallengeFreeScaledAir, &swapped, &[]).is_err());
+}
+
+/// One main column `x` cycling through `0..table_height`, and one auxiliary column
+/// holding the table `β·j` for each `j`, only `table_height` rows tall.
+///
+/// Every row, and the next, reads `β·x` from the table.
+pub struct ShortTableAir {
+    table_height: usize,
+}
+
+impl<F> BaseAir<F> for ShortTableAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ShortTableAir {}
+
+impl<F> PuncturedAir<F> for ShortTableAir {}
+
+impl<F> AirLayout<F> for ShortTableAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ShortTableAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["beta"]
+    }
+
+    fn build_aux_traces(
+        &self,
+        _main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let beta = challenges["beta"];
+        AuxTraces {
+            ext: RowMajorMatrix::new_col(
+                (0..self.table_height as u32)
+                    .map(|j| beta * F::from_u32(j))
+                    .collect(),
+            ),
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: Vec::new(),
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ShortTableAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let beta: AB::ExprEF = builder.challenge("beta").into();
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        let t: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let t_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+        let (x, x_next) = (builder.embed_main(x), builder.embed_main(x_next));
+        builder.assert_eq_ext(t, beta.clone() * x);
+        builder.assert_eq_ext(t_next, beta * x_next);
+    }
+}
+
+fn cycling_trace(height: usize, period: u32) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col(
+        (0..height as u32)
+            .map(|row| Val::from_u32(row % period))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_short_aux_trace() {
+    let air = ShortTableAir { table_height: 4 };
+    let beta = [Challenge::from_u32(7)];
+    check_constraints::<Val, Challenge, _>(&air, &cycling_trace(16, 4), None, &beta, &[])
+        .expect("every row reads its table row");
+    assert!(
+        check_constraints::<Val, Challenge, _>(&air, &cycling_trace(16, 8), None, &beta, &[])
+            .is_err()
+    );
+    let prover =
+        MockProver::<Val, Challenge>::run(&air, &cycling_trace(16, 4), &[]).expect("well formed");
+    assert_eq!(prover.aux_traces().ext.height(), 4);
+    assert!(prover.verify().is_ok());
+
+    // The table is committed on a domain of its own
+    let config = default_config();
+    let proof = prove(&config, &air, cycling_trace(16, 4), &[]).expect("proving failed");
+    assert_eq!((proof.log_degree, proof.log_aux_degree), (4, 2));
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+    for log_aux_degree in [3, 4, 5] {
+        let mut tampered = proof.clone();
+        tampered.log_aux_degree = log_aux_degree;
+        assert!(verify(&config, &air, &tampered, &[]).is_err());
+    }
+
+    // The table's height must divide the main trace's
+    assert!(matches!(
+        MockProver::<Val, Challenge>::run(
+            &ShortTableAir { table_height: 3 },
+            &cycling_trace(16, 3),
+            &[]
+        ),
+        Err(MockProverError::AuxShapeMismatch(_))
+    ));
+}
@@ -0,0 +1,156 @@
+//! Several independent statements proven in one trace
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Val};
+use p3_uni_stark_mt::{
+    check_constraints, keygen, prove, verify, AirLayout, AuxTraceBuilder, ProverError,
+    PuncturedAir, StatementBuilder,
+};
+
+/// Fibonacci statements `(a, b) -> x`, one per segment of the trace.
+///
+/// Each statement has public values `[a, b, x]`: its first row is `(a, b)`, its rows
+/// step `(l, r) -> (r, l + r)` and its last row ends with `x`.
+pub struct BatchFibonacciAir {
+    num_statements: usize,
+}
+
+impl<F> BaseAir<F> for BatchFibonacciAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for BatchFibonacciAir {
+    fn num_public_values(&self) -> usize {
+        3 * self.num_statements
+    }
+}
+
+impl<F> PuncturedAir<F> for BatchFibonacciAir {}
+
+impl<F> AirLayout<F> for BatchFibonacciAir {
+    fn num_statements(&self) -> usize {
+        self.num_statements
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for BatchFibonacciAir {}
+
+impl<AB: StatementBuilder> Air<AB> for BatchFibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        let statement = builder.statement_public_values().to_vec();
+        let (a, b, x) = (
+            statement[0].clone(),
+            statement[1].clone(),
+            statement[2].clone(),
+        );
+
+        let mut when_first_row = builder.when_first_statement_row();
+        when_first_row.assert_eq(local[0].clone(), a);
+        when_first_row.assert_eq(local[1].clone(), b);
+
+        let mut when_transition = builder.when_statement_transition();
+        when_transition.assert_eq(local[1].clone(), next[0].clone());
+        when_transition.assert_eq(local[0].clone() + local[1].clone(), next[1].clone());
+
+        builder
+            .when_last_statement_row()
+            .assert_eq(local[1].clone(), x);
+    }
+}
+
+/// Trace of the statements starting from each of `starts`, `height` rows each, and their
+/// public values.
+fn batch_trace(starts: &[(u32, u32)], height: usize) -> (RowMajorMatrix<Val>, Vec<Val>) {
+    let mut values = Vec::with_capacity(2 * height * starts.len());
+    let mut public_values = Vec::with_capacity(3 * starts.len());
+    for &(a, b) in starts {
+        let (mut left, mut right) = (Val::from_u32(a), Val::from_u32(b));
+        for _ in 0..height {
+            values.extend([left, right]);
+            (left, right) = (right, left + right);
+        }
+        let last = values[values.len() - 1];
+        public_values.extend([Val::from_u32(a), Val::from_u32(b), last]);
+    }
+    (RowMajorMatrix::new(values, 2), public_values)
+}
+
+const STARTS: [(u32, u32); 4] = [(0, 1), (2, 3), (1, 1), (5, 8)];
+
+#[test]
+fn test_batch_prove_verify() {
+    let air = BatchFibonacciAir { num_statements: 4 };
+    let (trace, public_values) = batch_trace(&STARTS, 8);
+    check_constraints::<Val, Challenge, _>(&air, &trace, None, &[], &public_values)
+        .expect("every statement holds");
+
+    let config = default_config();
+    assert_eq!(keygen(&config, &air).num_statements, 4);
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
+    verify(&config, &air, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_batch_rejects_wrong_statement() {
+    let air = BatchFibonacciAir { num_statements: 4 };
+    let (trace, public_values) = batch_trace(&STARTS, 8);
+    let config = default_config();
+    let proof = prove(&config, &air, trace.clone(), &public_values).expect("proving failed");
+
+    // A wrong output for one statement
+    let mut wrong_output = public_values.clone();
+    wrong_output[3 * 2 + 2] += Val::ONE;
+    assert!(verify(&config, &air, &proof, &wrong_output).is_err());
+
+    // Statements bound in a different order
+    let mut swapped = public_values.clone();
+    swapped[..6].rotate_left(3);
+    assert!(verify(&config, &air, &proof, &swapped).is_err());
+
+    let violations = check_constraints::<Val, Challenge, _>(&air, &trace, None, &[], &wrong_output)
+        .expect_err("wrong output must be rejected")
+        .0;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].row, 3 * 8 - 1);
+}
+
+#[test]
+fn test_single_statement() {
+    let air = BatchFibonacciAir { num_statements: 1 };
+    let (trace, public_values) = batch_trace(&STARTS[..1], 16);
+    let config = default_config();
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
+    verify(&config, &air, &proof, &public_values).expect("verification failed");
+}
+
+#[test]
+fn test_statements_must_split_trace() {
+    let config = default_config();
+
+    // Statements of a single row
+    let air = BatchFibonacciAir { num_statements: 4 };
+    let (trace, public_values) = batch_trace(&STARTS, 1);
+    assert!(matches!(
+        prove(&config, &air, trace, &public_values),
+        Err(ProverError::InvalidStatements(_))
+    ));
+
+    // More statements than the trace can be split into evenly
+    let air = BatchFibonacciAir { num_statements: 3 };
+    let (trace, public_values) = batch_trace(&STARTS[..3], 4);
+    let trace = RowMajorMatrix::new(trace.values[..2 * 8].to_vec(), 2);
+    assert!(matches!(
+        prove(&config, &air, trace, &public_values),
+        Err(ProverError::InvalidStatements(_))
+    ));
+}
@@ -0,0 +1,59 @@
+//! Binary size budget of the `minimal` feature
+//!
+//! Builds the verifier-only `minimal_verifier` example in release mode with
+//! `--no-default-features --features minimal,presets`, `presets` only for the
+//! configuration it verifies under, and checks the binary stays below a fixed budget.
+//! The bound is what an embedded or on-chain verifier can afford to ship; a prover-side
+//! dependency or a tracing call site that leaks into the minimal build shows up here long
+//! before it shows up in a deployment.
+//!
+//! | Binary             | Preset                | Profile | Bound (bytes) |
+//! |--------------------|-----------------------|---------|---------------|
+//! | `minimal_verifier` | `baby_bear_poseidon2` | release | 2 MiB         |
+//!
+//! The build takes a while, so the test is ignored by default:
+//!
+//! ```text
+//! cargo test --test binary_size -- --ignored
+//! ```
+
+use std::path::PathBuf;
+use std::process::Command;
+
+const MINIMAL_VERIFIER_MAX_BYTES: u64 = 2 << 20;
+
+#[test]
+#[ignore = "builds the example in release mode"]
+fn test_minimal_verifier_size() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // A target directory of its own, so the build doesn't wait on the test's lock
+    let target_dir = manifest_dir.join("../target/binary-size");
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let status = Command::new(cargo)
+        .current_dir(&manifest_dir)
+        .args([
+            "build",
+            "--release",
+            "--example",
+            "minimal_verifier",
+            "--no-default-features",
+            "--features",
+            "minimal,presets",
+            "--target-dir",
+        ])
+        .arg(&target_dir)
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "minimal build failed");
+
+    let binary = target_dir
+        .join("release/examples/minimal_verifier")
+        .with_extension(std::env::consts::EXE_EXTENSION);
+    let size = std::fs::metadata(&binary)
+        .expect("example binary is missing")
+        .len();
+    assert!(
+        size <= MINIMAL_VERIFIER_MAX_BYTES,
+        "minimal_verifier is {size} bytes, budget is {MINIMAL_VERIFIER_MAX_BYTES}"
+    );
+}
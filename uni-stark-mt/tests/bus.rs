@@ -0,0 +1,174 @@
+//! Buses balanced across separately proven tables
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Val};
+use p3_uni_stark_mt::{
+    check_bus_balance, keygen, prove, verify, AirLayout, AuxBuilder, AuxTraceBuilder, AuxTraces,
+    BusContribution, BusDecodeError, ChallengeSet, PuncturedAir, VerifyingKey,
+};
+
+/// One main column `x` sent to (`multiplicity` 1) or received from (`multiplicity` -1) a
+/// LogUp bus, exposing its share `multiplicity·Σ 1/(α - x)`.
+///
+/// The bus challenge `α` is set by the application for all tables, and bound as the
+/// only public value.
+pub struct BusAir {
+    multiplicity: i32,
+    alpha: u32,
+}
+
+/// Fixed by the application once every table's main trace is committed
+const ALPHA: u32 = 1 << 20;
+
+const SENDER: BusAir = BusAir {
+    multiplicity: 1,
+    alpha: ALPHA,
+};
+const RECEIVER: BusAir = BusAir {
+    multiplicity: -1,
+    alpha: ALPHA,
+};
+
+impl<F> BaseAir<F> for BusAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for BusAir {
+    fn num_public_values(&self) -> usize {
+        1
+    }
+}
+
+impl<F> PuncturedAir<F> for BusAir {}
+
+impl<F> AirLayout<F> for BusAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for BusAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_aux_exposed_values(&self) -> usize {
+        1
+    }
+
+    fn bus_contribution_index(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        _challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let (alpha, m) = (EF::from_u32(self.alpha), EF::from_i32(self.multiplicity));
+        let mut sum = EF::ZERO;
+        let values: Vec<EF> = main
+            .values
+            .iter()
+            .map(|&x| {
+                sum += m * (alpha - x).inverse();
+                sum
+            })
+            .collect();
+        AuxTraces {
+            ext: RowMajorMatrix::new_col(values),
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: vec![sum],
+        }
+    }
+}
+
+impl<AB: AuxBuilder + AirBuilderWithPublicValues> Air<AB> for BusAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let alpha = builder.embed_main(builder.public_values()[0]);
+        let m = builder.embed_main(AB::Expr::from_i32(self.multiplicity));
+        let total: AB::ExprEF = builder.aux_exposed_values()[0].into();
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        let is_first_row = builder.embed_main(builder.is_first_row());
+        let is_transition = builder.embed_main(builder.is_transition());
+        let is_last_row = builder.embed_main(builder.is_last_row());
+        let (x, x_next) = (builder.embed_main(x), builder.embed_main(x_next));
+        // s_0 = m/(α - x_0), s_{i+1} = s_i + m/(α - x_{i+1}) and the total is the last s
+        builder.assert_eq_ext(
+            is_first_row.clone() * s.clone() * (alpha.clone() - x),
+            is_first_row * m.clone(),
+        );
+        builder.assert_eq_ext(
+            is_transition.clone() * (s_next - s.clone()) * (alpha - x_next),
+            is_transition * m,
+        );
+        builder.assert_eq_ext(is_last_row.clone() * total, is_last_row * s);
+    }
+}
+
+fn trace(values: impl IntoIterator<Item = u32>) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col(values.into_iter().map(Val::from_u32).collect())
+}
+
+#[test]
+fn test_bus_balances_across_proofs() {
+    let config = default_config();
+    let alpha = Val::from_u32(ALPHA);
+
+    let sent = prove(&config, &SENDER, trace(0..8), &[alpha]).expect("proving failed");
+    let received =
+        prove(&config, &RECEIVER, trace((0..8).rev()), &[alpha]).expect("proving failed");
+    verify(&config, &SENDER, &sent, &[alpha]).expect("verification failed");
+    verify(&config, &RECEIVER, &received, &[alpha]).expect("verification failed");
+
+    let contributions = [
+        BusContribution::from_proof(&keygen(&config, &SENDER), &sent).unwrap(),
+        BusContribution::from_proof(&keygen(&config, &RECEIVER), &received).unwrap(),
+    ];
+    assert_ne!(contributions[0].sum, Challenge::ZERO);
+    check_bus_balance(&contributions).expect("the bus is balanced");
+
+    // Contributions travel between machines in their canonical encoding
+    let bytes = contributions[0].to_bytes();
+    assert_eq!(BusContribution::from_bytes(&bytes), Ok(contributions[0]));
+    assert_eq!(
+        BusContribution::<Challenge>::from_bytes(&bytes[..1]),
+        Err(BusDecodeError::Malformed)
+    );
+
+    // A receiver missing a value leaves the bus unbalanced
+    let short = prove(&config, &RECEIVER, trace((1..9).rev()), &[alpha]).expect("proving failed");
+    verify(&config, &RECEIVER, &short, &[alpha]).expect("verification failed");
+    let unbalanced = [
+        contributions[0],
+        BusContribution::from_proof(&keygen(&config, &RECEIVER), &short).unwrap(),
+    ];
+    let imbalance = check_bus_balance(&unbalanced).expect_err("value 0 is never received");
+    assert_eq!(imbalance.sum, unbalanced[0].sum + unbalanced[1].sum);
+}
+
+#[test]
+fn test_no_bus_contribution_by_default() {
+    let config = default_config();
+    let vk: VerifyingKey = keygen(&config, &SENDER);
+    assert_eq!(vk.bus_contribution_index, Some(0));
+    let without_bus = VerifyingKey {
+        bus_contribution_index: None,
+        ..vk
+    };
+    let proof =
+        prove(&config, &SENDER, trace(0..8), &[Val::from_u32(ALPHA)]).expect("proving failed");
+    assert_eq!(BusContribution::from_proof(&without_bus, &proof), None);
+}
@@ -0,0 +1,54 @@
+//! Capability discovery of the linked build
+
+use p3_uni_stark_mt::{capabilities, Capability, ProofEncoding, PROTOCOL_VERSION};
+
+#[test]
+fn test_capabilities_describe_the_build() {
+    let capabilities = capabilities();
+    assert_eq!(capabilities.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(capabilities.protocol_version, PROTOCOL_VERSION);
+    assert_eq!(
+        capabilities.has(Capability::Parallel),
+        cfg!(feature = "parallel")
+    );
+    // Enabled for the integration tests by the dev-dependency on the crate itself
+    assert!(capabilities.has(Capability::Circle));
+    assert_eq!(
+        capabilities.has(Capability::Symbolic),
+        !cfg!(feature = "minimal")
+    );
+    assert!(capabilities
+        .proof_encodings
+        .contains(&ProofEncoding::Postcard));
+    assert!(capabilities
+        .proof_encodings
+        .contains(&ProofEncoding::Versioned));
+
+    let baby_bear = capabilities
+        .preset("baby_bear_poseidon2")
+        .expect("presets are built");
+    assert_eq!(baby_bear.extension_degree, 4);
+    assert!(!baby_bear.zk);
+    let goldilocks = capabilities
+        .preset("goldilocks_keccak")
+        .expect("presets are built");
+    assert_eq!(goldilocks.extension_degree, 2);
+    assert!(capabilities.preset("mersenne_31").is_none());
+}
+
+#[test]
+fn test_check_reports_first_missing_capability() {
+    let capabilities = capabilities();
+    assert_eq!(capabilities.check(&[]), Ok(()));
+    if cfg!(feature = "minimal") {
+        assert_eq!(
+            capabilities.check(&[Capability::Recursion, Capability::ProofDiff]),
+            Err(Capability::Recursion)
+        );
+    } else {
+        assert_eq!(
+            capabilities.check(&[Capability::Recursion, Capability::ProofDiff]),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! Proving over Mersenne31 with the circle PCS
+
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_circle::CirclePcs;
+use p3_commit::ExtensionMmcs;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::create_test_fri_params;
+use p3_keccak::Keccak256Hash;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{prove, verify, StarkConfig};
+
+type Val = Mersenne31;
+type Challenge = BinomialExtensionField<Val, 3>;
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn make_config() -> MyConfig {
+    let byte_hash = ByteHash {};
+    let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_params = create_test_fri_params(challenge_mmcs, 0);
+    let pcs = Pcs::new(val_mmcs, fri_params);
+    MyConfig::new(pcs, Challenger::from_hasher(vec![], byte_hash))
+}
+
+#[test]
+fn test_circle_fibonacci() {
+    let config = make_config();
+    for log_n in [3, 6] {
+        let proof = prove(&config, &FibonacciAir, fibonacci_trace(1 << log_n), &[])
+            .expect("proving failed");
+        verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+    }
+}
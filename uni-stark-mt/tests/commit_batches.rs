@@ -0,0 +1,152 @@
+//! Wide main traces committed in column batches, possibly streamed batch by batch
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Val};
+use p3_uni_stark_mt::{
+    commit_main, commit_main_streaming, prove, prove_with_committed_main, prove_with_streamed_main,
+    verify, AirLayout, AuxTraceBuilder, OpeningSchedule, PuncturedAir,
+};
+
+const WIDTH: usize = 10;
+
+/// Column `j` counts up from `j`, one per row.
+pub struct CountersAir {
+    openings: OpeningSchedule,
+}
+
+impl<F> BaseAir<F> for CountersAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for CountersAir {}
+
+impl<F> PuncturedAir<F> for CountersAir {}
+
+impl<F> AirLayout<F> for CountersAir {
+    fn main_openings(&self) -> OpeningSchedule {
+        self.openings
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CountersAir {}
+
+impl<AB: AirBuilder> Air<AB> for CountersAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        for j in 0..WIDTH {
+            builder
+                .when_first_row()
+                .assert_eq(local[j].clone(), AB::Expr::from_usize(j));
+            builder
+                .when_transition()
+                .assert_eq(local[j].clone() + AB::Expr::ONE, next[j].clone());
+        }
+    }
+}
+
+fn counters_trace(height: usize) -> RowMajorMatrix<Val> {
+    let values = (0..height)
+        .flat_map(|row| (0..WIDTH).map(move |j| Val::from_usize(row + j)))
+        .collect();
+    RowMajorMatrix::new(values, WIDTH)
+}
+
+const AIR: CountersAir = CountersAir {
+    openings: OpeningSchedule::TRANSITION,
+};
+
+#[test]
+fn test_batched_main_commit() {
+    let config = default_config().with_main_commit_batch_width(4);
+    let proof = prove(&config, &AIR, counters_trace(16), &[]).expect("proving failed");
+    assert_eq!(proof.main_commits.len(), 3);
+    assert_eq!(proof.main_local.len(), WIDTH);
+    assert_eq!(proof.main_next.len(), WIDTH);
+    verify(&config, &AIR, &proof, &[]).expect("verification failed");
+
+    // The verifier must split the trace the same way
+    assert!(verify(&default_config(), &AIR, &proof, &[]).is_err());
+    let config_of_five = default_config().with_main_commit_batch_width(5);
+    assert!(verify(&config_of_five, &AIR, &proof, &[]).is_err());
+
+    // Batches are bound in order
+    let mut swapped = proof;
+    swapped.main_commits.swap(0, 1);
+    assert!(verify(&config, &AIR, &swapped, &[]).is_err());
+}
+
+#[test]
+fn test_batch_wider_than_trace_is_one_commit() {
+    let config = default_config().with_main_commit_batch_width(WIDTH);
+    let proof = prove(&config, &AIR, counters_trace(16), &[]).expect("proving failed");
+    assert_eq!(proof.main_commits.len(), 1);
+
+    // A batch covering the whole trace proves exactly as without batching
+    let unbatched =
+        prove(&default_config(), &AIR, counters_trace(16), &[]).expect("proving failed");
+    verify(&default_config(), &AIR, &proof, &[]).expect("verification failed");
+    assert_eq!(proof.size_in_bytes(), unbatched.size_in_bytes());
+}
+
+#[test]
+fn test_batched_commit_reused_across_proofs() {
+    let config = default_config().with_main_commit_batch_width(3);
+    let committed = commit_main(&config, &AIR, counters_trace(8)).expect("commit failed");
+    assert_eq!(committed.commits.len(), 4);
+    let proof = prove_with_committed_main(&config, &AIR, &committed, &[]).expect("proving failed");
+    verify(&config, &AIR, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_batched_last_row_opening() {
+    let air = CountersAir {
+        openings: OpeningSchedule::TRANSITION.with_last_row(),
+    };
+    let config = default_config().with_main_commit_batch_width(4);
+    let proof = prove(&config, &air, counters_trace(16), &[]).expect("proving failed");
+    assert_eq!(proof.main_last.len(), WIDTH);
+    assert_eq!(
+        proof.main_last[WIDTH - 1],
+        Challenge::from(Val::from_usize(15 + WIDTH - 1))
+    );
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_streamed_main_commit() {
+    let air = CountersAir {
+        openings: OpeningSchedule::TRANSITION.with_last_row(),
+    };
+    let config = default_config().with_main_commit_batch_width(4);
+
+    // Each batch is generated when asked for, never the whole trace
+    let mut requested = Vec::new();
+    let streamed = commit_main_streaming(&config, &air, 16, |columns| {
+        requested.push(columns.clone());
+        let values = (0..16)
+            .flat_map(|row| columns.clone().map(move |j| Val::from_usize(row + j)))
+            .collect();
+        RowMajorMatrix::new(values, columns.len())
+    })
+    .expect("commit failed");
+    assert_eq!(requested, [0..4, 4..8, 8..10]);
+
+    let proof = prove_with_streamed_main(&config, &air, &streamed, &[]).expect("proving failed");
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+
+    // Same proof as from the whole trace
+    let whole = prove(&config, &air, counters_trace(16), &[]).expect("proving failed");
+    assert_eq!(
+        postcard::to_allocvec(&proof).unwrap(),
+        postcard::to_allocvec(&whole).unwrap()
+    );
+}
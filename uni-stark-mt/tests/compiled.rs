@@ -0,0 +1,249 @@
+//! Evaluating AIRs through compiled constraint programs
+#![cfg(not(feature = "minimal"))]
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{Challenge, Val};
+use p3_uni_stark_mt::test_utils::{roundtrip, roundtrip_config};
+use p3_uni_stark_mt::{
+    compile_air, keygen, symbolic_constraints, verify_program, AirLayout, AuxBuilder,
+    AuxTraceBuilder, AuxTraces, ChallengeSet, ConstraintProgram, ConstraintValue, Instruction,
+    Leaf, MockProver, PuncturedAir, RowOffset, VerificationError, VerifyingKey,
+};
+
+/// Three main columns `a`, `b` and `c`, with `c = (a + b)²` written out as a product of
+/// two separately built sums.
+pub struct SquaredSumAir;
+
+impl<F> BaseAir<F> for SquaredSumAir {
+    fn width(&self) -> usize {
+        3
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SquaredSumAir {}
+
+impl<F> PuncturedAir<F> for SquaredSumAir {}
+
+impl<F> AirLayout<F> for SquaredSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SquaredSumAir {}
+
+impl<AB: AirBuilder> Air<AB> for SquaredSumAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let (a, b, c) = (local[0].clone(), local[1].clone(), local[2].clone());
+        let sum = || AB::Expr::from(a.clone()) + b.clone().into();
+        builder.assert_eq(c, sum() * sum());
+    }
+}
+
+fn squared_sum_trace(height: usize) -> RowMajorMatrix<Val> {
+    let values = (0..height as u32)
+        .flat_map(|row| {
+            let (a, b) = (Val::from_u32(row), Val::from_u32(3 * row + 1));
+            [a, b, (a + b).square()]
+        })
+        .collect();
+    RowMajorMatrix::new(values, 3)
+}
+
+/// One main column `x` counting up from 0, and one auxiliary column `s` holding the
+/// running sum of `β·x`.
+pub struct ScaledSumAir;
+
+impl<F> BaseAir<F> for ScaledSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ScaledSumAir {}
+
+impl<F> PuncturedAir<F> for ScaledSumAir {}
+
+impl<F> AirLayout<F> for ScaledSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ScaledSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["beta"]
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let beta = challenges["beta"];
+        let mut sum = EF::ZERO;
+        let values: Vec<EF> = main
+            .values
+            .iter()
+            .map(|&x| {
+                sum += beta * x;
+                sum
+            })
+            .collect();
+        AuxTraces {
+            ext: RowMajorMatrix::new_col(values),
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: Vec::new(),
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ScaledSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let beta: AB::ExprEF = builder.challenge("beta").into();
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        builder.when_first_row().assert_zero(x.clone());
+        builder
+            .when_transition()
+            .assert_eq(x.clone() + AB::Expr::ONE, x_next.clone());
+
+        let is_first_row = builder.embed_main(builder.is_first_row());
+        let is_transition = builder.embed_main(builder.is_transition());
+        let (x, x_next) = (builder.embed_main(x), builder.embed_main(x_next));
+        builder.assert_eq_ext(
+            is_first_row.clone() * s.clone(),
+            is_first_row * beta.clone() * x,
+        );
+        builder.assert_eq_ext(
+            is_transition.clone() * (s_next - s),
+            is_transition * beta * x_next,
+        );
+    }
+}
+
+fn counter_trace(height: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col((0..height as u32).map(Val::from_u32).collect())
+}
+
+#[test]
+fn test_program_shares_subexpressions() {
+    let symbolic = symbolic_constraints::<Val, Challenge, _>(&SquaredSumAir);
+    let program = ConstraintProgram::compile(&symbolic);
+
+    // c, a, b, a + b, (a + b)², c - (a + b)², against 9 nodes in the expression tree
+    assert_eq!(program.num_registers(), 6);
+    assert_eq!(
+        program
+            .instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Add(..)))
+            .count(),
+        1
+    );
+    assert_eq!(program.constraints.len(), 1);
+    assert!(!program.constraints[0].extension);
+
+    // The program computes what the expression does
+    let values = program.run(
+        |_| Val::from_u32(5),
+        |_| -> Challenge { unreachable!("no extension-field leaves") },
+    );
+    assert_eq!(values.len(), 1);
+    let ConstraintValue::Base(value) = values[0] else {
+        panic!("base-field constraint evaluated in the extension field");
+    };
+    assert_eq!(
+        value,
+        symbolic.constraints[0].expr.evaluate(&|_| Val::from_u32(5))
+    );
+}
+
+#[test]
+fn test_compiled_air_roundtrip() {
+    let compiled = compile_air::<Val, Challenge, _>(&SquaredSumAir);
+    let trace = squared_sum_trace(8);
+    let prover = MockProver::<Val, Challenge>::run(&compiled, &trace, &[]).expect("well formed");
+    assert!(prover.verify().is_ok());
+    roundtrip(&compiled, trace, &[]).expect("round trip failed");
+
+    // A wrong square is caught on its row
+    let mut trace = squared_sum_trace(8);
+    trace.values[3 * 5 + 2] += Val::ONE;
+    let prover = MockProver::<Val, Challenge>::run(&compiled, &trace, &[]).expect("well formed");
+    let violations = prover.verify().expect_err("a wrong square must be caught");
+    assert_eq!(violations.0.len(), 1);
+    assert_eq!(violations.0[0].row, 5);
+}
+
+#[test]
+fn test_compiled_air_with_aux_roundtrip() {
+    let compiled = compile_air::<Val, Challenge, _>(&ScaledSumAir);
+    let program = compiled.program();
+    let extension: Vec<bool> = program
+        .constraints
+        .iter()
+        .map(|constraint| constraint.extension)
+        .collect();
+    assert_eq!(extension, [false, false, true, true]);
+
+    let prover =
+        MockProver::<Val, Challenge>::run(&compiled, &counter_trace(8), &[]).expect("well formed");
+    assert!(prover.verify().is_ok());
+    roundtrip(&compiled, counter_trace(8), &[]).expect("round trip failed");
+}
+
+#[test]
+fn test_verify_program_without_air() {
+    let proof = roundtrip(&ScaledSumAir, counter_trace(8), &[]).expect("round trip failed");
+    let config = roundtrip_config();
+
+    // The verifier only receives the program and the verifying key, as bytes
+    let program = postcard::to_allocvec(compile_air::<Val, Challenge, _>(&ScaledSumAir).program())
+        .expect("programs serialize");
+    let vk = postcard::to_allocvec(&keygen(&config, &ScaledSumAir)).expect("keys serialize");
+    let program: ConstraintProgram<Val> = postcard::from_bytes(&program).expect("round trip");
+    let vk: VerifyingKey = postcard::from_bytes(&vk).expect("round trip");
+    verify_program(&config, &vk, &program, &proof, &[]).expect("proof should verify");
+
+    // Other constraints reject the proof
+    let mut swapped = program.clone();
+    swapped.constraints.swap(0, 1);
+    assert!(matches!(
+        verify_program(&config, &vk, &swapped, &proof, &[]),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+
+    // Programs that don't fit the AIR are rejected before being run
+    let mut truncated = program.clone();
+    truncated.constraints.pop();
+    assert!(matches!(
+        verify_program(&config, &vk, &truncated, &proof, &[]),
+        Err(VerificationError::InvalidConstraintProgram(_))
+    ));
+    let mut out_of_range = program;
+    let leaf = out_of_range
+        .instructions
+        .iter_mut()
+        .find(|instruction| matches!(instruction, Instruction::Leaf(Leaf::Main { .. })))
+        .expect("the program reads the main trace");
+    *leaf = Instruction::Leaf(Leaf::Main {
+        row: RowOffset::Local,
+        column: 1,
+    });
+    assert!(matches!(
+        verify_program(&config, &vk, &out_of_range, &proof, &[]),
+        Err(VerificationError::InvalidConstraintProgram(_))
+    ));
+}
@@ -0,0 +1,103 @@
+//! Proofs carry a digest of the parameters they were made with
+
+use p3_baby_bear::default_babybear_poseidon2_16;
+use p3_fri::create_test_fri_params;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{self as bb, default_config};
+use p3_uni_stark_mt::config::goldilocks_keccak;
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{
+    config_digest, prove, verify, SecurityParameters, StarkConfig, StarkConfigBuilder,
+    VerificationError,
+};
+
+/// The BabyBear preset's components with `num_queries` FRI queries, assembled by hand
+/// rather than by the builder, so they record no parameters.
+fn hand_built(num_queries: usize) -> bb::Config {
+    let perm = default_babybear_poseidon2_16();
+    let val_mmcs = bb::ValMmcs::new(bb::Hash::new(perm.clone()), bb::Compress::new(perm.clone()));
+    let mut fri_params = create_test_fri_params(bb::ChallengeMmcs::new(val_mmcs.clone()), 0);
+    fri_params.num_queries = num_queries;
+    let pcs = bb::Pcs::new(bb::Dft::default(), val_mmcs, fri_params);
+    StarkConfig::new(pcs, bb::Challenger::new(perm))
+}
+
+#[test]
+fn test_config_digest_tracks_parameters() {
+    let digest = config_digest(&default_config());
+    assert!(digest.is_some());
+    // Configurations built alike agree
+    assert_eq!(
+        config_digest(&StarkConfigBuilder::baby_bear().build().unwrap()),
+        digest
+    );
+
+    for other in [
+        StarkConfigBuilder::baby_bear()
+            .log_blowup(3)
+            .build()
+            .unwrap(),
+        StarkConfigBuilder::baby_bear().queries(50).build().unwrap(),
+    ] {
+        assert_ne!(config_digest(&other), digest);
+    }
+    assert_ne!(config_digest(&goldilocks_keccak::default_config()), digest);
+
+    // Grinding and out-of-domain points are read from the configuration itself
+    assert_ne!(config_digest(&default_config().with_pow_bits(8)), digest);
+    assert_ne!(
+        config_digest(&default_config().with_num_ood_points(2)),
+        digest
+    );
+}
+
+#[test]
+fn test_unrecorded_parameters_give_no_digest() {
+    // Nothing in the PCS tells these apart, so neither gets a digest rather than a shared
+    // one
+    let (few, many) = (hand_built(40), hand_built(80));
+    assert_eq!(config_digest(&few), None);
+    assert_eq!(config_digest(&many), None);
+    let proof = prove(&few, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    assert_eq!(proof.config_digest, None);
+    verify(&few, &FibonacciAir, &proof, &[]).expect("verification failed");
+
+    // Once recorded, the number of queries is covered
+    let parameters = |num_queries| SecurityParameters {
+        log_blowup: 1,
+        num_queries,
+        query_pow_bits: 0,
+        ood_pow_bits: 0,
+        log_challenge_field_size: 124,
+    };
+    let few = config_digest(&hand_built(40).with_security_parameters(parameters(40)));
+    let many = config_digest(&hand_built(80).with_security_parameters(parameters(80)));
+    assert!(few.is_some() && many.is_some());
+    assert_ne!(few, many);
+}
+
+#[test]
+fn test_proof_under_other_parameters_rejected() {
+    let config = default_config();
+    let mut proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    assert_eq!(proof.config_digest, config_digest(&config));
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+
+    let other = StarkConfigBuilder::baby_bear()
+        .log_blowup(3)
+        .build()
+        .unwrap();
+    let (expected, actual) = (
+        config_digest(&other).unwrap(),
+        config_digest(&config).unwrap(),
+    );
+    assert!(matches!(
+        verify(&other, &FibonacciAir, &proof, &[]),
+        Err(VerificationError::ConfigMismatch { expected: e, actual: a })
+            if e == expected && a == actual
+    ));
+
+    // Without a digest, the mismatch goes unexplained but the proof is still rejected
+    proof.config_digest = None;
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+    assert!(verify(&other, &FibonacciAir, &proof, &[]).is_err());
+}
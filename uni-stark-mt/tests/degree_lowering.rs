@@ -0,0 +1,153 @@
+//! Lowering high-degree constraints through helper columns
+#![cfg(not(feature = "minimal"))]
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{Challenge, Val};
+use p3_uni_stark_mt::test_utils::roundtrip;
+use p3_uni_stark_mt::{
+    lower_air, lower_degree, symbolic_constraints, AirLayout, AuxTraceBuilder, MockProver,
+    PuncturedAir, UnlowerableConstraint,
+};
+
+/// One main column `x` starting at 2, raised to the 7th power on every transition.
+pub struct SeventhPowerAir;
+
+impl<F> BaseAir<F> for SeventhPowerAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SeventhPowerAir {}
+
+impl<F> PuncturedAir<F> for SeventhPowerAir {}
+
+impl<F> AirLayout<F> for SeventhPowerAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SeventhPowerAir {}
+
+impl<AB: AirBuilder> Air<AB> for SeventhPowerAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        builder
+            .when_first_row()
+            .assert_eq(local.clone(), AB::Expr::TWO);
+        let x: AB::Expr = local.into();
+        let seventh: AB::Expr = (0..7).map(|_| x.clone()).product();
+        builder.when_transition().assert_eq(next, seventh);
+    }
+}
+
+fn seventh_power_trace(height: usize) -> RowMajorMatrix<Val> {
+    let mut x = Val::TWO;
+    let values = (0..height)
+        .map(|_| {
+            let value = x;
+            x = x.exp_u64(7);
+            value
+        })
+        .collect();
+    RowMajorMatrix::new(values, 1)
+}
+
+/// One main column `x` constrained under both the first- and last-row selectors: a
+/// constraint of degree 3, two of it from selectors no helper column can stand for.
+pub struct SelectorsAir;
+
+impl<F> BaseAir<F> for SelectorsAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SelectorsAir {}
+
+impl<F> PuncturedAir<F> for SelectorsAir {}
+
+impl<F> AirLayout<F> for SelectorsAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SelectorsAir {}
+
+impl<AB: AirBuilder> Air<AB> for SelectorsAir {
+    fn eval(&self, builder: &mut AB) {
+        let x = builder.main().row_slice(0).expect("Matrix is empty?")[0].clone();
+        builder.when_first_row().when_last_row().assert_zero(x);
+    }
+}
+
+#[test]
+fn test_lowered_constraints_stay_within_degree() {
+    let symbolic = symbolic_constraints::<Val, Challenge, _>(&SeventhPowerAir);
+    assert_eq!(symbolic.constraints[1].expr.degree(), 7);
+
+    let lowered = lower_degree(&symbolic, 3).expect("main trace products can be lowered");
+    // x^3, then x^3·x^2
+    assert_eq!(lowered.helpers.len(), 2);
+    assert_eq!(lowered.main_width, 1);
+    assert_eq!(lowered.symbolic.constraints.len(), 4);
+    assert!(lowered
+        .symbolic
+        .constraints
+        .iter()
+        .all(|constraint| constraint.expr.degree() <= 3));
+
+    // Degree 2 needs more helpers
+    let quadratic = lower_degree(&symbolic, 2).expect("main trace products can be lowered");
+    assert!(quadratic.helpers.len() > 2);
+    assert!(quadratic
+        .symbolic
+        .constraints
+        .iter()
+        .all(|constraint| constraint.expr.degree() <= 2));
+}
+
+#[test]
+fn test_lowered_air_roundtrip() {
+    let lowered = lower_air::<Val, Challenge, _>(&SeventhPowerAir, 3).expect("lowerable");
+    let trace = lowered.extend_trace(&seventh_power_trace(8), &[]);
+    assert_eq!(trace.width(), 3);
+    let x = trace.values[3];
+    assert_eq!(trace.values[4], x.exp_u64(3));
+    assert_eq!(trace.values[5], x.exp_u64(5));
+
+    let prover = MockProver::<Val, Challenge>::run(&lowered, &trace, &[]).expect("well formed");
+    assert!(prover.verify().is_ok());
+    roundtrip(&lowered, trace, &[]).expect("round trip failed");
+}
+
+#[test]
+fn test_lowered_air_rejects_bad_helpers() {
+    let lowered = lower_air::<Val, Challenge, _>(&SeventhPowerAir, 3).expect("lowerable");
+    let mut trace = lowered.extend_trace(&seventh_power_trace(8), &[]);
+    trace.values[3 * 3 + 1] += Val::ONE;
+
+    let prover = MockProver::<Val, Challenge>::run(&lowered, &trace, &[]).expect("well formed");
+    let violations = prover.verify().expect_err("a wrong helper must be caught");
+    // The helper's definition on its row, and the helper chained onto it
+    let constraints: Vec<_> = violations
+        .0
+        .iter()
+        .map(|violation| (violation.row, violation.constraint_index))
+        .collect();
+    assert_eq!(constraints, [(3, 2), (3, 3)]);
+}
+
+#[test]
+fn test_selector_products_cannot_be_lowered() {
+    let symbolic = symbolic_constraints::<Val, Challenge, _>(&SelectorsAir);
+    assert_eq!(
+        lower_degree(&symbolic, 2).unwrap_err(),
+        UnlowerableConstraint {
+            constraint_index: 0,
+            lowest_degree: 3,
+        }
+    );
+    assert!(lower_degree(&symbolic, 3).is_ok());
+}
@@ -5,10 +5,11 @@
 
 use core::borrow::Borrow;
 
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::DuplexChallenger;
 use p3_commit::ExtensionMmcs;
+use p3_commit::LagrangeSelectors;
 use p3_dft::Radix2DitParallel;
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{Field, PrimeCharacteristicRing, PrimeField64};
@@ -17,9 +18,16 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark_mt::{prove, verify, AuxTraceBuilder, StarkConfig};
+#[cfg(not(feature = "minimal"))]
+use p3_uni_stark_mt::RecursiveVerifierFolder;
+use p3_uni_stark_mt::{
+    check_constraints, keygen, prove, verify, verify_with_vk, AirLayout, AuxTraceBuilder,
+    ChallengeSet, ConfigError, OpeningSchedule, PuncturedAir, RotatedRows, SecurityBound,
+    SecurityError, SecurityLevel, SecurityTarget, StarkConfig, StarkConfigBuilder,
+    StatementSelectors, TraceProvenance, VerificationError, VerifierFolder,
+};
 use rand::rngs::SmallRng;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 
 /// Simple Fibonacci AIR without public values
 pub struct FibonacciAir {
@@ -33,6 +41,12 @@ impl<F> BaseAir<F> for FibonacciAir {
     }
 }
 
+impl<F> BaseAirWithPublicValues<F> for FibonacciAir {}
+
+impl<F> PuncturedAir<F> for FibonacciAir {}
+
+impl<F> AirLayout<F> for FibonacciAir {}
+
 // Implement AuxTraceBuilder with no auxiliary trace (single-phase proving)
 impl<F: Field, EF> AuxTraceBuilder<F, EF> for FibonacciAir
 where
@@ -41,10 +55,6 @@ where
     fn aux_width(&self) -> usize {
         0 // No auxiliary trace
     }
-
-    fn num_challenges(&self) -> usize {
-        0 // No challenges needed
-    }
 }
 
 impl<AB: AirBuilder> Air<AB> for FibonacciAir {
@@ -133,6 +143,18 @@ type Dft = Radix2DitParallel<Val>;
 type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
 
+fn make_config(log_final_poly_len: usize) -> MyConfig {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm.clone());
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    let fri_params = create_test_fri_params(challenge_mmcs, log_final_poly_len);
+    let pcs = Pcs::new(Dft::default(), val_mmcs, fri_params);
+    MyConfig::new(pcs, Challenger::new(perm))
+}
+
 #[test]
 fn test_fibonacci_basic() {
     let mut rng = SmallRng::seed_from_u64(1);
@@ -159,11 +181,14 @@ fn test_fibonacci_basic() {
     let public_values = vec![];
 
     println!("Generating proof...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
     );
+    let sizes = proof.size_breakdown();
+    println!("Proof size: {} bytes ({:?})", proof.size_in_bytes(), sizes);
+    assert!(sizes.opening_proof > 0);
     println!("Verifying proof...");
     verify(&config, &air, &proof, &public_values).expect("verification failed");
     println!("Verification successful!");
@@ -191,6 +216,331 @@ fn test_fibonacci_one_row() {
 
     let public_values = vec![];
 
-    let proof = prove(&config, &air, trace, &public_values);
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
     verify(&config, &air, &proof, &public_values).expect("verification failed");
 }
+
+#[test]
+fn test_fibonacci_check_constraints() {
+    let mut trace = generate_trace_rows::<Val>(0, 1, 8);
+    let air = FibonacciAir { expected_final: 21 };
+
+    check_constraints::<Val, Challenge, _>(&air, &trace, None, &[], &[])
+        .expect("valid trace should satisfy all constraints");
+
+    // Break the recurrence between rows 3 and 4
+    trace.values[4 * NUM_FIBONACCI_COLS + 1] += Val::ONE;
+
+    let violations = check_constraints::<Val, Challenge, _>(&air, &trace, None, &[], &[])
+        .expect_err("corrupted trace should violate constraints")
+        .0;
+    assert!(violations.iter().any(|v| v.row == 3));
+    assert!(violations.iter().all(|v| v.row == 3 || v.row == 4));
+}
+
+#[test]
+fn test_fibonacci_violation_provenance() {
+    let mut trace = generate_trace_rows::<Val>(0, 1, 8);
+    let air = FibonacciAir { expected_final: 21 };
+    let mut provenance = TraceProvenance::new();
+    provenance.tag(0, "fib(0)..fib(3)");
+    provenance.tag(4, "fib(4)..fib(7)");
+
+    // Break the recurrence between rows 3 and 4
+    trace.values[4 * NUM_FIBONACCI_COLS + 1] += Val::ONE;
+    let report = check_constraints::<Val, Challenge, _>(&air, &trace, None, &[], &[])
+        .expect_err("corrupted trace should violate constraints")
+        .report(&provenance);
+
+    let rows: Vec<&str> = report.lines().collect();
+    assert!(!rows.is_empty());
+    for row in rows {
+        assert!(
+            row.starts_with("row 3") || row.starts_with("row 4"),
+            "{row}"
+        );
+        // Provenance is only recorded in debug builds
+        if cfg!(debug_assertions) {
+            let label = if row.starts_with("row 3") {
+                "(fib(0)..fib(3))"
+            } else {
+                "(fib(4)..fib(7))"
+            };
+            assert!(row.ends_with(label), "{row}");
+        }
+    }
+}
+
+#[test]
+fn test_fibonacci_verify_with_vk() {
+    let config = make_config(2);
+    let air = FibonacciAir { expected_final: 21 };
+    let vk = keygen(&config, &air);
+    assert_eq!(vk.main_width, NUM_FIBONACCI_COLS);
+    assert_eq!(vk.aux_width, 0);
+    assert_eq!(vk.num_constraints, 4);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+    let proof = prove(&config, &air, trace, &[]).expect("proving failed");
+    verify_with_vk(&config, &vk, &air, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_fibonacci_builder_config() {
+    let config = StarkConfigBuilder::baby_bear()
+        .log_blowup(3)
+        .queries(40)
+        .build()
+        .expect("valid parameters");
+    let air = FibonacciAir { expected_final: 21 };
+    let proof =
+        prove(&config, &air, generate_trace_rows::<Val>(0, 1, 8), &[]).expect("proving failed");
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+
+    // Traces up to 2^25 rows over a 120-bit field: the conjectured level is bound by the
+    // folding challenges, the proven one by the queries
+    assert_eq!(
+        config.security_bits(),
+        Some(SecurityLevel {
+            conjectured: 92,
+            proven: 60
+        })
+    );
+    let more_queries = StarkConfigBuilder::baby_bear()
+        .log_blowup(3)
+        .queries(50)
+        .build()
+        .expect("valid parameters");
+    assert_eq!(
+        more_queries.security_bits().map(|bits| bits.proven),
+        Some(64)
+    );
+
+    // Validation names the term that falls short
+    assert!(config.validate(SecurityTarget::Bits(90)).is_ok());
+    assert_eq!(
+        config.validate(SecurityTarget::Bits(100)),
+        Err(SecurityError::Insufficient {
+            target: SecurityTarget::Bits(100),
+            achieved: 92,
+            limited_by: SecurityBound::FoldingChallenges,
+        })
+    );
+    assert_eq!(
+        config.validate(SecurityTarget::ProvenBits(64)),
+        Err(SecurityError::Insufficient {
+            target: SecurityTarget::ProvenBits(64),
+            achieved: 60,
+            limited_by: SecurityBound::Queries,
+        })
+    );
+    let mut unknown = config;
+    unknown.security_parameters = None;
+    assert_eq!(
+        unknown.validate(SecurityTarget::Bits(1)),
+        Err(SecurityError::UnknownParameters)
+    );
+
+    assert_eq!(
+        StarkConfigBuilder::baby_bear().log_blowup(1).build().err(),
+        Some(ConfigError::BlowupTooSmall {
+            log_blowup: 1,
+            min_log_blowup: 2
+        })
+    );
+    assert_eq!(
+        StarkConfigBuilder::baby_bear().queries(0).build().err(),
+        Some(ConfigError::NoQueries)
+    );
+}
+
+#[test]
+fn test_fibonacci_goldilocks_keccak() {
+    use p3_uni_stark_mt::config::goldilocks_keccak;
+
+    let config = goldilocks_keccak::default_config();
+    let air = FibonacciAir { expected_final: 21 };
+    let trace = generate_trace_rows::<goldilocks_keccak::Val>(0, 1, 8);
+    let proof = prove(&config, &air, trace, &[]).expect("proving failed");
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_fibonacci_trace_digest() {
+    let config = make_config(2);
+    let air = FibonacciAir { expected_final: 21 };
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+    let mut proof = prove(&config, &air, trace.clone(), &[]).expect("proving failed");
+    assert!(!proof.matches_trace(&trace, b"audit key"));
+
+    let size_without_digest = proof.size_in_bytes();
+    proof.attach_trace_digest(&trace, b"audit key");
+    assert!(proof.size_in_bytes() > size_without_digest);
+    assert!(proof.matches_trace(&trace, b"audit key"));
+    assert!(!proof.matches_trace(&trace, b"other key"));
+    assert!(!proof.matches_trace(&generate_trace_rows::<Val>(1, 1, 8), b"audit key"));
+
+    // The digest lives outside the transcript
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_fibonacci_prover_metrics() {
+    use p3_uni_stark_mt::prove_with_metrics;
+
+    let config = make_config(2);
+    let air = FibonacciAir { expected_final: 21 };
+    let (proof, metrics) =
+        prove_with_metrics(&config, &air, generate_trace_rows::<Val>(0, 1, 8), &[])
+            .expect("proving failed");
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+
+    assert_eq!(metrics.trace_height, 8);
+    assert_eq!(metrics.quotient_domain_size, 32);
+    assert_eq!(metrics.proof_size_bytes, proof.size_in_bytes());
+    assert!(metrics.total() >= metrics.commit_main + metrics.quotient + metrics.open);
+    // No aux trace, and this binary doesn't install `CountingAllocator`
+    assert!(metrics.aux.is_zero());
+    assert_eq!(metrics.peak_allocated_bytes, None);
+}
+
+#[test]
+fn test_fibonacci_malformed_proof_rejected() {
+    let config = make_config(2);
+    let air = FibonacciAir { expected_final: 21 };
+    let proof =
+        prove(&config, &air, generate_trace_rows::<Val>(0, 1, 8), &[]).expect("proving failed");
+
+    let mut bad = proof.clone();
+    bad.quotient_chunks.pop();
+    assert!(matches!(
+        verify(&config, &air, &bad, &[]),
+        Err(VerificationError::QuotientChunkCount {
+            expected: 4,
+            actual: 3
+        })
+    ));
+
+    let mut bad = proof.clone();
+    bad.main_next.truncate(1);
+    assert!(matches!(
+        verify(&config, &air, &bad, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+
+    let mut bad = proof;
+    bad.log_degree = u8::MAX;
+    assert!(matches!(
+        verify(&config, &air, &bad, &[]),
+        Err(VerificationError::LogDegreeOutOfRange {
+            log_degree: u8::MAX,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_fibonacci_swapped_quotient_chunks_rejected() {
+    let config = make_config(2);
+    let air = FibonacciAir { expected_final: 21 };
+    let proof =
+        prove(&config, &air, generate_trace_rows::<Val>(0, 1, 8), &[]).expect("proving failed");
+    assert_ne!(proof.quotient_chunks[0], proof.quotient_chunks[1]);
+
+    // Each chunk's opening is bound to its own chunk domain
+    let mut bad = proof;
+    bad.quotient_chunks.swap(0, 1);
+    assert!(matches!(
+        verify(&config, &air, &bad, &[]),
+        Err(VerificationError::PcsVerificationFailed)
+    ));
+}
+
+#[test]
+fn test_fibonacci_concurrent_verify() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MyConfig>();
+    assert_send_sync::<p3_uni_stark_mt::VerifyingKey>();
+    assert_send_sync::<p3_uni_stark_mt::Proof<MyConfig>>();
+
+    let config = make_config(2);
+    let air = FibonacciAir { expected_final: 21 };
+    let vk = keygen(&config, &air);
+    let proof =
+        prove(&config, &air, generate_trace_rows::<Val>(0, 1, 8), &[]).expect("proving failed");
+
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                verify_with_vk(&config, &vk, &air, &proof, &[]).expect("verification failed");
+            });
+        }
+    });
+}
+
+#[test]
+#[cfg(not(feature = "minimal"))]
+fn test_recursive_folder_matches_verifier_folder() {
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut sample = || -> Challenge { rng.random() };
+    let main_local = [sample(), sample()];
+    let main_next = [sample(), sample()];
+    let (is_first_row, is_last_row, is_transition, alpha) =
+        (sample(), sample(), sample(), sample());
+    let air = FibonacciAir { expected_final: 21 };
+
+    let mut folder = VerifierFolder::<MyConfig> {
+        main_local: &main_local,
+        main_next: &main_next,
+        main_last_row: &[],
+        main_openings: OpeningSchedule::TRANSITION,
+        aux_openings: OpeningSchedule::TRANSITION,
+        aux_local: &[],
+        aux_next: &[],
+        aux_base_local: &[],
+        aux_base_next: &[],
+        challenges: ChallengeSet::empty(),
+        aux_exposed_values: &[],
+        aux_prev_row: &[],
+        aux_base_prev_row: &[],
+        main_prev_row: &[],
+        aux_rotated: RotatedRows::empty(),
+        aux_base_rotated: RotatedRows::empty(),
+        main_rotated: RotatedRows::empty(),
+        aux_last_row: &[],
+        aux_base_last_row: &[],
+        public_values: &[],
+        is_first_row,
+        is_last_row,
+        is_transition,
+        statements: StatementSelectors {
+            is_first_row,
+            is_last_row,
+            is_transition,
+            public_values: &[],
+        },
+        alpha,
+        accumulator: Challenge::ZERO,
+    };
+    air.eval(&mut folder);
+
+    // Concrete challenges are a valid (trivial) target representation
+    let mut recursive = RecursiveVerifierFolder::<Val, Challenge, Challenge, Challenge>::new(
+        &main_local,
+        &main_next,
+        &[],
+        &[],
+        &[],
+        LagrangeSelectors {
+            is_first_row,
+            is_last_row,
+            is_transition,
+            inv_vanishing: Challenge::ONE,
+        },
+        alpha,
+    );
+    air.eval(&mut recursive);
+
+    assert_eq!(recursive.accumulator, folder.accumulator);
+}
@@ -0,0 +1,323 @@
+//! Proving several chips under one transcript
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Config, Val};
+use p3_uni_stark_mt::{
+    prove_machine, verify_machine, AirLayout, AuxBuilder, AuxTraceBuilder, AuxTraces, ChallengeSet,
+    Chip, MachinePublicValues, ProverError, PuncturedAir, VerificationError,
+};
+
+/// One main column counting up from `start`, or from its public value if `start` is
+/// `None`.
+pub struct CounterAir {
+    start: Option<u32>,
+}
+
+const COUNTER: CounterAir = CounterAir { start: Some(0) };
+const PUBLIC_COUNTER: CounterAir = CounterAir { start: None };
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for CounterAir {
+    fn num_public_values(&self) -> usize {
+        usize::from(self.start.is_none())
+    }
+}
+
+impl<F> PuncturedAir<F> for CounterAir {}
+
+impl<F> AirLayout<F> for CounterAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterAir {}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let start: AB::Expr = match self.start {
+            Some(start) => AB::Expr::from_u32(start),
+            None => builder.public_values()[0].into(),
+        };
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        builder.when_first_row().assert_eq(x.clone(), start);
+        builder
+            .when_transition()
+            .assert_eq(x + AB::Expr::ONE, x_next);
+    }
+}
+
+/// One main column `x` sent to (`multiplicity` 1) or received from (`multiplicity` -1) a
+/// LogUp bus under the machine's challenge `gamma`, exposing its share
+/// `multiplicity·Σ 1/(γ - x)`.
+pub struct LogUpAir {
+    multiplicity: i32,
+}
+
+const SENDER: LogUpAir = LogUpAir { multiplicity: 1 };
+const RECEIVER: LogUpAir = LogUpAir { multiplicity: -1 };
+
+impl<F> BaseAir<F> for LogUpAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for LogUpAir {}
+
+impl<F> PuncturedAir<F> for LogUpAir {}
+
+impl<F> AirLayout<F> for LogUpAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for LogUpAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn num_aux_exposed_values(&self) -> usize {
+        1
+    }
+
+    fn bus_contribution_index(&self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["gamma"]
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let (gamma, m) = (challenges["gamma"], EF::from_i32(self.multiplicity));
+        let mut sum = EF::ZERO;
+        let values: Vec<EF> = main
+            .values
+            .iter()
+            .map(|&x| {
+                sum += m * (gamma - x).inverse();
+                sum
+            })
+            .collect();
+        AuxTraces {
+            ext: RowMajorMatrix::new_col(values),
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: vec![sum],
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for LogUpAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let gamma: AB::ExprEF = builder.challenge("gamma").into();
+        let m = builder.embed_main(AB::Expr::from_i32(self.multiplicity));
+        let total: AB::ExprEF = builder.aux_exposed_values()[0].into();
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        let is_first_row = builder.embed_main(builder.is_first_row());
+        let is_transition = builder.embed_main(builder.is_transition());
+        let is_last_row = builder.embed_main(builder.is_last_row());
+        let (x, x_next) = (builder.embed_main(x), builder.embed_main(x_next));
+        // s_0 = m/(γ - x_0), s_{i+1} = s_i + m/(γ - x_{i+1}) and the total is the last s
+        builder.assert_eq_ext(
+            is_first_row.clone() * s.clone() * (gamma.clone() - x),
+            is_first_row * m.clone(),
+        );
+        builder.assert_eq_ext(
+            is_transition.clone() * (s_next - s.clone()) * (gamma - x_next),
+            is_transition * m,
+        );
+        builder.assert_eq_ext(is_last_row.clone() * total, is_last_row * s);
+    }
+}
+
+/// Public values of a machine whose chips take none
+const NONE: MachinePublicValues<'static, Val> = MachinePublicValues::new(&[], &[]);
+
+fn trace(values: impl IntoIterator<Item = u32>) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col(values.into_iter().map(Val::from_u32).collect())
+}
+
+/// A counter of 8 rows, and 16 values sent and received on a bus
+fn machine() -> ([&'static dyn Chip<Config>; 3], Vec<RowMajorMatrix<Val>>) {
+    (
+        [&COUNTER, &SENDER, &RECEIVER],
+        vec![trace(0..8), trace(0..16), trace((0..16).rev())],
+    )
+}
+
+#[test]
+fn test_machine_roundtrip() {
+    let config = default_config();
+    let (chips, traces) = machine();
+    let proof = prove_machine(&config, &chips, traces, &NONE).expect("proving failed");
+    assert_eq!(
+        proof
+            .chips
+            .iter()
+            .map(|chip| chip.log_degree)
+            .collect::<Vec<_>>(),
+        [3, 4, 4]
+    );
+    assert!(proof.aux_commit.is_some());
+    // The counter has no auxiliary phase
+    assert!(proof.chips[0].aux_local.is_empty());
+    verify_machine(&config, &chips, &proof, &NONE).expect("verification failed");
+
+    // Both bus chips drew the same challenge, so their shares cancel out
+    let shares: Challenge = proof
+        .chips
+        .iter()
+        .flat_map(|chip| &chip.aux_exposed_values)
+        .copied()
+        .sum();
+    assert_eq!(shares, Challenge::ZERO);
+
+    // The proof survives serialization
+    let bytes = postcard::to_allocvec(&proof).expect("proofs serialize");
+    let decoded = postcard::from_bytes(&bytes).expect("round trip");
+    verify_machine(&config, &chips, &decoded, &NONE).expect("verification failed");
+}
+
+#[test]
+fn test_machine_rejects_tampering() {
+    let config = default_config();
+    let (chips, traces) = machine();
+    let proof = prove_machine(&config, &chips, traces, &NONE).expect("proving failed");
+
+    // A changed opened value breaks the opening proof
+    let mut tampered = proof.clone();
+    tampered.chips[1].main_local[0] += Challenge::ONE;
+    assert!(verify_machine(&config, &chips, &tampered, &NONE).is_err());
+
+    // A chip's height is bound by the transcript
+    let mut tampered = proof.clone();
+    tampered.chips[0].log_degree += 1;
+    assert!(verify_machine(&config, &chips, &tampered, &NONE).is_err());
+
+    // The chips are checked in the prover's order
+    let swapped: [&dyn Chip<Config>; 3] = [&COUNTER, &RECEIVER, &SENDER];
+    assert!(verify_machine(&config, &swapped, &proof, &NONE).is_err());
+
+    // Every chip is proven
+    assert!(matches!(
+        verify_machine(&config, &chips[..2], &proof, &NONE),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_machine_rejects_unsupported_chips() {
+    let config = default_config();
+    let chips: [&dyn Chip<Config>; 0] = [];
+    assert!(matches!(
+        prove_machine(&config, &chips, Vec::new(), &NONE),
+        Err(ProverError::InvalidMachine(_))
+    ));
+
+    // Each chip is checked against its own constraints
+    let shifted = CounterAir { start: Some(1) };
+    let chips: [&dyn Chip<Config>; 2] = [&COUNTER, &shifted];
+    let proof = prove_machine(&config, &chips, vec![trace(0..8), trace(1..9)], &NONE)
+        .expect("proving failed");
+    verify_machine(&config, &chips, &proof, &NONE).expect("verification failed");
+    let unshifted: [&dyn Chip<Config>; 2] = [&COUNTER, &COUNTER];
+    assert!(matches!(
+        verify_machine(&config, &unshifted, &proof, &NONE),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+}
+
+#[test]
+fn test_machine_rejects_unbalanced_bus() {
+    let config = default_config();
+    let chips: [&dyn Chip<Config>; 2] = [&SENDER, &RECEIVER];
+
+    // Sending 0..16 and receiving 1..17 leaves 0 and 16 unmatched
+    let proof = prove_machine(&config, &chips, vec![trace(0..16), trace(1..17)], &NONE)
+        .expect("each chip satisfies its constraints");
+    assert!(matches!(
+        verify_machine(&config, &chips, &proof, &NONE),
+        Err(VerificationError::UnbalancedBus)
+    ));
+
+    // Values received in another order still balance
+    let proof = prove_machine(
+        &config,
+        &chips,
+        vec![
+            trace((0..16).chain(0..16)),
+            trace((0..16).rev().chain(0..16)),
+        ],
+        &NONE,
+    )
+    .expect("proving failed");
+    verify_machine(&config, &chips, &proof, &NONE).expect("verification failed");
+}
+
+#[test]
+fn test_machine_public_values() {
+    let config = default_config();
+    let chips: [&dyn Chip<Config>; 3] = [&PUBLIC_COUNTER, &COUNTER, &PUBLIC_COUNTER];
+    let traces = || vec![trace(3..11), trace(0..8), trace(5..21)];
+    let digest = [Val::from_u32(7), Val::from_u32(11)];
+    let starts: [&[Val]; 3] = [&[Val::from_u32(3)], &[], &[Val::from_u32(5)]];
+    let public_values = MachinePublicValues::new(&digest, &starts);
+    let proof = prove_machine(&config, &chips, traces(), &public_values).expect("proving failed");
+    verify_machine(&config, &chips, &proof, &public_values).expect("verification failed");
+
+    // Each chip's values, and the global segment, are bound by the transcript
+    let other_starts: [&[Val]; 3] = [&[Val::from_u32(3)], &[], &[Val::from_u32(6)]];
+    let other_digest = [Val::from_u32(7), Val::from_u32(12)];
+    for other in [
+        MachinePublicValues::new(&digest, &other_starts),
+        MachinePublicValues::new(&other_digest, &starts),
+        MachinePublicValues::new(&digest[..1], &starts),
+    ] {
+        assert!(verify_machine(&config, &chips, &proof, &other).is_err());
+    }
+
+    // A chip's slice must have the length it declares, and every chip needs one
+    let short: [&[Val]; 3] = [&[Val::from_u32(3)], &[], &[]];
+    assert!(matches!(
+        verify_machine(
+            &config,
+            &chips,
+            &proof,
+            &MachinePublicValues::new(&digest, &short)
+        ),
+        Err(VerificationError::PublicValuesMismatch {
+            expected: 1,
+            actual: 0
+        })
+    ));
+    assert!(matches!(
+        prove_machine(
+            &config,
+            &chips,
+            traces(),
+            &MachinePublicValues::new(&digest, &starts[..2])
+        ),
+        Err(ProverError::InvalidMachine(_))
+    ));
+}
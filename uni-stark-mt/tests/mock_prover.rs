@@ -0,0 +1,310 @@
+//! Validating AIRs with the PCS-free mock prover
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_baby_bear::BabyBear;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::{
+    checked_batch_inverse, AirLayout, AuxBuilder, AuxTraceBuilder, AuxTraces, ChallengeSet,
+    MockProver, MockProverError, PuncturedAir, TraceProvenance, ZeroDenominator,
+    ZeroDenominatorPolicy,
+};
+
+type Val = BabyBear;
+type Challenge = BinomialExtensionField<Val, 4>;
+
+/// One main column `x` counting up from 0, and one auxiliary column `s` holding the
+/// running sum of `β·x`.
+///
+/// `skew` is added to `s` from row 5 on, to build a broken auxiliary trace.
+pub struct ScaledSumAir {
+    skew: u32,
+}
+
+impl<F> BaseAir<F> for ScaledSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ScaledSumAir {}
+
+impl<F> PuncturedAir<F> for ScaledSumAir {}
+
+impl<F> AirLayout<F> for ScaledSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ScaledSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["beta"]
+    }
+
+    fn build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<F>,
+        challenges: &ChallengeSet<'_, EF>,
+    ) -> AuxTraces<F, EF> {
+        let beta = challenges["beta"];
+        let mut sum = EF::ZERO;
+        let values: Vec<EF> = main
+            .values
+            .iter()
+            .enumerate()
+            .map(|(row, &x)| {
+                sum += beta * x;
+                if row == 5 {
+                    sum += EF::from_u32(self.skew);
+                }
+                sum
+            })
+            .collect();
+        AuxTraces {
+            ext: RowMajorMatrix::new_col(values),
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: Vec::new(),
+        }
+    }
+}
+
+impl<AB: AuxBuilder> Air<AB> for ScaledSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let beta: AB::ExprEF = builder.challenge("beta").into();
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let s_next: AB::ExprEF = aux.row_slice(1).expect("Matrix only has 1 row?")[0].into();
+
+        builder.when_first_row().assert_zero(x.clone());
+        builder
+            .when_transition()
+            .assert_eq(x.clone() + AB::Expr::ONE, x_next.clone());
+
+        let is_first_row = builder.embed_main(builder.is_first_row());
+        let is_transition = builder.embed_main(builder.is_transition());
+        let (x, x_next) = (builder.embed_main(x), builder.embed_main(x_next));
+        builder.assert_eq_ext(
+            is_first_row.clone() * s.clone(),
+            is_first_row * beta.clone() * x,
+        );
+        builder.assert_eq_ext(
+            is_transition.clone() * (s_next - s),
+            is_transition * beta * x_next,
+        );
+    }
+}
+
+fn main_trace(n: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col((0..n as u32).map(Val::from_u32).collect())
+}
+
+#[test]
+fn test_mock_prover_accepts_valid_trace() {
+    let air = ScaledSumAir { skew: 0 };
+    let prover = MockProver::<Val, Challenge>::run(&air, &main_trace(8), &[]).unwrap();
+    prover
+        .verify()
+        .expect("valid trace should satisfy all constraints");
+
+    // The auxiliary trace is built from the pseudo-challenge, which avoids the base field
+    let beta = prover.challenges()[0];
+    assert!(!beta.is_in_basefield());
+    assert_eq!(
+        prover.aux_traces().ext.values.last(),
+        Some(&(beta * Val::from_u32(28)))
+    );
+
+    // Pseudo-challenges are the same from run to run
+    let again = MockProver::<Val, Challenge>::run(&air, &main_trace(8), &[]).unwrap();
+    assert_eq!(again.challenges(), prover.challenges());
+}
+
+#[test]
+fn test_mock_prover_reports_violations() {
+    // Break the counter at row 3: both transitions around it fail
+    let mut trace = main_trace(8);
+    trace.values[3] += Val::ONE;
+    let violations = MockProver::<Val, Challenge>::run(&ScaledSumAir { skew: 0 }, &trace, &[])
+        .unwrap()
+        .verify()
+        .expect_err("corrupted trace should violate constraints");
+    let located: Vec<_> = violations
+        .0
+        .iter()
+        .map(|v| (v.row, v.constraint_index, v.value))
+        .collect();
+    assert_eq!(
+        located,
+        [(2, 1, -Challenge::ONE), (3, 1, Challenge::ONE)],
+        "the auxiliary trace is built from the broken trace, so only the counter fails"
+    );
+
+    let mut provenance = TraceProvenance::new();
+    provenance.tag(0, "counter");
+    let report = violations.report(&provenance);
+    assert!(report.starts_with("row 2, constraint 1"), "{report}");
+    assert!(report.contains("(counter)"), "{report}");
+
+    // A broken auxiliary trace shows up on the auxiliary constraints
+    let violations =
+        MockProver::<Val, Challenge>::run(&ScaledSumAir { skew: 7 }, &main_trace(8), &[])
+            .unwrap()
+            .verify()
+            .expect_err("skewed auxiliary trace should violate constraints")
+            .0;
+    assert_eq!(violations.len(), 1);
+    assert_eq!((violations[0].row, violations[0].constraint_index), (4, 3));
+    assert_eq!(violations[0].value, Challenge::from_u32(7));
+}
+
+#[test]
+fn test_mock_prover_rejects_malformed_inputs() {
+    let air = ScaledSumAir { skew: 0 };
+    let run = |trace: &RowMajorMatrix<Val>, public_values: &[Val]| {
+        MockProver::<Val, Challenge>::run(&air, trace, public_values).map(|_| ())
+    };
+    assert_eq!(
+        run(&main_trace(8), &[Val::ONE]),
+        Err(MockProverError::PublicValuesMismatch {
+            expected: 0,
+            actual: 1
+        })
+    );
+    assert_eq!(
+        run(&RowMajorMatrix::new(Val::zero_vec(16), 2), &[]),
+        Err(MockProverError::MainWidthMismatch {
+            expected: 1,
+            actual: 2
+        })
+    );
+    assert!(matches!(
+        run(&main_trace(1), &[]),
+        Err(MockProverError::InvalidStatements(_))
+    ));
+}
+
+/// One main column `x` counting up from 0, and one auxiliary column holding
+/// `1/(α - x - offset)`.
+pub struct InverseAir {
+    offset: Challenge,
+    policy: ZeroDenominatorPolicy,
+}
+
+impl<F> BaseAir<F> for InverseAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for InverseAir {}
+
+impl<F> PuncturedAir<F> for InverseAir {}
+
+impl<F> AirLayout<F> for InverseAir {}
+
+impl AuxTraceBuilder<Val, Challenge> for InverseAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["alpha"]
+    }
+
+    fn try_build_aux_traces(
+        &self,
+        main: &RowMajorMatrix<Val>,
+        challenges: &ChallengeSet<'_, Challenge>,
+    ) -> Result<AuxTraces<Val, Challenge>, ZeroDenominator> {
+        let alpha = challenges["alpha"];
+        let denominators: Vec<Challenge> = main
+            .values
+            .iter()
+            .map(|&x| alpha - x - self.offset)
+            .collect();
+        Ok(AuxTraces {
+            ext: RowMajorMatrix::new_col(checked_batch_inverse(&denominators, "alpha")?),
+            base: RowMajorMatrix::new(Vec::new(), 0),
+            exposed: Vec::new(),
+        })
+    }
+
+    fn zero_denominator_policy(&self) -> ZeroDenominatorPolicy {
+        self.policy
+    }
+}
+
+impl<AB: AuxBuilder<F = Val, EF = Challenge>> Air<AB> for InverseAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let alpha: AB::ExprEF = builder.challenge("alpha").into();
+        let x = builder.embed_main(main.row_slice(0).expect("Matrix is empty?")[0].clone());
+        let inverse: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        builder.assert_eq_ext(
+            inverse * (alpha - x - AB::ExprEF::from(self.offset)),
+            AB::ExprEF::ONE,
+        );
+    }
+}
+
+#[test]
+fn test_checked_batch_inverse() {
+    let values = [Val::ONE, Val::TWO, Val::from_u32(5)];
+    let inverses = checked_batch_inverse(&values, "alpha").unwrap();
+    assert!(values
+        .iter()
+        .zip(&inverses)
+        .all(|(&v, &i)| v * i == Val::ONE));
+
+    let values = [Val::ONE, Val::ZERO, Val::ZERO];
+    assert_eq!(
+        checked_batch_inverse(&values, "beta"),
+        Err(ZeroDenominator {
+            row: 1,
+            challenge: "beta"
+        })
+    );
+}
+
+#[test]
+fn test_mock_prover_zero_denominator() {
+    let run = |offset, policy| {
+        MockProver::<Val, Challenge>::run(&InverseAir { offset, policy }, &main_trace(8), &[])
+    };
+    let first = run(Challenge::ZERO, ZeroDenominatorPolicy::Fail).unwrap();
+    first
+        .verify()
+        .expect("valid trace should satisfy all constraints");
+
+    // Shift the denominators so that row 3's vanishes for the first pseudo-challenge
+    let offset = first.challenges()[0] - Val::from_u32(3);
+    assert_eq!(
+        run(offset, ZeroDenominatorPolicy::Fail).map(|_| ()),
+        Err(MockProverError::ZeroDenominator(ZeroDenominator {
+            row: 3,
+            challenge: "alpha"
+        }))
+    );
+
+    // Resampling draws the next pseudo-challenge instead
+    let resampled = run(offset, ZeroDenominatorPolicy::Resample { max_attempts: 2 }).unwrap();
+    assert_ne!(resampled.challenges(), first.challenges());
+    resampled
+        .verify()
+        .expect("resampled trace should satisfy all constraints");
+}
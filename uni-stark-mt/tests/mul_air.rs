@@ -1,7 +1,7 @@
 //! Test for multiplication AIR with different constraint degrees
 
 use itertools::Itertools;
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::DuplexChallenger;
 use p3_commit::ExtensionMmcs;
@@ -13,7 +13,10 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
-use p3_uni_stark_mt::{prove, verify, AuxTraceBuilder, StarkConfig};
+use p3_uni_stark_mt::{
+    keygen, prove, verify, AirLayout, AuxTraceBuilder, ChallengeSet, PuncturedAir, StarkConfig,
+    StarkConfigBuilder, VerificationError,
+};
 use rand::distr::{Distribution, StandardUniform};
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
@@ -70,6 +73,12 @@ impl<F> BaseAir<F> for MulAir {
     }
 }
 
+impl<F> BaseAirWithPublicValues<F> for MulAir {}
+
+impl<F> PuncturedAir<F> for MulAir {}
+
+impl<F> AirLayout<F> for MulAir {}
+
 impl<AB: AirBuilder> Air<AB> for MulAir {
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
@@ -107,11 +116,11 @@ where
         0 // No auxiliary columns
     }
 
-    fn num_challenges(&self) -> usize {
-        0 // No challenges needed
-    }
-
-    fn build_aux_trace(&self, _main: &RowMajorMatrix<F>, _challenges: &[EF]) -> RowMajorMatrix<EF> {
+    fn build_aux_trace(
+        &self,
+        _main: &RowMajorMatrix<F>,
+        _challenges: &ChallengeSet<'_, EF>,
+    ) -> RowMajorMatrix<EF> {
         // Return empty matrix
         RowMajorMatrix::new(vec![], 0)
     }
@@ -195,7 +204,7 @@ fn test_mul_air_deg2() {
     let public_values = vec![];
 
     println!("Generating proof for degree 2...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
@@ -220,7 +229,7 @@ fn test_mul_air_deg3() {
     let public_values = vec![];
 
     println!("Generating proof for degree 3...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
@@ -245,7 +254,7 @@ fn test_mul_air_deg4() {
     let public_values = vec![];
 
     println!("Generating proof for degree 4...");
-    let proof = prove(&config, &air, trace, &public_values);
+    let proof = prove(&config, &air, trace, &public_values).expect("proving failed");
     println!(
         "Proof generated. Quotient chunks: {}",
         proof.quotient_chunks.len()
@@ -255,3 +264,36 @@ fn test_mul_air_deg4() {
     verify(&config, &air, &proof, &public_values).expect("verification failed");
     println!("Verification successful!");
 }
+
+#[test]
+fn test_mul_air_deg7() {
+    // The quotient of a degree 7 constraint needs 8 chunks, and so a blowup of 8
+    let config = StarkConfigBuilder::baby_bear()
+        .log_blowup(3)
+        .build()
+        .expect("valid parameters");
+    let air = MulAir {
+        degree: 7,
+        ..Default::default()
+    };
+    let vk = keygen(&config, &air);
+    assert_eq!((vk.constraint_degree, vk.log_quotient_degree), (7, 3));
+    // Lower degrees keep the smallest quotient
+    let vk = keygen(&config, &MulAir::default());
+    assert_eq!((vk.constraint_degree, vk.log_quotient_degree), (3, 2));
+
+    let proof = prove(&config, &air, air.random_valid_trace(1 << 4), &[]).expect("proving failed");
+    assert_eq!(proof.quotient_chunks.len(), 8);
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+
+    // The count follows from the constraint degree, not from the proof
+    let mut bad = proof.clone();
+    bad.quotient_chunks.truncate(4);
+    assert!(matches!(
+        verify(&config, &air, &bad, &[]),
+        Err(VerificationError::QuotientChunkCount {
+            expected: 8,
+            actual: 4
+        })
+    ));
+}
@@ -0,0 +1,514 @@
+//! Traces opened at the points their AIR's schedule asks for
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Val};
+use p3_uni_stark_mt::{
+    check_constraints, keygen, prove, verify, AirLayout, AuxBuilder, AuxTraceBuilder,
+    BoundaryConstraint, ConstraintViolation, OpeningSchedule, ProtocolMismatch, ProverError,
+    PuncturedAir, Rotations, VerificationError,
+};
+#[cfg(not(feature = "minimal"))]
+use p3_uni_stark_mt::{symbolic_constraints, Entry, RowOffset};
+
+/// `x * x = y` on every row: constraints never read the next row.
+pub struct SquareAir {
+    openings: OpeningSchedule,
+}
+
+impl<F> BaseAir<F> for SquareAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SquareAir {}
+
+impl<F> PuncturedAir<F> for SquareAir {}
+
+impl<F> AirLayout<F> for SquareAir {
+    fn main_openings(&self) -> OpeningSchedule {
+        self.openings
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SquareAir {}
+
+impl<AB: AirBuilder> Air<AB> for SquareAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        builder.assert_eq(local[0].clone() * local[0].clone(), local[1].clone());
+    }
+}
+
+fn square_trace(n: u32) -> RowMajorMatrix<Val> {
+    let values = (0..n)
+        .flat_map(|i| [Val::from_u32(i), Val::from_u32(i * i)])
+        .collect();
+    RowMajorMatrix::new(values, 2)
+}
+
+/// A counter whose final value is the public value, read from the last row on every row.
+pub struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for CounterAir {
+    fn num_public_values(&self) -> usize {
+        1
+    }
+}
+
+impl<F> PuncturedAir<F> for CounterAir {}
+
+impl<F> AirLayout<F> for CounterAir {
+    fn main_openings(&self) -> OpeningSchedule {
+        OpeningSchedule::TRANSITION.with_last_row()
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterAir {}
+
+impl<AB: AuxBuilder + AirBuilderWithPublicValues> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        builder.when_first_row().assert_zero(local[0].clone());
+        builder
+            .when_transition()
+            .assert_eq(local[0].clone() + AB::Expr::ONE, next[0].clone());
+
+        let last: AB::Expr = builder.main_last_row()[0].into();
+        let total: AB::Expr = builder.public_values()[0].into();
+        builder.assert_eq(last, total);
+    }
+}
+
+fn counter_trace(n: u32) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new((0..n).map(Val::from_u32).collect(), 1)
+}
+
+/// A counter `x` and its double `y`, with `x` and `y` on row 5 and `y` on row 12 pinned to
+/// the public values.
+pub struct PinnedAir;
+
+impl<F> BaseAir<F> for PinnedAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for PinnedAir {
+    fn num_public_values(&self) -> usize {
+        3
+    }
+}
+
+impl<F> PuncturedAir<F> for PinnedAir {}
+
+impl<F> AirLayout<F> for PinnedAir {
+    fn boundary_constraints(&self) -> Vec<BoundaryConstraint> {
+        vec![
+            BoundaryConstraint::new(5, 0, 0),
+            BoundaryConstraint::new(5, 1, 1),
+            BoundaryConstraint::new(12, 1, 2),
+        ]
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for PinnedAir {}
+
+impl<AB: AirBuilder> Air<AB> for PinnedAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        builder.when_first_row().assert_zero(local[0].clone());
+        builder
+            .when_transition()
+            .assert_eq(local[0].clone() + AB::Expr::ONE, next[0].clone());
+        builder.assert_eq(
+            AB::Expr::from(local[0].clone()) * AB::Expr::TWO,
+            local[1].clone(),
+        );
+    }
+}
+
+/// A counter checked against the previous row rather than the next one.
+pub struct LookBehindAir;
+
+impl<F> BaseAir<F> for LookBehindAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for LookBehindAir {}
+
+impl<F> PuncturedAir<F> for LookBehindAir {}
+
+impl<F> AirLayout<F> for LookBehindAir {
+    fn main_openings(&self) -> OpeningSchedule {
+        OpeningSchedule::LOCAL.with_prev_row()
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for LookBehindAir {}
+
+impl<AB: AuxBuilder> Air<AB> for LookBehindAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0).expect("Matrix is empty?");
+        let prev: AB::Expr = builder.main_prev_row()[0].into();
+        builder.when_first_row().assert_zero(local[0].clone());
+
+        // The first row's previous row is the last, so the step skips it
+        let not_first_row = AB::Expr::ONE - builder.is_first_row();
+        builder
+            .when(not_first_row)
+            .assert_eq(prev + AB::Expr::ONE, local[0].clone());
+    }
+}
+
+/// A column repeating `0, 1, 3, 2`: rows four apart are equal, and rows two apart sum to
+/// 3.
+pub struct PeriodicAir;
+
+impl<F> BaseAir<F> for PeriodicAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for PeriodicAir {}
+
+impl<F> PuncturedAir<F> for PeriodicAir {}
+
+impl<F> AirLayout<F> for PeriodicAir {
+    fn main_openings(&self) -> OpeningSchedule {
+        OpeningSchedule::LOCAL.with_rotations(&[-2, 4])
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for PeriodicAir {}
+
+impl<AB: AuxBuilder> Air<AB> for PeriodicAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local: AB::Expr = main.row_slice(0).expect("Matrix is empty?")[0].into();
+        let behind: AB::Expr = builder.main_rotated_row(-2)[0].into();
+        let ahead: AB::Expr = builder.main_rotated_row(4)[0].into();
+
+        // The height is a multiple of the period, so both hold across the wraparound
+        builder.assert_eq(ahead, local.clone());
+        builder.assert_eq(behind + local, AB::Expr::from_u32(3));
+    }
+}
+
+fn periodic_trace(n: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col(
+        [0, 1, 3, 2]
+            .into_iter()
+            .cycle()
+            .take(n)
+            .map(Val::from_u32)
+            .collect(),
+    )
+}
+
+fn pinned_trace(n: u32) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new(
+        (0..n)
+            .flat_map(|x| [Val::from_u32(x), Val::from_u32(2 * x)])
+            .collect(),
+        2,
+    )
+}
+
+#[test]
+fn test_local_schedule_skips_next_row() {
+    let air = SquareAir {
+        openings: OpeningSchedule::LOCAL,
+    };
+    check_constraints::<Val, Challenge, _>(&air, &square_trace(16), None, &[], &[])
+        .expect("every row squares");
+
+    let config = default_config();
+    assert_eq!(keygen(&config, &air).main_openings, OpeningSchedule::LOCAL);
+    let proof = prove(&config, &air, square_trace(16), &[]).expect("proving failed");
+    assert_eq!(proof.main_local.len(), 2);
+    assert!(proof.main_next.is_empty());
+    verify(&config, &air, &proof, &[]).expect("verification failed");
+
+    let transition = SquareAir {
+        openings: OpeningSchedule::TRANSITION,
+    };
+    let full = prove(&config, &transition, square_trace(16), &[]).expect("proving failed");
+    verify(&config, &transition, &full, &[]).expect("verification failed");
+    assert!(proof.size_breakdown().opened_values < full.size_breakdown().opened_values);
+
+    // Next-row values the schedule doesn't open are rejected, not ignored
+    let mut padded = proof.clone();
+    padded.main_next = full.main_next;
+    assert!(verify(&config, &air, &padded, &[]).is_err());
+
+    // A proof is bound to its schedule
+    assert!(verify(&config, &transition, &proof, &[]).is_err());
+}
+
+#[test]
+#[cfg(not(feature = "minimal"))]
+fn test_local_schedule_has_no_next_row_variables() {
+    let air = SquareAir {
+        openings: OpeningSchedule::LOCAL,
+    };
+    let symbolic = symbolic_constraints::<Val, Challenge, _>(&air);
+    assert_eq!(symbolic.variables.len(), 2);
+    assert!(symbolic.variables.iter().all(|entry| matches!(
+        entry,
+        Entry::Main {
+            row: RowOffset::Local,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_main_last_row_opening() {
+    let total = [Val::from_u32(15)];
+    check_constraints::<Val, Challenge, _>(&CounterAir, &counter_trace(16), None, &[], &total)
+        .expect("the counter ends at the public value");
+
+    let config = default_config();
+    assert!(keygen(&config, &CounterAir).main_openings.last_row);
+    let proof = prove(&config, &CounterAir, counter_trace(16), &total).expect("proving failed");
+    assert_eq!(proof.main_last.len(), 1);
+    verify(&config, &CounterAir, &proof, &total).expect("verification failed");
+
+    assert!(verify(&config, &CounterAir, &proof, &[Val::from_u32(16)]).is_err());
+
+    // The opening is bound by the PCS
+    let mut tampered = proof;
+    tampered.main_last[0] += Challenge::ONE;
+    assert!(verify(&config, &CounterAir, &tampered, &total).is_err());
+}
+
+#[test]
+fn test_multiple_ood_points() {
+    let total = [Val::from_u32(15)];
+    let config = default_config().with_num_ood_points(3);
+    assert_eq!(keygen(&config, &CounterAir).protocol_id.num_ood_points, 3);
+    let proof = prove(&config, &CounterAir, counter_trace(16), &total).expect("proving failed");
+    verify(&config, &CounterAir, &proof, &total).expect("verification failed");
+
+    // The first point keeps the schedule, further points open local and next rows only
+    assert_eq!(proof.extra_ood_openings.len(), 2);
+    for opening in &proof.extra_ood_openings {
+        assert_eq!(opening.main_local.len(), 1);
+        assert_eq!(opening.main_next.len(), 1);
+        assert_eq!(opening.quotient_chunks.len(), proof.quotient_chunks.len());
+    }
+    let single =
+        prove(&default_config(), &CounterAir, counter_trace(16), &total).expect("proving failed");
+    assert!(single.extra_ood_openings.is_empty());
+    assert!(single.size_breakdown().opened_values < proof.size_breakdown().opened_values);
+
+    // Verifiers must agree on the number of points
+    let error = verify(&default_config(), &CounterAir, &proof, &total)
+        .expect_err("a verifier at one point rejects proofs at three");
+    assert!(matches!(
+        error,
+        VerificationError::ProtocolMismatch(ProtocolMismatch::OodPoints {
+            expected: 1,
+            actual: 3
+        })
+    ));
+    assert_eq!(
+        error.to_string(),
+        "protocol mismatch: expected 1 out-of-domain points, got 3"
+    );
+    #[cfg(feature = "std")]
+    assert_eq!(
+        std::error::Error::source(&error).map(ToString::to_string),
+        Some("expected 1 out-of-domain points, got 3".to_string())
+    );
+
+    // Openings at every point are bound by the PCS
+    let mut tampered = proof.clone();
+    tampered.extra_ood_openings[1].main_local[0] += Challenge::ONE;
+    assert!(verify(&config, &CounterAir, &tampered, &total).is_err());
+    let mut dropped = proof;
+    dropped.extra_ood_openings.pop();
+    assert!(matches!(
+        verify(&config, &CounterAir, &dropped, &total),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_boundary_constraints_at_inner_rows() {
+    let pinned = [5, 10, 24].map(Val::from_u32);
+    check_constraints::<Val, Challenge, _>(&PinnedAir, &pinned_trace(16), None, &[], &pinned)
+        .expect("the pinned cells hold the public values");
+
+    // A wrong public value is reported on its row, after the AIR's own constraints
+    let wrong = [5, 10, 26].map(Val::from_u32);
+    let violations =
+        check_constraints::<Val, Challenge, _>(&PinnedAir, &pinned_trace(16), None, &[], &wrong)
+            .expect_err("row 12 doesn't hold 26");
+    assert_eq!(
+        violations.0,
+        [ConstraintViolation {
+            row: 12,
+            constraint_index: 3 + 2,
+            value: Challenge::from(-Val::TWO),
+        }]
+    );
+
+    let config = default_config();
+    assert_eq!(
+        keygen(&config, &PinnedAir).boundary_constraints,
+        AirLayout::<Val>::boundary_constraints(&PinnedAir)
+    );
+    let proof = prove(&config, &PinnedAir, pinned_trace(16), &pinned).expect("proving failed");
+    verify(&config, &PinnedAir, &proof, &pinned).expect("verification failed");
+
+    // Row 5 is opened once for its two pinned cells
+    assert_eq!(proof.main_boundary.len(), 2);
+    assert!(proof.main_boundary.iter().all(|row| row.len() == 2));
+    assert_eq!(proof.main_boundary[1][1], Challenge::from(pinned[2]));
+
+    assert!(matches!(
+        verify(&config, &PinnedAir, &proof, &wrong),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+
+    // The openings are bound by the PCS
+    let mut tampered = proof.clone();
+    tampered.main_boundary[1][1] += Challenge::ONE;
+    assert!(verify(
+        &config,
+        &PinnedAir,
+        &tampered,
+        &[5, 10, 25].map(Val::from_u32)
+    )
+    .is_err());
+    let mut dropped = proof;
+    dropped.main_boundary.pop();
+    assert!(matches!(
+        verify(&config, &PinnedAir, &dropped, &pinned),
+        Err(VerificationError::InvalidProof(_))
+    ));
+
+    // Rows past the end of the trace can't be pinned
+    assert!(matches!(
+        prove(&config, &PinnedAir, pinned_trace(8), &pinned),
+        Err(ProverError::InvalidBoundaryConstraints(_))
+    ));
+}
+
+#[test]
+fn test_prev_row_opening() {
+    check_constraints::<Val, Challenge, _>(&LookBehindAir, &counter_trace(16), None, &[], &[])
+        .expect("every row is one more than the previous");
+
+    // A skipped step is caught on the row after it
+    let mut skipped = counter_trace(16);
+    skipped.values[7] += Val::ONE;
+    let violations =
+        check_constraints::<Val, Challenge, _>(&LookBehindAir, &skipped, None, &[], &[])
+            .expect_err("row 7 skips a step");
+    let rows: Vec<usize> = violations.0.iter().map(|violation| violation.row).collect();
+    assert_eq!(rows, [7, 8]);
+
+    let config = default_config();
+    assert!(keygen(&config, &LookBehindAir).main_openings.prev_row);
+    let proof = prove(&config, &LookBehindAir, counter_trace(16), &[]).expect("proving failed");
+    assert_eq!(proof.main_prev.len(), 1);
+    assert!(proof.main_next.is_empty());
+    verify(&config, &LookBehindAir, &proof, &[]).expect("verification failed");
+
+    // The opening is bound by the PCS
+    let mut tampered = proof.clone();
+    tampered.main_prev[0] += Challenge::ONE;
+    assert!(verify(&config, &LookBehindAir, &tampered, &[]).is_err());
+    let mut dropped = proof;
+    dropped.main_prev.clear();
+    assert!(matches!(
+        verify(&config, &LookBehindAir, &dropped, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_rotated_row_openings() {
+    check_constraints::<Val, Challenge, _>(&PeriodicAir, &periodic_trace(16), None, &[], &[])
+        .expect("the column repeats every four rows");
+
+    // A wrong value is caught on its row, four rows before it and two rows after it
+    let mut broken = periodic_trace(16);
+    broken.values[5] += Val::ONE;
+    let violations = check_constraints::<Val, Challenge, _>(&PeriodicAir, &broken, None, &[], &[])
+        .expect_err("row 5 breaks the period");
+    let mut rows: Vec<usize> = violations.0.iter().map(|violation| violation.row).collect();
+    rows.dedup();
+    assert_eq!(rows, [1, 5, 7]);
+
+    let config = default_config();
+    let vk = keygen(&config, &PeriodicAir);
+    assert_eq!(
+        vk.main_openings.rotations.iter().collect::<Vec<_>>(),
+        [-2, 4]
+    );
+    assert!(!vk.main_openings.prev_row && !vk.main_openings.next_row);
+    let proof = prove(&config, &PeriodicAir, periodic_trace(16), &[]).expect("proving failed");
+    assert_eq!(proof.main_rotated.len(), 2);
+    assert!(proof.main_rotated.iter().all(|row| row.len() == 1));
+    assert!(proof.main_prev.is_empty());
+    verify(&config, &PeriodicAir, &proof, &[]).expect("verification failed");
+
+    // The openings are bound by the PCS
+    let mut tampered = proof.clone();
+    tampered.main_rotated[1][0] += Challenge::ONE;
+    assert!(verify(&config, &PeriodicAir, &tampered, &[]).is_err());
+    let mut dropped = proof;
+    dropped.main_rotated.pop();
+    assert!(matches!(
+        verify(&config, &PeriodicAir, &dropped, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_rotations() {
+    // Adjacent rows keep their own flags, the others go in the bitset
+    let schedule = OpeningSchedule::LOCAL.with_rotations(&[3, -1, 1, -5, 3]);
+    assert!(schedule.next_row && schedule.prev_row);
+    assert_eq!(schedule.rotations.iter().collect::<Vec<_>>(), [-5, 3]);
+    assert_eq!(schedule.num_points(), 5);
+    assert_eq!(schedule.rotations.position(3), Some(1));
+    assert_eq!(schedule.rotations.position(2), None);
+
+    let all = Rotations::NONE
+        .with(Rotations::MIN_OFFSET)
+        .with(Rotations::MAX_OFFSET);
+    assert_eq!(all.union(schedule.rotations).len(), 4);
+}
+
+#[test]
+#[should_panic]
+fn test_rotation_out_of_range() {
+    let _ = Rotations::NONE.with(Rotations::MAX_OFFSET + 1);
+}
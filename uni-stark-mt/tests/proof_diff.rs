@@ -0,0 +1,57 @@
+//! Structural proof diffs
+#![cfg(not(feature = "minimal"))]
+
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Val};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{diff_proofs, prove, OpenedValueDiff, ValueDiff};
+
+#[test]
+fn test_proving_is_deterministic() {
+    let config = default_config();
+    let a = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    let b = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    let diff = diff_proofs(&a, &b);
+    assert!(diff.is_empty(), "{diff:?}");
+}
+
+#[test]
+fn test_diff_reports_components() {
+    let config = default_config();
+    let a = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+
+    // Field-level opened value changes
+    let mut b = a.clone();
+    b.main_next[1] += Challenge::ONE;
+    b.quotient_chunks[0].pop();
+    let diff = diff_proofs(&a, &b);
+    assert!(!diff.main_commit && !diff.opening_proof);
+    assert_eq!(
+        diff.opened_values,
+        [
+            OpenedValueDiff {
+                component: "main_next",
+                chunk: None,
+                diff: ValueDiff::Value { index: 1 },
+            },
+            OpenedValueDiff {
+                component: "quotient_chunks",
+                chunk: Some(0),
+                diff: ValueDiff::Length {
+                    a: a.quotient_chunks[0].len(),
+                    b: a.quotient_chunks[0].len() - 1,
+                },
+            },
+        ]
+    );
+    assert!(diff.sizes.is_some());
+
+    // A different trace changes every commitment
+    let mut shifted = fibonacci_trace::<Val>(9);
+    shifted.values.drain(..2);
+    let c = prove(&config, &FibonacciAir, shifted, &[]).expect("proving failed");
+    let diff = diff_proofs(&a, &c);
+    assert!(diff.main_commit && diff.quotient_commit && diff.opening_proof);
+    assert!(!diff.aux_commit && !diff.protocol_id);
+    assert_eq!((diff.log_degree, diff.quotient_chunk_counts), (None, None));
+}
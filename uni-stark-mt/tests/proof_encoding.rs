@@ -0,0 +1,144 @@
+//! Versioned binary encoding of proofs
+
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Config};
+use p3_uni_stark_mt::config::goldilocks_keccak;
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{
+    prove, verify, FieldId, PcsId, Proof, ProofDecodeError, ProofHeader, PROOF_FORMAT_VERSION,
+    PROOF_HEADER_LEN, PROOF_MAGIC,
+};
+
+fn encoded_proof() -> Vec<u8> {
+    let config = default_config();
+    let proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    let bytes = proof.to_bytes();
+    assert_eq!(bytes.len(), PROOF_HEADER_LEN + proof.size_in_bytes());
+    bytes
+}
+
+#[test]
+fn test_encoded_proof_roundtrip() {
+    let bytes = encoded_proof();
+    let (header, payload) = ProofHeader::read(&bytes).expect("the header reads");
+    assert_eq!(
+        header,
+        ProofHeader {
+            format_version: PROOF_FORMAT_VERSION,
+            field: FieldId {
+                order: 0x7800_0001,
+                extension_degree: 4,
+            },
+            pcs: PcsId::TwoAdicFri,
+            payload_len: payload.len() as u32,
+        }
+    );
+    header
+        .check::<Config>()
+        .expect("the header fits the config");
+
+    let proof = Proof::<Config>::from_bytes(&bytes).expect("the proof decodes");
+    verify(&default_config(), &FibonacciAir, &proof, &[]).expect("verification failed");
+    assert_eq!(proof.to_bytes(), bytes);
+}
+
+#[test]
+fn test_header_layout_is_frozen() {
+    let bytes = encoded_proof();
+    // Magic, format version 2, BabyBear's order, quartic extension, two-adic FRI
+    assert_eq!(bytes[..4], PROOF_MAGIC);
+    assert_eq!(
+        bytes[4..16],
+        [2, 0, 0x01, 0x00, 0x00, 0x78, 0, 0, 0, 0, 4, 0]
+    );
+    assert_eq!(
+        u32::from_le_bytes(bytes[16..20].try_into().unwrap()) as usize,
+        bytes.len() - PROOF_HEADER_LEN
+    );
+}
+
+#[test]
+fn test_earlier_format_decodes() {
+    let bytes = encoded_proof();
+    let mut proof = Proof::<Config>::from_bytes(&bytes).expect("the proof decodes");
+    proof.config_digest = None;
+
+    // Version 1 is version 2 without the configuration digest, its last field
+    let mut v1 = proof.to_bytes();
+    v1.pop();
+    v1[4..6].copy_from_slice(&1u16.to_le_bytes());
+    let payload_len = (v1.len() - PROOF_HEADER_LEN) as u32;
+    v1[16..20].copy_from_slice(&payload_len.to_le_bytes());
+
+    let decoded = Proof::<Config>::from_bytes(&v1).expect("version 1 still decodes");
+    assert_eq!(decoded.config_digest, None);
+    verify(&default_config(), &FibonacciAir, &decoded, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_malformed_encodings_rejected() {
+    let bytes = encoded_proof();
+    let decode = |bytes: &[u8]| Proof::<Config>::from_bytes(bytes).err();
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] ^= 1;
+    assert_eq!(decode(&bad_magic), Some(ProofDecodeError::BadMagic));
+    assert_eq!(decode(b"P3"), Some(ProofDecodeError::Truncated));
+    assert_eq!(decode(&bytes[..10]), Some(ProofDecodeError::Truncated));
+    assert_eq!(
+        decode(&bytes[..bytes.len() - 1]),
+        Some(ProofDecodeError::Truncated)
+    );
+    assert_eq!(
+        decode(&[bytes.as_slice(), &[0u8]].concat()),
+        Some(ProofDecodeError::TrailingBytes)
+    );
+
+    // A format from a later version of the crate is recognized, not misread
+    let mut future = bytes.clone();
+    future[4..6].copy_from_slice(&(PROOF_FORMAT_VERSION + 1).to_le_bytes());
+    assert_eq!(
+        decode(&future),
+        Some(ProofDecodeError::UnsupportedFormatVersion(
+            PROOF_FORMAT_VERSION + 1
+        ))
+    );
+    let mut unknown_pcs = bytes.clone();
+    unknown_pcs[15] = 0xff;
+    assert_eq!(
+        decode(&unknown_pcs),
+        Some(ProofDecodeError::UnknownPcs(0xff))
+    );
+
+    // A payload of the right length that isn't a proof
+    let mut garbled = bytes.clone();
+    garbled[PROOF_HEADER_LEN..].fill(0xff);
+    assert_eq!(decode(&garbled), Some(ProofDecodeError::Malformed));
+}
+
+#[test]
+fn test_proof_for_another_config_rejected() {
+    let bytes = encoded_proof();
+    assert_eq!(
+        Proof::<goldilocks_keccak::Config>::from_bytes(&bytes).err(),
+        Some(ProofDecodeError::FieldMismatch {
+            expected: FieldId {
+                order: 0xffff_ffff_0000_0001,
+                extension_degree: 2,
+            },
+            actual: FieldId {
+                order: 0x7800_0001,
+                extension_degree: 4,
+            },
+        })
+    );
+
+    let mut circle = bytes;
+    circle[15] = PcsId::CircleFri.to_byte();
+    assert_eq!(
+        Proof::<Config>::from_bytes(&circle).err(),
+        Some(ProofDecodeError::PcsMismatch {
+            expected: PcsId::TwoAdicFri,
+            actual: PcsId::CircleFri,
+        })
+    );
+}
@@ -0,0 +1,61 @@
+//! Golden proof-size bounds for each config preset
+//!
+//! Proves a canonical AIR under every preset and checks the serialized proof stays below
+//! a fixed budget. A structural change that grows proofs (extra openings, a different
+//! quotient chunk layout, ...) fails here; if the growth is intended, raise the bound in
+//! the same change and say why.
+//!
+//! | Preset                | AIR                 | Rows | Bound (bytes) |
+//! |-----------------------|---------------------|------|---------------|
+//! | `baby_bear_poseidon2` | Fibonacci, 2 cols   | 2^8  | 400_000       |
+//! | `goldilocks_keccak`   | Fibonacci, 2 cols   | 2^8  | 400_000       |
+//!
+//! Both presets use their default parameters (100 queries, blowup 4), where the FRI
+//! query proofs dominate the size.
+
+use p3_uni_stark_mt::config::{baby_bear_poseidon2, goldilocks_keccak};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{prove, verify, ProofSizeBreakdown};
+
+const LOG_ROWS: usize = 8;
+const BABY_BEAR_POSEIDON2_MAX_BYTES: usize = 400_000;
+const GOLDILOCKS_KECCAK_MAX_BYTES: usize = 400_000;
+
+fn assert_within(preset: &str, breakdown: ProofSizeBreakdown, max_bytes: usize) {
+    assert!(
+        breakdown.total() <= max_bytes,
+        "{preset}: proof is {} bytes, budget is {max_bytes} ({breakdown:?})",
+        breakdown.total()
+    );
+    // Everything but the opening proof is a handful of commitments and opened values
+    assert!(
+        breakdown.opening_proof > breakdown.total() / 2,
+        "{preset}: opening proof no longer dominates the proof size ({breakdown:?})"
+    );
+}
+
+#[test]
+fn test_proof_size_baby_bear_poseidon2() {
+    let config = baby_bear_poseidon2::default_config();
+    let trace = fibonacci_trace::<baby_bear_poseidon2::Val>(1 << LOG_ROWS);
+    let proof = prove(&config, &FibonacciAir, trace, &[]).expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+    assert_within(
+        "baby_bear_poseidon2",
+        proof.size_breakdown(),
+        BABY_BEAR_POSEIDON2_MAX_BYTES,
+    );
+}
+
+#[test]
+fn test_proof_size_goldilocks_keccak() {
+    let config = goldilocks_keccak::default_config();
+    let trace = fibonacci_trace::<goldilocks_keccak::Val>(1 << LOG_ROWS);
+    let proof = prove(&config, &FibonacciAir, trace, &[]).expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+    assert_within(
+        "goldilocks_keccak",
+        proof.size_breakdown(),
+        GOLDILOCKS_KECCAK_MAX_BYTES,
+    );
+}
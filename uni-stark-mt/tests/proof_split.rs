@@ -0,0 +1,74 @@
+//! Proofs split into claims and an opening proof, verified in two stages
+
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Config};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{
+    keygen, prove, verify, verify_claims, verify_openings, Claims, OpeningProof, Proof,
+    VerificationError,
+};
+
+/// Both verification stages, one after the other.
+fn verify_in_stages(
+    config: &Config,
+    claims: &Claims<Config>,
+    opening_proof: &OpeningProof<Config>,
+) -> Result<(), VerificationError> {
+    let vk = keygen(config, &FibonacciAir);
+    let deferred = verify_claims(config, &vk, &FibonacciAir, claims, &[])?;
+    verify_openings(config, deferred, opening_proof)
+}
+
+#[test]
+fn test_split_proof_verifies_in_stages() {
+    let config = default_config();
+    let proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    let bytes = postcard::to_allocvec(&proof).unwrap();
+
+    // The parts travel separately, e.g. to an aggregator and a later PCS check
+    let (claims, opening_proof) = proof.split();
+    let claims: Claims<Config> =
+        postcard::from_bytes(&postcard::to_allocvec(&claims).unwrap()).unwrap();
+    let opening_proof: OpeningProof<Config> =
+        postcard::from_bytes(&postcard::to_allocvec(&opening_proof).unwrap()).unwrap();
+    verify_in_stages(&config, &claims, &opening_proof).expect("verification failed");
+
+    // Reassembling them gives back the proof
+    let proof = Proof::from_parts(claims, opening_proof);
+    assert_eq!(postcard::to_allocvec(&proof).unwrap(), bytes);
+    assert_eq!(
+        postcard::to_allocvec(&proof.claims()).unwrap(),
+        postcard::to_allocvec(&proof.split().0).unwrap()
+    );
+}
+
+#[test]
+fn test_stages_reject_what_verify_rejects() {
+    let config = default_config();
+    let proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    // Same shape, but opened under another transcript
+    let other_config = default_config().with_domain_separator(b"other");
+    let other =
+        prove(&other_config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+
+    // A wrong claimed value fails the algebraic check
+    let mut bad = proof.clone();
+    bad.main_local[0] += Challenge::ONE;
+    assert!(verify(&config, &FibonacciAir, &bad, &[]).is_err());
+    let (claims, opening_proof) = bad.split();
+    assert!(matches!(
+        verify_in_stages(&config, &claims, &opening_proof),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+
+    // Valid claims with another proof's opening proof fail the deferred PCS check
+    let (claims, _) = proof.split();
+    let (_, opening_proof) = other.split();
+    let vk = keygen(&config, &FibonacciAir);
+    let deferred = verify_claims(&config, &vk, &FibonacciAir, &claims, &[])
+        .expect("the claims alone are consistent");
+    assert!(matches!(
+        verify_openings(&config, deferred, &opening_proof),
+        Err(VerificationError::PcsVerificationFailed)
+    ));
+}
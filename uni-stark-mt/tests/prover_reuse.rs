@@ -0,0 +1,44 @@
+//! A prover kept across proofs of one AIR
+
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Val};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{keygen, prove, verify, Prover, ProverError};
+
+#[test]
+fn test_prover_reused_across_heights() {
+    let config = default_config();
+    let mut prover = Prover::new(&config, &FibonacciAir);
+    assert_eq!(prover.vk(), &keygen(&config, &FibonacciAir));
+
+    // Back to a height proven before, after another one
+    for n in [8, 8, 32, 8] {
+        let trace = fibonacci_trace::<Val>(n);
+        let proof = prover.prove(&trace, &[]).expect("proving failed");
+        verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+
+        let expected = prove(&config, &FibonacciAir, &trace, &[]).expect("proving failed");
+        assert_eq!(
+            postcard::to_allocvec(&proof).unwrap(),
+            postcard::to_allocvec(&expected).unwrap()
+        );
+    }
+}
+
+#[test]
+fn test_prover_errors_leave_it_usable() {
+    let config = default_config();
+    let mut prover = Prover::new(&config, &FibonacciAir);
+    assert!(matches!(
+        prover.prove(fibonacci_trace(8), &[Val::ONE]),
+        Err(ProverError::PublicValuesMismatch {
+            expected: 0,
+            actual: 1
+        })
+    ));
+
+    let proof = prover
+        .prove(fibonacci_trace(8), &[])
+        .expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+}
@@ -0,0 +1,199 @@
+//! Public value handling: binding, typed shape errors and reproving
+
+use core::borrow::Borrow;
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::{Field, PrimeCharacteristicRing, PrimeField64};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Val};
+use p3_uni_stark_mt::{
+    commit_main, keygen, prove, prove_with_committed_main, verify, AirLayout, AuxTraceBuilder,
+    ProverError, PuncturedAir, VerificationError,
+};
+
+const NUM_FIBONACCI_COLS: usize = 2;
+
+/// Fibonacci AIR with public values `[a, b, x]`: the sequence starts at `(a, b)` and its
+/// last element is `x`.
+pub struct FibonacciAir;
+
+impl<F> BaseAir<F> for FibonacciAir {
+    fn width(&self) -> usize {
+        NUM_FIBONACCI_COLS
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for FibonacciAir {
+    fn num_public_values(&self) -> usize {
+        3
+    }
+}
+
+impl<F> PuncturedAir<F> for FibonacciAir {}
+
+impl<F> AirLayout<F> for FibonacciAir {}
+
+impl<F: Field, EF: p3_field::ExtensionField<F>> AuxTraceBuilder<F, EF> for FibonacciAir {}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for FibonacciAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let pis = builder.public_values();
+        let (a, b, x) = (pis[0], pis[1], pis[2]);
+
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        let local: &FibonacciRow<AB::Var> = (*local).borrow();
+        let next: &FibonacciRow<AB::Var> = (*next).borrow();
+
+        let mut when_first_row = builder.when_first_row();
+        when_first_row.assert_eq(local.left.clone(), a);
+        when_first_row.assert_eq(local.right.clone(), b);
+
+        let mut when_transition = builder.when_transition();
+        when_transition.assert_eq(local.right.clone(), next.left.clone());
+        when_transition.assert_eq(local.left.clone() + local.right.clone(), next.right.clone());
+
+        builder.when_last_row().assert_eq(local.right.clone(), x);
+    }
+}
+
+pub fn generate_trace_rows<F: PrimeField64>(a: u64, b: u64, n: usize) -> RowMajorMatrix<F> {
+    assert!(n.is_power_of_two());
+
+    let mut values = Vec::with_capacity(n * NUM_FIBONACCI_COLS);
+    let (mut left, mut right) = (F::from_u64(a), F::from_u64(b));
+    for _ in 0..n {
+        values.extend([left, right]);
+        (left, right) = (right, left + right);
+    }
+    RowMajorMatrix::new(values, NUM_FIBONACCI_COLS)
+}
+
+pub struct FibonacciRow<F> {
+    pub left: F,
+    pub right: F,
+}
+
+impl<F> Borrow<FibonacciRow<F>> for [F] {
+    fn borrow(&self) -> &FibonacciRow<F> {
+        debug_assert_eq!(self.len(), NUM_FIBONACCI_COLS);
+        let (prefix, shorts, suffix) = unsafe { self.align_to::<FibonacciRow<F>>() };
+        debug_assert!(prefix.is_empty(), "Alignment should match");
+        debug_assert!(suffix.is_empty(), "Alignment should match");
+        debug_assert_eq!(shorts.len(), 1);
+        &shorts[0]
+    }
+}
+
+fn pis(a: u32, b: u32, x: u32) -> [Val; 3] {
+    [Val::from_u32(a), Val::from_u32(b), Val::from_u32(x)]
+}
+
+#[test]
+fn test_public_values_roundtrip() {
+    let config = default_config();
+    let vk = keygen(&config, &FibonacciAir);
+    assert_eq!(vk.num_public_values, 3);
+    assert_eq!(vk.num_constraints, 5);
+
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+    let proof = prove(&config, &FibonacciAir, trace, &pis(0, 1, 21)).expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &pis(0, 1, 21)).expect("verification failed");
+}
+
+#[test]
+fn test_public_values_wrong_length() {
+    let config = default_config();
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+
+    assert!(matches!(
+        prove(&config, &FibonacciAir, trace.clone(), &pis(0, 1, 21)[..2]),
+        Err(ProverError::PublicValuesMismatch {
+            expected: 3,
+            actual: 2
+        })
+    ));
+
+    let proof = prove(&config, &FibonacciAir, trace, &pis(0, 1, 21)).expect("proving failed");
+    assert!(matches!(
+        verify(&config, &FibonacciAir, &proof, &pis(0, 1, 21)[..2]),
+        Err(VerificationError::PublicValuesMismatch {
+            expected: 3,
+            actual: 2
+        })
+    ));
+    let mut long = pis(0, 1, 21).to_vec();
+    long.push(Val::ZERO);
+    let error = verify(&config, &FibonacciAir, &proof, &long).expect_err("too many values");
+    assert!(matches!(
+        error,
+        VerificationError::PublicValuesMismatch {
+            expected: 3,
+            actual: 4
+        }
+    ));
+    // Errors read as messages, for applications reporting them
+    assert_eq!(error.to_string(), "expected 3 public values, got 4");
+}
+
+#[test]
+fn test_public_values_wrong_order_or_value() {
+    let config = default_config();
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+    let proof = prove(&config, &FibonacciAir, trace, &pis(0, 1, 21)).expect("proving failed");
+
+    for wrong in [pis(1, 0, 21), pis(0, 1, 22)] {
+        let result = verify(&config, &FibonacciAir, &proof, &wrong);
+        assert!(matches!(
+            result,
+            Err(VerificationError::PcsVerificationFailed
+                | VerificationError::ConstraintVerificationFailed)
+        ));
+    }
+}
+
+#[test]
+fn test_reprove_with_committed_main() {
+    let config = default_config();
+    let committed = commit_main(&config, &FibonacciAir, generate_trace_rows::<Val>(0, 1, 8))
+        .expect("commit failed");
+
+    // A rejected public-value vector leaves the committed trace usable
+    assert!(prove_with_committed_main(&config, &FibonacciAir, &committed, &[]).is_err());
+
+    let reproof = prove_with_committed_main(&config, &FibonacciAir, &committed, &pis(0, 1, 21))
+        .expect("proving failed");
+    verify(&config, &FibonacciAir, &reproof, &pis(0, 1, 21)).expect("verification failed");
+
+    let proof = prove(
+        &config,
+        &FibonacciAir,
+        generate_trace_rows::<Val>(0, 1, 8),
+        &pis(0, 1, 21),
+    )
+    .expect("proving failed");
+    assert_eq!(proof.main_commits, reproof.main_commits);
+    assert_eq!(proof.quotient_commit, reproof.quotient_commit);
+}
+
+#[test]
+fn test_prove_borrowed_trace() {
+    let config = default_config();
+    let trace = generate_trace_rows::<Val>(0, 1, 8);
+
+    // The trace stays with the caller, e.g. to prove other public values
+    let borrowed = prove(&config, &FibonacciAir, &trace, &pis(0, 1, 21)).expect("proving failed");
+    verify(&config, &FibonacciAir, &borrowed, &pis(0, 1, 21)).expect("verification failed");
+    let committed = commit_main(&config, &FibonacciAir, &trace).expect("commit failed");
+    assert_eq!(committed.trace.values, trace.values);
+
+    let owned = prove(&config, &FibonacciAir, trace, &pis(0, 1, 21)).expect("proving failed");
+    assert_eq!(
+        postcard::to_allocvec(&borrowed).unwrap(),
+        postcard::to_allocvec(&owned).unwrap()
+    );
+}
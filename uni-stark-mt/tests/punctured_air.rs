@@ -0,0 +1,127 @@
+//! AIRs whose constraints exclude the last rows of the trace
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Val};
+use p3_uni_stark_mt::{
+    check_constraints, keygen, prove, verify, AirLayout, AuxTraceBuilder, ProverError,
+    PuncturedAir, VerificationError,
+};
+
+/// A counter `x' = x + 1` enforced on every row but the punctured ones.
+///
+/// No transition selector is used: with two punctured rows the step into the last row
+/// and the wrap-around from the last row to the first are both unconstrained, so the
+/// last row is free padding.
+pub struct CounterAir {
+    num_punctured_rows: usize,
+}
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for CounterAir {}
+
+impl<F> PuncturedAir<F> for CounterAir {
+    fn num_punctured_rows(&self) -> usize {
+        self.num_punctured_rows
+    }
+}
+
+impl<F> AirLayout<F> for CounterAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterAir {}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        builder.assert_eq(next[0].clone(), local[0].clone() + AB::Expr::ONE);
+    }
+}
+
+/// `0, 1, ..., n - 2` followed by one row of padding.
+fn counter_trace(n: usize) -> RowMajorMatrix<Val> {
+    let mut values: Vec<Val> = (0..n as u32 - 1).map(Val::from_u32).collect();
+    values.push(Val::from_u32(42));
+    RowMajorMatrix::new_col(values)
+}
+
+#[test]
+fn test_punctured_counter() {
+    let config = default_config();
+    let air = CounterAir {
+        num_punctured_rows: 2,
+    };
+    for log_n in [2, 5] {
+        let trace = counter_trace(1 << log_n);
+        check_constraints::<Val, Val, _>(&air, &trace, None, &[], &[])
+            .expect("padding row is not constrained");
+        let proof = prove(&config, &air, trace, &[]).expect("proving failed");
+        verify(&config, &air, &proof, &[]).expect("verification failed");
+    }
+    assert_eq!(keygen(&config, &air).num_punctured_rows, 2);
+}
+
+#[test]
+fn test_punctured_counter_wrong_punctures() {
+    let trace = counter_trace(8);
+
+    // With fewer punctures the padding row is constrained
+    let violations = check_constraints::<Val, Val, _>(
+        &CounterAir {
+            num_punctured_rows: 1,
+        },
+        &trace,
+        None,
+        &[],
+        &[],
+    )
+    .expect_err("padding row must be constrained")
+    .0;
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].row, 6);
+
+    // A proof for two punctures doesn't verify against one
+    let config = default_config();
+    let proof = prove(
+        &config,
+        &CounterAir {
+            num_punctured_rows: 2,
+        },
+        trace,
+        &[],
+    )
+    .expect("proving failed");
+    assert!(matches!(
+        verify(
+            &config,
+            &CounterAir {
+                num_punctured_rows: 1
+            },
+            &proof,
+            &[]
+        ),
+        Err(VerificationError::ConstraintVerificationFailed)
+    ));
+}
+
+#[test]
+fn test_punctured_counter_every_row() {
+    let config = default_config();
+    let air = CounterAir {
+        num_punctured_rows: 4,
+    };
+    assert!(matches!(
+        prove(&config, &air, counter_trace(4), &[]),
+        Err(ProverError::InvalidPunctures(_))
+    ));
+}
@@ -0,0 +1,69 @@
+//! Conversions between trace, quotient and LDE row indices
+
+use p3_uni_stark_mt::{LdeOrdering, LdeRow, QuotientRow, TraceRow};
+
+#[test]
+fn test_trace_rows() {
+    assert_eq!(TraceRow::last(8), TraceRow(7));
+    assert_eq!(TraceRow(3).next(8), TraceRow(4));
+    // The next row of the last row is the first
+    assert_eq!(TraceRow::last(8).next(8), TraceRow(0));
+}
+
+#[test]
+fn test_quotient_rows() {
+    // A trace of 8 rows and a quotient domain 4 times larger
+    let (log_quotient_degree, quotient_size) = (2, 32);
+    assert_eq!(
+        TraceRow(3).to_quotient(log_quotient_degree),
+        QuotientRow(12)
+    );
+    assert_eq!(
+        QuotientRow(12).to_trace(log_quotient_degree),
+        Some(TraceRow(3))
+    );
+    assert_eq!(QuotientRow(13).to_trace(log_quotient_degree), None);
+
+    // One trace step later, not one quotient row later
+    assert_eq!(
+        QuotientRow(13).next_trace_step(log_quotient_degree, quotient_size),
+        QuotientRow(17)
+    );
+    // Wrapping around, past the last trace step
+    assert_eq!(
+        QuotientRow(30).next_trace_step(log_quotient_degree, quotient_size),
+        QuotientRow(2)
+    );
+    let last = TraceRow::last(8).to_quotient(log_quotient_degree);
+    assert_eq!(
+        last.next_trace_step(log_quotient_degree, quotient_size),
+        TraceRow(0).to_quotient(log_quotient_degree)
+    );
+}
+
+#[test]
+fn test_lde_rows() {
+    // A trace of 8 rows extended by 2: 16 LDE rows
+    let (log_height, log_blowup) = (3, 1);
+    assert_eq!(
+        TraceRow(3).to_lde(log_height, log_blowup, LdeOrdering::Natural),
+        LdeRow(6)
+    );
+    // 6 = 0b0110 reversed over 4 bits
+    assert_eq!(
+        TraceRow(3).to_lde(log_height, log_blowup, LdeOrdering::BitReversed),
+        LdeRow(6)
+    );
+    // 2 = 0b0010 reversed over 4 bits
+    assert_eq!(
+        TraceRow(1).to_lde(log_height, log_blowup, LdeOrdering::BitReversed),
+        LdeRow(4)
+    );
+
+    for ordering in [LdeOrdering::Natural, LdeOrdering::BitReversed] {
+        for natural in 0..16 {
+            let row = LdeRow::from_natural(natural, 4, ordering);
+            assert_eq!(row.to_natural(4, ordering), natural);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Proving an execution in shards chained by their state digests
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Val};
+use p3_uni_stark_mt::{
+    prove_shards, verify_chain, AirLayout, AuxTraceBuilder, ChainError, ChainStates, PuncturedAir,
+    StateDigests, VerificationError,
+};
+
+/// A counter whose public values are the state it starts from and the one it ends in,
+/// the value after its last row.
+pub struct CounterShardAir {
+    digests: Option<StateDigests>,
+}
+
+const SHARD: CounterShardAir = CounterShardAir {
+    digests: Some(StateDigests {
+        input_offset: 0,
+        output_offset: 1,
+        len: 1,
+    }),
+};
+
+impl<F> BaseAir<F> for CounterShardAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for CounterShardAir {
+    fn num_public_values(&self) -> usize {
+        2
+    }
+}
+
+impl<F> PuncturedAir<F> for CounterShardAir {}
+
+impl<F> AirLayout<F> for CounterShardAir {
+    fn state_digests(&self) -> Option<StateDigests> {
+        self.digests
+    }
+}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterShardAir {}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for CounterShardAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (input, output) = (builder.public_values()[0], builder.public_values()[1]);
+        let (x, x_next) = (
+            main.row_slice(0).expect("Matrix is empty?")[0].clone(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0].clone(),
+        );
+        builder.when_first_row().assert_eq(x.clone(), input);
+        builder
+            .when_transition()
+            .assert_eq(x.clone() + AB::Expr::ONE, x_next);
+        builder.when_last_row().assert_eq(x + AB::Expr::ONE, output);
+    }
+}
+
+/// The shard counting from `start` to `end`, and its public values.
+fn shard(start: u32, end: u32) -> (RowMajorMatrix<Val>, Vec<Val>) {
+    (
+        RowMajorMatrix::new_col((start..end).map(Val::from_u32).collect()),
+        vec![Val::from_u32(start), Val::from_u32(end)],
+    )
+}
+
+#[test]
+fn test_chain_of_shards() {
+    let config = default_config();
+    let shards = prove_shards(&config, &SHARD, [shard(0, 8), shard(8, 24), shard(24, 32)])
+        .expect("proving failed");
+    assert_eq!(
+        verify_chain(&config, &SHARD, &shards).expect("the chain should verify"),
+        ChainStates {
+            input: vec![Val::ZERO],
+            output: vec![Val::from_u32(32)],
+        }
+    );
+    // A prefix of a chain is a chain
+    assert_eq!(
+        verify_chain(&config, &SHARD, &shards[..1])
+            .expect("the chain should verify")
+            .output,
+        [Val::from_u32(8)]
+    );
+
+    // A dropped or reordered shard breaks the chain
+    let skipping = [shards[0].clone(), shards[2].clone()];
+    assert!(matches!(
+        verify_chain(&config, &SHARD, &skipping),
+        Err(ChainError::Discontinuity { index: 1 })
+    ));
+    let reordered = [shards[1].clone(), shards[0].clone(), shards[2].clone()];
+    assert!(matches!(
+        verify_chain(&config, &SHARD, &reordered),
+        Err(ChainError::Discontinuity { index: 1 })
+    ));
+
+    // So does a shard claiming another state than it proved
+    let mut rebased = shards.clone();
+    rebased[1].public_values[0] = Val::from_u32(9);
+    assert!(matches!(
+        verify_chain(&config, &SHARD, &rebased),
+        Err(ChainError::Shard {
+            index: 1,
+            error: VerificationError::ConstraintVerificationFailed
+                | VerificationError::PcsVerificationFailed,
+        })
+    ));
+}
+
+#[test]
+fn test_chain_needs_state_digests() {
+    let config = default_config();
+    let shards = prove_shards(&config, &SHARD, [shard(0, 8)]).expect("proving failed");
+
+    assert!(matches!(
+        verify_chain(&config, &SHARD, &[]),
+        Err(ChainError::Empty)
+    ));
+    let without_digests = CounterShardAir { digests: None };
+    assert!(matches!(
+        verify_chain(&config, &without_digests, &shards),
+        Err(ChainError::InvalidStateDigests)
+    ));
+    let out_of_range = CounterShardAir {
+        digests: Some(StateDigests {
+            input_offset: 0,
+            output_offset: 1,
+            len: 2,
+        }),
+    };
+    assert!(matches!(
+        verify_chain(&config, &out_of_range, &shards),
+        Err(ChainError::InvalidStateDigests)
+    ));
+}
@@ -0,0 +1,144 @@
+//! Symbolic constraint extraction
+#![cfg(not(feature = "minimal"))]
+
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field};
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{Challenge, Val};
+use p3_uni_stark_mt::test_utils::FibonacciAir;
+use p3_uni_stark_mt::{
+    symbolic_constraints, AirLayout, AuxBuilder, AuxTraceBuilder, Entry, PuncturedAir, RowOffset,
+};
+
+/// A running sum `s` of one main column `x`, whose final value is exposed as `T`.
+///
+/// `x` starts at the public value, `s` starts at 0 and `T = s + γ·x` on the last row.
+pub struct ExposedSumAir;
+
+impl<F> BaseAir<F> for ExposedSumAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for ExposedSumAir {
+    fn num_public_values(&self) -> usize {
+        1
+    }
+}
+
+impl<F> PuncturedAir<F> for ExposedSumAir {}
+
+impl<F> AirLayout<F> for ExposedSumAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for ExposedSumAir {
+    fn aux_width(&self) -> usize {
+        1
+    }
+
+    fn challenge_names(&self) -> Vec<&'static str> {
+        vec!["gamma"]
+    }
+
+    fn num_aux_exposed_values(&self) -> usize {
+        1
+    }
+}
+
+impl<AB: AuxBuilder + AirBuilderWithPublicValues> Air<AB> for ExposedSumAir
+where
+    AB::MAux: Matrix<AB::VarEF>,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let aux = builder.aux();
+        let x = main.row_slice(0).expect("Matrix is empty?")[0].clone();
+        let start = builder.public_values()[0];
+        builder.when_first_row().assert_eq(x.clone(), start);
+
+        let gamma: AB::ExprEF = builder.challenge("gamma").into();
+        let total: AB::ExprEF = builder.aux_exposed_values()[0].into();
+        let s: AB::ExprEF = aux.row_slice(0).expect("Matrix is empty?")[0].into();
+        let is_last_row = builder.embed_main(builder.is_last_row());
+        let x = builder.embed_main(x);
+        builder.assert_zero_ext(is_last_row * (total - s - gamma * x));
+    }
+}
+
+#[test]
+fn test_fibonacci_symbolic_constraints() {
+    let symbolic = symbolic_constraints::<Val, Challenge, _>(&FibonacciAir);
+    assert_eq!(
+        symbolic.to_string(),
+        "is_transition * (main_local_1 - main_next_0) = 0\n\
+         is_transition * (main_local_0 + main_local_1 - main_next_1) = 0\n"
+    );
+    assert!(symbolic.constraints.iter().all(|c| !c.extension));
+    assert_eq!(symbolic.variables.len(), 4);
+    assert_eq!(symbolic.max_degree(), 1);
+    assert_eq!(symbolic.variables[2].row(), Some(RowOffset::Next));
+    assert_eq!(
+        symbolic.variables[2],
+        Entry::Main {
+            row: RowOffset::Next,
+            column: 0
+        }
+    );
+}
+
+#[test]
+fn test_exposed_sum_symbolic_constraints() {
+    let symbolic = symbolic_constraints::<Val, Challenge, _>(&ExposedSumAir);
+    assert_eq!(symbolic.constraints.len(), 2);
+    assert!(!symbolic.constraints[0].extension);
+    assert!(symbolic.constraints[1].extension);
+    assert_eq!(
+        symbolic.constraints[1].expr.to_string(),
+        "is_last_row * (exposed_0 - aux_local_0 - challenge_gamma * main_local_0)"
+    );
+    for entry in [
+        Entry::Public(0),
+        Entry::Challenge("gamma"),
+        Entry::AuxExposed(0),
+    ] {
+        assert!(symbolic.variables.contains(&entry), "{entry:?}");
+    }
+
+    // Selectors count as degree one, the transition selector as zero
+    assert_eq!(symbolic.max_degree(), 2);
+    assert_eq!(
+        symbolic.constraints[1].expr.entries(),
+        [
+            Entry::AuxExposed(0),
+            Entry::Aux {
+                row: RowOffset::Local,
+                column: 0
+            },
+            Entry::Challenge("gamma"),
+            Entry::Main {
+                row: RowOffset::Local,
+                column: 0
+            },
+        ]
+    );
+    assert_eq!(
+        symbolic.unused_variables(),
+        [
+            Entry::Main {
+                row: RowOffset::Next,
+                column: 0
+            },
+            Entry::Aux {
+                row: RowOffset::Next,
+                column: 0
+            },
+            Entry::StatementPublic(0),
+        ]
+    );
+
+    let smt = symbolic.to_smtlib();
+    assert!(smt.contains("(define-sort F () (_ FiniteField 2013265921))"));
+    assert!(smt.contains("(declare-const public_0 F)\n"));
+    assert!(smt.contains("(declare-const challenge_gamma F) ; extension\n"));
+    assert_eq!(smt.matches("(assert ").count(), 2);
+}
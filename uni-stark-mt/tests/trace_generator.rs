@@ -0,0 +1,79 @@
+//! Main traces produced by trace generators
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Val};
+use p3_uni_stark_mt::test_utils::FibonacciAir;
+use p3_uni_stark_mt::{prove, verify, AirLayout, AuxTraceBuilder, PuncturedAir, TraceGenerator};
+
+/// Row `i` holds `(i, i^2)`: every row only depends on its index.
+pub struct SquaresAir;
+
+impl<F> BaseAir<F> for SquaresAir {
+    fn width(&self) -> usize {
+        2
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for SquaresAir {}
+
+impl<F> PuncturedAir<F> for SquaresAir {}
+
+impl<F> AirLayout<F> for SquaresAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for SquaresAir {}
+
+impl<AB: AirBuilder> Air<AB> for SquaresAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        builder.when_first_row().assert_zero(local[0].clone());
+        builder
+            .when_transition()
+            .assert_eq(local[0].clone() + AB::Expr::ONE, next[0].clone());
+        builder.assert_eq(local[1].clone(), local[0].clone() * local[0].clone());
+    }
+}
+
+impl<F: Field> TraceGenerator<F> for SquaresAir {
+    /// Number of rows
+    type Inputs = usize;
+
+    fn height(&self, height: &usize) -> usize {
+        *height
+    }
+
+    fn generate_row(&self, _height: &usize, index: usize, row: &mut [F]) {
+        let i = F::from_usize(index);
+        row.copy_from_slice(&[i, i.square()]);
+    }
+}
+
+#[test]
+fn test_rowwise_generator() {
+    let trace: RowMajorMatrix<Val> = SquaresAir.generate(&64);
+    assert_eq!((trace.width(), trace.height()), (2, 64));
+    let expected: Vec<Val> = (0..64u32)
+        .flat_map(|i| [Val::from_u32(i), Val::from_u32(i * i)])
+        .collect();
+    assert_eq!(trace.values, expected);
+
+    let config = default_config();
+    let proof = prove(&config, &SquaresAir, trace, &[]).expect("proving failed");
+    verify(&config, &SquaresAir, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_sequential_generator() {
+    let trace: RowMajorMatrix<Val> = FibonacciAir.generate(&8);
+    assert_eq!(trace.values[2 * 7 + 1], Val::from_u32(21));
+
+    let config = default_config();
+    let proof = prove(&config, &FibonacciAir, trace, &[]).expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+}
@@ -0,0 +1,329 @@
+//! Transcript domain separation (phase tags, application separators), commitment
+//! encodings, extension challenge sampling, seeded challengers, uniform auxiliary phases,
+//! grinding and AIR fingerprints
+
+use p3_air::{Air, AirBuilder, BaseAir, BaseAirWithPublicValues};
+use p3_field::{ExtensionField, Field, PrimeCharacteristicRing};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Val};
+use p3_uni_stark_mt::config::goldilocks_keccak;
+use p3_uni_stark_mt::test_utils::mutate::{mutations, Mutation};
+use p3_uni_stark_mt::test_utils::SeededConfig;
+use p3_uni_stark_mt::{
+    keygen, prove, verify, verify_with_vk, AirLayout, AuxTraceBuilder, ExtensionSampling, Proof,
+    ProtocolMismatch, PuncturedAir, StarkGenericConfig, TranscriptPhase, VerificationError,
+    VerifyingKey,
+};
+
+/// One main column counting up from 0.
+pub struct CounterAir;
+
+impl<F> BaseAir<F> for CounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for CounterAir {}
+
+impl<F> PuncturedAir<F> for CounterAir {}
+
+impl<F> AirLayout<F> for CounterAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for CounterAir {}
+
+impl<AB: AirBuilder> Air<AB> for CounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next) = (
+            main.row_slice(0).expect("Matrix is empty?"),
+            main.row_slice(1).expect("Matrix only has 1 row?"),
+        );
+        builder.when_first_row().assert_zero(local[0].clone());
+        builder
+            .when_transition()
+            .assert_eq(local[0].clone() + AB::Expr::ONE, next[0].clone());
+    }
+}
+
+/// [`CounterAir`] with its transition constraint doubled: the same shape, satisfied by
+/// the same traces, but not the same constraints.
+pub struct DoubledCounterAir;
+
+impl<F> BaseAir<F> for DoubledCounterAir {
+    fn width(&self) -> usize {
+        1
+    }
+}
+
+impl<F> BaseAirWithPublicValues<F> for DoubledCounterAir {}
+
+impl<F> PuncturedAir<F> for DoubledCounterAir {}
+
+impl<F> AirLayout<F> for DoubledCounterAir {}
+
+impl<F: Field, EF: ExtensionField<F>> AuxTraceBuilder<F, EF> for DoubledCounterAir {}
+
+impl<AB: AirBuilder> Air<AB> for DoubledCounterAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let (local, next): (AB::Expr, AB::Expr) = (
+            main.row_slice(0).expect("Matrix is empty?")[0]
+                .clone()
+                .into(),
+            main.row_slice(1).expect("Matrix only has 1 row?")[0]
+                .clone()
+                .into(),
+        );
+        builder.when_first_row().assert_zero(local.clone());
+        builder
+            .when_transition()
+            .assert_eq(local.double() + AB::Expr::TWO, next.double());
+    }
+}
+
+fn counter_trace(height: usize) -> RowMajorMatrix<Val> {
+    RowMajorMatrix::new_col((0..height).map(Val::from_usize).collect())
+}
+
+#[test]
+fn test_phase_tags_are_distinct() {
+    let phases = [
+        TranscriptPhase::Main,
+        TranscriptPhase::Aux,
+        TranscriptPhase::Quotient,
+        TranscriptPhase::Opening,
+    ];
+    for (i, a) in phases.iter().enumerate() {
+        for b in &phases[i + 1..] {
+            assert_ne!(
+                a.to_field_element::<Val>(),
+                b.to_field_element::<Val>(),
+                "{a:?} and {b:?} share a tag"
+            );
+        }
+    }
+}
+
+/// Check the verifier replays the prover's transcript, and absorbs every commitment in
+/// it: the proof verifies, and no longer does with any two commitments swapped.
+fn check_commitments_absorbed<SC: StarkGenericConfig + Clone>(
+    proof: &Proof<SC>,
+    verifies: impl Fn(&Proof<SC>) -> bool,
+) {
+    assert!(verifies(proof));
+    let swaps = mutations(proof)
+        .into_iter()
+        .filter(|mutation| matches!(mutation, Mutation::SwapCommitments(..)));
+    for mutation in swaps {
+        let mut swapped = proof.clone();
+        mutation.apply(&mut swapped);
+        assert!(!verifies(&swapped), "{mutation:?} accepted");
+    }
+}
+
+#[test]
+fn test_field_digest_commitments() {
+    // Poseidon2 Merkle roots are field elements, absorbed one by one. The uniform
+    // auxiliary phase adds the placeholder commitment.
+    let config = default_config().with_uniform_aux_phase(true);
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    check_commitments_absorbed(&proof, |proof| {
+        verify(&config, &CounterAir, proof, &[]).is_ok()
+    });
+}
+
+#[test]
+fn test_byte_digest_commitments() {
+    // Keccak Merkle roots are bytes, absorbed through the serializing challenger
+    let config = goldilocks_keccak::default_config().with_uniform_aux_phase(true);
+    let trace = RowMajorMatrix::new_col((0..8).map(goldilocks_keccak::Val::from_usize).collect());
+    let proof = prove(&config, &CounterAir, trace, &[]).expect("proving failed");
+    check_commitments_absorbed(&proof, |proof| {
+        verify(&config, &CounterAir, proof, &[]).is_ok()
+    });
+}
+
+#[test]
+fn test_domain_separator_binds_proof() {
+    let config = default_config().with_domain_separator(b"app-a v1");
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&config, &CounterAir, &proof, &[]).expect("verification failed");
+
+    // Another application, or none at all, derives different challenges
+    let other = default_config().with_domain_separator(b"app-b v1");
+    assert!(verify(&other, &CounterAir, &proof, &[]).is_err());
+    assert!(verify(&default_config(), &CounterAir, &proof, &[]).is_err());
+
+    // The length is absorbed too, so a trailing zero byte is a different separator
+    let padded = default_config().with_domain_separator(b"app-a v1\0");
+    assert!(verify(&padded, &CounterAir, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_empty_domain_separator_is_default() {
+    let config = default_config().with_domain_separator(b"");
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&default_config(), &CounterAir, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_seeded_challenger_is_reproducible() {
+    let config = SeededConfig::new(default_config(), 0x5eed);
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&config, &CounterAir, &proof, &[]).expect("verification failed");
+
+    // A fresh configuration with the same seed proves byte for byte the same
+    let again = SeededConfig::new(default_config(), 0x5eed);
+    let reproved = prove(&again, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    assert_eq!(
+        postcard::to_allocvec(&proof).unwrap(),
+        postcard::to_allocvec(&reproved).unwrap()
+    );
+
+    // Seeds are bound, down to the high limb
+    let other = SeededConfig::new(default_config(), 0x5eed | (1 << 48));
+    assert!(verify(&other, &CounterAir, &proof, &[]).is_err());
+    assert!(verify(&default_config(), &CounterAir, &proof, &[]).is_err());
+}
+
+#[test]
+fn test_extension_sampling_is_bound() {
+    let samplings = [
+        ExtensionSampling::Direct,
+        ExtensionSampling::BaseSamples,
+        ExtensionSampling::BaseSamplesRejecting,
+    ];
+    for sampling in samplings {
+        let config = default_config().with_extension_sampling(sampling);
+        assert_eq!(
+            keygen(&config, &CounterAir).protocol_id.extension_sampling,
+            sampling
+        );
+        let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+        verify(&config, &CounterAir, &proof, &[]).expect("verification failed");
+
+        // A verifier sampling otherwise rejects the proof before drawing anything
+        for other in samplings.into_iter().filter(|&other| other != sampling) {
+            let config = default_config().with_extension_sampling(other);
+            let err = verify(&config, &CounterAir, &proof, &[]).unwrap_err();
+            assert!(
+                matches!(
+                    err,
+                    VerificationError::ProtocolMismatch(ProtocolMismatch::ExtensionSampling {
+                        expected,
+                        actual,
+                    }) if expected == other && actual == sampling
+                ),
+                "{err:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pow_bits_are_ground() {
+    let config = default_config().with_pow_bits(8);
+    assert_eq!(keygen(&config, &CounterAir).protocol_id.pow_bits, 8);
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&config, &CounterAir, &proof, &[]).expect("verification failed");
+
+    // Another witness moves the out-of-domain points, if it meets the difficulty at all
+    let mut tampered = proof.clone();
+    tampered.pow_witness += Val::ONE;
+    assert!(verify(&config, &CounterAir, &tampered, &[]).is_err());
+
+    // A verifier without grinding rejects the proof before replaying its transcript
+    let err = verify(&default_config(), &CounterAir, &proof, &[]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            VerificationError::ProtocolMismatch(ProtocolMismatch::PowBits {
+                expected: 0,
+                actual: 8,
+            })
+        ),
+        "{err:?}"
+    );
+
+    // Without grinding, the witness is zero
+    let mut proof =
+        prove(&default_config(), &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    assert_eq!(proof.pow_witness, Val::ZERO);
+    proof.pow_witness = Val::ONE;
+    assert!(matches!(
+        verify(&default_config(), &CounterAir, &proof, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_uniform_aux_phase() {
+    let config = default_config().with_uniform_aux_phase(true);
+    assert_eq!(keygen(&config, &CounterAir).protocol_id.num_aux_phases, 1);
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&config, &CounterAir, &proof, &[]).expect("verification failed");
+
+    // Every proof carries the same placeholder, whatever its trace
+    let taller = prove(&config, &CounterAir, counter_trace(32), &[]).expect("proving failed");
+    verify(&config, &CounterAir, &taller, &[]).expect("verification failed");
+    assert!(proof.aux_commit.is_some());
+    assert_eq!(
+        postcard::to_allocvec(&proof.aux_commit).unwrap(),
+        postcard::to_allocvec(&taller.aux_commit).unwrap()
+    );
+
+    // Any other commitment in its place is rejected
+    let mut swapped = proof.clone();
+    swapped.aux_commit = Some(proof.quotient_commit.clone());
+    assert!(matches!(
+        verify(&config, &CounterAir, &swapped, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+
+    // Verifiers must agree on the option
+    assert!(verify(&default_config(), &CounterAir, &proof, &[]).is_err());
+    let plain =
+        prove(&default_config(), &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    assert!(matches!(
+        verify(&config, &CounterAir, &plain, &[]),
+        Err(VerificationError::ProtocolMismatch(
+            ProtocolMismatch::AuxPhases {
+                expected: 1,
+                actual: 0
+            }
+        ))
+    ));
+}
+
+#[test]
+fn test_air_fingerprint_binds_proof() {
+    let config = default_config();
+    let vk = keygen(&config, &CounterAir);
+    let doubled = keygen(&config, &DoubledCounterAir);
+    assert_eq!(vk.fingerprint, keygen(&config, &CounterAir).fingerprint);
+    assert_ne!(vk.fingerprint, doubled.fingerprint);
+    // Everything else about the keys is the same
+    assert_eq!(
+        vk,
+        VerifyingKey {
+            fingerprint: vk.fingerprint,
+            ..doubled
+        }
+    );
+
+    // The trace satisfies both AIRs, yet a proof for one doesn't verify as the other
+    let proof = prove(&config, &CounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&config, &CounterAir, &proof, &[]).expect("verification failed");
+    assert!(verify(&config, &DoubledCounterAir, &proof, &[]).is_err());
+    let doubled_proof =
+        prove(&config, &DoubledCounterAir, counter_trace(8), &[]).expect("proving failed");
+    verify(&config, &DoubledCounterAir, &doubled_proof, &[]).expect("verification failed");
+    assert!(verify(&config, &CounterAir, &doubled_proof, &[]).is_err());
+
+    // Nor against a key with another fingerprint
+    let mut tampered = vk.clone();
+    tampered.fingerprint[0] ^= 1;
+    assert!(verify_with_vk(&config, &tampered, &CounterAir, &proof, &[]).is_err());
+}
@@ -0,0 +1,175 @@
+//! Verification never panics on untrusted proofs
+//!
+//! Mutates a valid proof field by field, and at random, and checks that every mutation is
+//! rejected with a [`VerificationError`] rather than a panic.
+
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Config, Val};
+use p3_uni_stark_mt::test_utils::mutate::{mutations, CommitmentSlot, Mutation, Opening};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{
+    keygen, prove, verify, verify_with_vk, Proof, ProverError, VerificationError, VerifyingKey,
+};
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+fn valid_proof() -> (Config, Proof<Config>) {
+    let config = default_config();
+    let proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+    (config, proof)
+}
+
+fn assert_rejected(config: &Config, proof: &Proof<Config>, what: &str) {
+    assert!(
+        verify(config, &FibonacciAir, proof, &[]).is_err(),
+        "{what}: mutated proof verified"
+    );
+}
+
+#[test]
+fn test_out_of_range_log_degree_rejected() {
+    let (config, proof) = valid_proof();
+    // BabyBear's two-adic subgroup has 2^27 elements and the quotient domain is 4x the
+    // trace domain, so no trace degree above 2^25 can be built, nor overflow a shift
+    for log_degree in 26..=u8::MAX {
+        let mut bad = proof.clone();
+        bad.log_degree = log_degree;
+        assert!(matches!(
+            verify(&config, &FibonacciAir, &bad, &[]),
+            Err(VerificationError::LogDegreeOutOfRange {
+                max_log_degree: 25,
+                ..
+            })
+        ));
+    }
+}
+
+#[test]
+fn test_log_degree_above_config_maximum_rejected() {
+    let (_, proof) = valid_proof();
+    let config = default_config().with_max_log_degree(2);
+    // The prover refuses traces taller than the configuration accepts...
+    assert!(matches!(
+        prove(&config, &FibonacciAir, fibonacci_trace(8), &[]),
+        Err(ProverError::DegreeExceedsFieldTwoAdicity {
+            height: 8,
+            max_height: 4
+        })
+    ));
+    // ...and the verifier rejects proofs of them before reading their openings
+    assert!(matches!(
+        verify(&config, &FibonacciAir, &proof, &[]),
+        Err(VerificationError::LogDegreeOutOfRange {
+            log_degree: 3,
+            max_log_degree: 2
+        })
+    ));
+
+    // Traces as tall as the maximum are proven as usual
+    let config = default_config().with_max_log_degree(3);
+    let proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+    verify(&config, &FibonacciAir, &proof, &[]).expect("verification failed");
+}
+
+#[test]
+fn test_opened_value_shapes_rejected() {
+    let (config, proof) = valid_proof();
+    let shapes: [(&str, fn(&mut Proof<Config>)); 10] = [
+        ("main_local truncated", |p| p.main_local.clear()),
+        ("main_next extended", |p| p.main_next.push(Challenge::ONE)),
+        ("aux_local extended", |p| p.aux_local.push(Challenge::ONE)),
+        ("aux_base_next extended", |p| {
+            p.aux_base_next.push(Challenge::ONE)
+        }),
+        ("aux_last extended", |p| p.aux_last.push(Challenge::ONE)),
+        ("quotient chunk removed", |p| {
+            p.quotient_chunks.pop();
+        }),
+        ("quotient chunk added", |p| p.quotient_chunks.push(vec![])),
+        ("quotient chunk truncated", |p| p.quotient_chunks[0].clear()),
+        ("aux commitment added", |p| {
+            p.aux_commit = Some(p.main_commits[0].clone())
+        }),
+        ("protocol changed", |p| p.protocol_id.num_aux_phases = 1),
+    ];
+    for (what, mutate) in shapes {
+        let mut bad = proof.clone();
+        mutate(&mut bad);
+        assert_rejected(&config, &bad, what);
+    }
+
+    // Public values are untrusted too
+    assert!(matches!(
+        verify(&config, &FibonacciAir, &proof, &[Val::ONE]),
+        Err(VerificationError::PublicValuesMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_openings_narrower_than_air_rejected() {
+    let (config, mut proof) = valid_proof();
+    // Openings of one main column, consistent with a key for one, would have the AIR read
+    // past them
+    let vk = VerifyingKey {
+        main_width: 1,
+        ..keygen(&config, &FibonacciAir)
+    };
+    proof.main_local.truncate(1);
+    proof.main_next.truncate(1);
+    assert!(matches!(
+        verify_with_vk(&config, &vk, &FibonacciAir, &proof, &[]),
+        Err(VerificationError::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn test_random_mutations_rejected() {
+    let (config, proof) = valid_proof();
+    let mut rng = SmallRng::seed_from_u64(1298);
+    for _ in 0..200 {
+        let mut bad = proof.clone();
+        let delta: Challenge = rng.random();
+        let target = match rng.random_range(0..3) {
+            0 => &mut bad.main_local,
+            1 => &mut bad.main_next,
+            _ => {
+                let chunk = rng.random_range(0..bad.quotient_chunks.len());
+                &mut bad.quotient_chunks[chunk]
+            }
+        };
+        let i = rng.random_range(0..target.len());
+        target[i] += delta;
+        if delta != Challenge::ZERO {
+            assert_rejected(&config, &bad, "opened value");
+        }
+    }
+}
+
+#[test]
+fn test_every_single_mutation_rejected() {
+    let (config, proof) = valid_proof();
+    let all = mutations(&proof);
+    assert!(all.contains(&Mutation::FlipOpenedValue {
+        opening: Opening::QuotientChunk(0),
+        index: 0,
+    }));
+    assert!(all.contains(&Mutation::SwapCommitments(
+        CommitmentSlot::Main(0),
+        CommitmentSlot::Quotient
+    )));
+    // Lists the proof doesn't carry have nothing to flip
+    assert!(!all.iter().any(|mutation| matches!(
+        mutation,
+        Mutation::FlipOpenedValue {
+            opening: Opening::AuxLocal,
+            ..
+        }
+    )));
+
+    for mutation in all {
+        let mut bad = proof.clone();
+        mutation.apply(&mut bad);
+        assert_rejected(&config, &bad, &format!("{mutation:?}"));
+    }
+}
@@ -0,0 +1,51 @@
+//! A verifier kept across proofs against one key
+
+use p3_field::PrimeCharacteristicRing;
+use p3_uni_stark_mt::config::baby_bear_poseidon2::{default_config, Challenge, Val};
+use p3_uni_stark_mt::test_utils::{fibonacci_trace, FibonacciAir};
+use p3_uni_stark_mt::{keygen, prove, VerificationError, Verifier};
+
+#[test]
+fn test_verifier_reused_across_heights() {
+    let config = default_config();
+    let vk = keygen(&config, &FibonacciAir);
+    let mut verifier = Verifier::new(&config, &vk);
+    assert_eq!(verifier.vk(), &vk);
+
+    // Back to a height verified before, after another one
+    for n in [8, 8, 32, 8] {
+        let proof = prove(&config, &FibonacciAir, fibonacci_trace(n), &[]).expect("proving failed");
+        verifier
+            .verify(&FibonacciAir, &proof, &[])
+            .expect("verification failed");
+    }
+}
+
+#[test]
+fn test_verifier_rejections_leave_it_usable() {
+    let config = default_config();
+    let vk = keygen(&config, &FibonacciAir);
+    let mut verifier = Verifier::new(&config, &vk);
+    let proof = prove(&config, &FibonacciAir, fibonacci_trace(8), &[]).expect("proving failed");
+
+    let mut tampered = proof.clone();
+    tampered.main_local[0] += Challenge::ONE;
+    assert!(verifier.verify(&FibonacciAir, &tampered, &[]).is_err());
+    assert!(matches!(
+        verifier.verify(&FibonacciAir, &proof, &[Val::ONE]),
+        Err(VerificationError::PublicValuesMismatch {
+            expected: 0,
+            actual: 1
+        })
+    ));
+    let mut too_tall = proof.clone();
+    too_tall.log_degree = u8::MAX;
+    assert!(matches!(
+        verifier.verify(&FibonacciAir, &too_tall, &[]),
+        Err(VerificationError::LogDegreeOutOfRange { .. })
+    ));
+
+    verifier
+        .verify(&FibonacciAir, &proof, &[])
+        .expect("verification failed");
+}